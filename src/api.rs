@@ -0,0 +1,201 @@
+//! A JSON API over the stored feeds, for scripts and other tools, served on a local port
+//! with `--api-server`.
+
+use crate::config::{Config, SharedConfig};
+use crate::repo::SharedRepository;
+use anyhow::{Context, Result};
+use hyper::header::CONTENT_TYPE;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+use percent_encoding::percent_decode_str;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use tokio::task::JoinHandle;
+
+const DEFAULT_PAGE_SIZE: usize = 50;
+const MAX_PAGE_SIZE: usize = 500;
+
+/// Serves the API on `port` of the loopback interface until the handle is aborted, reading
+/// `config` afresh for each request.
+pub fn start(port: u16, repo: SharedRepository, config: SharedConfig) -> Result<JoinHandle<()>> {
+    let addr = SocketAddr::from(([127, 0, 0, 1], port));
+    let server = Server::try_bind(&addr)
+        .with_context(|| format!("could not listen on port {}", port))?
+        .serve(make_service_fn(move |_| {
+            let (repo, config) = (repo.clone(), config.clone());
+            async move {
+                Ok::<_, Infallible>(service_fn(move |req| {
+                    handle(req, repo.clone(), config.clone())
+                }))
+            }
+        }));
+    log::info!("Serving the API on {}", addr);
+    Ok(tokio::spawn(async move {
+        if let Err(err) = server.await {
+            log::error!("API server stopped: {}", err);
+        }
+    }))
+}
+
+async fn handle(
+    req: Request<Body>,
+    repo: SharedRepository,
+    config: SharedConfig,
+) -> Result<Response<Body>, Infallible> {
+    let config = config.read().clone();
+    let segments = req
+        .uri()
+        .path()
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| percent_decode_str(segment).decode_utf8_lossy().into_owned())
+        .collect::<Vec<_>>();
+    let segments = segments.iter().map(String::as_str).collect::<Vec<_>>();
+    let params = reqwest::Url::parse(&format!("http://localhost{}", req.uri()))
+        .map(|uri| uri.query_pairs().into_owned().collect::<HashMap<_, _>>())
+        .unwrap_or_default();
+
+    let response = match (req.method(), segments.as_slice()) {
+        (&Method::GET, ["feeds"]) => list_feeds(&repo, &config),
+        (&Method::GET, ["feeds", feed_id, "items"]) => list_items(&repo, &config, feed_id, &params),
+        (&Method::POST, ["feeds", feed_id, "items", guid, "read"]) => {
+            mark_read(&repo, feed_id, guid)
+        }
+        (&Method::GET, ["items", "search"]) => search(&repo, &params),
+        (&Method::POST, ["refresh"]) => {
+            repo.lock().refresh_all(&config);
+            reply(
+                StatusCode::ACCEPTED,
+                json!({ "refreshing": config.feed_urls().len() }),
+            )
+        }
+        _ => error(StatusCode::NOT_FOUND, "no such endpoint"),
+    };
+    Ok(response)
+}
+
+/// Every stored feed with its count of unread items.
+fn list_feeds(repo: &SharedRepository, config: &Config) -> Response<Body> {
+    let Ok(feeds) = repo.lock().read_all(config) else {
+        return error(StatusCode::INTERNAL_SERVER_ERROR, "failed to read feeds");
+    };
+    let feeds = feeds
+        .iter()
+        .map(|feed| {
+            json!({
+                "id": feed.id(),
+                "title": feed.title(),
+                "url": feed.url(),
+                "link": feed.link(),
+                "unread": feed.unread_count(),
+                "last_fetched": feed.last_fetched(),
+            })
+        })
+        .collect::<Vec<_>>();
+    reply(StatusCode::OK, json!(feeds))
+}
+
+/// A page of a feed's items in the configured order, from `offset`, at most `limit` long.
+fn list_items(
+    repo: &SharedRepository,
+    config: &Config,
+    feed_id: &str,
+    params: &HashMap<String, String>,
+) -> Response<Body> {
+    let offset = params
+        .get("offset")
+        .and_then(|offset| offset.parse().ok())
+        .unwrap_or(0);
+    let limit = params
+        .get("limit")
+        .and_then(|limit| limit.parse::<usize>().ok())
+        .unwrap_or(DEFAULT_PAGE_SIZE)
+        .min(MAX_PAGE_SIZE);
+
+    let page = repo
+        .lock()
+        .get_items_page(feed_id, offset, limit, config.item_sort_order());
+    let Ok(items) = page else {
+        return error(StatusCode::INTERNAL_SERVER_ERROR, "failed to read items");
+    };
+    let items = items
+        .iter()
+        .map(|item| {
+            json!({
+                "guid": item.id(),
+                "feed_id": item.feed_id(),
+                "title": item.title(),
+                "link": item.link(),
+                "author": item.author(),
+                "pub_date": item.pub_date(),
+                "read": item.is_read(),
+                "starred": item.is_starred(),
+            })
+        })
+        .collect::<Vec<_>>();
+    reply(
+        StatusCode::OK,
+        json!({ "offset": offset, "limit": limit, "items": items }),
+    )
+}
+
+fn mark_read(repo: &SharedRepository, feed_id: &str, guid: &str) -> Response<Body> {
+    let mut repo = repo.lock();
+    if repo.get_item_detail(feed_id, guid).is_err() {
+        return error(StatusCode::NOT_FOUND, "no such item");
+    }
+    match repo.mark_read_elsewhere(feed_id, guid) {
+        Ok(_) => Response::builder()
+            .status(StatusCode::NO_CONTENT)
+            .body(Body::empty())
+            .expect("valid response"),
+        Err(_) => error(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "failed to mark item read",
+        ),
+    }
+}
+
+/// Items of every feed whose title or text contains the `q` parameter, ignoring case.
+fn search(repo: &SharedRepository, params: &HashMap<String, String>) -> Response<Body> {
+    let Some(query) = params.get("q").map(|q| q.trim()).filter(|q| !q.is_empty()) else {
+        return error(StatusCode::BAD_REQUEST, "missing query parameter q");
+    };
+    let limit = params
+        .get("limit")
+        .and_then(|limit| limit.parse::<usize>().ok())
+        .unwrap_or(DEFAULT_PAGE_SIZE)
+        .min(MAX_PAGE_SIZE);
+
+    let Ok(results) = repo.lock().search_items(query, limit) else {
+        return error(StatusCode::INTERNAL_SERVER_ERROR, "failed to search items");
+    };
+    let results = results
+        .iter()
+        .map(|result| {
+            json!({
+                "feed_id": result.feed_id(),
+                "feed_title": result.feed_title(),
+                "guid": result.item_id(),
+                "title": result.item_title(),
+                "link": result.item_link(),
+                "snippet": result.snippet(),
+            })
+        })
+        .collect::<Vec<_>>();
+    reply(StatusCode::OK, json!(results))
+}
+
+fn reply(status: StatusCode, body: Value) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .header(CONTENT_TYPE, "application/json")
+        .body(Body::from(body.to_string()))
+        .expect("valid response")
+}
+
+fn error(status: StatusCode, message: &str) -> Response<Body> {
+    reply(status, json!({ "error": message }))
+}
@@ -1,23 +1,89 @@
-use crate::config::Config;
-use crate::feed::{Feed, Item};
-use crate::repo::{Repository, RepositoryEvent};
-use anyhow::Result;
+use crate::config::keymap::KeyMap;
+use crate::config::theme::BUILT_IN_THEMES;
+use crate::config::{
+    Color, Config, FilterPreset, PaneLayout, ScrollbarStyle, SharedConfig, SyncBackend,
+};
+use crate::feed::{
+    json, Feed, HealthStatus, Item, ItemSummary, QueuedItem, SearchResult, TimelineItem,
+};
+use crate::handler::PendingKey;
+use crate::ipc::{IpcCommand, UnixSocketServer};
+use crate::logging;
+use crate::markdown;
+use crate::notification;
+use crate::opml;
+use crate::render;
+use crate::repo::storage::FeedStats;
+use crate::repo::{Repository, RepositoryEvent, SharedRepository};
+use crate::report;
+use crate::sync::miniflux::MinifluxClient;
+use crate::ui::symbols::SymbolSet;
+use crate::ui::{PANE_FRAME_HEIGHT, STATUS_BAR_HEIGHT, TABS_BAR_HEIGHT};
+use crate::util;
+use anyhow::{anyhow, Result};
 use clap::Parser;
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::env;
 use std::error;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, IsTerminal, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
 use std::process::{Child, Command, Stdio};
 use std::str::FromStr;
 use std::task::Poll;
-use tokio::sync::mpsc::{self, UnboundedReceiver};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use tui::layout::Rect;
+use tui::text::Line;
 use tui::widgets::{ListState, ScrollbarState};
 
-#[derive(Parser, Debug)]
+const ONESHOT_POLL_RATE: Duration = Duration::from_millis(50);
+const DOUBLE_CLICK_TIME: Duration = Duration::from_millis(400);
+/// The most lines of summary an expanded item shows in the items list.
+const EXPANDED_SUMMARY_LINES: usize = 3;
+/// How far an expanded item's summary is indented under its title.
+pub const SUMMARY_INDENT: u16 = 2;
+/// How many feeds a new items notification names before summing up the rest.
+const MAX_NOTIFIED_FEEDS: usize = 3;
+/// How long a message such as "Copied!" stays in the status bar, unless given a time of its own.
+const STATUS_MESSAGE_TIME: Duration = Duration::from_secs(2);
+/// How long a failed sync is reported in the status bar.
+const SYNC_WARNING_TIME: Duration = Duration::from_secs(5);
+/// How long the notice that the last session crashed is shown in the status bar.
+const CRASH_NOTICE_TIME: Duration = Duration::from_secs(10);
+/// How long the result of `--check-db` is shown in the status bar.
+const DB_CHECK_MESSAGE_TIME: Duration = Duration::from_secs(5);
+const MIN_HORIZONTAL_WIDTH: u16 = 120;
+/// Rows a `VirtualList` keeps loaded above and below the visible ones.
+const WINDOW_MARGIN: usize = 50;
+/// How many timeline rows are read at a time, as the selection nears the last of them.
+const TIMELINE_PAGE_SIZE: usize = 200;
+/// The most items a search lists.
+const SEARCH_LIMIT: usize = 200;
+/// Terminal widths from which the feeds pane shows when each feed was last fetched, with the
+/// panes side by side and stacked.
+const MIN_LAST_FETCHED_WIDTH: (u16, u16) = (160, 80);
+/// Where the selection is saved on quitting, in the data directory.
+const SESSION_FILE: &str = "session.json";
+
+#[derive(Parser, Debug, Clone, Default)]
 #[command(author, version, about, long_about = None)]
 pub struct Args {
     /// Set a custom config file
     #[arg(short, long)]
     pub config: Option<String>,
 
-    /// Set a custom theme, either built-in or a path to a theme file
+    /// Use a profile from the profiles directory, merged over the config file
+    #[arg(short, long, value_name = "NAME")]
+    pub profile: Option<String>,
+
+    /// Set a custom theme: built-in, installed in the themes directory, or a path to a theme file
     #[arg(short = 's', long)]
     pub color_scheme: Option<String>,
 
@@ -29,9 +95,163 @@ pub struct Args {
     #[arg(short, long)]
     pub timeout: Option<u64>,
 
+    /// How often, in milliseconds, the interface checks for finished work and redraws
+    #[arg(long, value_name = "MS")]
+    pub tick_rate: Option<u64>,
+
+    /// Tick once a second and fetch one feed at a time, to save battery
+    #[arg(long)]
+    pub low_power: bool,
+
+    /// Set the User-Agent sent with feed requests
+    #[arg(long, value_name = "STRING")]
+    pub user_agent: Option<String>,
+
     /// Do not cache feeds in local file-backed database
     #[arg(short, long)]
     pub no_cache: bool,
+
+    /// Use a database file other than the one in the config
+    #[arg(long, value_name = "PATH", value_parser = absolute_path)]
+    pub db: Option<PathBuf>,
+
+    /// Import feeds from an OPML file into the config, then exit
+    #[arg(long, value_name = "PATH")]
+    pub import_opml: Option<PathBuf>,
+
+    /// Export subscribed feeds to an OPML file, then exit
+    #[arg(long, value_name = "PATH")]
+    pub export_opml: Option<PathBuf>,
+
+    /// Export every cached feed and its items to a JSON Feed file, then exit
+    #[arg(long, value_name = "PATH")]
+    pub export_json: Option<PathBuf>,
+
+    /// Export the items of every cached feed to a Markdown file, then exit
+    #[arg(long, value_name = "PATH")]
+    pub export: Option<PathBuf>,
+
+    /// Print the cached items whose title or text contains a query, then exit
+    #[arg(long, value_name = "QUERY")]
+    pub search: Option<String>,
+
+    /// Set a request header for a feed in the config, then exit
+    #[arg(long, num_args = 3, value_names = ["URL", "NAME", "VALUE"])]
+    pub add_feed_header: Option<Vec<String>>,
+
+    /// List the built-in themes and those in the themes directory, then exit
+    #[arg(long)]
+    pub list_themes: bool,
+
+    /// Refresh all feeds, print how many new items each has, then exit
+    #[arg(long)]
+    pub refresh_print: bool,
+
+    /// Print the --refresh-print summary as JSON
+    #[arg(long, requires = "refresh_print")]
+    pub json: bool,
+
+    /// Print the unread items of each feed as plain text, then exit, as when stdout is piped
+    #[arg(long)]
+    pub print_unread: bool,
+
+    /// Refresh feeds in the background without the interface, until stopped
+    #[arg(long)]
+    pub daemon: bool,
+
+    /// Serve a JSON API on a local port, 7878 unless given, alongside the interface or --daemon
+    #[arg(long, value_name = "PORT", num_args = 0..=1, default_missing_value = "7878")]
+    pub api_server: Option<u16>,
+
+    /// Send a command, as JSON, to the running instance, then exit
+    #[arg(long, value_name = "JSON")]
+    pub send: Option<String>,
+
+    /// Log records at this level and above: off, error, warn, info, debug or trace [default: warn]
+    #[arg(long, value_name = "LEVEL", ignore_case = true)]
+    pub log_level: Option<log::LevelFilter>,
+
+    /// Log to this file as lines of JSON
+    #[arg(long, value_name = "PATH")]
+    pub log_file: Option<PathBuf>,
+
+    /// Replace the database with a backup, after asking, then exit
+    #[arg(long, value_name = "PATH")]
+    pub restore: Option<PathBuf>,
+
+    /// Check the database thoroughly at startup, repairing what can be
+    #[arg(long)]
+    pub check_db: bool,
+
+    /// List the environment variables that override the config file, then exit
+    #[arg(long)]
+    pub help_env: bool,
+
+    /// Send desktop notifications for new items
+    #[arg(long)]
+    pub notify: bool,
+
+    /// Choose a feed, theme and refresh interval, as on the first run
+    #[arg(long)]
+    pub setup: bool,
+
+    /// Start with nothing selected, rather than where the last session left off
+    #[arg(long)]
+    pub no_restore_session: bool,
+
+    #[command(subcommand)]
+    pub command: Option<CliCommand>,
+}
+
+impl Args {
+    /// Whether this run prints the unread items as text in place of the interface, as it does
+    /// with `--print-unread` or when stdout is piped.
+    pub fn prints_unread(&self) -> bool {
+        self.print_unread || !io::stdout().is_terminal()
+    }
+
+    /// Whether this run draws the interface, rather than running a command and exiting or
+    /// running as a daemon.
+    pub fn opens_interface(&self) -> bool {
+        !(self.daemon
+            || self.refresh_print
+            || self.prints_unread()
+            || self.list_themes
+            || self.help_env
+            || self.command.is_some()
+            || self.send.is_some()
+            || self.search.is_some()
+            || self.import_opml.is_some()
+            || self.export_opml.is_some()
+            || self.export_json.is_some()
+            || self.export.is_some()
+            || self.add_feed_header.is_some()
+            || self.restore.is_some())
+    }
+}
+
+/// Resolves a path given on the command line against the working directory it was run from.
+fn absolute_path(path: &str) -> std::io::Result<PathBuf> {
+    std::path::absolute(path)
+}
+
+#[derive(clap::Subcommand, Debug, Clone)]
+pub enum CliCommand {
+    /// Print a completion script for your shell, then exit
+    ///
+    /// Save the output wherever your shell loads completions from, for example:
+    ///
+    ///   mcsn completions bash > ~/.local/share/bash-completion/completions/mcsn
+    ///   mcsn completions zsh > "${fpath[1]}/_mcsn"
+    ///   mcsn completions fish > ~/.config/fish/completions/mcsn.fish
+    #[command(verbatim_doc_comment)]
+    Completions { shell: clap_complete::Shell },
+    /// Copy the database while it may be in use, then exit
+    Backup {
+        /// Where to write the copy [default: beside the database, named for the time]
+        #[arg(short, long, value_name = "PATH")]
+        output: Option<PathBuf>,
+    },
 }
 
 /// Application result type.
@@ -44,11 +264,148 @@ pub enum Status {
     Done,
 }
 
+/// Which of an item's bodies the detail pane shows, when it has both.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum ContentMode {
+    /// The whole post, from `<content>` or `<content:encoded>`.
+    #[default]
+    Full,
+    /// The excerpt, from `<summary>` or `<description>`.
+    Summary,
+}
+
+impl ContentMode {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ContentMode::Full => "Full",
+            ContentMode::Summary => "Summary",
+        }
+    }
+}
+
+/// How recently items must have been published to be listed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TimeWindow {
+    #[default]
+    All,
+    Last24Hours,
+    LastWeek,
+    LastMonth,
+    Custom(Duration),
+}
+
+impl TimeWindow {
+    /// The window after this one; a custom window is followed by all items.
+    pub fn next(&self) -> TimeWindow {
+        match self {
+            TimeWindow::All => TimeWindow::Last24Hours,
+            TimeWindow::Last24Hours => TimeWindow::LastWeek,
+            TimeWindow::LastWeek => TimeWindow::LastMonth,
+            TimeWindow::LastMonth | TimeWindow::Custom(_) => TimeWindow::All,
+        }
+    }
+
+    pub fn label(&self) -> String {
+        match self {
+            TimeWindow::All => "all time".to_string(),
+            TimeWindow::Last24Hours => "last 24h".to_string(),
+            TimeWindow::LastWeek => "last week".to_string(),
+            TimeWindow::LastMonth => "last month".to_string(),
+            TimeWindow::Custom(duration) => format!("last {}", util::format_duration(*duration)),
+        }
+    }
+
+    fn duration(&self) -> Option<Duration> {
+        const DAY: u64 = 24 * 60 * 60;
+        match self {
+            TimeWindow::All => None,
+            TimeWindow::Last24Hours => Some(Duration::from_secs(DAY)),
+            TimeWindow::LastWeek => Some(Duration::from_secs(7 * DAY)),
+            TimeWindow::LastMonth => Some(Duration::from_secs(30 * DAY)),
+            TimeWindow::Custom(duration) => Some(*duration),
+        }
+    }
+
+    /// Whether an item published at `pub_date` falls in the window. Undated items only
+    /// fall in the window of all items.
+    pub fn contains(&self, pub_date: Option<&str>) -> bool {
+        let Some(duration) = self.duration() else {
+            return true;
+        };
+        let Some(published) = pub_date.and_then(util::parse_pub_date) else {
+            return false;
+        };
+        chrono::Duration::from_std(duration)
+            .map(|duration| published >= chrono::Utc::now() - duration)
+            .unwrap_or(true)
+    }
+}
+
+/// Reads a window as written in the config: `all`, `24h`, `week`, `month`, or any other span
+/// such as `3d`.
+impl FromStr for TimeWindow {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "all" => Ok(TimeWindow::All),
+            "24h" | "day" => Ok(TimeWindow::Last24Hours),
+            "week" => Ok(TimeWindow::LastWeek),
+            "month" => Ok(TimeWindow::LastMonth),
+            span => util::parse_duration(span)
+                .map(TimeWindow::Custom)
+                .ok_or_else(|| anyhow!("unknown time window '{}'", s)),
+        }
+    }
+}
+
+/// Writes a window as it is read from the config.
+impl fmt::Display for TimeWindow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TimeWindow::All => write!(f, "all"),
+            TimeWindow::Last24Hours => write!(f, "24h"),
+            TimeWindow::LastWeek => write!(f, "week"),
+            TimeWindow::LastMonth => write!(f, "month"),
+            TimeWindow::Custom(duration) => write!(f, "{}", util::format_duration(*duration)),
+        }
+    }
+}
+
+/// Why the last refresh of a feed failed.
+#[derive(Debug, Clone)]
+pub enum FeedError {
+    TimedOut,
+    /// Failed after a number of attempts, for a reason such as `"HTTP 403 Forbidden"`.
+    Failed(u8, String),
+}
+
+impl FeedError {
+    pub fn icon(&self, symbols: &SymbolSet) -> &'static str {
+        match self {
+            FeedError::TimedOut => "!",
+            FeedError::Failed(..) => symbols.warning,
+        }
+    }
+
+    pub fn reason(&self) -> &str {
+        match self {
+            FeedError::TimedOut => "Timed out",
+            FeedError::Failed(_, reason) => reason,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum ConsoleCommand {
     AddFeed(String),
     DeleteFeed(Option<String>),
     Search(String),
+    SetCredentials(String, String),
+    Export(PathBuf),
+    SwitchProfile(String),
+    TimeFilter(Duration),
+    SavePreset(String),
 }
 
 #[derive(Debug)]
@@ -70,13 +427,43 @@ impl FromStr for ConsoleCommand {
                     None => Err(ConsoleCommandError::BadArgument),
                 },
                 ":s" | ":search" => {
-                    let query = parts.iter().skip(1).copied().collect::<String>();
+                    let query = parts[1..].join(" ");
                     if query.is_empty() {
                         Err(ConsoleCommandError::BadArgument)
                     } else {
                         Ok(ConsoleCommand::Search(query))
                     }
                 }
+                ":auth" => match (parts.get(1), parts.get(2)) {
+                    (Some(username), Some(password)) => Ok(ConsoleCommand::SetCredentials(
+                        username.to_string(),
+                        password.to_string(),
+                    )),
+                    _ => Err(ConsoleCommandError::BadArgument),
+                },
+                ":export" => {
+                    if parts.len() < 2 {
+                        Err(ConsoleCommandError::BadArgument)
+                    } else {
+                        Ok(ConsoleCommand::Export(PathBuf::from(parts[1..].join(" "))))
+                    }
+                }
+                ":profile" => match parts.get(1) {
+                    Some(name) => Ok(ConsoleCommand::SwitchProfile(name.to_string())),
+                    None => Err(ConsoleCommandError::BadArgument),
+                },
+                ":within" => match parts.get(1).and_then(|span| util::parse_duration(span)) {
+                    Some(duration) => Ok(ConsoleCommand::TimeFilter(duration)),
+                    None => Err(ConsoleCommandError::BadArgument),
+                },
+                ":savepreset" => {
+                    let name = parts[1..].join(" ");
+                    if name.is_empty() {
+                        Err(ConsoleCommandError::BadArgument)
+                    } else {
+                        Ok(ConsoleCommand::SavePreset(name))
+                    }
+                }
                 ":d" | ":delete" => match parts.get(1) {
                     Some(url) => Ok(ConsoleCommand::DeleteFeed(Some(url.to_string()))),
                     None => Ok(ConsoleCommand::DeleteFeed(None)),
@@ -93,57 +480,234 @@ impl FromStr for ConsoleCommand {
 #[derive(Debug)]
 pub struct App {
     pub config: Config,
-    pub repo: Repository,
+    /// The config as the API server reads it, updated whenever `config` changes.
+    shared_config: SharedConfig,
+    pub repo: SharedRepository,
     pub running: bool,
     pub active_view: View,
     pub active_tab: Tab,
-    pub feeds: StatefulList<Feed>,
+    pub feeds: StatefulList<GroupedFeed>,
     pub feeds_scroll: ScrollbarState,
-    pub items: StatefulList<Item>,
+    pub items: VirtualList<ItemSummary>,
+    /// Items showing the start of their summary in the items list, by id.
+    pub expanded_items: HashSet<String>,
+    /// The selected item in full, read from storage when the detail pane shows it.
+    pub current_item_detail: Option<Item>,
+    pub starred: StatefulList<ItemRef>,
+    pub feed_candidates: StatefulList<String>,
+    /// The presets popup: a row turning every filter off, then each preset in the config.
+    pub preset_picker: StatefulList<String>,
+    /// The answers to the setup wizard, while it is shown.
+    pub wizard: Option<Wizard>,
+    /// The row of the feed waiting for its deletion to be confirmed.
+    pending_delete: Option<usize>,
+    /// Item counts for every feed, below a row of totals.
+    pub stats: StatefulList<FeedStats>,
+    /// Items of every feed, newest first, read from storage a page at a time.
+    pub timeline: StatefulList<TimelineItem>,
+    /// Whether the timeline holds every stored item, so there are no more pages to read.
+    timeline_complete: bool,
+    /// The query typed on the search tab.
+    pub search: InputState,
+    /// Items of every feed matching the search, newest first.
+    pub search_results: StatefulList<SearchResult>,
     pub items_scroll: ScrollbarState,
     pub detail_scroll: ScrollbarState,
     pub detail_scroll_index: u16,
+    /// Lines of the detail pane's body as last drawn, wrapped to its width.
+    pub detail_line_count: usize,
     pub show_keybinds: bool,
+    pub keymap: KeyMap,
     pub status: Status,
+    /// A short-lived message shown in the status bar in place of the last fetch time, and
+    /// when it expires.
+    pub status_message: Option<(String, Instant)>,
+    clipboard: Option<Clipboard>,
     pub command_state: InputState,
+    /// The pattern typed into the items pane to filter the current feed's items.
+    pub item_filter: InputState,
+    item_filter_regex: Option<Regex>,
+    pub feed_filter: InputState,
+    feed_filter_query: Option<String>,
+    /// The pattern typed into the feeds pane to jump between the feeds it matches.
+    pub list_search: InputState,
+    /// The search of the focused pane that `n` and `N` step through: the feeds pane's
+    /// search, or the items pane's filter.
+    last_search: Option<Regex>,
+    /// The first key of an unfinished two-key sequence, such as `gg` in vim mode.
+    pub pending_key: PendingKey,
+    /// Whether the jump key was pressed in the feeds pane, so that the next letter or digit
+    /// jumps to a feed in place of whatever it is bound to.
+    awaiting_feed_jump: bool,
+    /// Where each pane was last drawn, to find what the mouse points at.
+    pub pane_areas: PaneAreas,
+    last_click: Option<(Instant, u16, u16)>,
+    pub feed_errors: HashMap<String, FeedError>,
+    pending_feed_errors: HashMap<String, FeedError>,
+    /// The URL of the feed whose error is shown in a popup.
+    error_detail: Option<String>,
+    collapsed_groups: HashSet<String>,
+    hidden_feeds: Vec<Feed>,
+    /// Whether read items, and feeds without unread items, are hidden.
+    unread_filter_active: bool,
+    /// Hides items published before the window, and counts only those in it.
+    time_filter: TimeWindow,
+    /// The URL of the feed being refreshed on its own, named in the status bar as it loads.
+    refreshing_feed: Option<String>,
+    /// The preset whose filters are applied, until one of them is changed.
+    active_preset: Option<String>,
+    /// Lists only the feeds of this group, as set by a preset.
+    group_filter: Option<String>,
+    /// A preset's keyword, which filters the items of each feed selected while it applies.
+    preset_keyword: Option<String>,
+    content_mode: ContentMode,
+    /// Items saved to read later, frontmost first, as stored.
+    reading_queue: VecDeque<QueuedItem>,
+    pub queue_state: ListState,
+    /// Links of the items read this session and not yet marked read on the sync server.
+    sync_pending: HashSet<String>,
     dimensions: (u16, u16),
+    /// The title last given to the terminal window.
+    window_title: Option<String>,
+    pub symbols: SymbolSet,
     repo_rx: UnboundedReceiver<RepositoryEvent>,
+    /// Sends `ConfigChanged` when the config files change on disk, if they can be watched.
+    config_watcher: Option<RecommendedWatcher>,
+    /// Takes commands from other invocations, once listening.
+    ipc: Option<UnixSocketServer>,
 }
 
 impl App {
-    pub fn init(dimensions: (u16, u16)) -> Result<Self> {
-        let args = Args::parse();
+    pub fn init(args: Args, dimensions: (u16, u16)) -> Result<Self> {
+        let opens_interface = args.opens_interface();
+        let restore = opens_interface && !args.setup && !args.no_restore_session;
         let config = Config::new(args)?;
 
         let (tx, rx) = mpsc::unbounded_channel::<RepositoryEvent>();
+        let config_watcher = watch_config(&config, tx.clone());
         let mut repo = Repository::init(&config, tx)?;
 
-        let items = repo.read_all(&config).unwrap_or_default();
-        let feeds_count = items.len() as u16;
+        let feeds = repo.read_all(&config).unwrap_or_default();
+        let reading_queue = repo.load_queue().unwrap_or_default();
+        let keymap = KeyMap::from_config(&config);
+        let symbols = symbol_set(&config);
 
-        Ok(Self {
+        let mut app = Self {
+            shared_config: SharedConfig::new(config.clone()),
             config,
-            repo,
+            repo: SharedRepository::new(repo),
             running: true,
             dimensions,
             active_view: View::MainList,
             active_tab: Tab::Browse,
-            feeds: StatefulList::<Feed>::with_items(items),
-            feeds_scroll: ScrollbarState::default().content_length(feeds_count),
-            items: StatefulList::<Item>::default(),
+            feeds: StatefulList::<GroupedFeed>::with_items(vec![]),
+            feeds_scroll: ScrollbarState::default(),
+            items: VirtualList::<ItemSummary>::default(),
+            expanded_items: HashSet::new(),
+            current_item_detail: None,
+            starred: StatefulList::<ItemRef>::default(),
+            feed_candidates: StatefulList::<String>::default(),
+            preset_picker: StatefulList::<String>::default(),
+            wizard: None,
+            pending_delete: None,
+            stats: StatefulList::<FeedStats>::default(),
+            timeline: StatefulList::<TimelineItem>::default(),
+            timeline_complete: false,
+            search: InputState::new(),
+            search_results: StatefulList::<SearchResult>::default(),
             items_scroll: ScrollbarState::default(),
             detail_scroll: ScrollbarState::default(),
             detail_scroll_index: 0,
+            detail_line_count: 0,
             status: Status::Done,
+            status_message: None,
+            clipboard: None,
             show_keybinds: false,
+            keymap,
             command_state: InputState::new(),
+            item_filter: InputState::new(),
+            item_filter_regex: None,
+            feed_filter: InputState::new(),
+            feed_filter_query: None,
+            list_search: InputState::new(),
+            last_search: None,
+            pending_key: PendingKey::default(),
+            awaiting_feed_jump: false,
+            pane_areas: PaneAreas::default(),
+            last_click: None,
+            feed_errors: HashMap::new(),
+            pending_feed_errors: HashMap::new(),
+            error_detail: None,
+            collapsed_groups: HashSet::new(),
+            hidden_feeds: vec![],
+            unread_filter_active: false,
+            time_filter: TimeWindow::default(),
+            refreshing_feed: None,
+            active_preset: None,
+            group_filter: None,
+            preset_keyword: None,
+            content_mode: ContentMode::default(),
+            reading_queue,
+            queue_state: ListState::default(),
+            sync_pending: HashSet::new(),
+            window_title: None,
+            symbols,
             repo_rx: rx,
-        })
+            config_watcher,
+            ipc: None,
+        };
+        app.set_feeds(feeds);
+        app.repo.lock().pull_sync_state(&app.config);
+        if app.config.check_db() {
+            let summary = {
+                let repo = app.repo.lock();
+                let report = repo.integrity_report();
+                format!(
+                    "Database checked: {} repaired, {} warnings",
+                    report.repaired().len(),
+                    report.warnings().len()
+                )
+            };
+            app.set_status(summary, DB_CHECK_MESSAGE_TIME);
+        }
+        if opens_interface && previous_session_crashed() {
+            app.set_status(
+                "Previous session crashed. See crash.log.",
+                CRASH_NOTICE_TIME,
+            );
+        }
+        if restore && !app.config.is_first_run() {
+            if let Err(err) = app.restore_session() {
+                log::warn!("Could not restore the last session: {}", err);
+            }
+        }
+
+        Ok(app)
+    }
+
+    /// Starts taking commands sent with `--send`, unless another instance already is.
+    pub fn listen_for_commands(&mut self) {
+        match UnixSocketServer::start() {
+            Ok(server) => self.ipc = Some(server),
+            Err(err) => log::warn!("Not listening for commands: {}", err),
+        }
     }
 
     /// Handles the tick event of the terminal.
     pub fn tick(&mut self) {
-        self.repo.tick(&self.config);
+        self.repo.lock().tick(&self.config);
+
+        while let Some((command, reply)) = self.ipc.as_mut().and_then(UnixSocketServer::try_recv) {
+            let _ = reply.send(self.run_command(command));
+        }
+
+        if self
+            .status_message
+            .as_ref()
+            .is_some_and(|(_, expires)| Instant::now() >= *expires)
+        {
+            self.status_message = None;
+        }
 
         let waker = futures::task::noop_waker();
         let mut cx = std::task::Context::from_waker(&waker);
@@ -164,26 +728,59 @@ impl App {
                         };
                         self.status = Status::Loading(counts.0, counts.1);
                     }
-                    Some(RepositoryEvent::RetrievedAll(feeds)) => {
+                    Some(RepositoryEvent::FetchTimedOut(url)) => {
+                        self.pending_feed_errors.insert(url, FeedError::TimedOut);
+                    }
+                    Some(RepositoryEvent::FetchFailed(url, attempts, reason)) => {
+                        self.pending_feed_errors
+                            .entry(url)
+                            .or_insert(FeedError::Failed(attempts, reason));
+                    }
+                    Some(RepositoryEvent::RetrievedAll(mut feeds)) => {
+                        // errors reported during this refresh replace those from the last one,
+                        // only for the one feed if that was all that was refreshed
+                        let errors = std::mem::take(&mut self.pending_feed_errors);
+                        match self.refreshing_feed.take() {
+                            Some(url) => {
+                                self.feed_errors.remove(&url);
+                                self.feed_errors.extend(errors);
+                            }
+                            None => self.feed_errors = errors,
+                        }
+                        // feeds that have never been fetched are listed anyway, with their error
+                        let unfetched = self
+                            .feed_errors
+                            .keys()
+                            .filter(|url| !feeds.iter().any(|feed| feed.url() == *url))
+                            .map(|url| Feed::placeholder(url))
+                            .collect::<Vec<_>>();
+                        feeds.extend(unfetched);
+                        if self.config.notifications() {
+                            self.notify_new_items(&feeds);
+                        }
                         self.set_feeds(feeds);
+                        match self.active_tab {
+                            Tab::Stats => self.refresh_stats(),
+                            Tab::Timeline => self.refresh_timeline(),
+                            _ => {}
+                        }
                         self.status = Status::Done;
                         break;
                     }
                     Some(RepositoryEvent::RetrievedOne(feed)) => {
-                        match self
-                            .feeds
-                            .items
-                            .iter()
-                            .enumerate()
-                            .find(|(_, f)| f.link() == feed.link())
-                        {
-                            Some((i, f)) => {
-                                self.feeds.items[i] = f.clone();
-                            }
-                            None => {
-                                self.feeds.items.push(feed);
-                            }
+                        report!(
+                            self.config.add_feed_url(feed.url()),
+                            "Failed to add feed to config"
+                        );
+                        self.share_config();
+                        self.feed_errors.remove(feed.url());
+                        self.set_status(format!("Added {}", feed.title()), STATUS_MESSAGE_TIME);
+                        let mut feeds = self.take_feeds();
+                        match feeds.iter_mut().find(|f| f.link() == feed.link()) {
+                            Some(f) => *f = *feed,
+                            None => feeds.push(*feed),
                         }
+                        self.set_feeds(feeds);
 
                         match self.status {
                             Status::Loading(_, _) => {
@@ -194,12 +791,69 @@ impl App {
 
                         break;
                     }
+                    Some(RepositoryEvent::Pushed(feed)) => {
+                        if self.config.notifications() {
+                            self.notify_new_items(std::slice::from_ref(&*feed));
+                        }
+                        let mut feeds = self.take_feeds();
+                        match feeds.iter_mut().find(|f| f.url() == feed.url()) {
+                            Some(f) => *f = *feed,
+                            None => feeds.push(*feed),
+                        }
+                        self.set_feeds(feeds);
+                    }
+                    Some(RepositoryEvent::FeedUrlChecked(url, reason)) => {
+                        self.wizard_url_checked(&url, reason)
+                    }
+                    Some(RepositoryEvent::Discovered(candidates)) => {
+                        self.feed_candidates = StatefulList::with_items(candidates);
+                        self.feed_candidates.next();
+                        self.status = Status::Done;
+                        break;
+                    }
+                    Some(RepositoryEvent::ConfigChanged) => match self.read_config() {
+                        Ok(true) => self.refresh_all(),
+                        Ok(false) => {}
+                        Err(err) => self.status = Status::Errored(err.to_string()),
+                    },
+                    Some(RepositoryEvent::StarredSynced(links)) => self.merge_starred(&links),
+                    Some(RepositoryEvent::ItemRead(feed_id, item_id)) => {
+                        self.update_item(&feed_id, &item_id, |item| item.read = true);
+                    }
+                    Some(RepositoryEvent::SyncedItems(read, starred)) => {
+                        self.merge_synced_items(&read, &starred)
+                    }
+                    Some(RepositoryEvent::SyncFailed(reason)) => {
+                        self.set_status(format!("Sync failed: {}", reason), SYNC_WARNING_TIME);
+                    }
                     Some(RepositoryEvent::Errored) => {
                         self.status = Status::Errored("database transaction failed".into());
                         break;
                     }
-                    Some(RepositoryEvent::Refresh) => {}
+                    Some(RepositoryEvent::Pruned(count)) => {
+                        log::info!("Pruned {} items past their retention period", count);
+                    }
+                    Some(RepositoryEvent::ItemsPage(feed_id, offset, order, items)) => {
+                        if !self.has_item_filter()
+                            && !self.unread_filter_active
+                            && self.time_filter == TimeWindow::All
+                            && order == self.config.item_sort_order()
+                            && self.current_feed().is_some_and(|feed| feed.id() == feed_id)
+                        {
+                            self.items.fill(offset, items);
+                        }
+                    }
+                    Some(
+                        RepositoryEvent::Refresh
+                        | RepositoryEvent::FetchResults(..)
+                        | RepositoryEvent::RemoteItemsPulled(..)
+                        | RepositoryEvent::StreamItemsPulled(..)
+                        | RepositoryEvent::WebSubVerified(..)
+                        | RepositoryEvent::RequestItemsPage(..),
+                    ) => {}
                     Some(RepositoryEvent::Aborted) => {
+                        self.pending_feed_errors.clear();
+                        self.refreshing_feed = None;
                         self.status = Status::Done;
                         break;
                     }
@@ -214,106 +868,580 @@ impl App {
         }
     }
 
+    /// Runs a command sent by another invocation.
+    fn run_command(&mut self, command: IpcCommand) -> Result<()> {
+        log::info!("Received command {:?}", command);
+        match command {
+            IpcCommand::Refresh => self.refresh_all(),
+            IpcCommand::OpenUrl { url } => {
+                App::open_link(&url).ok_or_else(|| anyhow!("could not open {}", url))?;
+            }
+            IpcCommand::AddFeed { url } => self.add_feed_url(&url)?,
+            IpcCommand::Quit => self.quit(),
+        }
+        Ok(())
+    }
+
     /// Set running to false to quit the application.
     pub fn quit(&mut self) {
         self.running = false;
     }
 
+    /// Titles the terminal window with the unread count, the selected feed, and the item when
+    /// reading one, if the title has changed since it was last set.
+    pub fn update_window_title(&mut self) {
+        if !self.config.window_title() {
+            return;
+        }
+        let mut title = match self.unread_count() {
+            0 => "moccasin".to_owned(),
+            unread => format!("moccasin ({})", unread),
+        };
+        if let Some(feed) = self.current_feed() {
+            title.push_str(" — ");
+            title.push_str(feed.title());
+            if let (View::Detail, Some(item)) = (&self.active_view, self.current_item()) {
+                title.push_str(" / ");
+                title.push_str(item.title().unwrap_or("[no title]"));
+            }
+        }
+        // a title can't hold control characters, which would end the escape sequence early
+        title.retain(|c| !c.is_control());
+
+        if self.window_title.as_deref() == Some(title.as_str()) || !terminal_sets_title() {
+            return;
+        }
+        let _ = crossterm::execute!(io::stderr(), crossterm::terminal::SetTitle(&title));
+        self.window_title = Some(title);
+    }
+
+    /// Stores what is still waiting to be written to the database, and saves the selection,
+    /// focused pane and detail scroll to `session.json` for `restore_session`.
+    pub fn shutdown(&mut self) -> Result<()> {
+        self.repo.lock().flush(&self.config);
+
+        let session = SessionState {
+            selected_feed_index: self.feeds.state.selected(),
+            selected_item_index: self.items.state.selected(),
+            active_view: match self.active_view {
+                View::SubList => "items",
+                View::Detail => "detail",
+                View::MainList | View::Wizard => "feeds",
+            }
+            .to_owned(),
+            detail_scroll_index: self.detail_scroll_index,
+        };
+        let path = session_path();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        // written beside it first, so a crash can't leave half a file
+        let partial = path.with_extension("json.tmp");
+        fs::write(&partial, serde_json::to_string(&session)?)?;
+        fs::rename(&partial, &path)?;
+        log::info!("Saved session to {}", path.display());
+        Ok(())
+    }
+
+    /// Puts back the selection, focused pane and detail scroll saved when moccasin last quit,
+    /// as far as the feeds and items are still there, returning what was saved.
+    pub fn restore_session(&mut self) -> Result<Option<SessionState>> {
+        let path = session_path();
+        if !path.exists() {
+            return Ok(None);
+        }
+        let session = serde_json::from_str::<SessionState>(&fs::read_to_string(&path)?)?;
+
+        let Some(feed) = session.selected_feed_index.filter(|i| {
+            self.feeds
+                .items
+                .get(*i)
+                .and_then(GroupedFeed::feed)
+                .is_some()
+        }) else {
+            return Ok(Some(session));
+        };
+        self.feeds.state.select(Some(feed));
+        self.sync_feed_selection();
+
+        let Some(item) = session.selected_item_index.filter(|item| {
+            self.items
+                .select_absolute(item.saturating_sub(self.items.state.offset()))
+        }) else {
+            return Ok(Some(session));
+        };
+        self.sync_items_window();
+        self.scroll_items_to(item);
+        match session.active_view.as_str() {
+            "items" => self.active_view = View::SubList,
+            "detail" => {
+                self.active_view = View::Detail;
+                self.sync_item_detail();
+                self.detail_scroll_index = session.detail_scroll_index;
+                self.detail_scroll = self.detail_scroll.position(session.detail_scroll_index);
+            }
+            _ => {}
+        }
+        Ok(Some(session))
+    }
+
     pub fn set_dimensions(&mut self, dimensions: (u16, u16)) {
         self.dimensions = dimensions;
+        self.sync_feeds_scroll_length();
+        self.sync_items_scroll_length();
+    }
+
+    /// The layout to draw in a terminal of `dims`, falling back to stacked panes when there
+    /// isn't room for them side by side.
+    pub fn effective_layout(&self, dims: (u16, u16)) -> PaneLayout {
+        if dims.0 < MIN_HORIZONTAL_WIDTH {
+            PaneLayout::Vertical
+        } else {
+            self.config.layout()
+        }
+    }
+
+    /// Whether the feeds pane in a terminal of `dims` has room for when each feed was last
+    /// fetched.
+    pub fn should_render_last_fetched(&self, dims: (u16, u16)) -> bool {
+        match self.effective_layout(dims) {
+            PaneLayout::Horizontal => dims.0 >= MIN_LAST_FETCHED_WIDTH.0,
+            PaneLayout::Vertical => dims.0 >= MIN_LAST_FETCHED_WIDTH.1,
+        }
+    }
+
+    /// Rows between the tabs and status bars, which the panes share.
+    fn panes_height(&self) -> u16 {
+        self.dimensions
+            .1
+            .saturating_sub(TABS_BAR_HEIGHT + STATUS_BAR_HEIGHT)
+    }
+
+    /// How many rows of the feeds pane show without scrolling.
+    pub fn visible_feed_rows(&self) -> u16 {
+        let height = match self.effective_layout(self.dimensions) {
+            PaneLayout::Horizontal => self.panes_height(),
+            // the stacked panes split the height in thirds
+            PaneLayout::Vertical => self.panes_height() / 3,
+        };
+        height.saturating_sub(PANE_FRAME_HEIGHT)
+    }
+
+    /// How many rows of the items pane show without scrolling. Stacked, the items take the
+    /// detail pane's third as well until an item is selected.
+    pub fn visible_item_rows(&self) -> u16 {
+        let height = match self.effective_layout(self.dimensions) {
+            PaneLayout::Horizontal => self.panes_height(),
+            PaneLayout::Vertical if self.current_item().is_some() => self.panes_height() / 3,
+            PaneLayout::Vertical => self.panes_height() * 2 / 3,
+        };
+        height.saturating_sub(PANE_FRAME_HEIGHT)
     }
 
     pub fn should_render_feeds_scroll(&self) -> bool {
-        self.feeds.items().len() as u16 > self.dimensions.1 - 8
+        self.config.scrollbar_style() != ScrollbarStyle::None
+            && self.feeds.items().len() as u16 > self.visible_feed_rows()
     }
 
     pub fn should_render_items_scroll(&self) -> bool {
-        self.items.items().len() as u16 > self.dimensions.1 - 8
+        self.config.scrollbar_style() != ScrollbarStyle::None
+            && self.item_rows_before(self.items.len()) as u16 > self.visible_item_rows()
+    }
+
+    /// Sizes the feeds scrollbar to the rows of the whole list against those that show.
+    fn sync_feeds_scroll_length(&mut self) {
+        self.feeds_scroll = self
+            .feeds_scroll
+            .content_length(self.feeds.items().len() as u16)
+            .viewport_content_length(self.visible_feed_rows());
+    }
+
+    /// Sizes the items scrollbar to the rows of the whole list, counting expanded items and
+    /// the double display mode, against those that show.
+    fn sync_items_scroll_length(&mut self) {
+        let rows = self.item_rows_before(self.items.len());
+        self.items_scroll = self
+            .items_scroll
+            .content_length(rows as u16)
+            .viewport_content_length(self.visible_item_rows());
     }
 
     pub fn should_render_detail_scroll(&self) -> bool {
-        // TODO
-        false
+        self.config.scrollbar_style() != ScrollbarStyle::None
+            && self.detail_line_count > self.pane_areas.detail_body.height as usize
     }
 
     pub fn should_render_console(&self) -> bool {
         self.command_state.show_input
     }
 
+    pub fn feed_error(&self, feed: &Feed) -> Option<&FeedError> {
+        self.feed_errors.get(feed.url())
+    }
+
+    /// The feed row drawn `line` lines below the top of the feeds pane, where a selected feed
+    /// that failed to refresh takes a second line for the reason.
+    fn feed_index_at(&self, line: usize) -> usize {
+        let index = self.feeds.state.offset() + line;
+        match self.feeds.state.selected() {
+            Some(selected)
+                if index > selected
+                    && self
+                        .current_feed()
+                        .is_some_and(|feed| self.feed_error(feed).is_some()) =>
+            {
+                index - 1
+            }
+            _ => index,
+        }
+    }
+
+    /// Opens a popup with why the current feed failed to refresh.
+    pub fn show_error_detail(&mut self) -> Result<()> {
+        let feed = self
+            .current_feed()
+            .filter(|feed| self.feed_error(feed).is_some())
+            .ok_or_else(|| anyhow!("no refresh error for this feed"))?;
+        self.error_detail = Some(feed.url().to_owned());
+        Ok(())
+    }
+
+    pub fn dismiss_error_detail(&mut self) {
+        self.error_detail = None;
+    }
+
+    pub fn should_render_error_detail(&self) -> bool {
+        self.error_detail.is_some()
+    }
+
+    /// The feed shown in the error popup, and its error.
+    pub fn error_detail(&self) -> Option<(&Feed, &FeedError)> {
+        let url = self.error_detail.as_deref()?;
+        let feed = self.all_feeds().find(|feed| feed.url() == url)?;
+        Some((feed, self.feed_errors.get(url)?))
+    }
+
     pub fn current_feed(&self) -> Option<&Feed> {
         self.feeds
             .state
             .selected()
             .and_then(|i| self.feeds.items().get(i))
+            .and_then(GroupedFeed::feed)
     }
 
-    pub fn current_item(&self) -> Option<&Item> {
-        self.items
+    pub fn current_item(&self) -> Option<&ItemSummary> {
+        self.items.selected_item()
+    }
+
+    pub fn current_timeline_item(&self) -> Option<&TimelineItem> {
+        self.timeline
             .state
             .selected()
-            .and_then(|i| self.items.items().get(i))
+            .and_then(|i| self.timeline.items().get(i))
     }
 
-    pub fn next_feed(&mut self) {
-        self.feeds.next();
-        self.feeds_scroll = self.feeds_scroll.position(
-            self.feeds
-                .state
-                .selected()
-                .unwrap_or(self.feeds.state.offset()) as u16,
-        );
+    /// The item selected in the timeline on its tab, or in the items pane elsewhere.
+    fn selected_summary(&self) -> Option<&ItemSummary> {
+        match self.active_tab {
+            Tab::Timeline => self.current_timeline_item().map(TimelineItem::summary),
+            _ => self.current_item(),
+        }
+    }
+
+    /// Reads the selected item in full once it is selected, since the detail pane shows it
+    /// beside the items pane. An item missing from storage falls back to its loaded copy.
+    pub fn sync_item_detail(&mut self) {
+        let Some(summary) = self.selected_summary() else {
+            self.current_item_detail = None;
+            return;
+        };
+        if self
+            .current_item_detail
+            .as_ref()
+            .is_some_and(|item| summary.is_for(item))
+        {
+            return;
+        }
+
+        let detail = self
+            .repo
+            .lock()
+            .get_item_detail(summary.feed_id(), summary.id())
+            .ok()
+            .or_else(|| {
+                self.all_feeds()
+                    .filter(|feed| feed.id() == summary.feed_id())
+                    .flat_map(Feed::items)
+                    .find(|item| summary.is_for(item))
+                    .cloned()
+            });
+        self.current_item_detail = detail;
+    }
 
-        if let Some(channel) = self.current_feed() {
-            self.items.items = channel.items().into();
-            self.items_scroll = self
-                .items_scroll
-                .content_length(self.items.items.len() as u16);
+    pub fn next_feed(&mut self) {
+        // headers are not selectable, so step over them
+        for _ in 0..self.feeds.items.len() {
+            self.feeds.next();
+            if self.current_feed().is_some() {
+                break;
+            }
         }
+        self.sync_feed_selection();
     }
 
     pub fn prev_feed(&mut self) {
-        self.feeds.previous();
-        self.feeds_scroll = self.feeds_scroll.position(
-            self.feeds
-                .state
-                .selected()
-                .unwrap_or(self.feeds.state.offset()) as u16,
-        );
+        for _ in 0..self.feeds.items.len() {
+            self.feeds.previous();
+            if self.current_feed().is_some() {
+                break;
+            }
+        }
+        self.sync_feed_selection();
+    }
 
-        if let Some(channel) = self.current_feed() {
-            self.items.items = channel.items().into();
-            self.items_scroll = self
-                .items_scroll
-                .content_length(self.items.items.len() as u16);
+    /// Selects the next feed after the current one whose title starts with `c`, ignoring case
+    /// and wrapping around. The selection stays put when no feed matches.
+    pub fn jump_to_feed_by_prefix(&mut self, c: char) {
+        let found = feed_row_with_prefix(&self.feeds.items, self.feeds.state.selected(), c);
+        if let Some(index) = found {
+            self.feeds.state.select(Some(index));
+            self.sync_feed_selection();
         }
     }
 
-    pub fn next_item(&mut self) {
-        self.items.next();
-        self.items_scroll = self.items_scroll.position(
-            self.items
-                .state
-                .selected()
-                .unwrap_or(self.items.state.offset()) as u16,
-        );
+    /// Waits for a letter or digit to jump to a feed by, in the feeds pane.
+    pub fn start_feed_jump(&mut self) {
+        self.awaiting_feed_jump =
+            self.active_tab == Tab::Browse && self.active_view == View::MainList;
     }
 
-    pub fn prev_item(&mut self) {
-        self.items.previous();
-        self.items_scroll = self.items_scroll.position(
-            self.items
+    /// Whether the next key is taken as the first character of a feed to jump to, which
+    /// stops it waiting.
+    pub fn take_feed_jump(&mut self) -> bool {
+        std::mem::take(&mut self.awaiting_feed_jump)
+    }
+
+    pub fn is_awaiting_feed_jump(&self) -> bool {
+        self.awaiting_feed_jump
+    }
+
+    /// Shows the items of the newly selected feed.
+    fn sync_feed_selection(&mut self) {
+        self.reset_item_filter();
+
+        self.feeds_scroll = self.feeds_scroll.position(
+            self.feeds
                 .state
                 .selected()
-                .unwrap_or(self.items.state.offset()) as u16,
+                .unwrap_or(self.feeds.state.offset()) as u16,
         );
-    }
 
-    pub fn next_view(&mut self, wrap: bool) {
-        let has_current_feed = self.current_feed().is_some();
-        let has_current_item = self.current_item().is_some();
+        self.reload_items();
+    }
 
-        if !has_current_feed {
-            self.active_view = View::MainList;
+    /// Keeps the rows around the selected item loaded as the selection moves. The selected
+    /// row itself is read straight away if it is missing, and the rest on the next tick.
+    fn sync_items_window(&mut self) {
+        let index = self
+            .items
+            .state
+            .selected()
+            .unwrap_or(self.items.state.offset());
+        let Some(window) = self
+            .items
+            .scroll_to(index, self.visible_item_rows() as usize)
+        else {
+            return;
+        };
+        let Some(feed_id) = self.current_feed().map(|feed| feed.id().to_owned()) else {
+            return;
+        };
+
+        let order = self.config.item_sort_order();
+
+        if self.items.get(index).is_some() {
+            self.repo
+                .lock()
+                .request_items_page(&feed_id, window.start, window.len(), order);
+            return;
+        }
+        match self
+            .repo
+            .lock()
+            .get_items_page(&feed_id, window.start, window.len(), order)
+        {
+            Ok(items) => self.items.fill(window.start, items),
+            Err(_) => log::error!("Failed to read items of {}", feed_id),
+        }
+    }
+
+    /// Lists the current feed's items again, or those matching the filter, keeping the
+    /// selected item where it is still listed, and otherwise the selected row.
+    fn reload_items(&mut self) {
+        let selected_id = self.current_item().map(|item| item.id().to_owned());
+        let Some(feed) = self.current_feed() else {
+            return;
+        };
+        let order = self.config.item_sort_order();
+        let position_of = |items: &[ItemSummary]| {
+            let id = selected_id.as_deref()?;
+            items.iter().position(|item| item.id() == id)
+        };
+        // matches are few enough to hold in full, but a whole feed is read a page at a time
+        let position = if self.item_filter_regex.is_some()
+            || self.unread_filter_active
+            || self.time_filter != TimeWindow::All
+        {
+            let mut items = feed
+                .items()
+                .iter()
+                .filter(|item| !(self.unread_filter_active && item.is_read()))
+                .filter(|item| self.time_filter.contains(item.pub_date()))
+                .filter(|item| self.item_matches(item))
+                .map(ItemSummary::from)
+                .collect::<Vec<_>>();
+            util::sort_items(&mut items, order);
+            let position = position_of(&items);
+            self.items.set_all(items);
+            position
+        } else {
+            // the pages are read in the same order as the items are sorted here
+            let position = selected_id.is_some().then(|| {
+                let mut items = feed
+                    .items()
+                    .iter()
+                    .map(ItemSummary::from)
+                    .collect::<Vec<_>>();
+                util::sort_items(&mut items, order);
+                position_of(&items)
+            });
+            let total = feed.items().len();
+            self.items.reset(total);
+            position.flatten()
+        };
+
+        let len = self.items.len();
+        let selected = position.or(self.items.state.selected());
+        self.items
+            .state
+            .select(selected.filter(|_| len > 0).map(|i| i.min(len - 1)));
+        self.sync_items_window();
+        self.sync_items_scroll_length();
+        if let Some(selected) = self.items.state.selected() {
+            self.scroll_items_to(selected);
+        }
+    }
+
+    /// Moves the items scrollbar to the item at `index`, counting the rows of the items
+    /// above it.
+    fn scroll_items_to(&mut self, index: usize) {
+        self.items_scroll = self
+            .items_scroll
+            .position(self.item_rows_before(index) as u16);
+    }
+
+    /// Rows the item at `index` takes in the items pane: one, or two in the double display
+    /// mode, and the lines of its summary while it is expanded.
+    pub fn item_render_height(&self, index: usize) -> usize {
+        let expanded = self
+            .items
+            .get(index)
+            .map_or(0, |item| self.expanded_summary(item).len());
+        self.config.item_display_mode().rows() + expanded
+    }
+
+    /// Rows the items above `index` take in the items pane.
+    fn item_rows_before(&self, index: usize) -> usize {
+        if self.expanded_items.is_empty() {
+            return index * self.config.item_display_mode().rows();
+        }
+        (0..index).map(|i| self.item_render_height(i)).sum()
+    }
+
+    /// Which item, counted from the top of the items pane, is drawn `row` rows down it.
+    fn item_at_row(&self, row: usize) -> usize {
+        let offset = self.items.state.offset();
+        let mut top = 0;
+        let mut index = offset;
+        while index + 1 < self.items.len() {
+            top += self.item_render_height(index);
+            if top > row {
+                break;
+            }
+            index += 1;
+        }
+        index - offset
+    }
+
+    /// Up to three lines of an expanded item's summary, wrapped to the items pane, or none
+    /// while it isn't expanded.
+    pub fn expanded_summary(&self, item: &ItemSummary) -> Vec<Line<'static>> {
+        if !self.expanded_items.contains(item.id()) {
+            return vec![];
+        }
+        let summary = self
+            .current_feed()
+            .and_then(|feed| feed.items().iter().find(|full| full.id() == item.id()))
+            .and_then(Item::description);
+        let Some(summary) = summary else {
+            return vec![Line::from("[no summary]")];
+        };
+        let width = self.pane_areas.items.width.saturating_sub(SUMMARY_INDENT);
+        render::html_to_lines(summary, width)
+            .into_iter()
+            .filter(|line| line.width() > 0)
+            .take(EXPANDED_SUMMARY_LINES)
+            .collect()
+    }
+
+    /// Shows the start of the selected item's summary under it in the items list, or hides
+    /// it again.
+    pub fn toggle_expanded_item(&mut self) {
+        let Some(id) = self.current_item().map(|item| item.id().to_owned()) else {
+            return;
+        };
+        if !self.expanded_items.remove(&id) {
+            self.expanded_items.insert(id);
+        }
+        self.sync_items_scroll_length();
+        self.scroll_items_to(self.items.state.selected().unwrap_or(0));
+    }
+
+    pub fn next_item(&mut self) {
+        self.items.next();
+        self.sync_items_window();
+        self.scroll_items_to(
+            self.items
+                .state
+                .selected()
+                .unwrap_or(self.items.state.offset()),
+        );
+    }
+
+    pub fn prev_item(&mut self) {
+        self.items.previous();
+        self.sync_items_window();
+        self.scroll_items_to(
+            self.items
+                .state
+                .selected()
+                .unwrap_or(self.items.state.offset()),
+        );
+    }
+
+    pub fn next_view(&mut self, wrap: bool) {
+        if matches!(
+            self.active_tab,
+            Tab::Favorites | Tab::Stats | Tab::Timeline | Tab::Search | Tab::Queue
+        ) {
+            return;
+        }
+
+        let has_current_feed = self.current_feed().is_some();
+        let has_current_item = self.current_item().is_some();
+
+        if !has_current_feed {
+            self.active_view = View::MainList;
             return;
         }
 
@@ -340,12 +1468,25 @@ impl App {
                     None
                 }
             }
+            View::Wizard => None,
         } {
+            if next_view == View::Detail {
+                self.sync_item_detail();
+                self.mark_current_item_read();
+            }
+            self.clear_last_search();
             self.active_view = next_view;
         }
     }
 
     pub fn prev_view(&mut self, wrap: bool) {
+        if matches!(
+            self.active_tab,
+            Tab::Favorites | Tab::Stats | Tab::Timeline | Tab::Search | Tab::Queue
+        ) {
+            return;
+        }
+
         let has_current_feed = self.current_feed().is_some();
         let has_current_item = self.current_item().is_some();
 
@@ -366,12 +1507,38 @@ impl App {
             }
             View::SubList => Some(View::MainList),
             View::Detail => Some(View::SubList),
+            View::Wizard => None,
         } {
+            self.clear_last_search();
             self.active_view = next_view;
         }
     }
 
     pub fn next(&mut self) {
+        if self.active_tab == Tab::Favorites {
+            self.reset_detail_scroll();
+            self.starred.next();
+            return;
+        }
+        if self.active_tab == Tab::Stats {
+            self.stats.next();
+            return;
+        }
+        if self.active_tab == Tab::Timeline {
+            self.reset_detail_scroll();
+            self.load_more_timeline();
+            self.timeline.next();
+            return;
+        }
+        if self.active_tab == Tab::Search {
+            self.search_results.next();
+            return;
+        }
+        if self.active_tab == Tab::Queue {
+            self.move_by(1);
+            return;
+        }
+
         match self.active_view {
             View::MainList => {
                 self.reset_items_scroll();
@@ -386,10 +1553,34 @@ impl App {
                 self.detail_scroll_index = self.detail_scroll_index.saturating_add(1);
                 self.detail_scroll.next();
             }
+            View::Wizard => self.next_wizard_theme(),
         }
     }
 
     pub fn prev(&mut self) {
+        if self.active_tab == Tab::Favorites {
+            self.reset_detail_scroll();
+            self.starred.previous();
+            return;
+        }
+        if self.active_tab == Tab::Stats {
+            self.stats.previous();
+            return;
+        }
+        if self.active_tab == Tab::Timeline {
+            self.reset_detail_scroll();
+            self.timeline.previous();
+            return;
+        }
+        if self.active_tab == Tab::Search {
+            self.search_results.previous();
+            return;
+        }
+        if self.active_tab == Tab::Queue {
+            self.move_by(-1);
+            return;
+        }
+
         match self.active_view {
             View::MainList => {
                 self.reset_items_scroll();
@@ -404,6 +1595,209 @@ impl App {
                 self.detail_scroll_index = self.detail_scroll_index.saturating_sub(1);
                 self.detail_scroll.prev();
             }
+            View::Wizard => self.prev_wizard_theme(),
+        }
+    }
+
+    /// Moves the selection in the active pane by `delta` rows, stopping at either end
+    /// rather than wrapping around.
+    pub fn move_by(&mut self, delta: isize) {
+        if self.active_tab == Tab::Favorites {
+            self.reset_detail_scroll();
+            let target = clamp_row(
+                self.starred.state.selected(),
+                delta,
+                self.starred.items.len(),
+            );
+            self.starred.state.select(target);
+            return;
+        }
+        if self.active_tab == Tab::Stats {
+            let target = clamp_row(self.stats.state.selected(), delta, self.stats.items.len());
+            self.stats.state.select(target);
+            return;
+        }
+        if self.active_tab == Tab::Timeline {
+            self.reset_detail_scroll();
+            let target = clamp_row(
+                self.timeline.state.selected(),
+                delta,
+                self.timeline.items.len(),
+            );
+            self.timeline.state.select(target);
+            self.load_more_timeline();
+            return;
+        }
+        if self.active_tab == Tab::Search {
+            let target = clamp_row(
+                self.search_results.state.selected(),
+                delta,
+                self.search_results.items.len(),
+            );
+            self.search_results.state.select(target);
+            return;
+        }
+        if self.active_tab == Tab::Queue {
+            let target = clamp_row(self.queue_state.selected(), delta, self.reading_queue.len());
+            self.queue_state.select(target);
+            return;
+        }
+
+        match self.active_view {
+            View::MainList => {
+                let Some(target) =
+                    clamp_row(self.feeds.state.selected(), delta, self.feeds.items.len())
+                else {
+                    return;
+                };
+                // land on the nearest feed, since headers are not selectable
+                let is_feed = |i: &usize| self.feeds.items[*i].feed().is_some();
+                let ahead = (target..self.feeds.items.len()).find(is_feed);
+                let behind = (0..=target).rev().find(is_feed);
+                let row = if delta >= 0 {
+                    ahead.or(behind)
+                } else {
+                    behind.or(ahead)
+                };
+                if row.is_some() && row != self.feeds.state.selected() {
+                    self.reset_items_scroll();
+                    self.reset_detail_scroll();
+                    self.feeds.state.select(row);
+                    self.sync_feed_selection();
+                }
+            }
+            View::SubList => {
+                self.reset_detail_scroll();
+                let target = clamp_row(self.items.state.selected(), delta, self.items.len());
+                self.items.state.select(target);
+                self.sync_items_window();
+                self.scroll_items_to(target.unwrap_or(0));
+            }
+            View::Detail => {
+                let index = (self.detail_scroll_index as isize + delta).clamp(0, u16::MAX as isize);
+                self.detail_scroll_index = index as u16;
+                self.detail_scroll = self.detail_scroll.position(index as u16);
+            }
+            View::Wizard => {}
+        }
+    }
+
+    /// Selects the first row of the active pane, or scrolls to the top of the detail pane.
+    pub fn first(&mut self) {
+        self.move_by(isize::MIN / 2);
+    }
+
+    /// Selects the last row of the active pane. The detail pane has no known end, so it
+    /// stays put.
+    pub fn last(&mut self) {
+        if matches!(
+            self.active_tab,
+            Tab::Favorites | Tab::Stats | Tab::Timeline | Tab::Search | Tab::Queue
+        ) || self.active_view != View::Detail
+        {
+            self.move_by(isize::MAX / 2);
+        }
+    }
+
+    /// How many rows half a page of the list panes holds.
+    pub fn half_page(&self) -> isize {
+        let rows = match self.active_view {
+            View::MainList => self.visible_feed_rows(),
+            _ => self.visible_item_rows(),
+        };
+        (rows / 2).max(1) as isize
+    }
+
+    /// Scrolls the pane under the mouse, focusing it first.
+    pub fn scroll_at(&mut self, column: u16, row: u16, down: bool) {
+        if let Some(view) = self.pane_areas.view_at(column, row) {
+            if !matches!(
+                self.active_tab,
+                Tab::Favorites | Tab::Stats | Tab::Timeline | Tab::Search | Tab::Queue
+            ) && view != self.active_view
+            {
+                self.clear_last_search();
+                self.active_view = view;
+            }
+        }
+        if down {
+            self.next();
+        } else {
+            self.prev();
+        }
+    }
+
+    /// Focuses the pane under the mouse and selects the row clicked. Clicking the same spot
+    /// twice in quick succession opens the selection, as `o` does.
+    pub fn click_at(&mut self, column: u16, row: u16) {
+        let double_click = self
+            .last_click
+            .is_some_and(|(at, c, r)| at.elapsed() < DOUBLE_CLICK_TIME && c == column && r == row);
+        self.last_click = Some((Instant::now(), column, row));
+
+        let Some(view) = self.pane_areas.view_at(column, row) else {
+            return;
+        };
+
+        if self.active_tab == Tab::Favorites {
+            if view == View::MainList {
+                let rows = self.pane_areas.starred;
+                if self.starred.select_absolute((row - rows.y) as usize) {
+                    self.reset_detail_scroll();
+                }
+            }
+        } else if self.active_tab == Tab::Timeline {
+            if view == View::MainList {
+                let rows = self.pane_areas.timeline;
+                if self.timeline.select_absolute((row - rows.y) as usize) {
+                    self.reset_detail_scroll();
+                    self.load_more_timeline();
+                }
+            }
+        } else {
+            match view {
+                View::MainList => {
+                    let rows = self.pane_areas.feeds;
+                    let previous = self.feeds.state.selected();
+                    let index = self.feed_index_at((row - rows.y) as usize);
+                    // headers are not selectable
+                    if self
+                        .feeds
+                        .items
+                        .get(index)
+                        .and_then(GroupedFeed::feed)
+                        .is_none()
+                    {
+                        return;
+                    }
+                    self.feeds.state.select(Some(index));
+                    if self.feeds.state.selected() != previous {
+                        self.reset_items_scroll();
+                        self.reset_detail_scroll();
+                        self.sync_feed_selection();
+                    }
+                }
+                View::SubList => {
+                    let rows = self.pane_areas.items;
+                    if self
+                        .items
+                        .select_absolute(self.item_at_row((row - rows.y) as usize))
+                    {
+                        self.sync_items_window();
+                        self.reset_detail_scroll();
+                        self.scroll_items_to(self.items.state.selected().unwrap_or(0));
+                    }
+                }
+                View::Detail | View::Wizard => {}
+            }
+            if view != self.active_view {
+                self.clear_last_search();
+            }
+            self.active_view = view;
+        }
+
+        if double_click {
+            self.open();
         }
     }
 
@@ -411,24 +1805,44 @@ impl App {
         let next_tab = match self.active_tab {
             Tab::Browse => Tab::Favorites,
             Tab::Favorites => Tab::Tags,
-            Tab::Tags => Tab::Browse,
+            Tab::Tags => Tab::Stats,
+            Tab::Stats => Tab::Timeline,
+            Tab::Timeline => Tab::Search,
+            Tab::Search => Tab::Queue,
+            Tab::Queue => Tab::Browse,
         };
 
-        self.active_tab = next_tab;
+        self.set_tab(next_tab.index_of());
     }
 
     pub fn prev_tab(&mut self) {
         let prev_tab = match self.active_tab {
-            Tab::Browse => Tab::Tags,
+            Tab::Browse => Tab::Queue,
             Tab::Favorites => Tab::Browse,
             Tab::Tags => Tab::Favorites,
+            Tab::Stats => Tab::Tags,
+            Tab::Timeline => Tab::Stats,
+            Tab::Search => Tab::Timeline,
+            Tab::Queue => Tab::Search,
         };
 
-        self.active_tab = prev_tab;
+        self.set_tab(prev_tab.index_of());
     }
 
     pub fn set_tab(&mut self, index: usize) {
+        self.clear_last_search();
         self.active_tab = Tab::from(index);
+        match self.active_tab {
+            Tab::Stats => self.refresh_stats(),
+            Tab::Timeline => self.refresh_timeline(),
+            Tab::Search => self.search.show_input = true,
+            Tab::Queue
+                if self.queue_state.selected().is_none() && !self.reading_queue.is_empty() =>
+            {
+                self.queue_state.select(Some(0));
+            }
+            _ => {}
+        }
     }
 
     pub fn unselect(&mut self) {
@@ -441,6 +1855,43 @@ impl App {
     }
 
     pub fn open(&mut self) {
+        if self.active_tab == Tab::Favorites {
+            if let Some(link) = self.current_starred().and_then(|(_, item)| item.link()) {
+                let _ = App::open_link(link);
+            }
+            return;
+        }
+        if self.active_tab == Tab::Timeline {
+            if let Some(link) = self.selected_summary().and_then(ItemSummary::link) {
+                let _ = App::open_link(link);
+            }
+            return;
+        }
+        if self.active_tab == Tab::Search {
+            if let Some(link) = self
+                .search_results
+                .state
+                .selected()
+                .and_then(|i| self.search_results.items.get(i))
+                .and_then(SearchResult::item_link)
+            {
+                let _ = App::open_link(link);
+            }
+            return;
+        }
+        if self.active_tab == Tab::Queue {
+            if let Some(link) = self
+                .queue_state
+                .selected()
+                .and_then(|i| self.reading_queue.get(i))
+                .and_then(|queued| self.queued_item(queued))
+                .and_then(|(_, item)| item.link())
+            {
+                let _ = App::open_link(link);
+            }
+            return;
+        }
+
         match self.active_view {
             View::MainList => {
                 if let Some(feed) = self.current_feed() {
@@ -459,6 +1910,55 @@ impl App {
         }
     }
 
+    /// Opens the media attached to the selected item in the default player or browser.
+    pub fn open_enclosure(&mut self) -> Result<()> {
+        let url = match self.active_tab {
+            Tab::Favorites => self
+                .current_starred()
+                .and_then(|(_, item)| item.enclosure()),
+            _ => self.selected_summary().and_then(ItemSummary::enclosure),
+        }
+        .map(|enclosure| enclosure.url().to_owned())
+        .ok_or_else(|| anyhow!("no media to play"))?;
+
+        App::open_link(&url).ok_or_else(|| anyhow!("failed to open {}", url))?;
+        Ok(())
+    }
+
+    /// Shows `message` in the status bar for `duration`, replacing any message shown before.
+    pub fn set_status(&mut self, message: impl Into<String>, duration: Duration) {
+        self.status_message = Some((message.into(), Instant::now() + duration));
+    }
+
+    /// Copies the link of the selected item to the system clipboard.
+    pub fn yank_item_url(&mut self) -> Result<()> {
+        let link = match self.active_tab {
+            Tab::Favorites => self.current_starred().and_then(|(_, item)| item.link()),
+            _ => self.selected_summary().and_then(ItemSummary::link),
+        }
+        .ok_or_else(|| anyhow!("no link to copy"))?
+        .to_owned();
+
+        let clipboard = match &mut self.clipboard {
+            Some(clipboard) => clipboard,
+            clipboard => clipboard.insert(Clipboard(
+                arboard::Clipboard::new()
+                    .map_err(|err| anyhow!("clipboard unavailable: {}", err))?,
+            )),
+        };
+        clipboard
+            .0
+            .set_text(link)
+            .map_err(|err| anyhow!("failed to copy link: {}", err))?;
+
+        // the message shows in place of an earlier error
+        if matches!(self.status, Status::Errored(_)) {
+            self.status = Status::Done;
+        }
+        self.set_status("Copied!", STATUS_MESSAGE_TIME);
+        Ok(())
+    }
+
     pub fn open_config(&self) -> Option<Child> {
         if let Some(cfg_path) = self.config.config_file_path().as_path().to_str() {
             Self::open_link(cfg_path)
@@ -467,109 +1967,2041 @@ impl App {
         }
     }
 
-    pub fn refresh_all(&mut self) {
-        self.repo.refresh_all(&self.config)
+    pub fn mark_current_item_read(&mut self) {
+        self.set_current_item_read(true);
     }
 
-    pub fn toggle_keybinds(&mut self) {
-        self.show_keybinds = !self.show_keybinds;
+    pub fn toggle_current_item_read(&mut self) {
+        if let Some(read) = self.selected_summary().map(ItemSummary::is_read) {
+            self.set_current_item_read(!read);
+        }
     }
 
-    pub fn toggle_console(&mut self, cmd: Option<&str>) {
-        if let Some(cmd) = cmd {
-            self.command_state.input = cmd.into();
-            self.command_state.cursor_position = self.clamp_cursor(cmd.len());
-        } else {
-            self.command_state.input.clear();
-            self.reset_cursor();
+    pub fn bulk_mark_feed_read(&mut self, feed_index: usize) {
+        if let Some(feed) = self
+            .feeds
+            .items
+            .get_mut(feed_index)
+            .and_then(GroupedFeed::feed_mut)
+        {
+            report!(
+                self.repo.lock().mark_feed_read(feed.id()),
+                "Failed to mark feed as read"
+            );
+            let unread = feed.items.iter().filter(|item| !item.read);
+            self.sync_pending.extend(
+                unread
+                    .clone()
+                    .filter_map(|item| item.link().map(str::to_owned)),
+            );
+            let guids = unread.map(|item| item.id().to_owned()).collect::<Vec<_>>();
+            self.repo.lock().push_read(&self.config, &guids);
+            feed.items.iter_mut().for_each(|item| item.read = true);
+
+            if self.feeds.state.selected() == Some(feed_index) {
+                self.items.loaded_mut().for_each(|item| item.read = true);
+            }
         }
-        self.command_state.show_input = !self.command_state.show_input;
     }
 
-    pub fn move_cursor_left(&mut self) {
-        let cursor_moved_left = self.command_state.cursor_position.saturating_sub(1);
-        self.command_state.cursor_position = self.clamp_cursor(cursor_moved_left);
+    pub fn enter_starred_view(&mut self) {
+        self.active_tab = Tab::Favorites;
+        if self.starred.state.selected().is_none() {
+            self.starred.next();
+        }
     }
 
-    pub fn move_cursor_right(&mut self) {
-        let cursor_moved_right = self.command_state.cursor_position.saturating_add(1);
-        self.command_state.cursor_position = self.clamp_cursor(cursor_moved_right);
+    pub fn current_starred(&self) -> Option<(&Feed, &Item)> {
+        self.starred
+            .state
+            .selected()
+            .and_then(|i| self.starred.items().get(i))
+            .and_then(|item_ref| self.resolve_item(item_ref))
     }
 
-    pub fn enter_char(&mut self, new_char: char) {
-        self.command_state
-            .input
-            .insert(self.command_state.cursor_position, new_char);
-        self.move_cursor_right();
+    pub fn resolve_item(&self, item_ref: &ItemRef) -> Option<(&Feed, &Item)> {
+        let feed = self
+            .all_feeds()
+            .find(|feed| feed.id() == item_ref.feed_id)?;
+        let item = feed
+            .items()
+            .iter()
+            .find(|item| item.id() == item_ref.item_id)?;
+        Some((feed, item))
     }
 
-    pub fn delete_char(&mut self) {
-        let is_not_cursor_leftmost = self.command_state.cursor_position != 0;
-        if is_not_cursor_leftmost {
-            // Method "remove" is not used on the saved text for deleting the selected char.
-            // Reason: Using remove on String works on bytes instead of the chars.
-            // Using remove would require special care because of char boundaries.
-
-            let current_index = self.command_state.cursor_position;
-            let from_left_to_current_index = current_index - 1;
-
-            // Getting all characters before the selected character.
-            let before_char_to_delete = self
-                .command_state
-                .input
-                .chars()
-                .take(from_left_to_current_index);
-            // Getting all characters after selected character.
-            let after_char_to_delete = self.command_state.input.chars().skip(current_index);
+    /// Stars or unstars the selected item, in whichever view is showing.
+    pub fn toggle_star(&mut self) {
+        let target = match self.active_tab {
+            Tab::Favorites => self
+                .current_starred()
+                .map(|(_, item)| (item.feed_id().to_owned(), item.id().to_owned())),
+            _ => self
+                .selected_summary()
+                .map(|item| (item.feed_id().to_owned(), item.id().to_owned())),
+        };
 
-            // Put all characters together except the selected one.
-            // By leaving the selected one out, it is forgotten and therefore deleted.
-            self.command_state.input = before_char_to_delete.chain(after_char_to_delete).collect();
-            self.move_cursor_left();
+        if let Some((feed_id, item_id)) = target {
+            // the lock is let go before pushing, which takes it again
+            let toggled = self.repo.lock().toggle_star(&feed_id, &item_id);
+            match toggled {
+                Ok(starred) => {
+                    if starred {
+                        self.repo.lock().push_starred(&self.config, &item_id);
+                    }
+                    self.update_item(&feed_id, &item_id, |item| item.starred = starred);
+                    self.refresh_starred();
+                }
+                Err(_) => log::error!("Failed to toggle item star"),
+            }
         }
     }
 
-    fn clamp_cursor(&self, new_cursor_pos: usize) -> usize {
-        new_cursor_pos.clamp(0, self.command_state.input.len())
-    }
+    /// Reads the item counts of every feed in the config, keeping the selection in place.
+    /// The first row holds the totals across all of them.
+    fn refresh_stats(&mut self) {
+        let feeds = self
+            .config
+            .feed_urls()
+            .iter()
+            .filter_map(|url| match self.repo.lock().feed_stats(url) {
+                Ok(stats) => Some(stats),
+                Err(_) => {
+                    log::error!("Failed to read stats for {}", url);
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
 
-    fn reset_cursor(&mut self) {
-        self.command_state.cursor_position = 0;
+        let mut rows = vec![FeedStats::totals("All feeds", &feeds)];
+        rows.extend(feeds);
+        self.stats.items = rows;
+
+        let selected = self.stats.state.selected().unwrap_or(0);
+        self.stats
+            .state
+            .select(Some(selected.min(self.stats.items.len() - 1)));
     }
 
-    pub fn submit_command(&mut self) {
-        match self.command_state.input.parse::<ConsoleCommand>() {
-            Ok(ConsoleCommand::AddFeed(url)) => {
-                self.config.add_feed_url(&url);
-                self.repo.add_feed_url(&url, &self.config);
+    /// Reads the newest items of every feed from storage: at least a page, or as many as the
+    /// timeline already holds.
+    pub fn build_timeline(&self) -> Vec<TimelineItem> {
+        let limit = self.timeline.items.len().max(TIMELINE_PAGE_SIZE);
+        match self.repo.lock().get_all_items_sorted(limit, 0) {
+            Ok(items) => items,
+            Err(_) => {
+                log::error!("Failed to read the timeline");
+                vec![]
+            }
+        }
+    }
+
+    /// Rebuilds the timeline, keeping the selection in place.
+    fn refresh_timeline(&mut self) {
+        let limit = self.timeline.items.len().max(TIMELINE_PAGE_SIZE);
+        self.timeline.items = self.build_timeline();
+        self.timeline_complete = self.timeline.items.len() < limit;
+
+        let len = self.timeline.items.len();
+        let selected = self.timeline.state.selected().unwrap_or(0);
+        self.timeline
+            .state
+            .select((len > 0).then(|| selected.min(len - 1)));
+    }
+
+    /// Reads the next page of the timeline once the selection is within a screen of its end.
+    fn load_more_timeline(&mut self) {
+        let len = self.timeline.items.len();
+        let selected = self.timeline.state.selected().unwrap_or(0);
+        if self.timeline_complete || selected + (self.visible_item_rows() as usize) < len {
+            return;
+        }
+
+        match self
+            .repo
+            .lock()
+            .get_all_items_sorted(TIMELINE_PAGE_SIZE, len)
+        {
+            Ok(items) => {
+                self.timeline_complete = items.len() < TIMELINE_PAGE_SIZE;
+                self.timeline.items.extend(items);
+            }
+            Err(_) => log::error!("Failed to read the timeline"),
+        }
+    }
+
+    /// Finds items of every feed whose title or text contains `query`, ignoring case.
+    pub fn search(&self, query: &str) -> Vec<SearchResult> {
+        if query.trim().is_empty() {
+            return vec![];
+        }
+        match self.repo.lock().search_items(query.trim(), SEARCH_LIMIT) {
+            Ok(results) => results,
+            Err(_) => {
+                log::error!("Failed to search for {}", query);
+                vec![]
+            }
+        }
+    }
+
+    /// Goes to the search tab to type a query, or runs `query` straight away.
+    pub fn open_search(&mut self, query: Option<&str>) {
+        self.set_tab(Tab::Search.index_of());
+        if let Some(query) = query {
+            self.search.input = query.to_owned();
+            self.search.cursor_position = query.chars().count();
+            self.search.show_input = false;
+            self.run_search();
+        }
+    }
+
+    pub fn should_render_search_input(&self) -> bool {
+        self.active_tab == Tab::Search && self.search.show_input
+    }
+
+    pub fn search_push(&mut self, c: char) {
+        self.search.input.push(c);
+        self.search.cursor_position += 1;
+        self.run_search();
+    }
+
+    pub fn search_pop(&mut self) {
+        if self.search.input.pop().is_some() {
+            self.search.cursor_position -= 1;
+            self.run_search();
+        }
+    }
+
+    /// Stops typing the query, to move through the results.
+    pub fn close_search_input(&mut self) {
+        self.search.show_input = false;
+    }
+
+    fn run_search(&mut self) {
+        self.search_results.items = self.search(&self.search.input);
+        let selected = (!self.search_results.items.is_empty()).then_some(0);
+        self.search_results.state = ListState::default().with_selected(selected);
+    }
+
+    /// Shows the selected search result in the browse tab, selecting its feed and the item
+    /// within it. Filters that would hide it are cleared, and its group expanded.
+    pub fn open_search_result(&mut self) {
+        let Some(result) = self
+            .search_results
+            .state
+            .selected()
+            .and_then(|i| self.search_results.items.get(i))
+            .cloned()
+        else {
+            return;
+        };
+        self.show_item(result.feed_id(), result.item_id());
+    }
+
+    /// Selects an item in the Browse tab, clearing the filters that could hide it and
+    /// expanding its feed's group, and focuses the items pane. Returns whether it was found.
+    fn show_item(&mut self, feed_id: &str, item_id: &str) -> bool {
+        let Some(url) = self
+            .all_feeds()
+            .find(|feed| feed.id() == feed_id)
+            .map(|feed| feed.url().to_owned())
+        else {
+            return false;
+        };
+
+        self.set_tab(Tab::Browse.index_of());
+        if self.group_filter.is_some() {
+            self.set_group_filter(None);
+        }
+        if self.unread_filter_active {
+            self.toggle_unread_filter();
+        }
+        if self.time_filter != TimeWindow::All {
+            self.set_time_filter(TimeWindow::All);
+        }
+        let group = self.group_name(&url).to_owned();
+        if self.is_group_collapsed(&group) {
+            self.toggle_group_collapse(&group);
+        }
+        let Some(row) = self
+            .feeds
+            .items()
+            .iter()
+            .position(|row| row.feed().is_some_and(|feed| feed.url() == url))
+        else {
+            return false;
+        };
+        self.feeds.state.select(Some(row));
+        self.sync_feed_selection();
+
+        // the items pane lists the feed in the chosen order
+        let Some(feed) = self.current_feed() else {
+            return false;
+        };
+        let mut items = feed
+            .items()
+            .iter()
+            .map(ItemSummary::from)
+            .collect::<Vec<_>>();
+        util::sort_items(&mut items, self.config.item_sort_order());
+        let Some(index) = items.iter().position(|item| item.id() == item_id) else {
+            return false;
+        };
+        self.items.state.select(Some(index));
+        self.sync_items_window();
+        self.scroll_items_to(index);
+        self.reset_detail_scroll();
+        self.active_view = View::SubList;
+        true
+    }
+
+    pub fn reading_queue(&self) -> &VecDeque<QueuedItem> {
+        &self.reading_queue
+    }
+
+    /// The loaded feed and item a queue entry refers to, unless either has since gone.
+    pub fn queued_item(&self, queued: &QueuedItem) -> Option<(&Feed, &Item)> {
+        let feed = self
+            .all_feeds()
+            .find(|feed| feed.id() == queued.feed_id())?;
+        let item = feed
+            .items()
+            .iter()
+            .find(|item| item.id() == queued.item_id())?;
+        Some((feed, item))
+    }
+
+    /// Adds the selected item to the back of the reading queue, unless it is queued already.
+    pub fn enqueue_item(&mut self) {
+        let target = match self.active_tab {
+            Tab::Favorites => self
+                .current_starred()
+                .map(|(_, item)| QueuedItem::new(item.feed_id(), item.id())),
+            _ => self
+                .selected_summary()
+                .map(|item| QueuedItem::new(item.feed_id(), item.id())),
+        };
+        let Some(queued) = target else {
+            return;
+        };
+
+        if self.reading_queue.contains(&queued) {
+            self.set_status("Already in the reading queue", STATUS_MESSAGE_TIME);
+            return;
+        }
+        self.reading_queue.push_back(queued);
+        self.save_queue();
+        self.set_status(
+            format!("Queued, {} to read", self.reading_queue.len()),
+            STATUS_MESSAGE_TIME,
+        );
+    }
+
+    /// Takes the frontmost item off the reading queue and shows it.
+    pub fn dequeue_item(&mut self) {
+        if self.reading_queue.is_empty() {
+            self.set_status("The reading queue is empty", STATUS_MESSAGE_TIME);
+            return;
+        }
+        self.take_queued(0);
+    }
+
+    /// Takes the item selected in the Queue tab off the reading queue and shows it.
+    pub fn open_queued_item(&mut self) {
+        if let Some(index) = self.queue_state.selected() {
+            self.take_queued(index);
+        }
+    }
+
+    /// Removes the item selected in the Queue tab from the reading queue without reading it.
+    pub fn remove_queued_item(&mut self) {
+        if let Some(index) = self.queue_state.selected() {
+            self.reading_queue.remove(index);
+            self.save_queue();
+        }
+    }
+
+    fn take_queued(&mut self, index: usize) {
+        let Some(queued) = self.reading_queue.remove(index) else {
+            return;
+        };
+        self.save_queue();
+        if self.show_item(queued.feed_id(), queued.item_id()) {
+            self.next_view(false);
+        } else {
+            self.set_status("That item is no longer cached", STATUS_MESSAGE_TIME);
+        }
+    }
+
+    /// Stores the reading queue, keeping the selection in the Queue tab within it.
+    fn save_queue(&mut self) {
+        report!(
+            self.repo.lock().save_queue(&self.reading_queue),
+            "Failed to save the reading queue"
+        );
+        let len = self.reading_queue.len();
+        let selected = self.queue_state.selected();
+        self.queue_state
+            .select(selected.filter(|_| len > 0).map(|i| i.min(len - 1)));
+    }
+
+    /// Stars the items starred on the sync server, matched by link, in storage and in the
+    /// loaded feeds.
+    fn merge_starred(&mut self, links: &[String]) {
+        match self.repo.lock().star_items_with_links(links) {
+            Ok(0) => return,
+            Ok(count) => log::info!("Starred {} items from the sync server", count),
+            Err(_) => {
+                log::error!("Failed to star synced items");
+                return;
+            }
+        }
+
+        let links = links.iter().map(String::as_str).collect::<HashSet<_>>();
+        let starred = self
+            .all_feeds()
+            .flat_map(Feed::items)
+            .filter(|item| !item.is_starred())
+            .filter(|item| item.link().is_some_and(|link| links.contains(link)))
+            .map(|item| (item.feed_id().to_owned(), item.id().to_owned()))
+            .collect::<Vec<_>>();
+        for (feed_id, item_id) in starred {
+            self.update_item(&feed_id, &item_id, |item| item.starred = true);
+        }
+        self.refresh_starred();
+    }
+
+    /// Marks the items read and starred on the sync server read and starred in the loaded
+    /// feeds, which storage already has.
+    fn merge_synced_items(&mut self, read: &[String], starred: &[String]) {
+        let read = read.iter().map(String::as_str).collect::<HashSet<_>>();
+        let starred = starred.iter().map(String::as_str).collect::<HashSet<_>>();
+        let changed = self
+            .all_feeds()
+            .flat_map(Feed::items)
+            .filter_map(|item| {
+                let read = item.is_read() || read.contains(item.id());
+                let starred = item.is_starred() || starred.contains(item.id());
+                (read != item.is_read() || starred != item.is_starred()).then(|| {
+                    (
+                        item.feed_id().to_owned(),
+                        item.id().to_owned(),
+                        read,
+                        starred,
+                    )
+                })
+            })
+            .collect::<Vec<_>>();
+        if changed.is_empty() {
+            return;
+        }
+
+        for (feed_id, item_id, read, starred) in changed {
+            self.update_item(&feed_id, &item_id, |item| {
+                item.read = read;
+                item.starred = starred;
+            });
+        }
+        self.refresh_starred();
+    }
+
+    /// Marks the items read this session read on a Miniflux server too, if one is configured.
+    /// Runs as the app quits, so failures are only logged.
+    pub async fn push_read_items(&mut self) {
+        let Some(sync) = self.config.sync() else {
+            return;
+        };
+        let client = match sync.backend() {
+            SyncBackend::Miniflux { api_key } => MinifluxClient::new(sync.base_url(), api_key),
+            // pushed as they are read
+            SyncBackend::NextcloudNews { .. } | SyncBackend::GReader { .. } => return,
+        };
+        let links = self.sync_pending.drain().collect::<Vec<_>>();
+        if links.is_empty() {
+            return;
+        }
+        match client.push_read_items(&links).await {
+            Ok(()) => log::info!("Synced {} read items", links.len()),
+            Err(err) => log::warn!("Failed to push read items: {}", err),
+        }
+    }
+
+    /// Rebuilds the starred list from the loaded feeds, keeping the selection in place.
+    fn refresh_starred(&mut self) {
+        let selected = self.starred.state.selected();
+        self.starred.items = self
+            .all_feeds()
+            .flat_map(|feed| feed.items())
+            .filter(|item| item.is_starred())
+            .map(|item| ItemRef {
+                feed_id: item.feed_id().to_owned(),
+                item_id: item.id().to_owned(),
+            })
+            .collect();
+
+        let len = self.starred.items().len();
+        self.starred
+            .state
+            .select(selected.filter(|_| len > 0).map(|i| i.min(len - 1)));
+    }
+
+    /// Applies `update` to every loaded copy of an item, and its summary in the items pane.
+    fn update_item(&mut self, feed_id: &str, item_id: &str, update: impl Fn(&mut Item)) {
+        let mut updated = None;
+        for item in self
+            .feeds
+            .items
+            .iter_mut()
+            .filter_map(GroupedFeed::feed_mut)
+            .chain(self.hidden_feeds.iter_mut())
+            .filter(|feed| feed.id() == feed_id)
+            .flat_map(|feed| feed.items.iter_mut())
+            .chain(self.current_item_detail.as_mut())
+            .filter(|item| item.feed_id() == feed_id && item.id() == item_id)
+        {
+            update(item);
+            updated = Some(ItemSummary::from(&*item));
+        }
+
+        if let Some(updated) = updated {
+            self.items
+                .loaded_mut()
+                .chain(self.timeline.items.iter_mut().map(|row| &mut row.summary))
+                .filter(|summary| summary.feed_id() == feed_id && summary.id() == item_id)
+                .for_each(|summary| *summary = updated.clone());
+        }
+    }
+
+    fn set_current_item_read(&mut self, read: bool) {
+        let (feed_id, item_id, link) = match self.selected_summary() {
+            Some(item) if item.read != read => (
+                item.feed_id().to_owned(),
+                item.id().to_owned(),
+                item.link().map(str::to_owned),
+            ),
+            _ => return,
+        };
+        if let Some(link) = link {
+            if read {
+                self.sync_pending.insert(link);
+            } else {
+                self.sync_pending.remove(&link);
+            }
+        }
+
+        if read {
+            self.repo
+                .lock()
+                .push_read(&self.config, std::slice::from_ref(&item_id));
+        }
+
+        self.update_item(&feed_id, &item_id, |item| item.read = read);
+        if read {
+            report!(
+                self.repo.lock().mark_read(&feed_id, &item_id),
+                "Failed to mark item as read"
+            );
+        } else {
+            report!(
+                self.repo.lock().mark_unread(&feed_id, &item_id),
+                "Failed to mark item as unread"
+            );
+        }
+    }
+
+    pub fn export_opml(&self, path: &Path) -> Result<()> {
+        let urls = self.config.feed_urls();
+
+        // Cached feeds keep the current sort order, anything not yet fetched follows.
+        let mut outlines = self
+            .all_feeds()
+            .filter(|feed| urls.contains(feed.url()))
+            .map(|feed| {
+                let title = if feed.title().is_empty() {
+                    feed.url()
+                } else {
+                    feed.title()
+                };
+                (title.to_owned(), feed.url().to_owned())
+            })
+            .collect::<Vec<_>>();
+
+        let mut uncached = urls
+            .iter()
+            .filter(|url| !outlines.iter().any(|(_, u)| u == *url))
+            .cloned()
+            .collect::<Vec<_>>();
+        uncached.sort();
+        outlines.extend(uncached.into_iter().map(|url| (url.clone(), url)));
+
+        fs::write(path, opml::write_document(&outlines)?)?;
+        Ok(())
+    }
+
+    /// Writes the items being browsed to a Markdown file: those of the selected feed, or every
+    /// starred item, grouped by feed, from the Favorites tab.
+    pub fn export_markdown(&self, path: &Path) -> Result<()> {
+        let mut sections: Vec<(&str, Vec<&Item>)> = vec![];
+
+        if self.active_tab == Tab::Favorites {
+            for (feed, item) in self
+                .starred
+                .items()
+                .iter()
+                .filter_map(|item_ref| self.resolve_item(item_ref))
+            {
+                match sections
+                    .iter_mut()
+                    .find(|(title, _)| *title == feed.title())
+                {
+                    Some((_, items)) => items.push(item),
+                    None => sections.push((feed.title(), vec![item])),
+                }
+            }
+        } else if let Some(feed) = self.current_feed() {
+            let items = feed
+                .items()
+                .iter()
+                .filter(|item| self.item_matches(item))
+                .collect();
+            sections.push((feed.title(), items));
+        }
+
+        fs::write(path, markdown::write_document(&sections))?;
+        Ok(())
+    }
+
+    /// Writes the items of every cached feed to a Markdown file, returning how many were written.
+    pub fn export_all_markdown(&self, path: &Path) -> Result<usize> {
+        let sections = self
+            .all_feeds()
+            .map(|feed| (feed.title(), feed.items().iter().collect::<Vec<_>>()))
+            .collect::<Vec<_>>();
+
+        fs::write(path, markdown::write_document(&sections))?;
+        Ok(sections.iter().map(|(_, items)| items.len()).sum())
+    }
+
+    /// Writes the title and link of each unread item as text, under the title of its feed.
+    pub fn write_unread(&self, out: &mut impl Write) -> io::Result<()> {
+        for feed in self.all_feeds() {
+            let unread = feed
+                .items()
+                .iter()
+                .filter(|item| !item.is_read())
+                .collect::<Vec<_>>();
+            if unread.is_empty() {
+                continue;
+            }
+            writeln!(out, "{} ({} unread)", feed.title(), unread.len())?;
+            for item in unread {
+                writeln!(out, "  {}", item.title().unwrap_or("[no title]"))?;
+                if let Some(link) = item.link() {
+                    writeln!(out, "    {}", link)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes every cached feed and its items to a file as a JSON array of JSON Feed 1.1
+    /// documents, with read and starred flags in a `_moccasin` extension on each item.
+    pub fn export_json(&self, path: &Path) -> Result<()> {
+        // loaded items leave out their HTML, so read them in full
+        let feeds = self
+            .all_feeds()
+            .map(|feed| {
+                let items = feed
+                    .items()
+                    .iter()
+                    .map(|item| {
+                        self.repo
+                            .lock()
+                            .get_item_detail(item.feed_id(), item.id())
+                            .unwrap_or_else(|_| item.clone())
+                    })
+                    .collect();
+                feed.clone().with_items(items)
+            })
+            .collect::<Vec<_>>();
+
+        let mut writer = BufWriter::new(File::create(path)?);
+        json::write_document(&mut writer, feeds.iter())?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Refreshes every feed and waits for the results, returning each feed's title along
+    /// with how many of its items were not in the database beforehand. Feeds that failed
+    /// to refresh are left in `feed_errors`.
+    pub async fn oneshot_refresh(&mut self) -> Result<Vec<(String, usize)>> {
+        let known = self
+            .all_feeds()
+            .flat_map(Feed::items)
+            .map(|item| (item.feed_id().to_owned(), item.id().to_owned()))
+            .collect::<HashSet<_>>();
+
+        self.refresh_all();
+        loop {
+            tokio::time::sleep(ONESHOT_POLL_RATE).await;
+            self.tick();
+            match &self.status {
+                Status::Loading(_, _) => {}
+                Status::Errored(err) => return Err(anyhow!(err.clone())),
+                Status::Done => break,
+            }
+        }
+
+        Ok(self
+            .all_feeds()
+            .map(|feed| {
+                let title = if feed.title().is_empty() {
+                    feed.url()
+                } else {
+                    feed.title()
+                };
+                let new_items = feed
+                    .items()
+                    .iter()
+                    .filter(|item| {
+                        !known.contains(&(item.feed_id().to_owned(), item.id().to_owned()))
+                    })
+                    .count();
+                (title.to_owned(), new_items)
+            })
+            .collect())
+    }
+
+    /// Switches to the profile `name`, with its own feeds and database, and refreshes it.
+    pub fn switch_profile(&mut self, name: &str) -> Result<()> {
+        let config = self.config.load_profile(name)?;
+        let feeds = self.open_repo(&config)?;
+        log::info!("Switched to profile {}", name);
+        self.set_status(format!("Switched to profile {}", name), STATUS_MESSAGE_TIME);
+
+        self.keymap = KeyMap::from_config(&config);
+        self.symbols = symbol_set(&config);
+        self.config = config;
+        self.share_config();
+        self.status = Status::Done;
+        self.feed_errors.clear();
+        self.pending_feed_errors.clear();
+        self.collapsed_groups.clear();
+        self.hidden_feeds.clear();
+        self.feeds.state.select(None);
+        self.items = VirtualList::default();
+        self.active_view = View::MainList;
+        self.reset_items_scroll();
+        self.reset_detail_scroll();
+        self.set_feeds(feeds);
+        if self.active_tab == Tab::Stats {
+            self.refresh_stats();
+        }
+
+        self.refresh_all();
+        Ok(())
+    }
+
+    /// Reads the config files again and refreshes every feed. Feeds no longer listed leave
+    /// the feeds pane, but their items stay in the database.
+    pub fn reload_config(&mut self) -> Result<()> {
+        self.read_config()?;
+        self.set_status("Reloaded config", STATUS_MESSAGE_TIME);
+        self.refresh_all();
+        Ok(())
+    }
+
+    /// Reads the config files again, returning whether the list of feeds changed.
+    fn read_config(&mut self) -> Result<bool> {
+        let config = self.config.load_profile(self.config.profile())?;
+        let reopen = config.db_path() != self.config.db_path()
+            || config.should_cache() != self.config.should_cache()
+            || config.refreshes_automatically() != self.config.refreshes_automatically();
+        let feeds_changed = config.feed_urls() != self.config.feed_urls();
+
+        let feeds = if reopen {
+            self.open_repo(&config)?
+        } else {
+            self.all_feeds().cloned().collect()
+        };
+        let feeds = feeds
+            .into_iter()
+            .filter(|feed| config.feed_urls().contains(feed.url()))
+            .collect();
+        log::info!(
+            "Reloaded config from {}",
+            config.config_file_path().display()
+        );
+
+        self.keymap = KeyMap::from_config(&config);
+        self.symbols = symbol_set(&config);
+        self.config = config;
+        self.share_config();
+        if matches!(self.status, Status::Errored(_)) {
+            self.status = Status::Done;
+        }
+        self.set_feeds(feeds);
+        if self.active_tab == Tab::Stats {
+            self.refresh_stats();
+        }
+        Ok(reopen || feeds_changed)
+    }
+
+    /// Sends a desktop notification naming the feeds with unread items in `feeds` that
+    /// weren't loaded before this refresh.
+    fn notify_new_items(&self, feeds: &[Feed]) {
+        let known = self
+            .all_feeds()
+            .flat_map(Feed::items)
+            .map(|item| (item.feed_id(), item.id()))
+            .collect::<HashSet<_>>();
+
+        let (mut count, mut titles) = (0, vec![]);
+        for feed in feeds {
+            let new_items = feed
+                .items()
+                .iter()
+                .filter(|item| !item.is_read() && !known.contains(&(item.feed_id(), item.id())))
+                .count();
+            if new_items > 0 {
+                count += new_items;
+                titles.push(feed.title());
+            }
+        }
+        if count == 0 {
+            return;
+        }
+
+        let mut body = titles[..titles.len().min(MAX_NOTIFIED_FEEDS)].join(", ");
+        if titles.len() > MAX_NOTIFIED_FEEDS {
+            body.push_str(&format!(" and {} more", titles.len() - MAX_NOTIFIED_FEEDS));
+        }
+        let summary = match count {
+            1 => "moccasin — 1 new item".to_owned(),
+            count => format!("moccasin — {} new items", count),
+        };
+        notification::send(&summary, &body);
+    }
+
+    /// Opens the database for `config` on a new channel, returning the feeds cached there.
+    fn open_repo(&mut self, config: &Config) -> Result<Vec<Feed>> {
+        let (tx, rx) = mpsc::unbounded_channel::<RepositoryEvent>();
+        let config_watcher = watch_config(config, tx.clone());
+        let mut repo = Repository::init(config, tx)?;
+        let feeds = repo.read_all(config).unwrap_or_default();
+        self.reading_queue = repo.load_queue().unwrap_or_default();
+        self.queue_state = ListState::default();
+
+        self.repo.replace(repo);
+        self.repo_rx = rx;
+        self.config_watcher = config_watcher;
+        Ok(feeds)
+    }
+
+    pub fn refresh_all(&mut self) {
+        self.refreshing_feed = None;
+        self.repo.lock().refresh_all(&self.config)
+    }
+
+    /// Fetches only the selected feed. While another refresh is running, that one goes on
+    /// instead.
+    pub fn refresh_current_feed(&mut self) -> Result<()> {
+        let url = self
+            .current_feed()
+            .map(|feed| feed.url().to_owned())
+            .ok_or_else(|| anyhow!("no feed selected to refresh"))?;
+        if self.repo.lock().is_refreshing() {
+            self.set_status("A refresh is already running", STATUS_MESSAGE_TIME);
+            return Ok(());
+        }
+        self.repo.lock().refresh_one(&url, &self.config);
+        self.refreshing_feed = Some(url);
+        Ok(())
+    }
+
+    /// The title of the feed being refreshed on its own.
+    pub fn refreshing_feed_title(&self) -> Option<&str> {
+        let url = self.refreshing_feed.as_deref()?;
+        self.all_feeds()
+            .find(|feed| feed.url() == url)
+            .map(Feed::title)
+    }
+
+    /// Fetches the feed at `url`, or the feeds a page at `url` links to, and adds it to the
+    /// config once it loads. Fails without a request if `url` is not an HTTP(S) URL or is
+    /// already subscribed.
+    pub fn add_feed_url(&mut self, url: &str) -> Result<()> {
+        let parsed =
+            reqwest::Url::parse(url).map_err(|err| anyhow!("invalid URL {}: {}", url, err))?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return Err(anyhow!("invalid URL {}: not an http(s) URL", url));
+        }
+        if self.config.feed_urls().contains(url) {
+            return Err(anyhow!("already subscribed to {}", url));
+        }
+
+        self.repo.lock().add_feed_url(url, &self.config, true);
+        Ok(())
+    }
+
+    /// Gives the selected feed the next color of the palette in the feeds pane, and saves it
+    /// to the config.
+    pub fn cycle_feed_color(&mut self) -> Result<()> {
+        let Some(url) = self.current_feed().map(|feed| feed.url().to_owned()) else {
+            return Ok(());
+        };
+        let color = Color::next_in_palette(self.config.feed_color(&url));
+        self.config.set_feed_color(&url, color)?;
+        self.share_config();
+
+        let name = color.map_or_else(|| "default".to_owned(), |color| color.to_string());
+        self.set_status(format!("Feed color: {}", name), STATUS_MESSAGE_TIME);
+        Ok(())
+    }
+
+    /// Saves HTTP Basic credentials for the feed at `url` and refetches it with them.
+    pub fn set_feed_credentials(
+        &mut self,
+        url: &str,
+        username: &str,
+        password: &str,
+    ) -> Result<()> {
+        self.config.set_feed_credentials(url, username, password)?;
+        self.share_config();
+        self.repo.lock().add_feed_url(url, &self.config, false);
+        Ok(())
+    }
+
+    pub fn should_render_feed_candidates(&self) -> bool {
+        !self.feed_candidates.items().is_empty()
+    }
+
+    pub fn next_feed_candidate(&mut self) {
+        self.feed_candidates.next();
+    }
+
+    pub fn prev_feed_candidate(&mut self) {
+        self.feed_candidates.previous();
+    }
+
+    pub fn add_feed_candidate(&mut self) {
+        if let Some(url) = self
+            .feed_candidates
+            .state
+            .selected()
+            .and_then(|i| self.feed_candidates.items.get(i))
+            .cloned()
+        {
+            self.repo.lock().add_feed_url(&url, &self.config, false);
+        }
+        self.dismiss_feed_candidates();
+    }
+
+    pub fn dismiss_feed_candidates(&mut self) {
+        self.feed_candidates = StatefulList::default();
+    }
+
+    /// Shows the setup wizard in place of the feeds, with the refresh interval filled in.
+    pub fn start_wizard(&mut self) {
+        let interval = match self.config.refresh_interval() {
+            0 => "0".to_owned(),
+            secs => util::format_duration(Duration::from_secs(secs)),
+        };
+        self.wizard = Some(Wizard::new(&interval));
+        self.active_view = View::Wizard;
+    }
+
+    pub fn should_render_wizard(&self) -> bool {
+        self.active_view == View::Wizard && self.wizard.is_some()
+    }
+
+    pub fn wizard_push(&mut self, c: char) {
+        if let Some(input) = self.wizard.as_mut().and_then(Wizard::input_mut) {
+            input.input.push(c);
+            input.cursor_position += 1;
+        }
+        if let Some(wizard) = &mut self.wizard {
+            wizard.checking = None;
+        }
+    }
+
+    pub fn wizard_pop(&mut self) {
+        if let Some(input) = self.wizard.as_mut().and_then(Wizard::input_mut) {
+            if input.input.pop().is_some() {
+                input.cursor_position -= 1;
+            }
+        }
+        if let Some(wizard) = &mut self.wizard {
+            wizard.checking = None;
+        }
+    }
+
+    pub fn next_wizard_theme(&mut self) {
+        if let Some(wizard) = &mut self.wizard {
+            let i = wizard.theme.selected().map_or(0, |i| i + 1);
+            wizard.theme.select(Some(i % BUILT_IN_THEMES.len()));
+        }
+    }
+
+    pub fn prev_wizard_theme(&mut self) {
+        if let Some(wizard) = &mut self.wizard {
+            let i = wizard.theme.selected().unwrap_or(0);
+            wizard
+                .theme
+                .select(Some(i.checked_sub(1).unwrap_or(BUILT_IN_THEMES.len() - 1)));
+        }
+    }
+
+    /// Accepts the answer to the current step: the feed URL once it has been reached, and the
+    /// refresh interval if it can be read, which finishes the setup.
+    pub fn submit_wizard_step(&mut self) {
+        let Some(wizard) = &mut self.wizard else {
+            return;
+        };
+        wizard.error = None;
+        match wizard.step {
+            WizardStep::FeedUrl => {
+                let url = wizard.feed_url.input.trim().to_owned();
+                match reqwest::Url::parse(&url) {
+                    Ok(parsed) if matches!(parsed.scheme(), "http" | "https") => {
+                        if wizard.checking.is_none() {
+                            wizard.checking = Some(url.clone());
+                            self.repo.lock().check_feed_url(&url, &self.config);
+                        }
+                    }
+                    _ => wizard.error = Some("Enter an http:// or https:// URL".into()),
+                }
+            }
+            WizardStep::Theme => wizard.step = WizardStep::RefreshInterval,
+            WizardStep::RefreshInterval => match wizard.refresh_interval() {
+                Some(_) => self.finish_wizard(),
+                None => {
+                    wizard.error =
+                        Some("Enter a number of seconds, or a span like 30m or 2h".into())
+                }
+            },
+        }
+    }
+
+    /// Moves back a step, or leaves the setup from the first, keeping the config as it is.
+    pub fn wizard_back(&mut self) {
+        let Some(wizard) = &mut self.wizard else {
+            return;
+        };
+        wizard.error = None;
+        wizard.checking = None;
+        match wizard.step {
+            WizardStep::FeedUrl => {
+                self.wizard = None;
+                self.active_view = View::MainList;
+                self.set_status(
+                    "Skipped setup, run with --setup to set up",
+                    SYNC_WARNING_TIME,
+                );
+            }
+            WizardStep::Theme => wizard.step = WizardStep::FeedUrl,
+            WizardStep::RefreshInterval => wizard.step = WizardStep::Theme,
+        }
+    }
+
+    /// Moves on from the feed URL once it is reached, unless it has been edited since.
+    fn wizard_url_checked(&mut self, url: &str, reason: Option<String>) {
+        let Some(wizard) = &mut self.wizard else {
+            return;
+        };
+        if wizard.checking.as_deref() != Some(url) {
+            return;
+        }
+        wizard.checking = None;
+        match reason {
+            Some(reason) => wizard.error = Some(format!("Could not reach {}: {}", url, reason)),
+            None => wizard.step = WizardStep::Theme,
+        }
+    }
+
+    /// Writes the answers to the config file and reads it again, refreshing the new feed.
+    fn finish_wizard(&mut self) {
+        let Some(wizard) = self.wizard.take() else {
+            return;
+        };
+        self.active_view = View::MainList;
+        let interval = wizard.refresh_interval().unwrap_or_default();
+        let written = self
+            .config
+            .write_setup(wizard.feed_url.input.trim(), wizard.theme_name(), interval)
+            .and_then(|_| self.reload_config());
+        match written {
+            Ok(()) => self.set_status(
+                format!(
+                    "Saved setup to {}",
+                    self.config.config_file_path().display()
+                ),
+                STATUS_MESSAGE_TIME,
+            ),
+            Err(err) => self.status = Status::Errored(err.to_string()),
+        }
+    }
+
+    pub fn should_render_delete_prompt(&self) -> bool {
+        self.pending_delete.is_some()
+    }
+
+    /// The feed the delete prompt asks about.
+    pub fn pending_delete_feed(&self) -> Option<&Feed> {
+        self.pending_delete
+            .and_then(|i| self.feeds.items().get(i))
+            .and_then(GroupedFeed::feed)
+    }
+
+    /// Asks to confirm deleting the selected feed.
+    pub fn prompt_delete_feed(&mut self) {
+        if self.current_feed().is_some() {
+            self.pending_delete = self.feeds.state.selected();
+        }
+    }
+
+    pub fn confirm_delete_feed(&mut self) {
+        if let Some(index) = self.pending_delete.take() {
+            if let Err(err) = self.delete_feed(index) {
+                self.status = Status::Errored(err.to_string());
+            }
+        }
+    }
+
+    pub fn dismiss_delete_prompt(&mut self) {
+        self.pending_delete = None;
+    }
+
+    /// Unsubscribes from the feed at row `index` and deletes it from the database, then
+    /// selects the feed before it, or the first feed when it was the first.
+    pub fn delete_feed(&mut self, index: usize) -> Result<()> {
+        let rows = self.feeds.items();
+        let url = rows
+            .get(index)
+            .and_then(GroupedFeed::feed)
+            .map(|feed| feed.url().to_owned())
+            .ok_or_else(|| anyhow!("no feed to delete"))?;
+        let neighbour = rows[..index]
+            .iter()
+            .rev()
+            .chain(&rows[index + 1..])
+            .find_map(GroupedFeed::feed)
+            .map(|feed| feed.url().to_owned());
+
+        self.remove_feed(&url)?;
+
+        let position = neighbour.and_then(|url| {
+            self.feeds
+                .items()
+                .iter()
+                .position(|row| row.feed().is_some_and(|feed| feed.url() == url))
+        });
+        self.feeds.state.select(position);
+        self.sync_feed_selection();
+        Ok(())
+    }
+
+    /// Removes the feed at `url` from the config, the database and the feeds pane.
+    fn remove_feed(&mut self, url: &str) -> Result<()> {
+        self.config.remove_feed_url(url)?;
+        self.share_config();
+        self.repo
+            .lock()
+            .remove_feed_url(url)
+            .map_err(|_| anyhow!("failed to delete {} from the database", url))?;
+
+        let mut feeds = self.take_feeds();
+        feeds.retain(|feed| feed.url() != url);
+        self.feeds.state.select(None);
+        self.set_feeds(feeds);
+        self.items.reset(0);
+        self.reset_items_scroll();
+        self.reset_detail_scroll();
+        self.set_status(format!("Deleted {}", url), STATUS_MESSAGE_TIME);
+        Ok(())
+    }
+
+    pub fn should_render_item_filter(&self) -> bool {
+        self.item_filter.show_input
+    }
+
+    pub fn has_item_filter(&self) -> bool {
+        self.item_filter_regex.is_some()
+    }
+
+    pub fn open_item_filter(&mut self) {
+        self.item_filter.show_input = true;
+    }
+
+    /// Stops editing the filter, keeping the items it matched.
+    pub fn close_item_filter(&mut self) {
+        self.item_filter.show_input = false;
+    }
+
+    /// Removes the filter, showing all of the current feed's items again.
+    pub fn clear_item_filter(&mut self) {
+        self.item_filter = InputState::new();
+        self.apply_item_filter("");
+    }
+
+    pub fn item_filter_push(&mut self, c: char) {
+        self.item_filter.input.push(c);
+        self.item_filter.cursor_position += 1;
+        self.apply_item_filter(&self.item_filter.input.clone());
+    }
+
+    pub fn item_filter_pop(&mut self) {
+        if self.item_filter.input.pop().is_some() {
+            self.item_filter.cursor_position -= 1;
+            self.apply_item_filter(&self.item_filter.input.clone());
+        }
+    }
+
+    /// Narrows the items list to the current feed's items whose title or description matches
+    /// `pattern`, ignoring case. An empty pattern shows every item, and one that doesn't compile
+    /// yet, like an unclosed group while it is typed, leaves the list as it was.
+    pub fn apply_item_filter(&mut self, pattern: &str) {
+        self.item_filter_regex = if pattern.is_empty() {
+            None
+        } else {
+            match RegexBuilder::new(pattern).case_insensitive(true).build() {
+                Ok(regex) => Some(regex),
+                Err(_) => return,
+            }
+        };
+        self.last_search = self.item_filter_regex.clone();
+        self.preset_keyword = None;
+        self.active_preset = None;
+
+        if self.current_feed().is_none() {
+            return;
+        }
+        self.items.state.select(Some(0));
+        self.reload_items();
+        self.items_scroll = self.items_scroll.position(0);
+        self.reset_detail_scroll();
+    }
+
+    /// Sets the filter back to the preset's keyword, or to nothing, without listing the items
+    /// again.
+    fn reset_item_filter(&mut self) {
+        let keyword = self.preset_keyword.clone().unwrap_or_default();
+        self.item_filter = InputState::new();
+        self.item_filter.cursor_position = keyword.chars().count();
+        self.item_filter_regex = RegexBuilder::new(&keyword)
+            .case_insensitive(true)
+            .build()
+            .ok()
+            .filter(|_| !keyword.is_empty());
+        self.item_filter.input = keyword;
+    }
+
+    pub fn item_matches(&self, item: &Item) -> bool {
+        let Some(regex) = &self.item_filter_regex else {
+            return true;
+        };
+        [item.title(), item.description()]
+            .into_iter()
+            .flatten()
+            .any(|text| regex.is_match(text))
+    }
+
+    pub fn should_render_feed_filter(&self) -> bool {
+        self.feed_filter.show_input
+    }
+
+    pub fn has_feed_filter(&self) -> bool {
+        self.feed_filter_query.is_some()
+    }
+
+    pub fn open_feed_filter(&mut self) {
+        self.feed_filter.show_input = true;
+    }
+
+    /// Stops editing the filter, keeping the feeds it matched.
+    pub fn close_feed_filter(&mut self) {
+        self.feed_filter.show_input = false;
+    }
+
+    /// Removes the filter, listing every feed in its group again.
+    pub fn clear_feed_filter(&mut self) {
+        self.feed_filter = InputState::new();
+        self.fuzzy_filter_feeds("");
+    }
+
+    pub fn feed_filter_push(&mut self, c: char) {
+        self.feed_filter.input.push(c);
+        self.feed_filter.cursor_position += 1;
+        self.fuzzy_filter_feeds(&self.feed_filter.input.clone());
+    }
+
+    pub fn feed_filter_pop(&mut self) {
+        if self.feed_filter.input.pop().is_some() {
+            self.feed_filter.cursor_position -= 1;
+            self.fuzzy_filter_feeds(&self.feed_filter.input.clone());
+        }
+    }
+
+    /// Lists only the feeds whose title, or failing that URL, fuzzily matches `query`, best
+    /// first and out of their groups, selecting the best. An empty query lists every feed,
+    /// keeping the one selected.
+    pub fn fuzzy_filter_feeds(&mut self, query: &str) {
+        let selected = self.current_feed().map(|feed| feed.url().to_owned());
+        self.feed_filter_query = Some(query.to_owned()).filter(|query| !query.is_empty());
+        let feeds = self.take_feeds();
+        self.set_feeds(feeds);
+
+        let position = if self.has_feed_filter() {
+            Some(0).filter(|_| !self.feeds.items().is_empty())
+        } else {
+            selected.and_then(|url| {
+                self.feeds
+                    .items()
+                    .iter()
+                    .position(|row| row.feed().is_some_and(|feed| feed.url() == url))
+            })
+        };
+        if position.is_some() {
+            self.feeds.state.select(position);
+            self.reload_items();
+        }
+        self.feeds_scroll = self.feeds_scroll.position(position.unwrap_or(0) as u16);
+    }
+
+    /// How well `feed` matches the feed filter, or `None` if it doesn't.
+    fn feed_filter_score(&self, feed: &Feed) -> Option<i64> {
+        let query = self.feed_filter_query.as_deref()?;
+        util::fuzzy_match(query, feed.title())
+            .or_else(|| util::fuzzy_match(query, feed.url()))
+            .map(|(score, _)| score)
+    }
+
+    /// The positions of the characters of `title` matched by the feed filter, to highlight.
+    pub fn feed_filter_matches(&self, title: &str) -> Vec<usize> {
+        self.feed_filter_query
+            .as_deref()
+            .and_then(|query| util::fuzzy_match(query, title))
+            .map(|(_, positions)| positions)
+            .unwrap_or_default()
+    }
+
+    pub fn should_render_list_search(&self) -> bool {
+        self.list_search.show_input
+    }
+
+    pub fn has_last_search(&self) -> bool {
+        self.last_search.is_some()
+    }
+
+    /// Starts a search of the feeds pane, which jumps to the feeds it matches as it is typed.
+    pub fn open_list_search(&mut self) {
+        self.clear_last_search();
+        self.list_search.show_input = true;
+    }
+
+    /// Stops editing the search, keeping it for `n` and `N`.
+    pub fn close_list_search(&mut self) {
+        self.list_search.show_input = false;
+    }
+
+    /// Forgets the search, as when the focus moves to another pane. The items pane's filter
+    /// is kept.
+    pub fn clear_last_search(&mut self) {
+        self.list_search = InputState::new();
+        self.last_search = None;
+    }
+
+    pub fn list_search_push(&mut self, c: char) {
+        self.list_search.input.push(c);
+        self.list_search.cursor_position += 1;
+        self.apply_list_search();
+    }
+
+    pub fn list_search_pop(&mut self) {
+        if self.list_search.input.pop().is_some() {
+            self.list_search.cursor_position -= 1;
+            self.apply_list_search();
+        }
+    }
+
+    /// Selects the first feed from the selected one on whose title the pattern typed so far
+    /// matches, ignoring case. Like the items filter, a pattern that doesn't compile yet
+    /// leaves the selection where it was.
+    fn apply_list_search(&mut self) {
+        let pattern = &self.list_search.input;
+        self.last_search = if pattern.is_empty() {
+            None
+        } else {
+            match RegexBuilder::new(pattern).case_insensitive(true).build() {
+                Ok(regex) => Some(regex),
+                Err(_) => return,
+            }
+        };
+
+        let matches = self.search_matches();
+        let current = self.feeds.state.selected().unwrap_or(0);
+        if let Some(&index) = matches
+            .iter()
+            .find(|&&index| index >= current)
+            .or(matches.first())
+        {
+            self.select_match(index);
+        }
+    }
+
+    /// Moves the selection to the next or previous row the last search matches, wrapping
+    /// around the ends of the list.
+    pub fn find_next(&mut self, direction: Direction) {
+        let matches = self.search_matches();
+        let selected = match self.active_view {
+            View::MainList => self.feeds.state.selected(),
+            View::SubList => self.items.state.selected(),
+            View::Detail | View::Wizard => return,
+        };
+        let found = match (direction, selected) {
+            (Direction::Forward, Some(current)) => matches
+                .iter()
+                .find(|&&index| index > current)
+                .or(matches.first()),
+            (Direction::Backward, Some(current)) => matches
+                .iter()
+                .rev()
+                .find(|&&index| index < current)
+                .or(matches.last()),
+            (Direction::Forward, None) => matches.first(),
+            (Direction::Backward, None) => matches.last(),
+        };
+        match found {
+            Some(&index) => self.select_match(index),
+            None => self.set_status("No matches", STATUS_MESSAGE_TIME),
+        }
+    }
+
+    /// Where the selection is among the last search's matches, counting from 1, and how many
+    /// there are, for the status bar.
+    pub fn search_position(&self) -> Option<(Option<usize>, usize)> {
+        self.last_search.as_ref()?;
+        let matches = self.search_matches();
+        let selected = match self.active_view {
+            View::MainList => self.feeds.state.selected(),
+            View::SubList => self.items.state.selected(),
+            View::Detail | View::Wizard => None,
+        };
+        let position = selected.and_then(|selected| {
+            matches
+                .iter()
+                .position(|&index| index == selected)
+                .map(|position| position + 1)
+        });
+        Some((position, matches.len()))
+    }
+
+    /// The rows of the focused pane that the last search matches, in order.
+    fn search_matches(&self) -> Vec<usize> {
+        let Some(regex) = &self.last_search else {
+            return vec![];
+        };
+        match self.active_view {
+            View::MainList => self
+                .feeds
+                .items()
+                .iter()
+                .enumerate()
+                .filter(|(_, row)| row.feed().is_some_and(|feed| regex.is_match(feed.title())))
+                .map(|(index, _)| index)
+                .collect(),
+            // the search is the items filter, so the items pane lists only its matches
+            View::SubList => (0..self.items.len()).collect(),
+            View::Detail | View::Wizard => vec![],
+        }
+    }
+
+    fn select_match(&mut self, index: usize) {
+        match self.active_view {
+            View::MainList => {
+                self.feeds.state.select(Some(index));
+                self.sync_feed_selection();
+            }
+            View::SubList => {
+                self.items.state.select(Some(index));
+                self.sync_items_window();
+                self.reset_detail_scroll();
+                self.scroll_items_to(index);
+            }
+            View::Detail | View::Wizard => {}
+        }
+    }
+
+    /// The config for the API server to read, which follows this one as it changes.
+    pub fn shared_config(&self) -> SharedConfig {
+        self.shared_config.clone()
+    }
+
+    fn share_config(&self) {
+        self.shared_config.replace(self.config.clone());
+    }
+
+    pub fn toggle_keybinds(&mut self) {
+        self.show_keybinds = !self.show_keybinds;
+    }
+
+    pub fn toggle_console(&mut self, cmd: Option<&str>) {
+        if let Some(cmd) = cmd {
+            self.command_state.input = cmd.into();
+            self.command_state.cursor_position = self.clamp_cursor(cmd.len());
+        } else {
+            self.command_state.input.clear();
+            self.reset_cursor();
+        }
+        self.command_state.show_input = !self.command_state.show_input;
+    }
+
+    pub fn move_cursor_left(&mut self) {
+        let cursor_moved_left = self.command_state.cursor_position.saturating_sub(1);
+        self.command_state.cursor_position = self.clamp_cursor(cursor_moved_left);
+    }
+
+    pub fn move_cursor_right(&mut self) {
+        let cursor_moved_right = self.command_state.cursor_position.saturating_add(1);
+        self.command_state.cursor_position = self.clamp_cursor(cursor_moved_right);
+    }
+
+    pub fn enter_char(&mut self, new_char: char) {
+        self.command_state
+            .input
+            .insert(self.command_state.cursor_position, new_char);
+        self.move_cursor_right();
+    }
+
+    pub fn delete_char(&mut self) {
+        let is_not_cursor_leftmost = self.command_state.cursor_position != 0;
+        if is_not_cursor_leftmost {
+            // Method "remove" is not used on the saved text for deleting the selected char.
+            // Reason: Using remove on String works on bytes instead of the chars.
+            // Using remove would require special care because of char boundaries.
+
+            let current_index = self.command_state.cursor_position;
+            let from_left_to_current_index = current_index - 1;
+
+            // Getting all characters before the selected character.
+            let before_char_to_delete = self
+                .command_state
+                .input
+                .chars()
+                .take(from_left_to_current_index);
+            // Getting all characters after selected character.
+            let after_char_to_delete = self.command_state.input.chars().skip(current_index);
+
+            // Put all characters together except the selected one.
+            // By leaving the selected one out, it is forgotten and therefore deleted.
+            self.command_state.input = before_char_to_delete.chain(after_char_to_delete).collect();
+            self.move_cursor_left();
+        }
+    }
+
+    fn clamp_cursor(&self, new_cursor_pos: usize) -> usize {
+        new_cursor_pos.clamp(0, self.command_state.input.len())
+    }
+
+    fn reset_cursor(&mut self) {
+        self.command_state.cursor_position = 0;
+    }
+
+    pub fn submit_command(&mut self) {
+        match self.command_state.input.parse::<ConsoleCommand>() {
+            Ok(ConsoleCommand::AddFeed(url)) => {
+                if let Err(err) = self.add_feed_url(&url) {
+                    self.status = Status::Errored(err.to_string());
+                }
             }
             Ok(ConsoleCommand::DeleteFeed(maybe_url)) => {
                 if let Some(url) =
                     maybe_url.or(self.current_feed().and_then(|f| Some(f.url().into())))
                 {
-                    self.config.remove_feed_url(&url);
-                    self.repo.remove_feed_url(&url);
+                    if let Err(err) = self.remove_feed(&url) {
+                        self.status = Status::Errored(err.to_string());
+                    }
+                }
+            }
+            Ok(ConsoleCommand::SetCredentials(username, password)) => {
+                if let Some(url) = self.current_feed().map(|feed| feed.url().to_owned()) {
+                    if let Err(err) = self.set_feed_credentials(&url, &username, &password) {
+                        self.status = Status::Errored(err.to_string());
+                    }
+                }
+            }
+            Ok(ConsoleCommand::Export(path)) => match self.export_markdown(&path) {
+                Ok(()) => self.set_status(
+                    format!("Exported to {}", path.display()),
+                    STATUS_MESSAGE_TIME,
+                ),
+                Err(err) => self.status = Status::Errored(err.to_string()),
+            },
+            Ok(ConsoleCommand::SwitchProfile(name)) => {
+                if let Err(err) = self.switch_profile(&name) {
+                    self.status = Status::Errored(err.to_string());
+                }
+            }
+            Ok(ConsoleCommand::Search(query)) => self.open_search(Some(&query)),
+            Ok(ConsoleCommand::TimeFilter(duration)) => {
+                self.set_time_filter(TimeWindow::Custom(duration))
+            }
+            Ok(ConsoleCommand::SavePreset(name)) => {
+                if let Err(err) = self.save_current_filters_as_preset(&name) {
+                    self.status = Status::Errored(err.to_string());
+                }
+            }
+            _ => self.status = Status::Errored("unrecognized command".into()),
+        }
+
+        self.command_state.input.clear();
+        self.reset_cursor();
+        self.toggle_console(None);
+    }
+
+    /// Every feed, including those hidden in collapsed groups.
+    pub fn all_feeds(&self) -> impl Iterator<Item = &Feed> {
+        self.feeds
+            .items()
+            .iter()
+            .filter_map(GroupedFeed::feed)
+            .chain(self.hidden_feeds.iter())
+    }
+
+    /// The name of the focused pane, or of the tab when it has only one, for the status bar.
+    pub fn active_view_name(&self) -> &'static str {
+        match (&self.active_tab, &self.active_view) {
+            (Tab::Browse, View::MainList) => "Feeds",
+            (Tab::Browse, View::SubList) => "Items",
+            (Tab::Browse, View::Detail) => "Item",
+            (_, View::Wizard) => "Setup",
+            (Tab::Favorites, _) => "Starred",
+            (Tab::Tags, _) => "Tags",
+            (Tab::Stats, _) => "Stats",
+            (Tab::Timeline, _) => "Timeline",
+            (Tab::Search, _) => "Search",
+            (Tab::Queue, _) => "Queue",
+        }
+    }
+
+    /// Unread items across every feed.
+    pub fn unread_count(&self) -> usize {
+        self.all_feeds().map(Feed::unread_count).sum()
+    }
+
+    /// How many feeds the unread, group and feed filters let through, while any of them
+    /// applies. Feeds in collapsed groups still count.
+    pub fn filtered_feed_count(&self) -> Option<usize> {
+        if !self.unread_filter_active && self.group_filter.is_none() && !self.has_feed_filter() {
+            return None;
+        }
+        let count = self
+            .all_feeds()
+            .filter(|feed| !self.unread_filter_active || self.feed_unread_count(feed) > 0)
+            .filter(|feed| self.in_group_filter(feed.url()))
+            .filter(|feed| !self.has_feed_filter() || self.feed_filter_score(feed).is_some())
+            .count();
+        Some(count)
+    }
+
+    /// Whether any unread item matches the `highlights` preference.
+    pub fn has_highlighted_items(&self) -> bool {
+        let highlights = self.config.highlights();
+        !highlights.is_empty()
+            && self
+                .all_feeds()
+                .flat_map(Feed::items)
+                .any(|item| !item.is_read() && highlights.matches(item))
+    }
+
+    pub fn feed_health(&self, feed: &Feed) -> HealthStatus {
+        feed.health(self.config.dead_after())
+    }
+
+    /// Feeds whose recent refreshes succeeded.
+    pub fn filter_healthy(&self) -> impl Iterator<Item = &Feed> {
+        self.all_feeds()
+            .filter(|feed| self.feed_health(feed) == HealthStatus::Healthy)
+    }
+
+    /// Feeds that are `Degraded` or `Dead`.
+    pub fn filter_unhealthy(&self) -> impl Iterator<Item = &Feed> {
+        self.all_feeds()
+            .filter(|feed| self.feed_health(feed) != HealthStatus::Healthy)
+    }
+
+    pub fn is_group_collapsed(&self, name: &str) -> bool {
+        self.collapsed_groups.contains(name)
+    }
+
+    /// The number of feeds hidden under a collapsed group.
+    pub fn hidden_feed_count(&self, name: &str) -> usize {
+        self.hidden_feeds
+            .iter()
+            .filter(|feed| self.group_name(feed.url()) == name)
+            .filter(|feed| self.in_group_filter(feed.url()))
+            .filter(|feed| !self.unread_filter_active || self.feed_unread_count(feed) > 0)
+            .count()
+    }
+
+    pub fn current_group(&self) -> Option<String> {
+        if self.config.groups().is_empty() {
+            return None;
+        }
+        self.current_feed()
+            .map(|feed| self.group_name(feed.url()).to_owned())
+    }
+
+    pub fn toggle_group_collapse(&mut self, group_name: &str) {
+        if !self.collapsed_groups.remove(group_name) {
+            self.collapsed_groups.insert(group_name.to_owned());
+        }
+        let feeds = self.take_feeds();
+        self.set_feeds(feeds);
+    }
+
+    /// Expands every group if any is collapsed, otherwise collapses them all.
+    pub fn toggle_all_groups(&mut self) {
+        if self.collapsed_groups.is_empty() {
+            self.collapsed_groups = self
+                .feeds
+                .items()
+                .iter()
+                .filter_map(GroupedFeed::header)
+                .map(str::to_owned)
+                .collect();
+        } else {
+            self.collapsed_groups.clear();
+        }
+        let feeds = self.take_feeds();
+        self.set_feeds(feeds);
+    }
+
+    fn group_name(&self, url: &str) -> &str {
+        self.config
+            .group_index(url)
+            .map(|i| self.config.groups()[i].name())
+            .unwrap_or(util::UNGROUPED)
+    }
+
+    fn take_feeds(&mut self) -> Vec<Feed> {
+        std::mem::take(&mut self.feeds.items)
+            .into_iter()
+            .filter_map(GroupedFeed::into_feed)
+            .chain(self.hidden_feeds.drain(..))
+            .collect()
+    }
+
+    pub fn is_unread_filter_active(&self) -> bool {
+        self.unread_filter_active
+    }
+
+    pub fn content_mode(&self) -> ContentMode {
+        self.content_mode
+    }
+
+    /// Switches the detail pane between an item's full content and its summary, if it has
+    /// both. The choice holds for the items shown after it.
+    pub fn toggle_content_mode(&mut self) {
+        if !self
+            .current_item_detail
+            .as_ref()
+            .is_some_and(Item::has_content_and_summary)
+        {
+            return;
+        }
+        self.content_mode = match self.content_mode {
+            ContentMode::Full => ContentMode::Summary,
+            ContentMode::Summary => ContentMode::Full,
+        };
+        self.detail_scroll_index = 0;
+        self.detail_scroll = self.detail_scroll.position(0);
+    }
 
-                    // TODO: refactor, this is so bad
-                    self.feeds.items.retain(|u| u.url() != url);
-                    self.feeds.state.select(None);
-                    self.reset_items_scroll();
-                    self.reset_detail_scroll();
+    /// Hides read items, and feeds without unread items, or shows them again.
+    pub fn toggle_unread_filter(&mut self) {
+        self.unread_filter_active = !self.unread_filter_active;
+        self.active_preset = None;
+        let feeds = self.take_feeds();
+        self.set_feeds(feeds);
+        if self.current_feed().is_some() {
+            self.items.state.select(Some(0));
+            self.reload_items();
+        }
+    }
+
+    pub fn time_filter(&self) -> TimeWindow {
+        self.time_filter
+    }
+
+    /// Lists only the items published within `window`, along with the unread filter if
+    /// that is active too.
+    pub fn set_time_filter(&mut self, window: TimeWindow) {
+        self.time_filter = window;
+        self.active_preset = None;
+        let feeds = self.take_feeds();
+        self.set_feeds(feeds);
+        if self.current_feed().is_some() {
+            self.items.state.select(Some(0));
+            self.reload_items();
+        }
+    }
+
+    pub fn cycle_time_filter(&mut self) {
+        self.set_time_filter(self.time_filter.next());
+    }
+
+    pub fn active_preset(&self) -> Option<&str> {
+        self.active_preset.as_deref()
+    }
+
+    pub fn group_filter(&self) -> Option<&str> {
+        self.group_filter.as_deref()
+    }
+
+    fn in_group_filter(&self, url: &str) -> bool {
+        self.group_filter
+            .as_deref()
+            .is_none_or(|group| self.group_name(url) == group)
+    }
+
+    /// Lists only the feeds of `group`, or those of every group with `None`.
+    fn set_group_filter(&mut self, group: Option<String>) {
+        self.group_filter = group;
+        self.active_preset = None;
+        let feeds = self.take_feeds();
+        self.set_feeds(feeds);
+    }
+
+    /// Applies every filter of the preset called `name` at once, in place of the current ones.
+    pub fn apply_preset(&mut self, name: &str) -> Result<()> {
+        let preset = self
+            .config
+            .filter_preset(name)
+            .cloned()
+            .ok_or_else(|| anyhow!("no filter preset named '{}'", name))?;
+        self.unread_filter_active = preset.unread_only();
+        self.time_filter = preset.time_window();
+        self.preset_keyword = preset.keyword().map(str::to_owned);
+        self.set_group_filter(preset.group().map(str::to_owned));
+        self.reset_item_filter();
+        if self.current_feed().is_some() {
+            self.items.state.select(Some(0));
+            self.reload_items();
+        }
+        self.active_preset = Some(name.to_owned());
+        Ok(())
+    }
+
+    /// Turns every filter off, as the first row of the presets popup does.
+    pub fn clear_filters(&mut self) {
+        self.unread_filter_active = false;
+        self.time_filter = TimeWindow::All;
+        self.preset_keyword = None;
+        self.set_group_filter(None);
+        self.reset_item_filter();
+        if self.current_feed().is_some() {
+            self.items.state.select(Some(0));
+            self.reload_items();
+        }
+    }
+
+    /// Writes the filters now applied to the config as a preset called `name`, replacing any
+    /// preset of that name.
+    pub fn save_current_filters_as_preset(&mut self, name: &str) -> Result<()> {
+        let keyword = self
+            .has_item_filter()
+            .then(|| self.item_filter.input.clone());
+        let preset = FilterPreset::new(
+            name,
+            self.time_filter,
+            self.unread_filter_active,
+            keyword.clone(),
+            self.group_filter.clone(),
+        );
+        self.config.save_filter_preset(preset)?;
+        self.share_config();
+        self.preset_keyword = keyword;
+        self.active_preset = Some(name.to_owned());
+        self.set_status(format!("Saved filter preset {}", name), STATUS_MESSAGE_TIME);
+        Ok(())
+    }
+
+    pub fn should_render_preset_picker(&self) -> bool {
+        !self.preset_picker.items().is_empty()
+    }
+
+    /// Opens the presets popup on the preset applied, if any.
+    pub fn open_preset_picker(&mut self) {
+        if self.config.filter_presets().is_empty() {
+            self.set_status("No filter presets in the config", STATUS_MESSAGE_TIME);
+            return;
+        }
+        let rows = std::iter::once("No filters".to_owned())
+            .chain(
+                self.config
+                    .filter_presets()
+                    .iter()
+                    .map(|preset| preset.name().to_owned()),
+            )
+            .collect::<Vec<_>>();
+        let selected = self
+            .active_preset
+            .as_ref()
+            .and_then(|name| rows.iter().skip(1).position(|row| row == name))
+            .map_or(0, |position| position + 1);
+        self.preset_picker = StatefulList::with_items(rows);
+        self.preset_picker.state.select(Some(selected));
+    }
+
+    pub fn next_preset(&mut self) {
+        self.preset_picker.next();
+    }
+
+    pub fn prev_preset(&mut self) {
+        self.preset_picker.previous();
+    }
+
+    pub fn pick_preset(&mut self) -> Result<()> {
+        let selected = self.preset_picker.state.selected();
+        let name = selected.and_then(|i| self.preset_picker.items.get(i).cloned());
+        self.dismiss_preset_picker();
+        match (selected, name) {
+            (Some(0), _) => self.clear_filters(),
+            (_, Some(name)) => self.apply_preset(&name)?,
+            _ => {}
+        }
+        Ok(())
+    }
+
+    pub fn dismiss_preset_picker(&mut self) {
+        self.preset_picker = StatefulList::default();
+    }
+
+    /// The number of unread items in `feed` published within the time filter.
+    pub fn feed_unread_count(&self, feed: &Feed) -> usize {
+        if self.time_filter == TimeWindow::All {
+            return feed.unread_count();
+        }
+        feed.items()
+            .iter()
+            .filter(|item| !item.is_read() && self.time_filter.contains(item.pub_date()))
+            .count()
+    }
+
+    /// Moves on to the next order of items within feeds, for this session only.
+    pub fn cycle_item_sort(&mut self) {
+        let order = self.config.item_sort_order().next();
+        self.config.set_item_sort_order(order);
+        self.share_config();
+        if self.current_feed().is_some() {
+            if self.items.state.selected().is_some() {
+                self.items.state.select(Some(0));
+            }
+            self.reload_items();
+        }
+    }
+
+    /// Rebuilds the feed rows, hiding feeds in collapsed groups, those without unread items
+    /// while the unread filter is active and those the feed filter doesn't match, and keeping
+    /// the selected feed.
+    pub(crate) fn set_feeds(&mut self, mut feeds: Vec<Feed>) {
+        let selected = self.current_feed().map(|feed| feed.url().to_owned());
+        let selected_row = self.feeds.state.selected();
+
+        util::sort_feeds(&mut feeds, &self.config);
+        let (mut hidden, visible): (Vec<_>, Vec<_>) = feeds.into_iter().partition(|feed| {
+            (self.unread_filter_active && self.feed_unread_count(feed) == 0)
+                || !self.in_group_filter(feed.url())
+                || !self.has_feed_filter()
+                    && !self.config.groups().is_empty()
+                    && self.collapsed_groups.contains(self.group_name(feed.url()))
+        });
+
+        if self.has_feed_filter() {
+            let mut matching = vec![];
+            for feed in visible {
+                match self.feed_filter_score(&feed) {
+                    Some(score) => matching.push((score, feed)),
+                    None => hidden.push(feed),
+                }
+            }
+            matching.sort_by_key(|(score, _)| Reverse(*score));
+            self.hidden_feeds = hidden;
+            self.feeds.items = matching
+                .into_iter()
+                .map(|(_, feed)| GroupedFeed::Feed(Box::new(feed)))
+                .collect();
+        } else {
+            self.hidden_feeds = hidden;
+            self.feeds.items = util::group_feeds(visible, &self.config);
+
+            // collapsed groups still need their header
+            for group in self.collapsed_groups.iter() {
+                if !self
+                    .feeds
+                    .items()
+                    .iter()
+                    .any(|row| row.header() == Some(group))
+                    && self.hidden_feed_count(group) > 0
+                {
+                    let position = self.header_position(group);
+                    self.feeds
+                        .items
+                        .insert(position, GroupedFeed::Header(group.clone()));
                 }
             }
-            Ok(ConsoleCommand::Search(_)) => todo!(),
-            _ => self.status = Status::Errored("unrecognized command".into()),
         }
 
-        self.command_state.input.clear();
-        self.reset_cursor();
-        self.toggle_console(None);
+        let position = selected.as_ref().and_then(|url| {
+            self.feeds
+                .items()
+                .iter()
+                .position(|row| row.feed().is_some_and(|feed| feed.url() == url))
+        });
+        // a feed gone from the list leaves the one now in its place selected
+        let replacement = selected_row
+            .filter(|_| selected.is_some() && position.is_none())
+            .and_then(|row| self.feed_row_near(row));
+        self.feeds.state.select(position.or(replacement));
+        if selected.is_some() && position.is_none() {
+            self.items.reset(0);
+            self.reset_items_scroll();
+            self.reset_detail_scroll();
+            self.active_view = View::MainList;
+            if replacement.is_some() {
+                self.sync_feed_selection();
+            }
+        } else if position.is_some() {
+            self.reload_items();
+        }
+        self.sync_feeds_scroll_length();
+        self.refresh_starred();
+    }
+
+    /// The feed row at `row` or the nearest below it, or failing that the nearest above.
+    fn feed_row_near(&self, row: usize) -> Option<usize> {
+        let rows = self.feeds.items();
+        (row..rows.len())
+            .chain((0..row.min(rows.len())).rev())
+            .find(|&i| rows[i].feed().is_some())
     }
 
-    fn set_feeds(&mut self, feeds: Vec<Feed>) {
-        self.feeds.items = feeds;
-        // self.items.state.select(None);
-        // self.active_view = ActiveView::Feeds;
+    /// Where a group's header belongs among the current rows, following config order.
+    fn header_position(&self, name: &str) -> usize {
+        let order = |name: &str| {
+            self.config
+                .groups()
+                .iter()
+                .position(|group| group.name() == name)
+                .unwrap_or(usize::MAX)
+        };
+        let rank = order(name);
+        self.feeds
+            .items()
+            .iter()
+            .position(|row| row.header().is_some_and(|header| order(header) > rank))
+            .unwrap_or(self.feeds.items().len())
     }
 
     fn reset_items_scroll(&mut self) {
@@ -583,20 +4015,7 @@ impl App {
     }
 
     fn open_link(link: &str) -> Option<Child> {
-        let null = Stdio::null();
-        if cfg!(target_os = "windows") {
-            Command::new("rundll32")
-                .args(["url.dll,FileProtocolHandler", link])
-                .stdout(null)
-                .spawn()
-                .ok()
-        } else if cfg!(target_os = "macos") {
-            Command::new("open").arg(link).stdout(null).spawn().ok()
-        } else if cfg!(target_os = "linux") {
-            Command::new("xdg-open").arg(link).stdout(null).spawn().ok()
-        } else {
-            None
-        }
+        open_command(link)?.stdout(Stdio::null()).spawn().ok()
     }
 }
 
@@ -605,6 +4024,14 @@ pub enum View {
     MainList,
     SubList,
     Detail,
+    Wizard,
+}
+
+/// Which way `n` and `N` step through the matches of a search.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Direction {
+    Forward,
+    Backward,
 }
 
 #[derive(Debug, PartialEq)]
@@ -612,6 +4039,10 @@ pub enum Tab {
     Browse,
     Favorites,
     Tags,
+    Stats,
+    Timeline,
+    Search,
+    Queue,
 }
 
 impl ToString for Tab {
@@ -620,6 +4051,10 @@ impl ToString for Tab {
             Self::Browse => "Browse".into(),
             Self::Favorites => "Favorites".into(),
             Self::Tags => "Tags".into(),
+            Self::Stats => "Stats".into(),
+            Self::Timeline => "Timeline".into(),
+            Self::Search => "Search".into(),
+            Self::Queue => "Queue".into(),
         }
     }
 }
@@ -630,6 +4065,10 @@ impl Tab {
             Self::Browse => 0,
             Self::Favorites => 1,
             Self::Tags => 2,
+            Self::Stats => 3,
+            Self::Timeline => 4,
+            Self::Search => 5,
+            Self::Queue => 6,
         }
     }
 }
@@ -639,11 +4078,126 @@ impl From<usize> for Tab {
         match value {
             1 => Tab::Favorites,
             2 => Tab::Tags,
+            3 => Tab::Stats,
+            4 => Tab::Timeline,
+            5 => Tab::Search,
+            6 => Tab::Queue,
             _ => Tab::Browse,
         }
     }
 }
 
+/// Watches the config file and the file of the current profile, sending `ConfigChanged`
+/// through `tx` whenever either is written. Their directories are watched rather than the
+/// files, since many editors save by replacing the file.
+fn watch_config(
+    config: &Config,
+    tx: UnboundedSender<RepositoryEvent>,
+) -> Option<RecommendedWatcher> {
+    let paths = [config.base_config_file_path(), config.config_file_path()]
+        .into_iter()
+        .filter_map(|path| {
+            let dir = path.parent()?.canonicalize().ok()?;
+            Some(dir.join(path.file_name()?))
+        })
+        .collect::<HashSet<_>>();
+
+    let watched = paths.clone();
+    let watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        let Ok(event) = event else { return };
+        if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_))
+            && event.paths.iter().any(|path| watched.contains(path))
+        {
+            let _ = tx.send(RepositoryEvent::ConfigChanged);
+        }
+    });
+
+    let result = watcher.and_then(|mut watcher| {
+        for dir in paths.iter().filter_map(|path| path.parent()) {
+            watcher.watch(dir, RecursiveMode::NonRecursive)?;
+        }
+        Ok(watcher)
+    });
+    match result {
+        Ok(watcher) => Some(watcher),
+        Err(err) => {
+            log::warn!("Failed to watch the config for changes: {}", err);
+            None
+        }
+    }
+}
+
+/// The row of the next feed after `selected` in `rows` whose title starts with `c`, ignoring
+/// case and wrapping around, or `None` if no feed does.
+fn feed_row_with_prefix(rows: &[GroupedFeed], selected: Option<usize>, c: char) -> Option<usize> {
+    let start = selected.map_or(0, |i| i + 1);
+    rows.iter()
+        .enumerate()
+        .cycle()
+        .skip(start)
+        .take(rows.len())
+        .find(|(_, row)| {
+            row.feed()
+                .and_then(|feed| feed.title().chars().next())
+                .is_some_and(|first| first.to_lowercase().eq(c.to_lowercase()))
+        })
+        .map(|(i, _)| i)
+}
+
+/// Holds the system clipboard open, since on Linux copied text is served by the app that
+/// copied it and is lost once its last handle is dropped.
+struct Clipboard(arboard::Clipboard);
+
+impl std::fmt::Debug for Clipboard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Clipboard")
+    }
+}
+
+/// Points at an item held by one of the loaded feeds, so merged lists need not clone items.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ItemRef {
+    feed_id: String,
+    item_id: String,
+}
+
+/// A row in the feeds pane.
+#[derive(Debug, Clone)]
+pub enum GroupedFeed {
+    Header(String),
+    Feed(Box<Feed>),
+}
+
+impl GroupedFeed {
+    pub fn feed(&self) -> Option<&Feed> {
+        match self {
+            GroupedFeed::Feed(feed) => Some(feed),
+            GroupedFeed::Header(_) => None,
+        }
+    }
+
+    pub fn feed_mut(&mut self) -> Option<&mut Feed> {
+        match self {
+            GroupedFeed::Feed(feed) => Some(feed),
+            GroupedFeed::Header(_) => None,
+        }
+    }
+
+    pub fn header(&self) -> Option<&str> {
+        match self {
+            GroupedFeed::Header(name) => Some(name),
+            GroupedFeed::Feed(_) => None,
+        }
+    }
+
+    fn into_feed(self) -> Option<Feed> {
+        match self {
+            GroupedFeed::Feed(feed) => Some(*feed),
+            GroupedFeed::Header(_) => None,
+        }
+    }
+}
+
 #[derive(Default, Debug)]
 pub struct StatefulList<T> {
     pub state: ListState,
@@ -694,6 +4248,18 @@ impl<T> StatefulList<T> {
         self.state.select(Some(i));
     }
 
+    /// Selects the row drawn `row` lines below the top of the list, returning whether there
+    /// was an item there.
+    pub fn select_absolute(&mut self, row: usize) -> bool {
+        let index = self.state.offset() + row;
+        if index < self.items.len() {
+            self.state.select(Some(index));
+            true
+        } else {
+            false
+        }
+    }
+
     #[allow(dead_code)]
     fn unselect(&mut self) {
         self.state.select(None);
@@ -704,6 +4270,180 @@ impl<T> StatefulList<T> {
     }
 }
 
+/// A list too long to hold in full, like the items of a large feed. Only the rows in a
+/// window around the selection are loaded, and the window follows the selection as it moves.
+#[derive(Debug)]
+pub struct VirtualList<T> {
+    /// The selection and offset, counted from the top of the whole list.
+    pub state: ListState,
+    total: usize,
+    window: Range<usize>,
+    /// Where `loaded` starts, which trails `window` until the page asked for arrives.
+    loaded_from: usize,
+    loaded: Vec<T>,
+}
+
+impl<T> Default for VirtualList<T> {
+    fn default() -> Self {
+        Self {
+            state: ListState::default(),
+            total: 0,
+            window: 0..0,
+            loaded_from: 0,
+            loaded: vec![],
+        }
+    }
+}
+
+impl<T> VirtualList<T> {
+    /// Empties the list, which now has `total` rows to load. The selection is kept.
+    fn reset(&mut self, total: usize) {
+        self.total = total;
+        self.window = 0..0;
+        self.loaded_from = 0;
+        self.loaded.clear();
+    }
+
+    /// Fills the list with every row at once, for lists that are short enough.
+    fn set_all(&mut self, items: Vec<T>) {
+        self.total = items.len();
+        self.window = 0..items.len();
+        self.loaded_from = 0;
+        self.loaded = items;
+    }
+
+    pub fn len(&self) -> usize {
+        self.total
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.total == 0
+    }
+
+    /// The row at `index`, if it is loaded.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        index
+            .checked_sub(self.loaded_from)
+            .and_then(|i| self.loaded.get(i))
+    }
+
+    pub fn selected_item(&self) -> Option<&T> {
+        self.state.selected().and_then(|i| self.get(i))
+    }
+
+    /// The loaded rows, and the index of the first of them.
+    pub fn loaded(&self) -> (usize, &[T]) {
+        (self.loaded_from, &self.loaded)
+    }
+
+    fn loaded_mut(&mut self) -> std::slice::IterMut<'_, T> {
+        self.loaded.iter_mut()
+    }
+
+    /// Moves the window once `index` comes within half a margin of either end of it, so that
+    /// `rows` rows either side of `index` and a margin beyond them are covered. Returns the
+    /// new window, which the caller loads and passes to `fill`.
+    fn scroll_to(&mut self, index: usize, rows: usize) -> Option<Range<usize>> {
+        let around =
+            |reach: usize| index.saturating_sub(reach)..(index + reach + 1).min(self.total);
+
+        let needed = around(rows + WINDOW_MARGIN / 2);
+        if self.window.start <= needed.start && needed.end <= self.window.end {
+            return None;
+        }
+        self.window = around(rows + WINDOW_MARGIN);
+        Some(self.window.clone())
+    }
+
+    /// Loads the rows from `offset` on, unless the window has moved since they were asked for.
+    fn fill(&mut self, offset: usize, items: Vec<T>) {
+        if offset == self.window.start {
+            self.loaded_from = offset;
+            self.loaded = items;
+        }
+    }
+
+    fn next(&mut self) {
+        if self.total == 0 {
+            return;
+        }
+
+        let i = match self.state.selected() {
+            Some(i) if i < self.total - 1 => i + 1,
+            _ => 0,
+        };
+        self.state.select(Some(i));
+    }
+
+    fn previous(&mut self) {
+        if self.total == 0 {
+            return;
+        }
+
+        let i = match self.state.selected() {
+            Some(0) => self.total - 1,
+            Some(i) => i - 1,
+            None => 0,
+        };
+        self.state.select(Some(i));
+    }
+
+    /// Selects the row drawn `row` lines below the top of the list, returning whether there
+    /// was an item there.
+    pub fn select_absolute(&mut self, row: usize) -> bool {
+        let index = self.state.offset() + row;
+        if index < self.total {
+            self.state.select(Some(index));
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// The rows of each pane as last drawn. Panes that were not drawn are left empty.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct PaneAreas {
+    pub feeds: Rect,
+    pub items: Rect,
+    pub detail: Rect,
+    /// The detail pane below the item's title, author and date.
+    pub detail_body: Rect,
+    pub starred: Rect,
+    pub timeline: Rect,
+}
+
+impl PaneAreas {
+    /// The pane at a screen position. The starred list and the timeline count as the main list.
+    fn view_at(&self, column: u16, row: u16) -> Option<View> {
+        let contains = |area: &Rect| {
+            column >= area.x
+                && column < area.x + area.width
+                && row >= area.y
+                && row < area.y + area.height
+        };
+
+        if contains(&self.feeds) || contains(&self.starred) || contains(&self.timeline) {
+            Some(View::MainList)
+        } else if contains(&self.items) {
+            Some(View::SubList)
+        } else if contains(&self.detail) {
+            Some(View::Detail)
+        } else {
+            None
+        }
+    }
+}
+
+/// The row `delta` rows from `selected`, kept within a list of `len` rows.
+fn clamp_row(selected: Option<usize>, delta: isize, len: usize) -> Option<usize> {
+    if len == 0 {
+        return None;
+    }
+    let from = selected.map_or(-1, |i| i as isize);
+    Some((from + delta).clamp(0, len as isize - 1) as usize)
+}
+
 #[derive(Debug)]
 pub struct InputState {
     pub input: String,
@@ -720,3 +4460,215 @@ impl InputState {
         }
     }
 }
+
+/// Where the interface was left on quitting: the selected rows of the feeds and items panes,
+/// the focused pane, one of `feeds`, `items` or `detail`, and how far the detail was scrolled.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SessionState {
+    pub selected_feed_index: Option<usize>,
+    pub selected_item_index: Option<usize>,
+    pub active_view: String,
+    pub detail_scroll_index: u16,
+}
+
+/// ASCII symbols with the `ascii_mode` preference, on `dumb` terminals, and where stderr, which
+/// the interface is drawn on, isn't a terminal, otherwise Unicode ones.
+fn symbol_set(config: &Config) -> SymbolSet {
+    let dumb = env::var("TERM").is_ok_and(|term| term == "dumb");
+    if config.ascii_mode() || dumb || !io::stderr().is_terminal() {
+        SymbolSet::ascii()
+    } else {
+        SymbolSet::unicode()
+    }
+}
+
+/// Whether the terminal takes a window title, as terminals other than `dumb` ones do.
+fn terminal_sets_title() -> bool {
+    match env::var("TERM") {
+        Ok(term) => term != "dumb",
+        Err(_) => env::var_os("COLORTERM").is_some() || cfg!(windows),
+    }
+}
+
+/// Whether the crash log was written after the session was last saved, which it is on
+/// quitting but not on a panic.
+fn previous_session_crashed() -> bool {
+    let modified = |path: PathBuf| fs::metadata(path).and_then(|meta| meta.modified()).ok();
+    match (
+        modified(logging::crash_log_path()),
+        modified(session_path()),
+    ) {
+        (Some(crashed), Some(saved)) => crashed > saved,
+        (crashed, _) => crashed.is_some(),
+    }
+}
+
+/// `session.json`, beside the database in the data directory.
+fn session_path() -> PathBuf {
+    Config::default_data_path().with_file_name(SESSION_FILE)
+}
+
+/// The steps of the setup wizard, in the order they are shown.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WizardStep {
+    FeedUrl,
+    Theme,
+    RefreshInterval,
+}
+
+impl WizardStep {
+    pub fn number(&self) -> usize {
+        match self {
+            Self::FeedUrl => 1,
+            Self::Theme => 2,
+            Self::RefreshInterval => 3,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Wizard {
+    pub step: WizardStep,
+    pub feed_url: InputState,
+    /// The selected one of `BUILT_IN_THEMES`.
+    pub theme: ListState,
+    pub refresh_interval: InputState,
+    /// Why the last answer was not accepted.
+    pub error: Option<String>,
+    /// The feed URL waiting for an answer to its HEAD request.
+    pub checking: Option<String>,
+}
+
+impl Wizard {
+    fn new(refresh_interval: &str) -> Self {
+        let mut interval = InputState::new();
+        interval.input = refresh_interval.to_owned();
+        interval.cursor_position = refresh_interval.chars().count();
+        Self {
+            step: WizardStep::FeedUrl,
+            feed_url: InputState::new(),
+            theme: ListState::default().with_selected(Some(0)),
+            refresh_interval: interval,
+            error: None,
+            checking: None,
+        }
+    }
+
+    /// The text being typed at this step, if it takes any.
+    pub fn input_mut(&mut self) -> Option<&mut InputState> {
+        match self.step {
+            WizardStep::FeedUrl => Some(&mut self.feed_url),
+            WizardStep::Theme => None,
+            WizardStep::RefreshInterval => Some(&mut self.refresh_interval),
+        }
+    }
+
+    pub fn theme_name(&self) -> &'static str {
+        BUILT_IN_THEMES[self.theme.selected().unwrap_or(0)]
+    }
+
+    /// The refresh interval in seconds, typed as a number of them or a span like `2h`.
+    fn refresh_interval(&self) -> Option<u64> {
+        let input = self.refresh_interval.input.trim();
+        input
+            .parse()
+            .ok()
+            .or_else(|| util::parse_duration(input).map(|duration| duration.as_secs()))
+    }
+}
+
+/// The command opening `link`, a URL or a file, with the platform's default handler.
+#[cfg(target_os = "windows")]
+fn open_command(link: &str) -> Option<Command> {
+    use std::os::windows::process::CommandExt;
+
+    // rundll32 reads the rest of its command line as the link, so it is passed as it is,
+    // where an argument would be quoted if it held a space, as paths under C:\Users may
+    let mut command = Command::new("rundll32");
+    command.raw_arg(format!("url.dll,FileProtocolHandler {}", link));
+    Some(command)
+}
+
+#[cfg(target_os = "macos")]
+fn open_command(link: &str) -> Option<Command> {
+    let mut command = Command::new("open");
+    command.arg(link);
+    Some(command)
+}
+
+#[cfg(target_os = "linux")]
+fn open_command(link: &str) -> Option<Command> {
+    let mut command = Command::new("xdg-open");
+    command.arg(link);
+    Some(command)
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+fn open_command(_link: &str) -> Option<Command> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn feed_rows(titles: &[&str]) -> Vec<GroupedFeed> {
+        titles
+            .iter()
+            .map(|title| {
+                let mut feed = Feed::placeholder(&format!("https://example.com/{}", title));
+                feed.title = title.to_string();
+                GroupedFeed::Feed(Box::new(feed))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn jumps_to_the_next_feed_with_the_prefix() {
+        let rows = feed_rows(&["Alpha", "beta", "Gamma", "Bravo"]);
+        assert_eq!(feed_row_with_prefix(&rows, None, 'b'), Some(1));
+        assert_eq!(feed_row_with_prefix(&rows, Some(0), 'B'), Some(1));
+        assert_eq!(feed_row_with_prefix(&rows, Some(1), 'g'), Some(2));
+    }
+
+    #[test]
+    fn wraps_around_past_the_last_feed() {
+        let rows = feed_rows(&["Alpha", "beta", "Gamma", "Delta"]);
+        assert_eq!(feed_row_with_prefix(&rows, Some(2), 'a'), Some(0));
+        assert_eq!(feed_row_with_prefix(&rows, Some(3), 'b'), Some(1));
+    }
+
+    #[test]
+    fn finds_nothing_without_a_match() {
+        let rows = feed_rows(&["Alpha", "beta"]);
+        assert_eq!(feed_row_with_prefix(&rows, Some(0), 'z'), None);
+        assert_eq!(feed_row_with_prefix(&[], None, 'a'), None);
+    }
+
+    #[test]
+    fn cycles_through_feeds_sharing_a_prefix() {
+        let rows = feed_rows(&["Apple", "Banana", "apricot", "Avocado"]);
+        let mut selected = None;
+        let mut visited = vec![];
+        for _ in 0..4 {
+            selected = feed_row_with_prefix(&rows, selected, 'a');
+            visited.extend(selected);
+        }
+        assert_eq!(visited, vec![0, 2, 3, 0]);
+    }
+
+    #[test]
+    fn skips_group_headers() {
+        let mut rows = vec![GroupedFeed::Header("Animals".into())];
+        rows.extend(feed_rows(&["Ant", "Bee"]));
+        assert_eq!(feed_row_with_prefix(&rows, None, 'a'), Some(1));
+        assert_eq!(feed_row_with_prefix(&rows, Some(1), 'a'), Some(1));
+    }
+
+    #[test]
+    fn keeps_the_only_match_selected() {
+        let rows = feed_rows(&["Alpha", "Beta"]);
+        assert_eq!(feed_row_with_prefix(&rows, Some(1), 'b'), Some(1));
+    }
+}
@@ -0,0 +1,131 @@
+//! Environment variables that override the config file, for running moccasin where writing
+//! one is awkward, such as in a container. Command line options still override them.
+
+use super::{load_theme, Config, THEMES_DIR};
+use std::env;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+pub const DB_PATH: &str = "MOCCASIN_DB_PATH";
+pub const REFRESH_INTERVAL: &str = "MOCCASIN_REFRESH_INTERVAL";
+pub const TIMEOUT: &str = "MOCCASIN_TIMEOUT";
+pub const FEED_URLS: &str = "MOCCASIN_FEED_URLS";
+pub const THEME: &str = "MOCCASIN_THEME";
+pub const LOG_LEVEL: &str = "MOCCASIN_LOG_LEVEL";
+
+/// Each variable, with what it overrides, for `--help-env`.
+pub const VARIABLES: [(&str, &str); 6] = [
+    (DB_PATH, "Database file, like --db"),
+    (
+        REFRESH_INTERVAL,
+        "How often to refetch feeds, in seconds, like --interval",
+    ),
+    (
+        TIMEOUT,
+        "How long to wait for each feed, in seconds, like --timeout",
+    ),
+    (
+        FEED_URLS,
+        "Feeds to read in place of [sources].feeds, separated by colons",
+    ),
+    (
+        THEME,
+        "Color scheme, built-in or from the themes directory, like --color-scheme",
+    ),
+    (
+        LOG_LEVEL,
+        "Least severe records to log: off, error, warn, info, debug or trace, like --log-level",
+    ),
+];
+
+impl Config {
+    /// `base` with the values set by environment variables in place of those from the config
+    /// file, except where the command line gave them. Values that can't be read are logged
+    /// and ignored.
+    pub fn load_with_env(mut base: Config) -> Config {
+        if base.args.db.is_none() {
+            if let Some(path) = env::var_os(DB_PATH).filter(|path| !path.is_empty()) {
+                let path = PathBuf::from(path);
+                base.db_path = std::path::absolute(&path).unwrap_or(path);
+            }
+        }
+        if base.args.interval.is_none() {
+            if let Some(interval) = parse_var(REFRESH_INTERVAL) {
+                base.refresh_interval = interval;
+            }
+        }
+        if base.args.timeout.is_none() {
+            if let Some(timeout) = parse_var(TIMEOUT) {
+                base.refresh_timeout = timeout;
+            }
+        }
+        if let Some(urls) = var(FEED_URLS) {
+            base.feed_urls = split_feed_urls(&urls)
+                .into_iter()
+                .map(str::to_owned)
+                .collect();
+        }
+        if base.args.color_scheme.is_none() {
+            if let Some(theme) =
+                var(THEME).and_then(|name| load_theme(&name, &base.dir_path.join(THEMES_DIR)))
+            {
+                base.theme = theme;
+            }
+        }
+        base
+    }
+}
+
+/// The log level set by `MOCCASIN_LOG_LEVEL`, read before the config is, to start logging.
+pub fn log_level() -> Option<log::LevelFilter> {
+    parse_var(LOG_LEVEL)
+}
+
+fn var(name: &str) -> Option<String> {
+    match env::var(name) {
+        Ok(value) if !value.trim().is_empty() => Some(value.trim().to_owned()),
+        Ok(_) | Err(env::VarError::NotPresent) => None,
+        Err(env::VarError::NotUnicode(_)) => {
+            log::warn!("Ignoring {}, which is not valid Unicode", name);
+            None
+        }
+    }
+}
+
+fn parse_var<T: FromStr>(name: &str) -> Option<T> {
+    let value = var(name)?;
+    value
+        .parse()
+        .map_err(|_| log::warn!("Ignoring invalid {}: {}", name, value))
+        .ok()
+}
+
+/// Splits a list of URLs at the colons that start another URL, rather than those within one,
+/// as in `https://a.example/feed:http://b.example:8080/rss`.
+fn split_feed_urls(urls: &str) -> Vec<&str> {
+    let mut split = vec![];
+    let mut start = 0;
+    for (i, _) in urls.match_indices(':') {
+        if starts_url(&urls[i + 1..]) {
+            split.push(&urls[start..i]);
+            start = i + 1;
+        }
+    }
+    split.push(&urls[start..]);
+    split
+        .into_iter()
+        .map(str::trim)
+        .filter(|url| !url.is_empty())
+        .collect()
+}
+
+/// Whether `s` starts with a URL scheme and `://`.
+fn starts_url(s: &str) -> bool {
+    let Some((scheme, _)) = s.split_once("://") else {
+        return false;
+    };
+    scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+        && scheme
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+}
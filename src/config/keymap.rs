@@ -0,0 +1,341 @@
+use super::Config;
+use anyhow::{bail, Context, Result};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use toml::{Table, Value};
+
+/// Something a key can be bound to in the `[keybindings]` table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    Quit,
+    Next,
+    Prev,
+    NextPane,
+    PrevPane,
+    Select,
+    Unselect,
+    NextTab,
+    PrevTab,
+    Browse,
+    Favorites,
+    Tags,
+    Stats,
+    Timeline,
+    Console,
+    Add,
+    Delete,
+    Search,
+    SearchAll,
+    Export,
+    Open,
+    OpenEnclosure,
+    CopyLink,
+    Star,
+    ToggleRead,
+    MarkAllRead,
+    ToggleGroup,
+    ToggleAllGroups,
+    CycleFeedColor,
+    ShowError,
+    ToggleUnreadFilter,
+    CycleItemSort,
+    CycleTimeFilter,
+    Presets,
+    Queue,
+    Enqueue,
+    Dequeue,
+    ToggleContent,
+    ToggleExpand,
+    Refresh,
+    RefreshFeed,
+    ReloadConfig,
+    Help,
+    EditConfig,
+    JumpToFeed,
+}
+
+const DEFAULT_BINDINGS: [(Action, &[&str]); 45] = [
+    (Action::Quit, &["q", "Q", "ctrl+c"]),
+    (Action::Next, &["j", "down"]),
+    (Action::Prev, &["k", "up"]),
+    (Action::NextPane, &["l", "right"]),
+    (Action::PrevPane, &["h", "left"]),
+    (Action::Select, &["enter"]),
+    (Action::Unselect, &["esc"]),
+    (Action::NextTab, &["tab"]),
+    (Action::PrevTab, &["backtab"]),
+    (Action::Browse, &["b"]),
+    (Action::Favorites, &["f"]),
+    (Action::Tags, &["t"]),
+    (Action::Stats, &["S"]),
+    (Action::Timeline, &["T"]),
+    (Action::Console, &[":"]),
+    (Action::Add, &["a"]),
+    (Action::Delete, &["d"]),
+    (Action::Search, &["/"]),
+    (Action::SearchAll, &["ctrl+f"]),
+    (Action::Export, &["E"]),
+    (Action::Open, &["o"]),
+    (Action::OpenEnclosure, &["p"]),
+    (Action::CopyLink, &["y"]),
+    (Action::Star, &["s"]),
+    (Action::ToggleRead, &["m"]),
+    (Action::MarkAllRead, &["M"]),
+    (Action::ToggleGroup, &["z"]),
+    (Action::ToggleAllGroups, &["Z"]),
+    (Action::CycleFeedColor, &["c"]),
+    (Action::ShowError, &["e"]),
+    (Action::ToggleUnreadFilter, &["u"]),
+    (Action::CycleItemSort, &["O"]),
+    (Action::CycleTimeFilter, &["w"]),
+    (Action::Presets, &["P"]),
+    (Action::Queue, &["R"]),
+    (Action::Enqueue, &["L"]),
+    (Action::Dequeue, &["n"]),
+    (Action::ToggleContent, &["F"]),
+    (Action::ToggleExpand, &["space"]),
+    (Action::Refresh, &["r"]),
+    (Action::RefreshFeed, &["ctrl+l"]),
+    (Action::ReloadConfig, &["ctrl+r"]),
+    (Action::Help, &["?"]),
+    (Action::EditConfig, &[","]),
+    (Action::JumpToFeed, &["'"]),
+];
+
+impl FromStr for Action {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "quit" => Ok(Action::Quit),
+            "next" => Ok(Action::Next),
+            "prev" => Ok(Action::Prev),
+            "next_pane" => Ok(Action::NextPane),
+            "prev_pane" => Ok(Action::PrevPane),
+            "select" => Ok(Action::Select),
+            "unselect" => Ok(Action::Unselect),
+            "next_tab" => Ok(Action::NextTab),
+            "prev_tab" => Ok(Action::PrevTab),
+            "browse" => Ok(Action::Browse),
+            "favorites" => Ok(Action::Favorites),
+            "tags" => Ok(Action::Tags),
+            "stats" => Ok(Action::Stats),
+            "timeline" => Ok(Action::Timeline),
+            "console" => Ok(Action::Console),
+            "add" => Ok(Action::Add),
+            "delete" => Ok(Action::Delete),
+            "search" => Ok(Action::Search),
+            "search_all" => Ok(Action::SearchAll),
+            "export" => Ok(Action::Export),
+            "open" => Ok(Action::Open),
+            "open_enclosure" => Ok(Action::OpenEnclosure),
+            "copy_link" => Ok(Action::CopyLink),
+            "star" => Ok(Action::Star),
+            "toggle_read" => Ok(Action::ToggleRead),
+            "mark_all_read" => Ok(Action::MarkAllRead),
+            "toggle_group" => Ok(Action::ToggleGroup),
+            "toggle_all_groups" => Ok(Action::ToggleAllGroups),
+            "cycle_feed_color" => Ok(Action::CycleFeedColor),
+            "show_error" => Ok(Action::ShowError),
+            "toggle_unread_filter" => Ok(Action::ToggleUnreadFilter),
+            "cycle_item_sort" => Ok(Action::CycleItemSort),
+            "cycle_time_filter" => Ok(Action::CycleTimeFilter),
+            "presets" => Ok(Action::Presets),
+            "queue" => Ok(Action::Queue),
+            "enqueue" => Ok(Action::Enqueue),
+            "dequeue" => Ok(Action::Dequeue),
+            "toggle_content" => Ok(Action::ToggleContent),
+            "toggle_expand" => Ok(Action::ToggleExpand),
+            "refresh" => Ok(Action::Refresh),
+            "refresh_feed" => Ok(Action::RefreshFeed),
+            "reload_config" => Ok(Action::ReloadConfig),
+            "help" => Ok(Action::Help),
+            "edit_config" => Ok(Action::EditConfig),
+            "jump_to_feed" => Ok(Action::JumpToFeed),
+            _ => Err(anyhow::anyhow!("unknown action '{}'", s)),
+        }
+    }
+}
+
+/// Which keys trigger which actions: the defaults, with any actions listed in the
+/// `[keybindings]` table bound to the keys given there instead.
+#[derive(Debug, Clone, Default)]
+pub struct KeyMap {
+    bindings: HashMap<Action, Vec<KeyEvent>>,
+}
+
+impl KeyMap {
+    pub fn from_config(config: &Config) -> KeyMap {
+        let overrides = config.keybindings();
+        let mut bindings = default_bindings();
+
+        // a key bound in the config leaves whatever action it had by default
+        for keys in bindings.values_mut() {
+            keys.retain(|key| {
+                !overrides
+                    .values()
+                    .flatten()
+                    .any(|bound| same_key(key, bound))
+            });
+        }
+        for (action, keys) in overrides {
+            bindings.insert(*action, keys.clone());
+        }
+
+        KeyMap { bindings }
+    }
+
+    pub fn action_for(&self, event: &KeyEvent) -> Option<Action> {
+        self.bindings
+            .iter()
+            .find(|(_, keys)| keys.iter().any(|key| same_key(key, event)))
+            .map(|(action, _)| *action)
+    }
+
+    /// A short label for the first key bound to `action`, for the keybinds overlay.
+    pub fn label(&self, action: Action) -> String {
+        self.bindings
+            .get(&action)
+            .and_then(|keys| keys.first())
+            .map(|key| KeyLabel(key).to_string())
+            .unwrap_or_default()
+    }
+}
+
+fn default_bindings() -> HashMap<Action, Vec<KeyEvent>> {
+    DEFAULT_BINDINGS
+        .iter()
+        .map(|(action, keys)| {
+            let keys = keys
+                .iter()
+                .map(|key| parse_key(key).expect("parse default keybinding"))
+                .collect();
+            (*action, keys)
+        })
+        .collect()
+}
+
+/// Reads the `[keybindings]` table, in which each action takes a key descriptor or an
+/// array of them.
+pub fn parse_bindings(table: &Table) -> Result<HashMap<Action, Vec<KeyEvent>>> {
+    table
+        .iter()
+        .map(|(name, keys)| {
+            let action = Action::from_str(name)?;
+            let keys = match keys {
+                Value::String(key) => vec![parse_key(key)?],
+                Value::Array(keys) => keys
+                    .iter()
+                    .map(|key| {
+                        key.as_str()
+                            .context(format!("invalid key for [keybindings].{}", name))
+                            .and_then(parse_key)
+                    })
+                    .collect::<Result<Vec<_>>>()?,
+                _ => bail!("invalid config entry for [keybindings].{}", name),
+            };
+            Ok((action, keys))
+        })
+        .collect()
+}
+
+/// Parses a key descriptor such as `"j"`, `"G"`, `"ctrl+r"` or `"f5"`.
+pub fn parse_key(descriptor: &str) -> Result<KeyEvent> {
+    let (modifier_names, key) = match descriptor.rsplit_once('+') {
+        // "+" on its own, or as the key in "ctrl++"
+        Some((rest, "")) if descriptor.ends_with("++") => (&rest[..rest.len() - 1], "+"),
+        Some((rest, key)) if !key.is_empty() => (rest, key),
+        _ => ("", descriptor),
+    };
+
+    let mut modifiers = KeyModifiers::NONE;
+    for name in modifier_names.split('+').filter(|name| !name.is_empty()) {
+        modifiers |= match name.to_ascii_lowercase().as_str() {
+            "ctrl" | "control" => KeyModifiers::CONTROL,
+            "alt" | "meta" => KeyModifiers::ALT,
+            "shift" => KeyModifiers::SHIFT,
+            _ => bail!("unknown modifier '{}' in key '{}'", name, descriptor),
+        };
+    }
+    let shift = modifiers.contains(KeyModifiers::SHIFT);
+
+    let mut chars = key.chars();
+    let code = match (chars.next(), chars.next()) {
+        (Some(c), None) if shift => KeyCode::Char(c.to_ascii_uppercase()),
+        (Some(c), None) => KeyCode::Char(c),
+        _ => match key.to_ascii_lowercase().as_str() {
+            "tab" if shift => KeyCode::BackTab,
+            "enter" | "return" => KeyCode::Enter,
+            "esc" | "escape" => KeyCode::Esc,
+            "tab" => KeyCode::Tab,
+            "backtab" => KeyCode::BackTab,
+            "backspace" => KeyCode::Backspace,
+            "delete" | "del" => KeyCode::Delete,
+            "insert" | "ins" => KeyCode::Insert,
+            "space" => KeyCode::Char(' '),
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" => KeyCode::PageUp,
+            "pagedown" => KeyCode::PageDown,
+            name => match name.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+                Some(n @ 1..=12) => KeyCode::F(n),
+                _ => bail!("unknown key '{}'", descriptor),
+            },
+        },
+    };
+
+    Ok(KeyEvent::new(code, without_shift(code, modifiers)))
+}
+
+/// Compares keys by code and modifiers only, ignoring the event kind and state.
+fn same_key(a: &KeyEvent, b: &KeyEvent) -> bool {
+    a.code == b.code && without_shift(a.code, a.modifiers) == without_shift(b.code, b.modifiers)
+}
+
+/// Terminals report shift on keys that already carry it, like `G` or `BackTab`.
+fn without_shift(code: KeyCode, modifiers: KeyModifiers) -> KeyModifiers {
+    match code {
+        KeyCode::Char(_) | KeyCode::BackTab => modifiers - KeyModifiers::SHIFT,
+        _ => modifiers,
+    }
+}
+
+struct KeyLabel<'a>(&'a KeyEvent);
+
+impl fmt::Display for KeyLabel<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "^")?;
+        }
+        if self.0.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "M-")?;
+        }
+        match self.0.code {
+            KeyCode::Char(' ') => write!(f, "Spc"),
+            KeyCode::Char(c) => write!(f, "{}", c),
+            KeyCode::F(n) => write!(f, "F{}", n),
+            KeyCode::Enter => write!(f, "Ent"),
+            KeyCode::Esc => write!(f, "Esc"),
+            KeyCode::Tab => write!(f, "Tab"),
+            KeyCode::BackTab => write!(f, "S-Tab"),
+            KeyCode::Backspace => write!(f, "BS"),
+            KeyCode::Delete => write!(f, "Del"),
+            KeyCode::Insert => write!(f, "Ins"),
+            KeyCode::Up => write!(f, "Up"),
+            KeyCode::Down => write!(f, "Dn"),
+            KeyCode::Left => write!(f, "Lt"),
+            KeyCode::Right => write!(f, "Rt"),
+            KeyCode::Home => write!(f, "Home"),
+            KeyCode::End => write!(f, "End"),
+            KeyCode::PageUp => write!(f, "PgUp"),
+            KeyCode::PageDown => write!(f, "PgDn"),
+            _ => write!(f, "?"),
+        }
+    }
+}
@@ -1,32 +1,124 @@
-use crate::app::Args;
-use anyhow::Result;
+use crate::app::{Args, TimeWindow};
+use crate::feed::Item;
+use crate::logging;
+use crate::opml;
+use anyhow::{bail, Context, Result};
+use crossterm::event::KeyEvent;
 use directories::ProjectDirs;
-use std::collections::HashSet;
-use std::fs::OpenOptions;
+use regex::{Regex, RegexBuilder};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
 use std::io::Write;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::sync::{Arc, Mutex, PoisonError, RwLock, RwLockReadGuard};
 use std::{fs, fs::File};
 use toml::{Table, Value};
-use toml_edit::{value, Array, Document};
+use toml_edit::{table, value, Array, Document};
 
-mod theme;
+pub mod env;
+pub mod keymap;
+pub mod theme;
 
-const DEFAULT_CONFIG_FILE: &'static str = "moccasin.toml";
-const DEFAULT_DB_FILE: &'static str = "moccasin.db";
+const DEFAULT_CONFIG_FILE: &'static str = "config.toml";
+const DEFAULT_DB_FILE: &'static str = "feeds.db";
+/// What the config file was called before moccasin followed the XDG layout, still read if
+/// there is no `config.toml`.
+const LEGACY_CONFIG_FILE: &str = "moccasin.toml";
+/// What the database was called when it was kept beside the config file, which it still is
+/// for a config given with `--config`.
+const LEGACY_DB_FILE: &str = "moccasin.db";
+const APP_DIR: &str = "moccasin";
+const THEMES_DIR: &str = "themes";
+const PROFILES_DIR: &str = "profiles";
+pub const DEFAULT_PROFILE: &str = "default";
 const DEFAULT_REFRESH_INTERVAL: u64 = 300;
 const DEFAULT_REFRESH_TIMEOUT: u64 = 5;
+const DEFAULT_MAX_RETRIES: u8 = 3;
+/// Retries back off exponentially, so past this many a feed would be retried for hours.
+const MAX_RETRIES_LIMIT: u8 = 10;
+const DEFAULT_MAX_RESPONSE_BYTES: usize = 10 * 1024 * 1024;
+const DEFAULT_MAX_CONCURRENT_FETCHES: usize = 8;
+const DEFAULT_TICK_RATE_MS: u64 = 250;
+const LOW_POWER_TICK_RATE_MS: u64 = 1000;
+const DEFAULT_MAX_REDIRECTS: usize = 10;
+const DEFAULT_USER_AGENT: &str = concat!(
+    "moccasin/",
+    env!("CARGO_PKG_VERSION"),
+    " (+https://github.com/nbsp/moccasin)"
+);
+const DEFAULT_DEAD_AFTER: u8 = 10;
+const DEFAULT_WEBSUB_PORT: u16 = 8095;
 
 #[derive(Debug, Default, Clone)]
 pub struct Config {
+    args: Args,
+    /// The file changes are written to: the profile's, or `config.toml` for the default.
     file_path: PathBuf,
+    base_file_path: PathBuf,
     dir_path: PathBuf,
+    profile: String,
+    db_path: PathBuf,
     feed_urls: HashSet<String>,
+    feed_configs: HashMap<String, FeedConfig>,
+    /// Passwords read from the keyring by feed URL, `None` where there is none, so each is
+    /// looked up once. Clones share them, as each fetch gets its own copy of the config.
+    passwords: Arc<Mutex<HashMap<String, Option<String>>>>,
+    groups: Vec<Group>,
+    filter_presets: Vec<FilterPreset>,
+    highlights: Highlights,
     sort_order: SortOrder,
+    item_sort_order: ItemSortOrder,
+    keybinding_mode: KeybindingMode,
+    keybindings: HashMap<keymap::Action, Vec<KeyEvent>>,
+    layout: PaneLayout,
+    scrollbar_style: ScrollbarStyle,
+    border_style: BorderStyle,
+    item_columns: Vec<Column>,
+    item_display_mode: ItemDisplayMode,
+    max_detail_lines: Option<usize>,
     cache_control: CacheControl,
     refresh_interval: u64,
     refresh_timeout: u64,
+    max_retries: u8,
+    max_response_bytes: usize,
+    max_concurrent_fetches: usize,
+    tick_rate_ms: u64,
+    follow_redirects: bool,
+    max_redirects: usize,
+    proxy_url: Option<String>,
+    user_agent: Option<String>,
+    retention_days: Option<u64>,
+    dead_after: u8,
+    notifications: bool,
+    window_title: bool,
+    ascii_mode: bool,
+    sync: Option<SyncConfig>,
+    websub: Option<WebSubConfig>,
     theme: theme::Theme,
+    /// Whether this run created the config file, so moccasin has not been set up yet.
+    first_run: bool,
+}
+
+/// A config shared between the interface and the API server, kept up to date by the
+/// interface as the config changes.
+#[derive(Clone, Debug, Default)]
+pub struct SharedConfig(Arc<RwLock<Config>>);
+
+impl SharedConfig {
+    pub fn new(config: Config) -> Self {
+        Self(Arc::new(RwLock::new(config)))
+    }
+
+    pub fn read(&self) -> RwLockReadGuard<'_, Config> {
+        self.0.read().expect("config lock poisoned")
+    }
+
+    /// Swaps in `config` for everything sharing this one.
+    pub fn replace(&self, config: Config) {
+        *self.0.write().expect("config lock poisoned") = config;
+    }
 }
 
 #[derive(Debug, Default, Clone)]
@@ -40,6 +132,229 @@ pub enum SortOrder {
     Custom,
 }
 
+/// The order of items within a feed.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum ItemSortOrder {
+    /// The order the feed lists them in.
+    #[default]
+    FeedDefault,
+    NewestFirst,
+    OldestFirst,
+    TitleAz,
+    TitleZa,
+    UnreadFirst,
+}
+
+impl ItemSortOrder {
+    /// The order after this one, for cycling through them.
+    pub fn next(self) -> Self {
+        match self {
+            ItemSortOrder::FeedDefault => ItemSortOrder::NewestFirst,
+            ItemSortOrder::NewestFirst => ItemSortOrder::OldestFirst,
+            ItemSortOrder::OldestFirst => ItemSortOrder::TitleAz,
+            ItemSortOrder::TitleAz => ItemSortOrder::TitleZa,
+            ItemSortOrder::TitleZa => ItemSortOrder::UnreadFirst,
+            ItemSortOrder::UnreadFirst => ItemSortOrder::FeedDefault,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ItemSortOrder::FeedDefault => "feed order",
+            ItemSortOrder::NewestFirst => "newest first",
+            ItemSortOrder::OldestFirst => "oldest first",
+            ItemSortOrder::TitleAz => "a-z",
+            ItemSortOrder::TitleZa => "z-a",
+            ItemSortOrder::UnreadFirst => "unread first",
+        }
+    }
+}
+
+impl FromStr for ItemSortOrder {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "feed" => Ok(ItemSortOrder::FeedDefault),
+            "newest" => Ok(ItemSortOrder::NewestFirst),
+            "oldest" => Ok(ItemSortOrder::OldestFirst),
+            "a-z" => Ok(ItemSortOrder::TitleAz),
+            "z-a" => Ok(ItemSortOrder::TitleZa),
+            "unread" => Ok(ItemSortOrder::UnreadFirst),
+            _ => Err(anyhow::anyhow!("unknown item sort order '{}'", s)),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum KeybindingMode {
+    #[default]
+    Default,
+    Vim,
+}
+
+/// How the feeds, items and detail panes are arranged.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum PaneLayout {
+    /// Side by side, from left to right.
+    #[default]
+    Horizontal,
+    /// Stacked, from top to bottom.
+    Vertical,
+}
+
+/// How the scrollbars of the feeds, items and detail panes are drawn.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum ScrollbarStyle {
+    /// A solid thumb on a solid track of the theme's scrollbar colors.
+    #[default]
+    Block,
+    /// A heavy line on a thin one.
+    Thin,
+    /// A solid thumb on a thin track, between arrows.
+    Arrows,
+    /// No scrollbar, leaving its column to the list.
+    None,
+}
+
+/// How the borders of the panes are drawn.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum BorderStyle {
+    #[default]
+    Plain,
+    Rounded,
+    Double,
+    Thick,
+    /// No borders, leaving their room as space between the panes.
+    None,
+}
+
+/// How many rows each item takes in the items pane.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum ItemDisplayMode {
+    /// The item's columns on one row.
+    #[default]
+    Single,
+    /// The title on one row, and its date, author and the start of its summary below.
+    Double,
+}
+
+impl ItemDisplayMode {
+    pub fn rows(&self) -> usize {
+        match self {
+            ItemDisplayMode::Single => 1,
+            ItemDisplayMode::Double => 2,
+        }
+    }
+}
+
+/// What a column of the items pane shows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ItemColumn {
+    Title,
+    Date,
+    Author,
+    /// A dot for unread items.
+    ReadIndicator,
+    Star,
+}
+
+impl ItemColumn {
+    fn default_width(&self) -> u16 {
+        match self {
+            ItemColumn::Title => 0,
+            ItemColumn::Date => 10,
+            ItemColumn::Author => 15,
+            ItemColumn::ReadIndicator | ItemColumn::Star => 1,
+        }
+    }
+}
+
+impl FromStr for ItemColumn {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "title" => Ok(ItemColumn::Title),
+            "date" => Ok(ItemColumn::Date),
+            "author" => Ok(ItemColumn::Author),
+            "read_indicator" => Ok(ItemColumn::ReadIndicator),
+            "star" => Ok(ItemColumn::Star),
+            _ => Err(anyhow::anyhow!("unknown item column '{}'", s)),
+        }
+    }
+}
+
+/// A column of the items pane, from the `item_columns` preference, and its width from
+/// `item_column_widths`. The title takes whatever width the other columns leave.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Column {
+    kind: ItemColumn,
+    width: u16,
+}
+
+impl Column {
+    pub fn kind(&self) -> ItemColumn {
+        self.kind
+    }
+
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+}
+
+const DEFAULT_ITEM_COLUMNS: [ItemColumn; 2] = [ItemColumn::Star, ItemColumn::Title];
+
+/// Reads the `item_columns` and `item_column_widths` preferences.
+fn parse_item_columns(prefs: Option<&Table>) -> Result<Vec<Column>> {
+    let kinds = match prefs.and_then(|prefs| prefs.get("item_columns")) {
+        Some(Value::Array(names)) => names
+            .iter()
+            .map(|name| {
+                name.as_str()
+                    .context("invalid config entry for item_columns")
+                    .and_then(ItemColumn::from_str)
+            })
+            .collect::<Result<Vec<_>>>()?,
+        Some(_) => bail!("invalid config entry for item_columns"),
+        None => DEFAULT_ITEM_COLUMNS.to_vec(),
+    };
+
+    let widths = match prefs.and_then(|prefs| prefs.get("item_column_widths")) {
+        Some(Value::Table(widths)) => widths
+            .iter()
+            .map(|(name, width)| {
+                let kind = ItemColumn::from_str(name)?;
+                let width = width
+                    .as_integer()
+                    .and_then(|width| u16::try_from(width).ok())
+                    .with_context(|| format!("invalid width for item column '{}'", name))?;
+                Ok((kind, width))
+            })
+            .collect::<Result<HashMap<_, _>>>()?,
+        Some(_) => bail!("invalid config entry for item_column_widths"),
+        None => HashMap::new(),
+    };
+
+    Ok(kinds
+        .into_iter()
+        .map(|kind| Column {
+            kind,
+            width: widths.get(&kind).copied().unwrap_or(kind.default_width()),
+        })
+        .collect())
+}
+
+fn default_item_columns() -> Vec<Column> {
+    DEFAULT_ITEM_COLUMNS
+        .iter()
+        .map(|&kind| Column {
+            kind,
+            width: kind.default_width(),
+        })
+        .collect()
+}
+
 #[derive(Debug, Default, Clone, PartialEq)]
 pub enum CacheControl {
     #[default]
@@ -57,6 +372,466 @@ impl From<bool> for CacheControl {
     }
 }
 
+/// Settings for a single feed, read from its `[feeds."<url>"]` table.
+#[derive(Debug, Default, Clone)]
+pub struct FeedConfig {
+    timeout_secs: Option<u64>,
+    refresh_every_secs: Option<u64>,
+    max_response_bytes: Option<usize>,
+    headers: HeaderMap,
+    username: Option<String>,
+    proxy_url: Option<String>,
+    user_agent: Option<String>,
+    color: Option<Color>,
+}
+
+impl FeedConfig {
+    pub fn timeout_secs(&self) -> Option<u64> {
+        self.timeout_secs
+    }
+
+    pub fn refresh_every_secs(&self) -> Option<u64> {
+        self.refresh_every_secs
+    }
+
+    pub fn max_response_bytes(&self) -> Option<usize> {
+        self.max_response_bytes
+    }
+
+    /// Extra request headers. Values are marked sensitive, so they are redacted from `Debug`.
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    pub fn username(&self) -> Option<&str> {
+        self.username.as_deref()
+    }
+
+    pub fn proxy_url(&self) -> Option<&str> {
+        self.proxy_url.as_deref()
+    }
+
+    pub fn user_agent(&self) -> Option<&str> {
+        self.user_agent.as_deref()
+    }
+
+    pub fn color(&self) -> Option<Color> {
+        self.color
+    }
+}
+
+impl From<&Table> for FeedConfig {
+    fn from(table: &Table) -> Self {
+        let timeout_secs = table.get("timeout_secs").and_then(|t| match t {
+            Value::Integer(t) if *t > 0 => Some(*t as u64),
+            _ => None,
+        });
+
+        let refresh_every_secs = table.get("refresh_every_secs").and_then(|t| match t {
+            Value::Integer(t) if *t >= 0 => Some(*t as u64),
+            _ => None,
+        });
+
+        let max_response_bytes = table.get("max_response_bytes").and_then(|b| match b {
+            Value::Integer(b) if *b > 0 => usize::try_from(*b).ok(),
+            _ => None,
+        });
+
+        let mut headers = HeaderMap::new();
+        if let Some(Value::Table(entries)) = table.get("headers") {
+            for (name, value) in entries {
+                match value.as_str().map(|value| parse_header(name, value)) {
+                    Some(Ok((name, value))) => {
+                        headers.insert(name, value);
+                    }
+                    _ => log::warn!("Ignoring invalid header {}", name),
+                }
+            }
+        }
+
+        let username = table
+            .get("username")
+            .and_then(Value::as_str)
+            .map(str::to_owned);
+
+        let proxy_url = table
+            .get("proxy_url")
+            .and_then(Value::as_str)
+            .map(str::to_owned);
+
+        let user_agent = table
+            .get("user_agent")
+            .and_then(Value::as_str)
+            .and_then(|user_agent| {
+                check_user_agent(user_agent)
+                    .map_err(|err| log::warn!("Ignoring user_agent: {}", err))
+                    .ok()
+            })
+            .map(str::to_owned);
+
+        let color = table
+            .get("color")
+            .and_then(Value::as_str)
+            .and_then(|color| {
+                Color::from_str(color)
+                    .map_err(|err| log::warn!("Ignoring {}", err))
+                    .ok()
+            });
+
+        Self {
+            timeout_secs,
+            refresh_every_secs,
+            max_response_bytes,
+            headers,
+            username,
+            proxy_url,
+            user_agent,
+            color,
+        }
+    }
+}
+
+/// The system keyring entry holding the password for `url`, stored under `moccasin:<url>`.
+fn keyring_entry(url: &str, username: &str) -> Result<keyring::Entry> {
+    Ok(keyring::Entry::new(&format!("moccasin:{}", url), username)?)
+}
+
+/// Checks that `user_agent` can be sent as a header, so building a client with it can't fail.
+fn check_user_agent(user_agent: &str) -> Result<&str> {
+    HeaderValue::from_str(user_agent)
+        .with_context(|| format!("invalid user_agent '{}'", user_agent.escape_debug()))?;
+    Ok(user_agent)
+}
+
+fn parse_header(name: &str, value: &str) -> Result<(HeaderName, HeaderValue)> {
+    let name = HeaderName::from_str(name).context("invalid header name")?;
+    let mut value = HeaderValue::from_str(value).context("invalid header value")?;
+    value.set_sensitive(true);
+    Ok((name, value))
+}
+
+/// The `[feeds."<url>"]` table in `toml`, created if missing. Parent tables stay implicit, so
+/// nothing gets a header line that doesn't need one.
+fn feed_entry<'a>(toml: &'a mut Document, url: &str) -> Result<&'a mut toml_edit::Table> {
+    let feeds = toml
+        .entry("feeds")
+        .or_insert(table())
+        .as_table_mut()
+        .context("invalid config entry for [feeds]")?;
+    feeds.set_implicit(true);
+    let entry = feeds
+        .entry(url)
+        .or_insert(table())
+        .as_table_mut()
+        .with_context(|| format!("invalid config entry for [feeds.\"{}\"]", url))?;
+    entry.set_implicit(true);
+    Ok(entry)
+}
+
+/// Patterns from the `highlights` preference. Each matches item titles and descriptions as a
+/// case-insensitive substring, or as a regex when it starts with `/`.
+#[derive(Debug, Default, Clone)]
+pub struct Highlights(Vec<Regex>);
+
+impl Highlights {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn matches(&self, item: &Item) -> bool {
+        [item.title(), item.description()]
+            .into_iter()
+            .flatten()
+            .any(|text| self.0.iter().any(|regex| regex.is_match(text)))
+    }
+}
+
+impl TryFrom<&Value> for Highlights {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &Value) -> Result<Self> {
+        let patterns = value
+            .as_array()
+            .context("invalid config entry for highlights")?;
+        let regexes = patterns
+            .iter()
+            .map(|pattern| {
+                let pattern = pattern
+                    .as_str()
+                    .context("invalid config entry for highlights")?;
+                let regex = match pattern.strip_prefix('/') {
+                    Some(regex) => Regex::new(regex.strip_suffix('/').unwrap_or(regex)),
+                    None => RegexBuilder::new(&regex::escape(pattern))
+                        .case_insensitive(true)
+                        .build(),
+                };
+                regex.with_context(|| format!("invalid highlight '{}'", pattern))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self(regexes))
+    }
+}
+
+/// A named set of feeds, read from a `[[group]]` table.
+#[derive(Debug, Default, Clone)]
+pub struct Group {
+    name: String,
+    feeds: Vec<String>,
+}
+
+impl Group {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn feeds(&self) -> &[String] {
+        &self.feeds
+    }
+}
+
+impl TryFrom<&Value> for Group {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &Value) -> Result<Self> {
+        let table = value
+            .as_table()
+            .context("invalid config entry for [[group]]")?;
+        let name = table
+            .get("name")
+            .and_then(Value::as_str)
+            .context("[[group]] is missing a name")?;
+        let feeds = match table.get("feeds") {
+            Some(Value::Array(els)) => els
+                .iter()
+                .filter_map(|v| v.as_str().map(str::to_owned))
+                .collect(),
+            Some(_) => anyhow::bail!("invalid config entry for [[group]].feeds"),
+            None => vec![],
+        };
+
+        Ok(Self {
+            name: name.to_owned(),
+            feeds,
+        })
+    }
+}
+
+/// A named set of filters applied at once, read from a `[[filter_presets]]` table. Filters
+/// left out show everything.
+#[derive(Debug, Default, Clone)]
+pub struct FilterPreset {
+    name: String,
+    time_window: TimeWindow,
+    unread_only: bool,
+    /// A case-insensitive regex on item titles and descriptions, as typed into `/`.
+    keyword: Option<String>,
+    /// The group to list the feeds of.
+    group: Option<String>,
+}
+
+impl FilterPreset {
+    pub fn new(
+        name: &str,
+        time_window: TimeWindow,
+        unread_only: bool,
+        keyword: Option<String>,
+        group: Option<String>,
+    ) -> Self {
+        Self {
+            name: name.to_owned(),
+            time_window,
+            unread_only,
+            keyword,
+            group,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn time_window(&self) -> TimeWindow {
+        self.time_window
+    }
+
+    pub fn unread_only(&self) -> bool {
+        self.unread_only
+    }
+
+    pub fn keyword(&self) -> Option<&str> {
+        self.keyword.as_deref()
+    }
+
+    pub fn group(&self) -> Option<&str> {
+        self.group.as_deref()
+    }
+
+    fn to_table(&self) -> toml_edit::Table {
+        let mut table = toml_edit::Table::new();
+        table.insert("name", value(self.name.as_str()));
+        if self.time_window != TimeWindow::All {
+            table.insert("time_window", value(self.time_window.to_string()));
+        }
+        if self.unread_only {
+            table.insert("unread_only", value(true));
+        }
+        if let Some(keyword) = &self.keyword {
+            table.insert("keyword", value(keyword.as_str()));
+        }
+        if let Some(group) = &self.group {
+            table.insert("group", value(group.as_str()));
+        }
+        table
+    }
+}
+
+impl TryFrom<&Value> for FilterPreset {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &Value) -> Result<Self> {
+        let table = value
+            .as_table()
+            .context("invalid config entry for [[filter_presets]]")?;
+        let name = table
+            .get("name")
+            .and_then(Value::as_str)
+            .context("[[filter_presets]] is missing a name")?;
+        let time_window = match table.get("time_window") {
+            Some(Value::String(window)) => TimeWindow::from_str(window)
+                .with_context(|| format!("invalid time_window for preset '{}'", name))?,
+            Some(_) => bail!("invalid config entry for [[filter_presets]].time_window"),
+            None => TimeWindow::All,
+        };
+        let keyword = table.get("keyword").and_then(Value::as_str);
+        if let Some(keyword) = keyword {
+            RegexBuilder::new(keyword)
+                .case_insensitive(true)
+                .build()
+                .with_context(|| format!("invalid keyword for preset '{}'", name))?;
+        }
+
+        Ok(Self {
+            name: name.to_owned(),
+            time_window,
+            unread_only: table
+                .get("unread_only")
+                .and_then(Value::as_bool)
+                .unwrap_or(false),
+            keyword: keyword.map(str::to_owned),
+            group: table
+                .get("group")
+                .and_then(Value::as_str)
+                .map(str::to_owned),
+        })
+    }
+}
+
+/// A service that keeps reading state in step across readers, read from the `[sync]` table.
+#[derive(Debug, Clone)]
+pub struct SyncConfig {
+    backend: SyncBackend,
+    base_url: String,
+}
+
+/// The kind of sync server, with the credentials it takes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SyncBackend {
+    Miniflux { api_key: String },
+    NextcloudNews { username: String, password: String },
+    GReader { username: String, password: String },
+}
+
+impl SyncConfig {
+    pub fn backend(&self) -> &SyncBackend {
+        &self.backend
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+}
+
+impl TryFrom<&Value> for SyncConfig {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &Value) -> Result<Self> {
+        let table = value
+            .as_table()
+            .context("invalid config entry for [sync]")?;
+        let field = |name: &str| {
+            table
+                .get(name)
+                .and_then(Value::as_str)
+                .map(str::to_owned)
+                .with_context(|| format!("[sync] is missing {}", name))
+        };
+        let backend = match field("backend")?.as_str() {
+            "miniflux" => SyncBackend::Miniflux {
+                api_key: field("api_key")?,
+            },
+            "nextcloud_news" => SyncBackend::NextcloudNews {
+                username: field("username")?,
+                password: field("password")?,
+            },
+            "greader" => SyncBackend::GReader {
+                username: field("username")?,
+                password: field("password")?,
+            },
+            backend => anyhow::bail!("unknown sync backend '{}'", backend),
+        };
+
+        Ok(Self {
+            backend,
+            base_url: field("base_url")?.trim_end_matches('/').to_owned(),
+        })
+    }
+}
+
+/// Where to receive WebSub pushes, read from the `[websub]` table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WebSubConfig {
+    port: u16,
+    callback_url: String,
+}
+
+impl WebSubConfig {
+    /// The local port the listener binds to.
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    /// The public URL hubs reach the listener at, which each subscription adds a path to.
+    pub fn callback_url(&self) -> &str {
+        &self.callback_url
+    }
+}
+
+impl TryFrom<&Value> for WebSubConfig {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &Value) -> Result<Self> {
+        let table = value
+            .as_table()
+            .context("invalid config entry for [websub]")?;
+        let port = match table.get("port") {
+            Some(port) => port
+                .as_integer()
+                .and_then(|port| u16::try_from(port).ok())
+                .context("invalid [websub] port")?,
+            None => DEFAULT_WEBSUB_PORT,
+        };
+        let callback_url = table
+            .get("callback_url")
+            .and_then(Value::as_str)
+            .context("[websub] is missing callback_url")?;
+
+        Ok(Self {
+            port,
+            callback_url: callback_url.trim_end_matches('/').to_owned(),
+        })
+    }
+}
+
 #[derive(Debug)]
 pub struct SortOrderError;
 
@@ -73,80 +848,419 @@ impl FromStr for SortOrder {
             _ => Ok(SortOrder::Az),
         }
     }
-}
+}
+
+impl FromStr for KeybindingMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "default" => Ok(KeybindingMode::Default),
+            "vim" => Ok(KeybindingMode::Vim),
+            _ => Err(anyhow::anyhow!("unknown keybinding mode '{}'", s)),
+        }
+    }
+}
+
+impl FromStr for PaneLayout {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "horizontal" => Ok(PaneLayout::Horizontal),
+            "vertical" => Ok(PaneLayout::Vertical),
+            _ => Err(anyhow::anyhow!("unknown layout '{}'", s)),
+        }
+    }
+}
+
+impl FromStr for ScrollbarStyle {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "block" => Ok(ScrollbarStyle::Block),
+            "thin" => Ok(ScrollbarStyle::Thin),
+            "arrows" => Ok(ScrollbarStyle::Arrows),
+            "none" => Ok(ScrollbarStyle::None),
+            _ => Err(anyhow::anyhow!("unknown scrollbar style '{}'", s)),
+        }
+    }
+}
+
+impl FromStr for BorderStyle {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "plain" => Ok(BorderStyle::Plain),
+            "rounded" => Ok(BorderStyle::Rounded),
+            "double" => Ok(BorderStyle::Double),
+            "thick" => Ok(BorderStyle::Thick),
+            "none" => Ok(BorderStyle::None),
+            _ => Err(anyhow::anyhow!("unknown border style '{}'", s)),
+        }
+    }
+}
+
+impl FromStr for ItemDisplayMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "single" => Ok(ItemDisplayMode::Single),
+            "double" => Ok(ItemDisplayMode::Double),
+            _ => Err(anyhow::anyhow!("unknown item display mode '{}'", s)),
+        }
+    }
+}
+
+/// A feed's color in the feeds pane: one of the terminal's named colors, or `#RRGGBB`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Color {
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    Gray,
+    DarkGray,
+    LightRed,
+    LightGreen,
+    LightYellow,
+    LightBlue,
+    LightMagenta,
+    LightCyan,
+    White,
+    Hex(u8, u8, u8),
+}
+
+impl Color {
+    /// The colors `cycle_feed_color` steps through.
+    pub const PALETTE: [Color; 6] = [
+        Color::Red,
+        Color::Green,
+        Color::Yellow,
+        Color::Blue,
+        Color::Magenta,
+        Color::Cyan,
+    ];
+
+    /// The color after `color` in the palette. After the last one, or a color from outside
+    /// the palette, there is none, which leaves the theme's color.
+    pub fn next_in_palette(color: Option<Color>) -> Option<Color> {
+        match color {
+            None => Some(Self::PALETTE[0]),
+            Some(color) => Self::PALETTE
+                .iter()
+                .position(|c| *c == color)
+                .and_then(|i| Self::PALETTE.get(i + 1))
+                .copied(),
+        }
+    }
+}
+
+impl FromStr for Color {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        if let Some(hex) = s.strip_prefix('#') {
+            let channel = |i: usize| {
+                hex.get(i..i + 2)
+                    .and_then(|channel| u8::from_str_radix(channel, 16).ok())
+            };
+            return match (hex.len(), channel(0), channel(2), channel(4)) {
+                (6, Some(r), Some(g), Some(b)) => Ok(Color::Hex(r, g, b)),
+                _ => Err(anyhow::anyhow!("invalid color '{}'", s)),
+            };
+        }
+
+        match s.to_ascii_lowercase().replace('-', "_").as_str() {
+            "black" => Ok(Color::Black),
+            "red" => Ok(Color::Red),
+            "green" => Ok(Color::Green),
+            "yellow" => Ok(Color::Yellow),
+            "blue" => Ok(Color::Blue),
+            "magenta" => Ok(Color::Magenta),
+            "cyan" => Ok(Color::Cyan),
+            "gray" | "grey" => Ok(Color::Gray),
+            "dark_gray" | "dark_grey" => Ok(Color::DarkGray),
+            "light_red" => Ok(Color::LightRed),
+            "light_green" => Ok(Color::LightGreen),
+            "light_yellow" => Ok(Color::LightYellow),
+            "light_blue" => Ok(Color::LightBlue),
+            "light_magenta" => Ok(Color::LightMagenta),
+            "light_cyan" => Ok(Color::LightCyan),
+            "white" => Ok(Color::White),
+            _ => Err(anyhow::anyhow!("unknown color '{}'", s)),
+        }
+    }
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Color::Black => "black",
+            Color::Red => "red",
+            Color::Green => "green",
+            Color::Yellow => "yellow",
+            Color::Blue => "blue",
+            Color::Magenta => "magenta",
+            Color::Cyan => "cyan",
+            Color::Gray => "gray",
+            Color::DarkGray => "dark_gray",
+            Color::LightRed => "light_red",
+            Color::LightGreen => "light_green",
+            Color::LightYellow => "light_yellow",
+            Color::LightBlue => "light_blue",
+            Color::LightMagenta => "light_magenta",
+            Color::LightCyan => "light_cyan",
+            Color::White => "white",
+            Color::Hex(r, g, b) => return write!(f, "#{:02x}{:02x}{:02x}", r, g, b),
+        };
+        f.write_str(name)
+    }
+}
+
+impl Config {
+    pub fn new(args: Args) -> Result<Self> {
+        let (dir_path, file_path): (PathBuf, PathBuf) = if let Some(path) = &args.config {
+            let file_path = Path::new(&path);
+            if !file_path.exists() {
+                bail!("no config file found at '{}'", file_path.display());
+            }
+
+            let dir_path = file_path.parent().expect("could not find config directory");
+            (dir_path.into(), file_path.into())
+        } else {
+            // the directory older versions kept moccasin.toml in differs on Windows
+            let legacy_path = project_dirs().config_local_dir().join(LEGACY_CONFIG_FILE);
+            let file_path = match Self::default_config_path() {
+                file_path if !file_path.exists() && legacy_path.exists() => legacy_path,
+                file_path => file_path,
+            };
+            let dir_path = file_path
+                .parent()
+                .expect("could not find config directory")
+                .to_owned();
+            fs::create_dir_all(&dir_path)?;
+            (dir_path, file_path)
+        };
+
+        logging::init(&args, &dir_path)?;
+
+        let config = if file_path.exists() {
+            Self::read_from_toml(args, dir_path, file_path)
+        } else {
+            Self::create_initialized(args, dir_path, file_path)
+        };
+        config.map(Self::load_with_env)
+    }
+
+    /// `config.toml` in `$XDG_CONFIG_HOME/moccasin`, or `~/.config/moccasin` if that is unset
+    /// or relative. On Windows it is in `%APPDATA%\moccasin`, which roams with the user.
+    pub fn default_config_path() -> PathBuf {
+        #[cfg(windows)]
+        let dir = base_dirs().config_dir().join(APP_DIR);
+        #[cfg(not(windows))]
+        let dir = project_dirs().config_local_dir().to_owned();
+        dir.join(DEFAULT_CONFIG_FILE)
+    }
+
+    /// `feeds.db` in `$XDG_DATA_HOME/moccasin`, or `~/.local/share/moccasin`. On Windows it is
+    /// in `%LOCALAPPDATA%\moccasin`, as the database is specific to the machine.
+    pub fn default_data_path() -> PathBuf {
+        #[cfg(windows)]
+        let dir = base_dirs().data_local_dir().join(APP_DIR);
+        #[cfg(not(windows))]
+        let dir = project_dirs().data_local_dir().to_owned();
+        dir.join(DEFAULT_DB_FILE)
+    }
+
+    /// `$XDG_CACHE_HOME/moccasin`, or `~/.cache/moccasin`, and `%LOCALAPPDATA%\moccasin\cache`
+    /// on Windows.
+    pub fn default_cache_path() -> PathBuf {
+        #[cfg(windows)]
+        let dir = base_dirs().cache_dir().join(APP_DIR).join("cache");
+        #[cfg(not(windows))]
+        let dir = project_dirs().cache_dir().to_owned();
+        dir
+    }
+
+    pub fn config_dir_path(&self) -> PathBuf {
+        Path::new(&self.dir_path).to_owned()
+    }
+
+    pub fn config_file_path(&self) -> PathBuf {
+        Path::new(&self.file_path).to_owned()
+    }
+
+    /// The config file that profiles are merged over.
+    pub fn base_config_file_path(&self) -> PathBuf {
+        self.base_file_path.clone()
+    }
+
+    pub fn db_path(&self) -> PathBuf {
+        self.db_path.clone()
+    }
+
+    pub fn profile(&self) -> &str {
+        &self.profile
+    }
+
+    pub fn profiles_path(&self) -> PathBuf {
+        self.config_dir_path().join(PROFILES_DIR)
+    }
+
+    /// Names of the profile files in the profiles directory, sorted.
+    pub fn installed_profiles(&self) -> Vec<String> {
+        let mut names = fs::read_dir(self.profiles_path())
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| entry.ok())
+                    .map(|entry| entry.path())
+                    .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+                    .filter_map(|path| Some(path.file_stem()?.to_str()?.to_owned()))
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        names.sort();
+        names
+    }
+
+    /// Reads the config again, with the profile `name` merged over it in place of the
+    /// current one. Command line options still apply.
+    pub fn load_profile(&self, name: &str) -> Result<Config> {
+        let args = Args {
+            profile: Some(name.to_owned()),
+            ..self.args.clone()
+        };
+        Self::read_from_toml(args, self.dir_path.clone(), self.base_file_path.clone())
+            .map(Self::load_with_env)
+    }
+
+    pub fn themes_path(&self) -> PathBuf {
+        self.config_dir_path().join(THEMES_DIR)
+    }
+
+    pub fn is_first_run(&self) -> bool {
+        self.first_run
+    }
+
+    pub fn theme(&self) -> &theme::Theme {
+        &self.theme
+    }
+
+    pub fn feed_urls(&self) -> &HashSet<String> {
+        &self.feed_urls
+    }
+
+    pub fn feed_config(&self, url: &str) -> Option<&FeedConfig> {
+        self.feed_configs.get(url)
+    }
+
+    pub fn feed_color(&self, url: &str) -> Option<Color> {
+        self.feed_config(url).and_then(FeedConfig::color)
+    }
+
+    pub fn groups(&self) -> &[Group] {
+        &self.groups
+    }
+
+    pub fn filter_presets(&self) -> &[FilterPreset] {
+        &self.filter_presets
+    }
+
+    pub fn filter_preset(&self, name: &str) -> Option<&FilterPreset> {
+        self.filter_presets
+            .iter()
+            .find(|preset| preset.name() == name)
+    }
+
+    /// Whether to check the database thoroughly at startup, rather than quickly.
+    pub fn check_db(&self) -> bool {
+        self.args.check_db
+    }
+
+    /// Whether to send a desktop notification when a refresh brings new items.
+    pub fn notifications(&self) -> bool {
+        self.notifications
+    }
+
+    /// Whether to set the terminal's title to the selected feed and item.
+    pub fn window_title(&self) -> bool {
+        self.window_title
+    }
+
+    pub fn ascii_mode(&self) -> bool {
+        self.ascii_mode
+    }
+
+    pub fn sync(&self) -> Option<&SyncConfig> {
+        self.sync.as_ref()
+    }
+
+    pub fn websub(&self) -> Option<&WebSubConfig> {
+        self.websub.as_ref()
+    }
 
-impl Config {
-    pub fn new(args: Args) -> Result<Self> {
-        let (dir_path, file_path): (PathBuf, PathBuf) = if let Some(path) = &args.config {
-            let file_path = Path::new(&path);
-            if !file_path.exists() {
-                panic!(
-                    "no config file found at '{}'",
-                    file_path.to_owned().to_str().unwrap()
-                )
-            }
+    pub fn highlights(&self) -> &Highlights {
+        &self.highlights
+    }
 
-            let dir_path = file_path.parent().expect("could not find config directory");
-            (dir_path.into(), file_path.into())
-        } else {
-            let dir_path = ProjectDirs::from("com", "rektsoft", "moccasin")
-                .unwrap()
-                .config_local_dir()
-                .to_owned();
-            let file_path = dir_path.join(DEFAULT_CONFIG_FILE).to_owned();
-            fs::create_dir_all(&dir_path)?;
-            (dir_path, file_path)
-        };
+    /// The position in `groups` of the first group listing `url`.
+    pub fn group_index(&self, url: &str) -> Option<usize> {
+        self.groups
+            .iter()
+            .position(|group| group.feeds().iter().any(|u| u == url))
+    }
 
-        if cfg!(debug_assertions) {
-            dbg!(&dir_path.join("moccasin.log"));
-            let file = OpenOptions::new()
-                .create(true)
-                .write(true)
-                .append(true)
-                .open(dir_path.join("moccasin.log"))
-                .expect("could not open file for witing");
-            simplelog::WriteLogger::init(
-                simplelog::LevelFilter::Info,
-                simplelog::Config::default(),
-                file,
-            )
-            .expect("could not initialize logger");
-        }
+    pub fn sort_order(&self) -> &SortOrder {
+        &self.sort_order
+    }
 
-        if file_path.exists() {
-            Self::read_from_toml(args, dir_path, file_path)
-        } else {
-            Self::create_initialized(args, dir_path, file_path)
-        }
+    pub fn item_sort_order(&self) -> ItemSortOrder {
+        self.item_sort_order
     }
 
-    pub fn config_dir_path(&self) -> PathBuf {
-        Path::new(&self.dir_path).to_owned()
+    /// Changes the order of items for this session, leaving the config file as it is.
+    pub fn set_item_sort_order(&mut self, order: ItemSortOrder) {
+        self.item_sort_order = order;
     }
 
-    pub fn config_file_path(&self) -> PathBuf {
-        Path::new(&self.file_path).to_owned()
+    pub fn keybinding_mode(&self) -> KeybindingMode {
+        self.keybinding_mode
     }
 
-    pub fn db_path(&self) -> PathBuf {
-        self.config_dir_path().join(DEFAULT_DB_FILE)
+    /// The keys set in the `[keybindings]` table, by action.
+    pub fn keybindings(&self) -> &HashMap<keymap::Action, Vec<KeyEvent>> {
+        &self.keybindings
     }
 
-    pub fn themes_path(&self) -> PathBuf {
-        self.config_dir_path().join("themes")
+    pub fn layout(&self) -> PaneLayout {
+        self.layout
     }
 
-    pub fn theme(&self) -> &theme::Theme {
-        &self.theme
+    pub fn scrollbar_style(&self) -> ScrollbarStyle {
+        self.scrollbar_style
     }
 
-    pub fn feed_urls(&self) -> &HashSet<String> {
-        &self.feed_urls
+    pub fn border_style(&self) -> BorderStyle {
+        self.border_style
     }
 
-    pub fn sort_order(&self) -> &SortOrder {
-        &self.sort_order
+    pub fn item_columns(&self) -> &[Column] {
+        &self.item_columns
+    }
+
+    pub fn item_display_mode(&self) -> ItemDisplayMode {
+        self.item_display_mode
+    }
+
+    pub fn max_detail_lines(&self) -> Option<usize> {
+        self.max_detail_lines
     }
 
     pub fn should_cache(&self) -> bool {
@@ -161,6 +1275,84 @@ impl Config {
         self.refresh_timeout
     }
 
+    pub fn max_retries(&self) -> u8 {
+        self.max_retries
+    }
+
+    pub fn retention_days(&self) -> Option<u64> {
+        self.retention_days
+    }
+
+    /// How many refreshes in a row a feed may fail before it is considered dead.
+    pub fn dead_after(&self) -> u8 {
+        self.dead_after
+    }
+
+    pub fn max_concurrent_fetches(&self) -> usize {
+        self.max_concurrent_fetches
+    }
+
+    /// How often, in milliseconds, the interface and daemon look for finished work.
+    pub fn tick_rate_ms(&self) -> u64 {
+        self.tick_rate_ms
+    }
+
+    pub fn follow_redirects(&self) -> bool {
+        self.follow_redirects
+    }
+
+    pub fn max_redirects(&self) -> usize {
+        self.max_redirects
+    }
+
+    /// The proxy for `url`, falling back to the global `proxy_url`. With neither set, requests
+    /// use the proxy from the `HTTP_PROXY`, `HTTPS_PROXY` and `NO_PROXY` environment variables.
+    pub fn feed_proxy(&self, url: &str) -> Option<&str> {
+        self.feed_config(url)
+            .and_then(FeedConfig::proxy_url)
+            .or(self.proxy_url.as_deref())
+    }
+
+    /// The `User-Agent` sent with requests, which defaults to naming moccasin and its version.
+    pub fn user_agent(&self) -> &str {
+        self.user_agent.as_deref().unwrap_or(DEFAULT_USER_AGENT)
+    }
+
+    /// The `User-Agent` for `url`, falling back to the global `user_agent`.
+    pub fn feed_user_agent(&self, url: &str) -> &str {
+        self.feed_config(url)
+            .and_then(FeedConfig::user_agent)
+            .unwrap_or(self.user_agent())
+    }
+
+    /// The request timeout for `url`, falling back to the global `refresh_timeout`.
+    /// How often to refresh the feed at `url`, in seconds, where 0 means never.
+    pub fn feed_refresh_interval(&self, url: &str) -> u64 {
+        self.feed_config(url)
+            .and_then(FeedConfig::refresh_every_secs)
+            .unwrap_or(self.refresh_interval)
+    }
+
+    /// Whether any feed refreshes on a schedule.
+    pub fn refreshes_automatically(&self) -> bool {
+        self.feed_urls
+            .iter()
+            .any(|url| self.feed_refresh_interval(url) > 0)
+    }
+
+    pub fn feed_timeout(&self, url: &str) -> u64 {
+        self.feed_config(url)
+            .and_then(FeedConfig::timeout_secs)
+            .unwrap_or(self.refresh_timeout)
+    }
+
+    /// The most bytes to read of a response for `url`, its own limit or the global one.
+    pub fn feed_max_response_bytes(&self, url: &str) -> usize {
+        self.feed_config(url)
+            .and_then(FeedConfig::max_response_bytes)
+            .unwrap_or(self.max_response_bytes)
+    }
+
     pub fn write_config(&self) -> Result<()> {
         let toml = fs::read_to_string(&self.file_path)?;
         let mut toml = toml.parse::<Document>()?;
@@ -176,6 +1368,31 @@ impl Config {
         Ok(())
     }
 
+    /// Writes the answers to the setup wizard to the config file. On the first run the feed
+    /// replaces the example feeds, and otherwise it is added to those listed.
+    pub fn write_setup(
+        &mut self,
+        feed_url: &str,
+        theme: &str,
+        refresh_interval: u64,
+    ) -> Result<()> {
+        if self.first_run {
+            self.feed_urls.clear();
+        }
+        self.feed_urls.insert(feed_url.into());
+        self.write_config()?;
+
+        let toml = fs::read_to_string(&self.file_path)?;
+        let mut toml = toml.parse::<Document>()?;
+        toml["preferences"]["color_scheme"] = value(theme);
+        toml["preferences"]["refresh_interval"] = value(refresh_interval as i64);
+        fs::write(&self.file_path, toml.to_string())?;
+
+        log::info!("Wrote setup to {}", self.file_path.display());
+        self.first_run = false;
+        Ok(())
+    }
+
     pub fn add_feed_url(&mut self, url: &str) -> Result<()> {
         if !self.feed_urls().contains(url) {
             log::info!("Adding new feed for {}", url);
@@ -185,6 +1402,127 @@ impl Config {
         Ok(())
     }
 
+    /// Sets a request header for `url` under its `[feeds."<url>".headers]` table.
+    pub fn add_feed_header(&mut self, url: &str, name: &str, value: &str) -> Result<()> {
+        let (header_name, header_value) = parse_header(name, value)?;
+
+        let toml = fs::read_to_string(&self.file_path)?;
+        let mut toml = toml.parse::<Document>()?;
+        feed_entry(&mut toml, url)?
+            .entry("headers")
+            .or_insert(table())
+            .as_table_mut()
+            .with_context(|| format!("invalid config entry for [feeds.\"{}\".headers]", url))?
+            .insert(name, toml_edit::value(value));
+        fs::write(&self.file_path, toml.to_string())?;
+
+        log::info!("Setting header {} for {}", name, url);
+        self.feed_configs
+            .entry(url.into())
+            .or_default()
+            .headers
+            .insert(header_name, header_value);
+        Ok(())
+    }
+
+    /// The HTTP Basic credentials for `url`. The password is read from the system keyring the
+    /// first time, which may block, and is `None` if none was stored or the keyring can't be
+    /// reached.
+    pub fn feed_credentials(&self, url: &str) -> Option<(String, Option<String>)> {
+        let username = self.feed_config(url)?.username()?;
+        let mut passwords = self
+            .passwords
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let password = passwords
+            .entry(url.to_owned())
+            .or_insert_with(|| {
+                match keyring_entry(url, username).map(|entry| entry.get_password()) {
+                    Ok(Ok(password)) => Some(password),
+                    _ => {
+                        log::warn!("No password found in keyring for {}", url);
+                        None
+                    }
+                }
+            })
+            .clone();
+        Some((username.to_owned(), password))
+    }
+
+    /// Stores the username for `url` in the config and its password in the system keyring.
+    pub fn set_feed_credentials(
+        &mut self,
+        url: &str,
+        username: &str,
+        password: &str,
+    ) -> Result<()> {
+        keyring_entry(url, username)?
+            .set_password(password)
+            .context("could not store password in keyring")?;
+
+        let toml = fs::read_to_string(&self.file_path)?;
+        let mut toml = toml.parse::<Document>()?;
+        feed_entry(&mut toml, url)?.insert("username", toml_edit::value(username));
+        fs::write(&self.file_path, toml.to_string())?;
+
+        log::info!("Setting credentials for {}", url);
+        self.feed_configs.entry(url.into()).or_default().username = Some(username.into());
+        self.passwords
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(url.to_owned(), Some(password.to_owned()));
+        Ok(())
+    }
+
+    /// Sets the color of the feed at `url` in the feeds pane, or removes it with `None`.
+    pub fn set_feed_color(&mut self, url: &str, color: Option<Color>) -> Result<()> {
+        let toml = fs::read_to_string(&self.file_path)?;
+        let mut toml = toml.parse::<Document>()?;
+        let entry = feed_entry(&mut toml, url)?;
+        match color {
+            Some(color) => {
+                entry.insert("color", toml_edit::value(color.to_string()));
+            }
+            None => {
+                entry.remove("color");
+            }
+        }
+        fs::write(&self.file_path, toml.to_string())?;
+
+        self.feed_configs.entry(url.into()).or_default().color = color;
+        Ok(())
+    }
+
+    /// Adds `preset` to the `[[filter_presets]]` tables, in place of any of the same name.
+    pub fn save_filter_preset(&mut self, preset: FilterPreset) -> Result<()> {
+        let toml = fs::read_to_string(&self.file_path)?;
+        let mut toml = toml.parse::<Document>()?;
+        let presets = toml
+            .entry("filter_presets")
+            .or_insert(toml_edit::Item::ArrayOfTables(Default::default()))
+            .as_array_of_tables_mut()
+            .context("invalid config entry for [[filter_presets]]")?;
+        let existing = presets.iter().position(|table| {
+            table.get("name").and_then(|name| name.as_str()) == Some(preset.name())
+        });
+        match existing.and_then(|i| presets.get_mut(i)) {
+            Some(table) => *table = preset.to_table(),
+            None => presets.push(preset.to_table()),
+        }
+        fs::write(&self.file_path, toml.to_string())?;
+
+        log::info!("Saving filter preset {}", preset.name());
+        match self
+            .filter_presets
+            .iter_mut()
+            .find(|saved| saved.name() == preset.name())
+        {
+            Some(saved) => *saved = preset,
+            None => self.filter_presets.push(preset),
+        }
+        Ok(())
+    }
+
     pub fn remove_feed_url(&mut self, url: &str) -> Result<()> {
         if self.feed_urls().contains(url) {
             log::info!("Deleting feed for {}", url);
@@ -194,9 +1532,77 @@ impl Config {
         Ok(())
     }
 
-    fn read_from_toml(args: Args, dir_path: PathBuf, file_path: PathBuf) -> Result<Self> {
-        let toml = fs::read_to_string(&file_path)?;
-        let table = toml.parse::<Table>()?;
+    pub fn import_opml(&mut self, path: &Path) -> Result<(usize, usize)> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("could not read OPML file '{}'", path.display()))?;
+        let urls = opml::read_feed_urls(&contents)
+            .with_context(|| format!("could not import OPML file '{}'", path.display()))?;
+
+        let mut known = self
+            .feed_urls()
+            .iter()
+            .map(|url| url.to_lowercase())
+            .collect::<HashSet<_>>();
+        let (mut added, mut present) = (0, 0);
+
+        for url in urls {
+            if known.insert(url.to_lowercase()) {
+                log::info!("Importing feed for {}", url);
+                self.feed_urls.insert(url);
+                added += 1;
+            } else {
+                present += 1;
+            }
+        }
+
+        if added > 0 {
+            self.write_config()?;
+        }
+        Ok((added, present))
+    }
+
+    fn read_from_toml(args: Args, dir_path: PathBuf, base_file_path: PathBuf) -> Result<Self> {
+        let toml = fs::read_to_string(&base_file_path)?;
+        let mut table = toml.parse::<Table>()?;
+
+        // a profile's values replace those in config.toml, except that each profile keeps
+        // its own database
+        let profile = args
+            .profile
+            .as_deref()
+            .unwrap_or(DEFAULT_PROFILE)
+            .to_owned();
+        let profile_path = dir_path
+            .join(PROFILES_DIR)
+            .join(format!("{}.toml", profile));
+        let (file_path, db_path) = if profile_path.exists() {
+            let overrides = fs::read_to_string(&profile_path)?
+                .parse::<Table>()
+                .with_context(|| format!("could not read profile '{}'", profile))?;
+            let db_path = db_path_preference(&overrides).unwrap_or_else(|| {
+                if profile == DEFAULT_PROFILE {
+                    db_path_preference(&table).unwrap_or_else(|| default_db_path(&args, &dir_path))
+                } else {
+                    Path::new(PROFILES_DIR).join(format!("{}.db", profile))
+                }
+            });
+            merge_tables(&mut table, overrides);
+            (profile_path, db_path)
+        } else if profile == DEFAULT_PROFILE {
+            let db_path =
+                db_path_preference(&table).unwrap_or_else(|| default_db_path(&args, &dir_path));
+            (base_file_path.clone(), db_path)
+        } else {
+            bail!(
+                "no profile named '{}' in {}",
+                profile,
+                dir_path.join(PROFILES_DIR).display()
+            );
+        };
+        let db_path = match &args.db {
+            Some(path) => path.clone(),
+            None => dir_path.join(db_path),
+        };
         let feeds: HashSet<String> = match table.get("sources") {
             Some(Value::Table(sources)) => match sources.get("feeds") {
                 Some(Value::Array(els)) => els
@@ -204,29 +1610,82 @@ impl Config {
                     .filter_map(|v| v.as_str().and_then(|v| Some(v.to_owned())))
                     .collect(),
                 Some(_) => {
-                    panic!("unexpected config entry for [sources].feeds")
+                    bail!("unexpected config entry for [sources].feeds")
                 }
                 _ => HashSet::new(),
             },
-            _ => panic!("unexpected config entry for [sources]"),
+            _ => bail!("unexpected config entry for [sources]"),
+        };
+
+        let feed_configs = match table.get("feeds") {
+            Some(Value::Table(entries)) => entries
+                .iter()
+                .filter_map(|(url, entry)| match entry {
+                    Value::Table(entry) => Some((url.to_owned(), FeedConfig::from(entry))),
+                    _ => None,
+                })
+                .collect(),
+            Some(_) => bail!("invalid config entry for [feeds]"),
+            None => HashMap::new(),
+        };
+
+        let groups = match table.get("group") {
+            Some(Value::Array(els)) => els
+                .iter()
+                .map(Group::try_from)
+                .collect::<Result<Vec<_>>>()?,
+            Some(_) => bail!("invalid config entry for [[group]]"),
+            None => vec![],
         };
 
+        let filter_presets = match table.get("filter_presets") {
+            Some(Value::Array(els)) => els
+                .iter()
+                .map(FilterPreset::try_from)
+                .collect::<Result<Vec<_>>>()?,
+            Some(_) => bail!("invalid config entry for [[filter_presets]]"),
+            None => vec![],
+        };
+
+        let keybindings = match table.get("keybindings") {
+            Some(Value::Table(bindings)) => keymap::parse_bindings(bindings)?,
+            Some(_) => bail!("invalid config entry for [keybindings]"),
+            None => HashMap::new(),
+        };
+
+        let sync = table.get("sync").map(SyncConfig::try_from).transpose()?;
+        let websub = table
+            .get("websub")
+            .map(WebSubConfig::try_from)
+            .transpose()?;
+
         let preferences = match table.get("preferences") {
             Some(Value::Table(prefs)) => Some(prefs),
-            Some(_) => panic!("invalid config entry for [preferences]"),
+            Some(_) => bail!("invalid config entry for [preferences]"),
             None => None,
         };
 
-        // TODO: load from args if present
+        let themes_dir = dir_path.join(THEMES_DIR);
         let theme = args
             .color_scheme
-            .and_then(|scheme| theme::Theme::from_str(&scheme).ok())
-            .or(preferences.and_then(|prefs| {
-                prefs
-                    .get("color_scheme")
-                    .and_then(|scheme| theme::Theme::try_from(scheme).ok())
-            }))
+            .as_deref()
+            .and_then(|scheme| load_theme(scheme, &themes_dir))
+            .or(
+                preferences.and_then(|prefs| match prefs.get("color_scheme") {
+                    Some(Value::String(scheme)) => load_theme(scheme, &themes_dir),
+                    Some(scheme) => theme::Theme::try_from(scheme).ok(),
+                    None => None,
+                }),
+            )
             .unwrap_or_default();
+        // rows can be striped in any scheme, the built-in ones included
+        let theme = match preferences
+            .and_then(|prefs| prefs.get("zebra_stripe"))
+            .and_then(Value::as_bool)
+        {
+            Some(zebra_stripe) => theme.with_zebra_stripe(zebra_stripe),
+            None => theme,
+        };
 
         let sort_order: SortOrder = preferences
             .and_then(|prefs| {
@@ -237,6 +1696,42 @@ impl Config {
             })
             .unwrap_or_default();
 
+        let item_sort_order = preferences
+            .and_then(|prefs| prefs.get("sort_items"))
+            .and_then(Value::as_str)
+            .and_then(|order| ItemSortOrder::from_str(order).ok())
+            .unwrap_or_default();
+
+        let keybinding_mode = preferences
+            .and_then(|prefs| prefs.get("keybinding_mode"))
+            .and_then(Value::as_str)
+            .and_then(|mode| KeybindingMode::from_str(mode).ok())
+            .unwrap_or_default();
+
+        let layout = preferences
+            .and_then(|prefs| prefs.get("layout"))
+            .and_then(Value::as_str)
+            .and_then(|layout| PaneLayout::from_str(layout).ok())
+            .unwrap_or_default();
+
+        let scrollbar_style = preferences
+            .and_then(|prefs| prefs.get("scrollbar_style"))
+            .and_then(Value::as_str)
+            .and_then(|style| ScrollbarStyle::from_str(style).ok())
+            .unwrap_or_default();
+
+        let border_style = preferences
+            .and_then(|prefs| prefs.get("border_style"))
+            .and_then(Value::as_str)
+            .and_then(|style| BorderStyle::from_str(style).ok())
+            .unwrap_or_default();
+
+        let item_display_mode = preferences
+            .and_then(|prefs| prefs.get("item_display_mode"))
+            .and_then(Value::as_str)
+            .and_then(|mode| ItemDisplayMode::from_str(mode).ok())
+            .unwrap_or_default();
+
         let refresh_interval = args
             .interval
             .or({
@@ -261,6 +1756,121 @@ impl Config {
             })
             .unwrap_or(DEFAULT_REFRESH_TIMEOUT);
 
+        let max_retries = preferences
+            .and_then(|prefs| {
+                prefs.get("max_retries").and_then(|i| match i {
+                    Value::Integer(i) if *i >= 0 => Some((*i).min(MAX_RETRIES_LIMIT.into()) as u8),
+                    _ => None,
+                })
+            })
+            .unwrap_or(DEFAULT_MAX_RETRIES);
+
+        let max_response_bytes = preferences
+            .and_then(|prefs| {
+                prefs.get("max_response_bytes").and_then(|b| match b {
+                    Value::Integer(b) if *b > 0 => usize::try_from(*b).ok(),
+                    _ => None,
+                })
+            })
+            .unwrap_or(DEFAULT_MAX_RESPONSE_BYTES);
+
+        let max_concurrent_fetches = low_power_fetches(&args)
+            .or_else(|| {
+                preferences.and_then(|prefs| {
+                    prefs.get("max_concurrent_fetches").and_then(|i| match i {
+                        Value::Integer(i) if *i > 0 => Some(*i as usize),
+                        _ => None,
+                    })
+                })
+            })
+            .unwrap_or(DEFAULT_MAX_CONCURRENT_FETCHES);
+
+        let tick_rate_ms = tick_rate_arg(&args)
+            .or_else(|| {
+                preferences.and_then(|prefs| {
+                    prefs.get("tick_rate_ms").and_then(|i| match i {
+                        Value::Integer(i) if *i > 0 => Some(*i as u64),
+                        _ => None,
+                    })
+                })
+            })
+            .unwrap_or(DEFAULT_TICK_RATE_MS);
+
+        let follow_redirects = preferences
+            .and_then(|prefs| prefs.get("follow_redirects"))
+            .and_then(Value::as_bool)
+            .unwrap_or(true);
+
+        let max_redirects = preferences
+            .and_then(|prefs| {
+                prefs.get("max_redirects").and_then(|i| match i {
+                    Value::Integer(i) if *i >= 0 => Some(*i as usize),
+                    _ => None,
+                })
+            })
+            .unwrap_or(DEFAULT_MAX_REDIRECTS);
+
+        let proxy_url = preferences
+            .and_then(|prefs| prefs.get("proxy_url"))
+            .and_then(Value::as_str)
+            .map(str::to_owned);
+
+        let user_agent = args.user_agent.clone().or_else(|| {
+            preferences
+                .and_then(|prefs| prefs.get("user_agent"))
+                .and_then(Value::as_str)
+                .map(str::to_owned)
+        });
+        if let Some(user_agent) = &user_agent {
+            check_user_agent(user_agent)?;
+        }
+
+        let highlights = match preferences.and_then(|prefs| prefs.get("highlights")) {
+            Some(patterns) => Highlights::try_from(patterns)?,
+            None => Highlights::default(),
+        };
+
+        let dead_after = preferences
+            .and_then(|prefs| {
+                prefs.get("dead_after").and_then(|i| match i {
+                    Value::Integer(i) => u8::try_from(*i).ok(),
+                    _ => None,
+                })
+            })
+            .unwrap_or(DEFAULT_DEAD_AFTER);
+
+        let retention_days = preferences.and_then(|prefs| {
+            prefs.get("retention_days").and_then(|i| match i {
+                Value::Integer(i) if *i > 0 => Some(*i as u64),
+                _ => None,
+            })
+        });
+
+        let item_columns = parse_item_columns(preferences)?;
+
+        let max_detail_lines = preferences.and_then(|prefs| {
+            prefs.get("max_detail_lines").and_then(|i| match i {
+                Value::Integer(i) if *i > 0 => Some(*i as usize),
+                _ => None,
+            })
+        });
+
+        let notifications = args.notify
+            || preferences
+                .and_then(|prefs| prefs.get("notifications"))
+                .and_then(Value::as_bool)
+                .unwrap_or_default();
+
+        let window_title = preferences
+            .and_then(|prefs| prefs.get("window_title"))
+            .and_then(Value::as_bool)
+            .unwrap_or(true);
+
+        let ascii_mode = preferences
+            .and_then(|prefs| prefs.get("ascii_mode"))
+            .and_then(Value::as_bool)
+            .unwrap_or(false);
+
         let cache_control = if args.no_cache {
             CacheControl::Never
         } else {
@@ -275,21 +1885,57 @@ impl Config {
         };
 
         Ok(Self {
+            args,
             file_path,
+            base_file_path,
             dir_path,
+            profile,
+            db_path,
             feed_urls: feeds,
+            feed_configs,
+            passwords: Default::default(),
+            groups,
+            filter_presets,
             sort_order,
+            item_sort_order,
+            keybinding_mode,
+            keybindings,
+            layout,
+            scrollbar_style,
+            border_style,
+            item_columns,
+            item_display_mode,
+            max_detail_lines,
             cache_control,
             refresh_interval,
             refresh_timeout,
+            max_retries,
+            max_response_bytes,
+            max_concurrent_fetches,
+            tick_rate_ms,
+            follow_redirects,
+            max_redirects,
+            proxy_url,
+            user_agent,
+            retention_days,
+            dead_after,
+            highlights,
+            notifications,
+            window_title,
+            ascii_mode,
+            sync,
+            websub,
             theme,
+            first_run: false,
         })
     }
 
     fn create_initialized(args: Args, dir_path: PathBuf, file_path: PathBuf) -> Result<Self> {
+        if let Some(user_agent) = &args.user_agent {
+            check_user_agent(user_agent)?;
+        }
         fs::create_dir_all(&dir_path)?;
-        let cfg_path = Path::new(dir_path.as_path()).join(DEFAULT_CONFIG_FILE);
-        let mut file = File::create(&cfg_path)?;
+        let mut file = File::create(&file_path)?;
         let toml = include_str!("moccasin.toml");
         let stub = toml.parse::<Table>()?;
         let feed_urls = stub["sources"]["feeds"]
@@ -301,13 +1947,243 @@ impl Config {
             .collect::<HashSet<_>>();
         file.write(toml.as_bytes())?;
 
-        // TODO: load theme from args if present
-        Ok(Self {
+        let theme = args
+            .color_scheme
+            .as_deref()
+            .and_then(|scheme| load_theme(scheme, &dir_path.join(THEMES_DIR)))
+            .unwrap_or_default();
+
+        let config = Self {
+            theme,
+            db_path: match &args.db {
+                Some(path) => path.clone(),
+                None => default_db_path(&args, &dir_path),
+            },
             dir_path: dir_path.to_owned(),
             file_path: file_path.to_owned(),
+            base_file_path: file_path.to_owned(),
+            profile: DEFAULT_PROFILE.to_owned(),
             feed_urls,
             refresh_interval: args.interval.unwrap_or(DEFAULT_REFRESH_INTERVAL),
+            refresh_timeout: args.timeout.unwrap_or(DEFAULT_REFRESH_TIMEOUT),
+            max_retries: DEFAULT_MAX_RETRIES,
+            max_response_bytes: DEFAULT_MAX_RESPONSE_BYTES,
+            max_concurrent_fetches: low_power_fetches(&args)
+                .unwrap_or(DEFAULT_MAX_CONCURRENT_FETCHES),
+            tick_rate_ms: tick_rate_arg(&args).unwrap_or(DEFAULT_TICK_RATE_MS),
+            follow_redirects: true,
+            window_title: true,
+            max_redirects: DEFAULT_MAX_REDIRECTS,
+            user_agent: args.user_agent.clone(),
+            dead_after: DEFAULT_DEAD_AFTER,
+            item_columns: default_item_columns(),
+            args: args.clone(),
             ..Default::default()
-        })
+        };
+
+        let mut config = match args.profile.as_deref() {
+            Some(name) if name != DEFAULT_PROFILE => config.load_profile(name)?,
+            _ => config,
+        };
+        config.first_run = true;
+        Ok(config)
+    }
+}
+
+/// The tick rate given by `--tick-rate`, or implied by `--low-power`.
+fn tick_rate_arg(args: &Args) -> Option<u64> {
+    args.tick_rate
+        .filter(|&ms| ms > 0)
+        .or(args.low_power.then_some(LOW_POWER_TICK_RATE_MS))
+}
+
+/// `--low-power` fetches one feed at a time.
+fn low_power_fetches(args: &Args) -> Option<usize> {
+    args.low_power.then_some(1)
+}
+
+fn project_dirs() -> ProjectDirs {
+    ProjectDirs::from("com", "rektsoft", APP_DIR).expect("could not find home directory")
+}
+
+#[cfg(windows)]
+fn base_dirs() -> directories::BaseDirs {
+    directories::BaseDirs::new().expect("could not find home directory")
+}
+
+/// Where the database is kept without a `db_path`: beside a config given with `--config` or
+/// where it was before moccasin followed the XDG layout, and otherwise in the data directory.
+fn default_db_path(args: &Args, dir_path: &Path) -> PathBuf {
+    let legacy_path = dir_path.join(LEGACY_DB_FILE);
+    if args.config.is_some() || legacy_path.exists() {
+        legacy_path
+    } else {
+        Config::default_data_path()
+    }
+}
+
+/// The `db_path` preference in `table`, relative to the config directory.
+fn db_path_preference(table: &Table) -> Option<PathBuf> {
+    table
+        .get("preferences")
+        .and_then(|prefs| prefs.get("db_path"))
+        .and_then(Value::as_str)
+        .map(PathBuf::from)
+}
+
+/// Overlays `overrides` on `base`, merging tables key by key and replacing other values.
+fn merge_tables(base: &mut Table, overrides: Table) {
+    for (key, value) in overrides {
+        match (base.get_mut(&key), value) {
+            (Some(Value::Table(base)), Value::Table(value)) => merge_tables(base, value),
+            (Some(existing), value) => *existing = value,
+            (None, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+fn load_theme(scheme: &str, themes_dir: &Path) -> Option<theme::Theme> {
+    theme::Theme::find(scheme, themes_dir)
+        .map_err(|err| log::warn!("Could not load color scheme '{}': {:#}", scheme, err))
+        .ok()
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+    use std::ffi::OsStr;
+    use tempfile::TempDir;
+
+    /// The environment is shared by every test thread.
+    static ENV: Mutex<()> = Mutex::new(());
+
+    /// Runs `f` with `HOME` in a temporary directory and the given XDG variables, restoring
+    /// the environment afterwards.
+    fn with_env(vars: &[(&str, Option<&OsStr>)], f: impl FnOnce(&Path)) {
+        let _guard = ENV.lock().unwrap_or_else(PoisonError::into_inner);
+        let home = TempDir::new().unwrap();
+        let names = ["HOME", "XDG_CONFIG_HOME", "XDG_DATA_HOME", "XDG_CACHE_HOME"];
+        let saved: Vec<_> = names
+            .iter()
+            .map(|name| (name, std::env::var_os(name)))
+            .collect();
+        std::env::set_var("HOME", home.path());
+        for (name, value) in vars {
+            match value {
+                Some(value) => std::env::set_var(name, value),
+                None => std::env::remove_var(name),
+            }
+        }
+        f(home.path());
+        for (name, value) in saved {
+            match value {
+                Some(value) => std::env::set_var(name, value),
+                None => std::env::remove_var(name),
+            }
+        }
+    }
+
+    #[test]
+    fn follows_xdg_config_and_data_homes() {
+        let xdg = TempDir::new().unwrap();
+        let config = xdg.path().join("config");
+        let data = xdg.path().join("data");
+        let vars = [
+            ("XDG_CONFIG_HOME", Some(config.as_os_str())),
+            ("XDG_DATA_HOME", Some(data.as_os_str())),
+        ];
+        with_env(&vars, |_| {
+            assert_eq!(
+                Config::default_config_path(),
+                config.join("moccasin").join("config.toml")
+            );
+            assert_eq!(
+                Config::default_data_path(),
+                data.join("moccasin").join("feeds.db")
+            );
+        });
+    }
+
+    #[test]
+    fn falls_back_to_home_when_xdg_homes_are_unset() {
+        let vars = [("XDG_CONFIG_HOME", None), ("XDG_DATA_HOME", None)];
+        with_env(&vars, |home| {
+            assert_eq!(
+                Config::default_config_path(),
+                home.join(".config/moccasin/config.toml")
+            );
+            assert_eq!(
+                Config::default_data_path(),
+                home.join(".local/share/moccasin/feeds.db")
+            );
+        });
+    }
+
+    #[test]
+    fn treats_empty_xdg_homes_as_unset() {
+        let empty = Some(OsStr::new(""));
+        let vars = [("XDG_CONFIG_HOME", empty), ("XDG_DATA_HOME", empty)];
+        with_env(&vars, |home| {
+            assert_eq!(
+                Config::default_config_path(),
+                home.join(".config/moccasin/config.toml")
+            );
+            assert_eq!(
+                Config::default_data_path(),
+                home.join(".local/share/moccasin/feeds.db")
+            );
+        });
+    }
+
+    #[test]
+    fn ignores_relative_xdg_homes() {
+        let relative = Some(OsStr::new("relative/dir"));
+        let vars = [("XDG_CONFIG_HOME", relative), ("XDG_DATA_HOME", relative)];
+        with_env(&vars, |home| {
+            assert_eq!(
+                Config::default_config_path(),
+                home.join(".config/moccasin/config.toml")
+            );
+            assert_eq!(
+                Config::default_data_path(),
+                home.join(".local/share/moccasin/feeds.db")
+            );
+        });
+    }
+
+    #[test]
+    fn keeps_a_legacy_database_beside_the_config() {
+        let dir = TempDir::new().unwrap();
+        let data = TempDir::new().unwrap();
+        let vars = [("XDG_DATA_HOME", Some(data.path().as_os_str()))];
+        with_env(&vars, |_| {
+            let args = Args::default();
+            assert_eq!(
+                default_db_path(&args, dir.path()),
+                data.path().join("moccasin").join("feeds.db")
+            );
+            File::create(dir.path().join(LEGACY_DB_FILE)).unwrap();
+            assert_eq!(
+                default_db_path(&args, dir.path()),
+                dir.path().join(LEGACY_DB_FILE)
+            );
+        });
+    }
+
+    #[test]
+    fn keeps_the_database_beside_a_config_given_on_the_command_line() {
+        let dir = TempDir::new().unwrap();
+        with_env(&[], |_| {
+            let args = Args {
+                config: Some(dir.path().join("moccasin.toml").display().to_string()),
+                ..Args::default()
+            };
+            assert_eq!(
+                default_db_path(&args, dir.path()),
+                dir.path().join(LEGACY_DB_FILE)
+            );
+        });
     }
 }
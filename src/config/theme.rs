@@ -1,5 +1,8 @@
+use anyhow::Context;
 use serde::{Deserialize, Serialize};
 use std::fmt;
+use std::fs;
+use std::path::Path;
 use std::{error::Error, str::FromStr};
 use toml::Value;
 use tui::style::{Color, Modifier, Style, Stylize};
@@ -44,6 +47,10 @@ fn make_color(c: &str) -> Color {
     }
 }
 
+pub const BUILT_IN_THEMES: [&str; 8] = [
+    "default", "borland", "darcula", "focus", "jungle", "matrix", "redshift", "wyse",
+];
+
 #[derive(Debug, Clone)]
 pub struct Theme {
     base: Style,
@@ -54,9 +61,66 @@ pub struct Theme {
     border: Option<Style>,
     border_active: Option<Style>,
     scrollbar: Option<Style>,
+    badge: Option<Style>,
+    highlight: Option<Style>,
+    zebra_stripe: bool,
+    stripe: Option<Style>,
 }
 
 impl Theme {
+    pub fn built_in(name: &str) -> Option<Self> {
+        match name {
+            "default" => Some(Self::default()),
+            "borland" => Some(Self::borland()),
+            "darcula" => Some(Self::darcula()),
+            "focus" => Some(Self::focus()),
+            "jungle" => Some(Self::jungle()),
+            "matrix" => Some(Self::matrix()),
+            "redshift" => Some(Self::redshift()),
+            "wyse" => Some(Self::wyse()),
+            _ => None,
+        }
+    }
+
+    /// Reads a theme from a TOML file laid out like a `[preferences.color_scheme]` table.
+    pub fn from_file(path: &Path) -> anyhow::Result<Self> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("could not read theme {}", path.display()))?;
+        let table = contents
+            .parse::<Value>()
+            .with_context(|| format!("invalid theme {}", path.display()))?;
+        Ok(Self::try_from(&table)?)
+    }
+
+    /// Resolves a color scheme name: a `<name>.toml` file in `themes_dir` comes first, then
+    /// the built-in themes, then `name` as a path to a theme file.
+    pub fn find(name: &str, themes_dir: &Path) -> anyhow::Result<Self> {
+        let installed = themes_dir.join(format!("{}.toml", name));
+        if installed.is_file() {
+            return Self::from_file(&installed);
+        }
+        match Self::built_in(name) {
+            Some(theme) => Ok(theme),
+            None => Self::from_file(Path::new(name)),
+        }
+    }
+
+    /// Names of the theme files in `themes_dir`, sorted.
+    pub fn installed(themes_dir: &Path) -> Vec<String> {
+        let mut names = fs::read_dir(themes_dir)
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| entry.ok())
+                    .map(|entry| entry.path())
+                    .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+                    .filter_map(|path| Some(path.file_stem()?.to_str()?.to_owned()))
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        names.sort();
+        names
+    }
+
     pub fn base(&self) -> Style {
         self.base.clone()
     }
@@ -125,6 +189,40 @@ impl Theme {
         }
     }
 
+    pub fn badge(&self) -> Style {
+        if let Some(s) = self.badge {
+            s.to_owned()
+        } else {
+            Style::default().add_modifier(Modifier::BOLD)
+        }
+    }
+
+    /// Titles of items matching one of the `highlights` keywords.
+    pub fn highlight(&self) -> Style {
+        if let Some(s) = self.highlight {
+            s.to_owned()
+        } else {
+            Style::default().fg(Color::Yellow)
+        }
+    }
+
+    /// Every other row of the lists while rows are striped: the `stripe` color, or else the
+    /// background nudged lighter, or darker if it is light, and away from the selection.
+    pub fn stripe(&self) -> Option<Style> {
+        if !self.zebra_stripe {
+            return None;
+        }
+        Some(self.stripe.unwrap_or_else(|| {
+            Style::default().bg(stripe_color(self.base.bg, self.selection().bg))
+        }))
+    }
+
+    /// The theme with its list rows striped, or not.
+    pub fn with_zebra_stripe(mut self, zebra_stripe: bool) -> Self {
+        self.zebra_stripe = zebra_stripe;
+        self
+    }
+
     pub fn borland() -> Self {
         let white = make_color("#FFFFFF");
         let gray = make_color("#bbbbbb");
@@ -141,6 +239,10 @@ impl Theme {
             selection: Some(Style::default().fg(midnight).bg(gray)),
             selection_active: Some(Style::default().fg(midnight).bg(yellow)),
             scrollbar: Some(Style::default().fg(white).bg(gray)),
+            badge: None,
+            highlight: None,
+            zebra_stripe: false,
+            stripe: None,
         }
     }
 
@@ -161,6 +263,10 @@ impl Theme {
             selection: Some(Style::default().fg(background).bg(bright_yellow)),
             selection_active: Some(Style::default().fg(background).bg(yellow)),
             scrollbar: Some(Style::default().fg(bright_black)),
+            badge: None,
+            highlight: None,
+            zebra_stripe: false,
+            stripe: None,
         }
     }
 
@@ -174,6 +280,10 @@ impl Theme {
             selection: Some(Style::default().reversed().dim()),
             selection_active: Some(Style::default().reversed().bold()),
             scrollbar: Some(Style::default()),
+            badge: None,
+            highlight: None,
+            zebra_stripe: false,
+            stripe: None,
         }
     }
 
@@ -187,6 +297,10 @@ impl Theme {
             selection: Some(Style::default().dim().reversed()),
             selection_active: Some(Style::default().green().reversed()),
             scrollbar: Some(Style::default().dim()),
+            badge: None,
+            highlight: None,
+            zebra_stripe: false,
+            stripe: None,
         }
     }
 
@@ -204,6 +318,10 @@ impl Theme {
             selection: Some(Style::default().fg(dark_green).bg(mid_green)),
             selection_active: Some(Style::default().fg(dark_green).bg(bright_green)),
             scrollbar: Some(Style::default()),
+            badge: None,
+            highlight: None,
+            zebra_stripe: false,
+            stripe: None,
         }
     }
 
@@ -220,6 +338,10 @@ impl Theme {
             selection: Some(Style::default().dim().reversed()),
             border: Some(Style::default().dim()),
             scrollbar: Some(Style::default().dim()),
+            badge: None,
+            highlight: None,
+            zebra_stripe: false,
+            stripe: None,
         }
     }
 
@@ -237,6 +359,10 @@ impl Theme {
             selection: Some(Style::default().fg(black).bg(dark_amber)),
             selection_active: Some(Style::default().fg(black).bg(bright_amber)),
             scrollbar: Some(Style::default()),
+            badge: None,
+            highlight: None,
+            zebra_stripe: false,
+            stripe: None,
         }
     }
 }
@@ -252,6 +378,10 @@ impl Default for Theme {
             border_active: None,
             border: None,
             scrollbar: Some(Style::default().dim()),
+            badge: None,
+            highlight: None,
+            zebra_stripe: false,
+            stripe: None,
         }
     }
 }
@@ -260,24 +390,9 @@ impl FromStr for Theme {
     type Err = ParseThemeError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
-            "default" => Ok(Self::default()),
-            "borland" => Ok(Self::borland()),
-            "darcula" => Ok(Self::darcula()),
-            "focus" => Ok(Self::focus()),
-            "jungle" => Ok(Self::jungle()),
-            "matrix" => Ok(Self::matrix()),
-            "redshift" => Ok(Self::redshift()),
-            "wyse" => Ok(Self::wyse()),
-            file => {
-                if std::path::Path::new(file).exists() {
-                    let contents = std::fs::read_to_string(file).or(Err(ParseThemeError))?;
-                    let table = contents.parse::<Value>().or(Err(ParseThemeError))?;
-                    Self::try_from(&table).or(Err(ParseThemeError))
-                } else {
-                    Err(ParseThemeError)
-                }
-            }
+        match Self::built_in(s) {
+            Some(theme) => Ok(theme),
+            None => Self::from_file(Path::new(s)).or(Err(ParseThemeError)),
         }
     }
 }
@@ -287,25 +402,7 @@ impl TryFrom<&toml::Value> for Theme {
 
     fn try_from(value: &toml::Value) -> Result<Self, Self::Error> {
         match value {
-            toml::Value::String(name) => match name.as_str() {
-                "default" => Ok(Self::default()),
-                "borland" => Ok(Self::borland()),
-                "darcula" => Ok(Self::darcula()),
-                "focus" => Ok(Self::focus()),
-                "jungle" => Ok(Self::jungle()),
-                "matrix" => Ok(Self::matrix()),
-                "redshift" => Ok(Self::redshift()),
-                "wyse" => Ok(Self::wyse()),
-                file => {
-                    if std::path::Path::new(file).exists() {
-                        let contents = std::fs::read_to_string(file).or(Err(ParseThemeError))?;
-                        let table = contents.parse::<Value>().or(Err(ParseThemeError))?;
-                        Self::try_from(&table).or(Err(ParseThemeError))
-                    } else {
-                        Err(ParseThemeError)
-                    }
-                }
-            },
+            toml::Value::String(name) => Self::from_str(name),
             toml::Value::Table(scheme) => Ok(Self {
                 base: scheme
                     .get("base")
@@ -332,12 +429,51 @@ impl TryFrom<&toml::Value> for Theme {
                 scrollbar: scheme
                     .get("scrollbar")
                     .and_then(|v| try_style_from_toml(v).ok()),
+                badge: scheme
+                    .get("badge")
+                    .and_then(|v| try_style_from_toml(v).ok()),
+                highlight: scheme
+                    .get("highlight")
+                    .and_then(|v| try_style_from_toml(v).ok()),
+                zebra_stripe: scheme
+                    .get("zebra_stripe")
+                    .and_then(toml::Value::as_bool)
+                    .unwrap_or_default(),
+                stripe: scheme
+                    .get("stripe")
+                    .and_then(|v| try_style_from_toml(v).ok()),
             }),
             _ => Err(ParseThemeError),
         }
     }
 }
 
+/// A background a little lighter than `background`, or darker if it is light, that stays
+/// apart from the `selection` background. Terminals' own backgrounds are taken to be dark.
+fn stripe_color(background: Option<Color>, selection: Option<Color>) -> Color {
+    const STEP: i16 = 16;
+    let Some(Color::Rgb(r, g, b)) = background else {
+        return Color::Indexed(236);
+    };
+    let light = r as u32 * 299 + g as u32 * 587 + b as u32 * 114 > 128_000;
+    let nudge = |step: i16| {
+        let step = if light { -step } else { step };
+        let channel = |c: u8| (c as i16 + step).clamp(0, 255) as u8;
+        Color::Rgb(channel(r), channel(g), channel(b))
+    };
+    let stripe = nudge(STEP);
+    let near = |a: Color, b: Color| match (a, b) {
+        (Color::Rgb(r1, g1, b1), Color::Rgb(r2, g2, b2)) => {
+            r1.abs_diff(r2).max(g1.abs_diff(g2)).max(b1.abs_diff(b2)) < STEP as u8
+        }
+        _ => false,
+    };
+    match selection {
+        Some(selection) if near(stripe, selection) => nudge(STEP / 2),
+        _ => stripe,
+    }
+}
+
 fn try_style_from_toml(value: &toml::Value) -> Result<Style, ParseColorError> {
     match value {
         toml::Value::String(name) => match name.to_lowercase().as_str() {
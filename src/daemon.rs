@@ -0,0 +1,175 @@
+use crate::api;
+use crate::app::Args;
+use crate::config::{Config, SharedConfig};
+use crate::feed::Feed;
+use crate::ipc;
+use crate::notification;
+use crate::repo::{Repository, RepositoryEvent, SharedRepository};
+use anyhow::{bail, Result};
+use std::collections::HashSet;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+use tokio::sync::mpsc::{self, UnboundedReceiver};
+use tokio::task::JoinHandle;
+
+const PID_FILE: &str = "moccasin.pid";
+
+/// Keeps feeds refreshed in the background, without the interface.
+///
+/// Refreshes run on the same schedule as in the interface, and are written to the same
+/// database, so the interface starts with fresh feeds. With `--api-server` it serves the API
+/// instead of the interface.
+pub struct DaemonRunner {
+    config: Config,
+    repo: SharedRepository,
+    api_port: Option<u16>,
+    repo_rx: UnboundedReceiver<RepositoryEvent>,
+    interval: tokio::time::Interval,
+    known_items: HashSet<(String, String)>,
+}
+
+impl DaemonRunner {
+    pub fn init(args: Args) -> Result<Self> {
+        let api_port = args.api_server;
+        let config = Config::new(args)?;
+        if !config.refreshes_automatically() && api_port.is_none() {
+            bail!("daemon mode needs a refresh_interval or refresh_every_secs greater than 0, or --api-server");
+        }
+        notification::warn_if_unsupported(config.notifications());
+
+        let (tx, rx) = mpsc::unbounded_channel::<RepositoryEvent>();
+        let mut repo = Repository::init(&config, tx)?;
+
+        let known_items = repo
+            .read_all(&config)
+            .unwrap_or_default()
+            .iter()
+            .flat_map(Feed::items)
+            .map(|item| (item.feed_id().to_owned(), item.id().to_owned()))
+            .collect();
+
+        let interval = tokio::time::interval(Duration::from_millis(config.tick_rate_ms()));
+        Ok(Self {
+            config,
+            repo: SharedRepository::new(repo),
+            api_port,
+            repo_rx: rx,
+            interval,
+            known_items,
+        })
+    }
+
+    /// Runs until the process receives `SIGINT` or `SIGTERM`.
+    pub async fn run(mut self) -> Result<()> {
+        let pid_path = pid_file_path()?;
+        fs::write(&pid_path, std::process::id().to_string())?;
+        log::info!("Daemon started, pid file at {}", pid_path.display());
+        let api = match self.api_port {
+            Some(port) => Some(api::start(
+                port,
+                self.repo.clone(),
+                SharedConfig::new(self.config.clone()),
+            )?),
+            None => None,
+        };
+
+        let shutdown = shutdown_signal();
+        tokio::pin!(shutdown);
+
+        loop {
+            tokio::select! {
+                _ = &mut shutdown => break,
+                _ = self.interval.tick() => self.tick(),
+            }
+        }
+
+        api.iter().for_each(JoinHandle::abort);
+        log::info!("Daemon stopped");
+        fs::remove_file(&pid_path)?;
+        Ok(())
+    }
+
+    fn tick(&mut self) {
+        self.repo.lock().tick(&self.config);
+
+        while let Ok(event) = self.repo_rx.try_recv() {
+            match event {
+                RepositoryEvent::RetrievedAll(feeds) => self.report_new_items(&feeds),
+                RepositoryEvent::Pushed(feed) => {
+                    self.report_new_items(std::slice::from_ref(&*feed))
+                }
+                RepositoryEvent::FetchTimedOut(url) => log::warn!("Timed out fetching {}", url),
+                RepositoryEvent::FetchFailed(url, attempts, reason) => {
+                    log::warn!(
+                        "Failed to fetch {} after {} attempts: {}",
+                        url,
+                        attempts,
+                        reason
+                    )
+                }
+                RepositoryEvent::Pruned(count) => {
+                    log::info!("Pruned {} items past their retention period", count)
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn report_new_items(&mut self, feeds: &[Feed]) {
+        for feed in feeds {
+            let new_items = feed
+                .items()
+                .iter()
+                .filter(|item| {
+                    self.known_items
+                        .insert((item.feed_id().to_owned(), item.id().to_owned()))
+                })
+                .filter(|item| !item.is_read())
+                .collect::<Vec<_>>();
+
+            if new_items.is_empty() {
+                continue;
+            }
+            log::info!("{} new items in {}", new_items.len(), feed.url());
+
+            // highlighted items get a notification of their own, even without notifications
+            let (highlighted, new_items): (Vec<_>, Vec<_>) = new_items
+                .into_iter()
+                .partition(|item| self.config.highlights().matches(item));
+            for item in highlighted {
+                notification::send(feed.title(), item.title().unwrap_or("[no title]"));
+            }
+
+            if self.config.notifications() && !new_items.is_empty() {
+                let body = match new_items.as_slice() {
+                    [item] => item.title().unwrap_or("1 new item").to_owned(),
+                    items => format!("{} new items", items.len()),
+                };
+                notification::send(feed.title(), &body);
+            }
+        }
+    }
+}
+
+/// Where the daemon records its process ID, beside the command socket.
+pub fn pid_file_path() -> Result<PathBuf> {
+    Ok(ipc::runtime_dir()?.join(PID_FILE))
+}
+
+/// Waits for `SIGINT` or `SIGTERM`, or Ctrl-C off Unix.
+#[cfg(unix)]
+pub async fn shutdown_signal() {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut terminate = signal(SignalKind::terminate()).expect("Failed to listen for SIGTERM");
+    tokio::select! {
+        _ = tokio::signal::ctrl_c() => {}
+        _ = terminate.recv() => {}
+    }
+}
+
+#[cfg(not(unix))]
+pub async fn shutdown_signal() {
+    let _ = tokio::signal::ctrl_c().await;
+}
@@ -15,6 +15,8 @@ pub enum Event {
     Mouse(MouseEvent),
     /// Terminal resize.
     Resize(u16, u16),
+    /// SIGINT or SIGTERM, asking to quit.
+    Signal,
 }
 
 /// Terminal event handler.
@@ -69,6 +71,11 @@ impl EventHandler {
         }
     }
 
+    /// A sender for events from outside the handler thread, such as signals.
+    pub fn sender(&self) -> mpsc::Sender<Event> {
+        self.sender.clone()
+    }
+
     /// Receive the next event from the handler thread.
     ///
     /// This function will always block the current thread if
@@ -0,0 +1,62 @@
+use html_parser::{Dom, Node};
+use reqwest::Url;
+
+const FEED_TYPES: [&str; 3] = [
+    "application/rss+xml",
+    "application/atom+xml",
+    "application/feed+json",
+];
+
+/// Collects the feeds advertised by `<link rel="alternate">` tags in an HTML page,
+/// resolving relative hrefs against `base`.
+pub fn feed_links(html: &str, base: &Url) -> Vec<String> {
+    let mut links = Vec::new();
+
+    // parse each tag on its own, so markup the parser chokes on elsewhere in the page
+    // doesn't hide the links
+    let lower = html.to_ascii_lowercase();
+    let mut rest = 0;
+    while let Some(start) = lower[rest..].find("<link").map(|i| i + rest) {
+        let end = match lower[start..].find('>') {
+            Some(i) => start + i + 1,
+            None => break,
+        };
+        rest = end;
+
+        let Ok(dom) = Dom::parse(&html[start..end]) else {
+            continue;
+        };
+        for node in dom.children {
+            if let Some(link) = feed_link(&node, base) {
+                if !links.contains(&link) {
+                    links.push(link);
+                }
+            }
+        }
+    }
+
+    links
+}
+
+fn feed_link(node: &Node, base: &Url) -> Option<String> {
+    let el = node.element()?;
+    let attr = |name: &str| {
+        el.attributes
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .and_then(|(_, value)| value.as_deref())
+    };
+
+    let is_alternate = attr("rel")?
+        .split_whitespace()
+        .any(|rel| rel.eq_ignore_ascii_case("alternate"));
+    let is_feed = FEED_TYPES
+        .iter()
+        .any(|ty| attr("type").is_some_and(|t| t.trim().eq_ignore_ascii_case(ty)));
+
+    if is_alternate && is_feed {
+        base.join(attr("href")?.trim()).ok().map(String::from)
+    } else {
+        None
+    }
+}
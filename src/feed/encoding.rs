@@ -0,0 +1,70 @@
+//! Which encoding a feed is in, from its byte order mark, the `charset` of its `Content-Type`
+//! or its XML declaration, in that order, as RFC 7303 has it.
+
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, UTF_8};
+
+/// The feed's encoding, UTF-8 if nothing names one or the name is unknown.
+pub fn detect(bytes: &[u8], content_type: Option<&str>) -> &'static Encoding {
+    if let Some((encoding, _)) = Encoding::for_bom(bytes) {
+        return encoding;
+    }
+    if let Some(encoding) = content_type
+        .and_then(charset)
+        .and_then(|label| Encoding::for_label(label.as_bytes()))
+    {
+        return encoding;
+    }
+    // a declaration readable as ASCII can't be in UTF-16, whatever it says
+    declared_encoding(bytes)
+        .and_then(|label| Encoding::for_label(label.as_bytes()))
+        .filter(|encoding| *encoding != UTF_16LE && *encoding != UTF_16BE)
+        .unwrap_or(UTF_8)
+}
+
+/// The `charset` parameter of a `Content-Type` such as `text/xml; charset=iso-8859-1`.
+fn charset(content_type: &str) -> Option<String> {
+    content_type.split(';').skip(1).find_map(|param| {
+        let (name, value) = param.split_once('=')?;
+        name.trim().eq_ignore_ascii_case("charset").then(|| {
+            value
+                .trim()
+                .trim_matches(|c| c == '"' || c == '\'')
+                .to_owned()
+        })
+    })
+}
+
+/// The range of the `encoding` value in the XML declaration, if there is one.
+fn declared_encoding_range(bytes: &[u8]) -> Option<std::ops::Range<usize>> {
+    let end = bytes
+        .strip_prefix(b"<?xml")?
+        .windows(2)
+        .position(|w| w == b"?>")?
+        + 5;
+    let declaration = &bytes[..end];
+    let attr = declaration.windows(8).position(|w| w == b"encoding")? + 8;
+    let quote = attr
+        + declaration[attr..]
+            .iter()
+            .position(|b| matches!(b, b'"' | b'\''))?;
+    let len = declaration[quote + 1..]
+        .iter()
+        .position(|b| *b == declaration[quote])?;
+    Some(quote + 1..quote + 1 + len)
+}
+
+fn declared_encoding(bytes: &[u8]) -> Option<String> {
+    let range = declared_encoding_range(bytes)?;
+    Some(String::from_utf8_lossy(&bytes[range]).into_owned())
+}
+
+/// Decodes `bytes` from `encoding`, replacing malformed sequences, and whether there were any.
+/// The XML declaration, if any, is marked UTF-8 so parsers don't decode the result again.
+pub fn transcode(bytes: &[u8], encoding: &'static Encoding) -> (String, bool) {
+    let (text, _, malformed) = encoding.decode(bytes);
+    let mut text = text.into_owned();
+    if let Some(range) = declared_encoding_range(text.as_bytes()) {
+        text.replace_range(range, "UTF-8");
+    }
+    (text, malformed)
+}
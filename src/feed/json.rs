@@ -0,0 +1,170 @@
+use super::{Feed, Item};
+use chrono::DateTime;
+use serde::{Deserialize, Serialize, Serializer};
+use std::io::Write;
+
+const VERSION: &str = "https://jsonfeed.org/version/1.1";
+
+/// A JSON Feed document, as described at https://jsonfeed.org/version/1.1.
+#[derive(Debug, Deserialize)]
+pub struct JsonFeed {
+    pub title: String,
+    pub home_page_url: Option<String>,
+    pub feed_url: Option<String>,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub items: Vec<JsonItem>,
+    #[serde(default)]
+    pub hubs: Vec<JsonHub>,
+}
+
+/// An endpoint that pushes the feed's updates, such as a WebSub hub.
+#[derive(Debug, Deserialize)]
+pub struct JsonHub {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub url: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct JsonItem {
+    pub id: String,
+    pub url: Option<String>,
+    pub title: Option<String>,
+    pub content_html: Option<String>,
+    pub content_text: Option<String>,
+    pub summary: Option<String>,
+    pub date_published: Option<String>,
+    #[serde(default)]
+    pub authors: Vec<JsonAuthor>,
+    // superseded by `authors` in 1.1, but still common in the wild
+    pub author: Option<JsonAuthor>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct JsonAuthor {
+    pub name: Option<String>,
+}
+
+impl JsonItem {
+    pub fn author_names(&self) -> Option<String> {
+        let names = self
+            .authors
+            .iter()
+            .chain(self.author.iter())
+            .filter_map(|a| a.name.as_deref())
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        if names.is_empty() {
+            None
+        } else {
+            Some(names)
+        }
+    }
+}
+
+/// Writes feeds as a JSON array of JSON Feed documents, serializing each item as it goes
+/// rather than building the whole document first.
+pub fn write_document<'a, W: Write>(
+    writer: W,
+    feeds: impl IntoIterator<Item = &'a Feed>,
+) -> serde_json::Result<()> {
+    let mut ser = serde_json::Serializer::pretty(writer);
+    ser.collect_seq(feeds.into_iter().map(FeedDocument::from))
+}
+
+#[derive(Serialize)]
+struct FeedDocument<'a> {
+    version: &'static str,
+    title: &'a str,
+    #[serde(skip_serializing_if = "str::is_empty")]
+    home_page_url: &'a str,
+    feed_url: &'a str,
+    #[serde(skip_serializing_if = "str::is_empty")]
+    description: &'a str,
+    #[serde(serialize_with = "serialize_items")]
+    items: &'a [Item],
+}
+
+impl<'a> From<&'a Feed> for FeedDocument<'a> {
+    fn from(feed: &'a Feed) -> Self {
+        Self {
+            version: VERSION,
+            title: feed.title(),
+            home_page_url: feed.link(),
+            feed_url: feed.url(),
+            description: feed.description(),
+            items: feed.items(),
+        }
+    }
+}
+
+fn serialize_items<S: Serializer>(items: &&[Item], ser: S) -> Result<S::Ok, S::Error> {
+    ser.collect_seq(items.iter().map(ItemDocument::from))
+}
+
+#[derive(Serialize)]
+struct ItemDocument<'a> {
+    id: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    url: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    title: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    content_html: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    content_text: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    date_published: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    authors: Vec<AuthorDocument<'a>>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<&'a str>,
+    #[serde(rename = "_moccasin")]
+    state: ItemState,
+}
+
+#[derive(Serialize)]
+struct AuthorDocument<'a> {
+    name: &'a str,
+}
+
+#[derive(Serialize)]
+struct ItemState {
+    read: bool,
+    starred: bool,
+}
+
+impl<'a> From<&'a Item> for ItemDocument<'a> {
+    fn from(item: &'a Item) -> Self {
+        Self {
+            id: item.id(),
+            url: item.link(),
+            title: item.title(),
+            content_html: item.content(),
+            content_text: item.description(),
+            // dates are kept as RFC 2822, but JSON Feed wants RFC 3339
+            date_published: item
+                .pub_date()
+                .and_then(|date| DateTime::parse_from_rfc2822(date).ok())
+                .map(|date| date.to_rfc3339()),
+            authors: item
+                .author()
+                .map(|name| AuthorDocument { name })
+                .into_iter()
+                .collect(),
+            tags: item
+                .categories()
+                .iter()
+                .map(|category| category.name.as_str())
+                .collect(),
+            state: ItemState {
+                read: item.is_read(),
+                starred: item.is_starred(),
+            },
+        }
+    }
+}
@@ -1,10 +1,131 @@
 use anyhow;
 use chrono::prelude::*;
+use rss::extension::dublincore::DublinCoreExtension;
 use rss::{Channel, Item as ChannelItem};
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
 use std::io::BufRead;
+use std::str::FromStr;
 
-mod html;
+mod discover;
+mod encoding;
+pub(crate) mod html;
+pub(crate) mod json;
+mod repair;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum FeedFormat {
+    #[default]
+    Rss,
+    /// RSS 1.0, an RDF document with `rdf:RDF` at its root.
+    Rdf,
+    Atom,
+    JsonFeed,
+}
+
+impl FeedFormat {
+    /// Picks a format from the response `Content-Type`, falling back to sniffing the body for a
+    /// leading `{`, or a `feed` or `rdf:RDF` root element.
+    pub fn detect(content_type: Option<&str>, body: &[u8]) -> Self {
+        let mime = content_type
+            .and_then(|ct| ct.split(';').next())
+            .map(|ct| ct.trim().to_ascii_lowercase());
+
+        match mime.as_deref() {
+            Some("application/feed+json") => FeedFormat::JsonFeed,
+            Some("application/atom+xml") => FeedFormat::Atom,
+            Some("application/rdf+xml") => FeedFormat::Rdf,
+            _ => {
+                let body = body.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(body);
+                match body.iter().find(|b| !b.is_ascii_whitespace()) {
+                    Some(b'{') => FeedFormat::JsonFeed,
+                    _ if root_element(body)
+                        .is_some_and(|name| name == b"feed" || name.ends_with(b":feed")) =>
+                    {
+                        FeedFormat::Atom
+                    }
+                    _ if root_element(body).is_some_and(|name| name.ends_with(b"RDF")) => {
+                        FeedFormat::Rdf
+                    }
+                    _ => FeedFormat::Rss,
+                }
+            }
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FeedFormat::Rss => "rss",
+            FeedFormat::Rdf => "rdf",
+            FeedFormat::Atom => "atom",
+            FeedFormat::JsonFeed => "json",
+        }
+    }
+}
+
+impl fmt::Display for FeedFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for FeedFormat {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "rss" => Ok(FeedFormat::Rss),
+            "rdf" => Ok(FeedFormat::Rdf),
+            "atom" => Ok(FeedFormat::Atom),
+            "json" => Ok(FeedFormat::JsonFeed),
+            _ => Err(()),
+        }
+    }
+}
+
+/// The name of the first element of an XML document, such as `rss` or `rdf:RDF`, skipping the
+/// declaration, comments and doctype before it.
+fn root_element(mut body: &[u8]) -> Option<&[u8]> {
+    loop {
+        let start = body.iter().position(|b| !b.is_ascii_whitespace())?;
+        body = &body[start..];
+        let end = if body.starts_with(b"<?") {
+            find(body, b"?>")? + 2
+        } else if body.starts_with(b"<!--") {
+            find(body, b"-->")? + 3
+        } else if body.starts_with(b"<!") {
+            find(body, b">")? + 1
+        } else {
+            let name = body.strip_prefix(b"<")?;
+            let len = name
+                .iter()
+                .position(|b| b.is_ascii_whitespace() || *b == b'>' || *b == b'/')
+                .unwrap_or(name.len());
+            return Some(&name[..len]);
+        };
+        body = &body[end..];
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+/// Consecutive failed refreshes after which a feed is `Degraded`.
+const DEGRADED_AFTER: u8 = 3;
+
+/// How reliably a feed has been refreshing lately.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum HealthStatus {
+    Healthy,
+    /// Failed its last few refreshes.
+    Degraded,
+    /// Failed more refreshes in a row than the configured `dead_after`.
+    Dead,
+}
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Feed {
@@ -19,6 +140,21 @@ pub struct Feed {
     pub(crate) items: Vec<Item>,
     pub(crate) pub_date: Option<String>,
     pub(crate) last_fetched: Option<String>,
+    pub(crate) etag: Option<String>,
+    pub(crate) last_modified: Option<String>,
+    #[serde(default)]
+    pub(crate) format: FeedFormat,
+    #[serde(default)]
+    pub(crate) consecutive_errors: u8,
+    /// The WebSub hub the feed advertises, and the topic URL to subscribe to there.
+    #[serde(skip)]
+    pub(crate) hub: Option<(String, String)>,
+    /// What was repaired to parse the feed, which is empty unless it was malformed.
+    #[serde(skip)]
+    pub(crate) parse_warnings: Vec<String>,
+    /// Where the request for `url` ended up after following redirects, if somewhere else.
+    #[serde(skip)]
+    pub(crate) resolved_url: Option<String>,
 }
 
 impl Feed {
@@ -54,6 +190,33 @@ impl Feed {
         &self.items
     }
 
+    pub fn parse_warnings(&self) -> &[String] {
+        &self.parse_warnings
+    }
+
+    pub fn resolved_url(&self) -> Option<&str> {
+        self.resolved_url.as_deref()
+    }
+
+    /// How many refreshes in a row have failed for this feed.
+    pub fn consecutive_errors(&self) -> u8 {
+        self.consecutive_errors
+    }
+
+    pub fn health(&self, dead_after: u8) -> HealthStatus {
+        if self.consecutive_errors > dead_after {
+            HealthStatus::Dead
+        } else if self.consecutive_errors >= DEGRADED_AFTER {
+            HealthStatus::Degraded
+        } else {
+            HealthStatus::Healthy
+        }
+    }
+
+    pub fn unread_count(&self) -> usize {
+        self.items.iter().filter(|item| !item.is_read()).count()
+    }
+
     pub fn pub_date(&self) -> Option<&str> {
         self.pub_date.as_deref()
     }
@@ -62,11 +225,66 @@ impl Feed {
         self.last_fetched.as_deref()
     }
 
+    pub fn last_fetched_at(&self) -> Option<DateTime<Utc>> {
+        self.last_fetched
+            .as_deref()
+            .and_then(|date| DateTime::parse_from_rfc2822(date).ok())
+            .map(|date| date.with_timezone(&Utc))
+    }
+
+    pub fn etag(&self) -> Option<&str> {
+        self.etag.as_deref()
+    }
+
+    pub fn last_modified(&self) -> Option<&str> {
+        self.last_modified.as_deref()
+    }
+
+    pub fn format(&self) -> FeedFormat {
+        self.format
+    }
+
+    /// The WebSub hub the feed was last fetched with, and the topic URL it is published
+    /// under there, which may differ from `url`.
+    pub fn hub(&self) -> Option<(&str, &str)> {
+        self.hub
+            .as_ref()
+            .map(|(hub, topic)| (hub.as_str(), topic.as_str()))
+    }
+
+    /// Stands in for a feed that has never been fetched, so it can be listed with its error.
+    pub fn placeholder(url: &str) -> Self {
+        Self {
+            id: url.to_owned(),
+            title: url.to_owned(),
+            description: String::new(),
+            categories: vec![],
+            url: url.to_owned(),
+            link: String::new(),
+            ttl: None,
+            items: vec![],
+            pub_date: None,
+            last_fetched: None,
+            etag: None,
+            last_modified: None,
+            format: FeedFormat::default(),
+            consecutive_errors: 0,
+            hub: None,
+            parse_warnings: vec![],
+            resolved_url: None,
+        }
+    }
+
     pub fn with_items(mut self, items: Vec<Item>) -> Self {
         self.items = items;
         self
     }
 
+    /// Drops the HTML of every item once it is stored, see [`Item::drop_markup`].
+    pub(crate) fn drop_item_markup(&mut self) {
+        self.items.iter_mut().for_each(Item::drop_markup);
+    }
+
     fn from_channel_with_url(value: Channel, url: String) -> Self {
         let id = value
             .dublin_core_ext()
@@ -82,7 +300,7 @@ impl Feed {
         Self {
             title: value.title.clone(),
             description: value.description.clone(),
-            url: url,
+            url: url.clone(),
             link: value.link.clone(),
             ttl: value.ttl.clone(),
             categories: value
@@ -93,26 +311,252 @@ impl Feed {
                     domain: c.domain.clone(),
                 })
                 .collect(),
-            items: value
-                .items
-                .iter()
-                .map(|i| Item::with_parent(id.as_str(), i))
-                .collect(),
+            items: dedup_items(
+                value
+                    .items
+                    .iter()
+                    .map(|i| Item::with_parent(id.as_str(), i)),
+            ),
             pub_date: value
                 .pub_date
-                .and_then(|s| DateTime::parse_from_rfc2822(&s).ok())
-                .and_then(|s| Some(DateTime::to_rfc2822(&s))),
+                .as_deref()
+                .and_then(|s| DateTime::parse_from_rfc2822(s).ok())
+                .and_then(|s| Some(DateTime::to_rfc2822(&s)))
+                .or(dublin_core_date(value.dublin_core_ext())),
             last_fetched: None,
+            etag: None,
+            last_modified: None,
+            format: FeedFormat::Rss,
+            consecutive_errors: 0,
+            hub: value
+                .atom_ext()
+                .and_then(|atom| websub_hub(atom.links(), &url)),
+            parse_warnings: vec![],
+            resolved_url: None,
+            id,
+        }
+    }
+
+    fn from_atom_with_url(value: atom_syndication::Feed, url: String) -> Self {
+        let id = value.id().to_owned();
+
+        Self {
+            title: value.title().as_str().to_owned(),
+            description: value
+                .subtitle()
+                .map(|subtitle| subtitle.as_str().to_owned())
+                .unwrap_or_default(),
+            url: url.clone(),
+            link: alternate_link(value.links()).unwrap_or_default(),
+            ttl: None,
+            categories: value
+                .categories()
+                .iter()
+                .map(|c| Category {
+                    name: c.term().to_owned(),
+                    domain: c.scheme().map(str::to_owned),
+                })
+                .collect(),
+            items: dedup_items(
+                value
+                    .entries()
+                    .iter()
+                    .map(|entry| Item::with_atom_parent(&id, entry)),
+            ),
+            pub_date: Some(value.updated().to_rfc2822()),
+            last_fetched: None,
+            etag: None,
+            last_modified: None,
+            format: FeedFormat::Atom,
+            consecutive_errors: 0,
+            hub: websub_hub(value.links(), &url),
+            parse_warnings: vec![],
+            resolved_url: None,
+            id,
+        }
+    }
+
+    fn from_json_feed_with_url(value: json::JsonFeed, url: String) -> Self {
+        let id = value
+            .feed_url
+            .clone()
+            .or(value.home_page_url.clone())
+            .unwrap_or(url.clone());
+        let hub = value
+            .hubs
+            .iter()
+            .find(|hub| hub.kind.eq_ignore_ascii_case("websub"))
+            .map(|hub| {
+                let topic = value.feed_url.clone().unwrap_or(url.clone());
+                (hub.url.clone(), topic)
+            });
+
+        Self {
+            title: value.title,
+            description: value.description.unwrap_or_default(),
+            url,
+            link: value.home_page_url.unwrap_or_default(),
+            ttl: None,
+            categories: vec![],
+            items: dedup_items(
+                value
+                    .items
+                    .iter()
+                    .map(|i| Item::with_json_parent(id.as_str(), i)),
+            ),
+            pub_date: None,
+            last_fetched: None,
+            etag: None,
+            last_modified: None,
+            format: FeedFormat::JsonFeed,
+            consecutive_errors: 0,
+            hub,
+            parse_warnings: vec![],
+            resolved_url: None,
             id,
         }
     }
 
     pub fn read_from<R: BufRead>(reader: R, url: String) -> anyhow::Result<Feed> {
-        let channel = Channel::read_from(reader)?;
-        let mut feed = Feed::from_channel_with_url(channel, url);
+        Feed::read_with_content_type(reader, None, url)
+    }
+
+    pub fn read_with_content_type<R: BufRead>(
+        mut reader: R,
+        content_type: Option<&str>,
+        url: String,
+    ) -> anyhow::Result<Feed> {
+        let format = FeedFormat::detect(content_type, reader.fill_buf()?);
+        let mut feed = match format {
+            FeedFormat::JsonFeed => Feed::from_json_feed_with_url(
+                serde_json::from_reader(reader)
+                    .map_err(|err| anyhow::anyhow!("JSON parse error: {}", err))?,
+                url,
+            ),
+            _ => {
+                let mut xml = Vec::new();
+                reader.read_to_end(&mut xml)?;
+                Feed::read_xml(xml, content_type, url)?
+            }
+        };
         feed.last_fetched = Some(Local::now().to_rfc2822());
         Ok(feed)
     }
+
+    /// The feed in `bytes` as UTF-8, decoded from the encoding named by its byte order mark,
+    /// the `charset` of `content_type` or its XML declaration. Fails on bytes that aren't
+    /// valid in that encoding.
+    pub fn detect_and_transcode(
+        bytes: &[u8],
+        content_type: Option<&str>,
+    ) -> anyhow::Result<String> {
+        let encoding = encoding::detect(bytes, content_type);
+        match encoding::transcode(bytes, encoding) {
+            (text, false) => Ok(text),
+            (_, true) => Err(anyhow::anyhow!("Invalid {} text", encoding.name())),
+        }
+    }
+
+    /// Parses an RSS or Atom feed, converted to UTF-8 if it is in another encoding, and
+    /// repaired if it is malformed.
+    fn read_xml(mut xml: Vec<u8>, content_type: Option<&str>, url: String) -> anyhow::Result<Feed> {
+        let mut warnings = vec![];
+        let encoding = encoding::detect(&xml, content_type);
+        if encoding != encoding_rs::UTF_8 {
+            let (text, malformed) = encoding::transcode(&xml, encoding);
+            if malformed {
+                warnings.push(format!("Replaced invalid {} sequences", encoding.name()));
+            }
+            xml = text.into_bytes();
+        }
+        // sniffed again, since UTF-16 can't be sniffed before it's converted
+        let format = FeedFormat::detect(content_type, &xml);
+
+        let mut feed = match Feed::from_xml(&xml, format, url.clone()) {
+            Ok(feed) => feed,
+            // retry malformed feeds once repaired, failing with the original error if that
+            // didn't help
+            Err(err) => {
+                let (repaired, repairs) = repair::repair_xml(&xml);
+                if repairs.is_empty() {
+                    return Err(err);
+                }
+                warnings.extend(repairs);
+                Feed::from_xml(&repaired, format, url).map_err(|_| err)?
+            }
+        };
+        for warning in &warnings {
+            tracing::warn!(url = %feed.url, warning, "Repaired malformed feed");
+        }
+        feed.format = format;
+        feed.parse_warnings = warnings;
+        Ok(feed)
+    }
+
+    fn from_xml(xml: &[u8], format: FeedFormat, url: String) -> anyhow::Result<Feed> {
+        let feed = match format {
+            FeedFormat::Atom => Feed::from_atom_with_url(
+                atom_syndication::Feed::read_from(xml)
+                    .map_err(|err| anyhow::anyhow!("XML parse error: {}", err))?,
+                url,
+            ),
+            _ => Feed::from_channel_with_url(
+                Channel::read_from(xml)
+                    .map_err(|err| anyhow::anyhow!("XML parse error: {}", err))?,
+                url,
+            ),
+        };
+        Ok(feed)
+    }
+
+    /// Finds the feeds advertised by the web page at `url`, which may be at most `max_bytes`
+    /// long.
+    pub async fn discover(
+        client: &reqwest::Client,
+        url: &str,
+        max_bytes: usize,
+    ) -> anyhow::Result<Vec<String>> {
+        let res = client.get(url).send().await?.error_for_status()?;
+        let base = res.url().clone();
+        let html = crate::util::read_body(res, max_bytes)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Page larger than {} bytes", max_bytes))?;
+        Ok(discover::feed_links(&String::from_utf8_lossy(&html), &base))
+    }
+}
+
+/// The `href` of the first link to the page itself, which Atom marks `rel="alternate"`, the
+/// default when there is no `rel` at all.
+fn alternate_link(links: &[atom_syndication::Link]) -> Option<String> {
+    links
+        .iter()
+        .find(|link| link.rel() == "alternate")
+        .map(|link| link.href().to_owned())
+}
+
+/// The hub in a `rel="hub"` link, with the topic in the `rel="self"` link, or `url` if there
+/// is none.
+fn websub_hub(links: &[atom_syndication::Link], url: &str) -> Option<(String, String)> {
+    let hub = links.iter().find(|link| link.rel() == "hub")?;
+    let topic = links
+        .iter()
+        .find(|link| link.rel() == "self")
+        .map_or(url, |link| link.href());
+    Some((hub.href().to_owned(), topic.to_owned()))
+}
+
+/// The `<dc:date>` that RSS 1.0 feeds give in place of `<pubDate>`, as RFC 2822.
+fn dublin_core_date(dc: Option<&DublinCoreExtension>) -> Option<String> {
+    dc?.dates()
+        .iter()
+        .find_map(|date| DateTime::parse_from_rfc3339(date.trim()).ok())
+        .map(|date| date.to_rfc2822())
+}
+
+/// Keeps the first of any items sharing an id, as when a feed lists a republished item twice.
+fn dedup_items(items: impl Iterator<Item = Item>) -> Vec<Item> {
+    let mut seen = HashSet::new();
+    items.filter(|item| seen.insert(item.id.clone())).collect()
 }
 
 #[derive(Clone, Default, Debug, Serialize, Deserialize)]
@@ -127,6 +571,15 @@ pub struct Item {
     pub(crate) categories: Vec<Category>,
     pub(crate) link: Option<String>,
     pub(crate) pub_date: Option<String>,
+    #[serde(default)]
+    pub(crate) enclosure: Option<Enclosure>,
+    /// The running time of a podcast episode, as given by `<itunes:duration>`.
+    #[serde(default)]
+    pub(crate) duration: Option<String>,
+    #[serde(default)]
+    pub(crate) read: bool,
+    #[serde(default)]
+    pub(crate) starred: bool,
 }
 
 impl Item {
@@ -142,10 +595,6 @@ impl Item {
         self.title.as_deref()
     }
 
-    pub fn author(&self) -> Option<&str> {
-        self.author.as_deref()
-    }
-
     pub fn content(&self) -> Option<&str> {
         self.content.as_deref()
     }
@@ -158,6 +607,18 @@ impl Item {
         }
     }
 
+    /// The description as the feed gave it, usually HTML, or its text once that is dropped.
+    pub fn html_description(&self) -> Option<&str> {
+        self.description
+            .as_deref()
+            .or(self.text_description.as_deref())
+    }
+
+    /// Whether the item has a full body besides its description, so either can be shown.
+    pub fn has_content_and_summary(&self) -> bool {
+        self.content.is_some() && self.html_description().is_some()
+    }
+
     pub fn categories(&self) -> &[Category] {
         &self.categories
     }
@@ -170,16 +631,38 @@ impl Item {
         self.pub_date.as_deref()
     }
 
+    pub fn author(&self) -> Option<&str> {
+        self.author.as_deref()
+    }
+
+    pub fn enclosure(&self) -> Option<&Enclosure> {
+        self.enclosure.as_ref()
+    }
+
+    pub fn duration(&self) -> Option<&str> {
+        self.duration.as_deref()
+    }
+
+    pub fn is_read(&self) -> bool {
+        self.read
+    }
+
+    pub fn is_starred(&self) -> bool {
+        self.starred
+    }
+
+    /// Drops the HTML `content` and `description`, which storage keeps for the detail view,
+    /// leaving the plain text description that filters and highlights search.
+    pub(crate) fn drop_markup(&mut self) {
+        self.text_description = self.text_description.take().or(self.description.take());
+        self.description = None;
+        self.content = None;
+    }
+
     fn with_parent(feed_id: &str, value: &ChannelItem) -> Self {
         let id = value
             .guid()
-            .and_then(|g| {
-                if g.is_permalink() {
-                    Some(g.value.clone())
-                } else {
-                    None
-                }
-            })
+            .map(|g| g.value.clone())
             .or(value.dublin_core_ext().and_then(|dc| {
                 if !dc.identifiers().is_empty() {
                     Some(dc.identifiers().concat())
@@ -235,11 +718,300 @@ impl Item {
                 })
                 .collect(),
             link: value.link.clone(),
-            pub_date: value.pub_date.clone(),
+            pub_date: value
+                .pub_date
+                .clone()
+                .or(dublin_core_date(value.dublin_core_ext())),
+            enclosure: value.enclosure().map(|enclosure| Enclosure {
+                url: enclosure.url().to_owned(),
+                length: enclosure.length().trim().parse().unwrap_or_default(),
+                mime_type: enclosure.mime_type().to_owned(),
+            }),
+            duration: value
+                .itunes_ext()
+                .and_then(|it| it.duration())
+                .map(str::to_owned),
+            read: false,
+            starred: false,
+        }
+    }
+
+    /// Reads an Atom entry, keeping its `<summary>` as the description and its `<content>`,
+    /// the full post, as the content.
+    fn with_atom_parent(feed_id: &str, value: &atom_syndication::Entry) -> Self {
+        let description = value.summary().map(|summary| summary.as_str().to_owned());
+        let content = value
+            .content()
+            .and_then(|content| content.value())
+            .map(str::to_owned);
+        let text_description = description
+            .as_deref()
+            .or(content.as_deref())
+            .and_then(|html| html::parse_html(html).ok());
+        let authors = value
+            .authors()
+            .iter()
+            .map(|person| person.name())
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        Self {
+            id: value.id().to_owned(),
+            feed_id: feed_id.to_owned(),
+            title: Some(value.title().as_str().to_owned()),
+            author: Some(authors).filter(|authors| !authors.is_empty()),
+            content,
+            description,
+            text_description,
+            categories: value
+                .categories()
+                .iter()
+                .map(|c| Category {
+                    name: c.term().to_owned(),
+                    domain: c.scheme().map(str::to_owned),
+                })
+                .collect(),
+            link: alternate_link(value.links()),
+            pub_date: Some(value.published().unwrap_or(value.updated()).to_rfc2822()),
+            enclosure: value
+                .links()
+                .iter()
+                .find(|link| link.rel() == "enclosure")
+                .map(|link| Enclosure {
+                    url: link.href().to_owned(),
+                    length: link
+                        .length()
+                        .and_then(|length| length.trim().parse().ok())
+                        .unwrap_or_default(),
+                    mime_type: link.mime_type().unwrap_or_default().to_owned(),
+                }),
+            duration: None,
+            read: false,
+            starred: false,
+        }
+    }
+
+    fn with_json_parent(feed_id: &str, value: &json::JsonItem) -> Self {
+        let text_description = value.content_text.clone().or(value
+            .content_html
+            .as_ref()
+            .and_then(|html| html::parse_html(html).ok()));
+
+        Self {
+            id: value.id.clone(),
+            feed_id: feed_id.to_owned(),
+            title: value.title.clone(),
+            author: value.author_names(),
+            content: value.content_html.clone(),
+            description: value
+                .content_html
+                .clone()
+                .or(value.summary.clone())
+                .or(value.content_text.clone()),
+            text_description,
+            categories: value
+                .tags
+                .iter()
+                .map(|tag| Category {
+                    name: tag.clone(),
+                    domain: None,
+                })
+                .collect(),
+            link: value.url.clone(),
+            pub_date: value
+                .date_published
+                .as_ref()
+                .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+                .map(|d| d.to_rfc2822()),
+            enclosure: None,
+            duration: None,
+            read: false,
+            starred: false,
         }
     }
 }
 
+/// What the items pane lists of an item. The full [`Item`] is read from storage when it is
+/// shown in the detail pane.
+#[derive(Clone, Default, Debug, PartialEq)]
+pub struct ItemSummary {
+    pub(crate) id: String,
+    pub(crate) feed_id: String,
+    pub(crate) title: Option<String>,
+    pub(crate) link: Option<String>,
+    pub(crate) pub_date: Option<String>,
+    pub(crate) author: Option<String>,
+    pub(crate) enclosure: Option<Enclosure>,
+    pub(crate) duration: Option<String>,
+    pub(crate) read: bool,
+    pub(crate) starred: bool,
+}
+
+impl ItemSummary {
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn feed_id(&self) -> &str {
+        &self.feed_id
+    }
+
+    pub fn title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    pub fn link(&self) -> Option<&str> {
+        self.link.as_deref()
+    }
+
+    pub fn pub_date(&self) -> Option<&str> {
+        self.pub_date.as_deref()
+    }
+
+    pub fn author(&self) -> Option<&str> {
+        self.author.as_deref()
+    }
+
+    pub fn enclosure(&self) -> Option<&Enclosure> {
+        self.enclosure.as_ref()
+    }
+
+    pub fn duration(&self) -> Option<&str> {
+        self.duration.as_deref()
+    }
+
+    pub fn is_read(&self) -> bool {
+        self.read
+    }
+
+    pub fn is_starred(&self) -> bool {
+        self.starred
+    }
+
+    /// Whether this summarizes `item`.
+    pub fn is_for(&self, item: &Item) -> bool {
+        self.feed_id == item.feed_id && self.id == item.id
+    }
+}
+
+impl From<&Item> for ItemSummary {
+    fn from(item: &Item) -> Self {
+        Self {
+            id: item.id.clone(),
+            feed_id: item.feed_id.clone(),
+            title: item.title.clone(),
+            link: item.link.clone(),
+            pub_date: item.pub_date.clone(),
+            author: item.author.clone(),
+            enclosure: item.enclosure.clone(),
+            duration: item.duration.clone(),
+            read: item.read,
+            starred: item.starred,
+        }
+    }
+}
+
+/// A media file attached to an item, such as a podcast episode.
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Enclosure {
+    pub(crate) url: String,
+    /// The size of the file in bytes, or 0 when the feed doesn't say.
+    pub(crate) length: u64,
+    pub(crate) mime_type: String,
+}
+
+impl Enclosure {
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn length(&self) -> u64 {
+        self.length
+    }
+
+    pub fn mime_type(&self) -> &str {
+        &self.mime_type
+    }
+}
+
+/// A row of the timeline: an item of any feed, with the title of the feed it came from.
+#[derive(Clone, Default, Debug, PartialEq)]
+pub struct TimelineItem {
+    pub(crate) summary: ItemSummary,
+    pub(crate) feed_title: String,
+}
+
+impl TimelineItem {
+    pub fn summary(&self) -> &ItemSummary {
+        &self.summary
+    }
+
+    pub fn feed_title(&self) -> &str {
+        &self.feed_title
+    }
+}
+
+/// An item matching a search across every feed, with the text around the match.
+#[derive(Clone, Default, Debug, PartialEq)]
+pub struct SearchResult {
+    pub(crate) feed_id: String,
+    pub(crate) item_id: String,
+    pub(crate) feed_title: String,
+    pub(crate) item_title: Option<String>,
+    pub(crate) item_link: Option<String>,
+    pub(crate) snippet: String,
+}
+
+impl SearchResult {
+    pub fn feed_id(&self) -> &str {
+        &self.feed_id
+    }
+
+    pub fn item_id(&self) -> &str {
+        &self.item_id
+    }
+
+    pub fn feed_title(&self) -> &str {
+        &self.feed_title
+    }
+
+    pub fn item_title(&self) -> Option<&str> {
+        self.item_title.as_deref()
+    }
+
+    pub fn item_link(&self) -> Option<&str> {
+        self.item_link.as_deref()
+    }
+
+    pub fn snippet(&self) -> &str {
+        &self.snippet
+    }
+}
+
+/// An item saved in the reading queue, to read later.
+#[derive(Clone, Default, Debug, PartialEq, Eq)]
+pub struct QueuedItem {
+    pub(crate) feed_id: String,
+    pub(crate) item_id: String,
+}
+
+impl QueuedItem {
+    pub fn new(feed_id: &str, item_id: &str) -> Self {
+        QueuedItem {
+            feed_id: feed_id.to_owned(),
+            item_id: item_id.to_owned(),
+        }
+    }
+
+    pub fn feed_id(&self) -> &str {
+        &self.feed_id
+    }
+
+    pub fn item_id(&self) -> &str {
+        &self.item_id
+    }
+}
+
 // impl From<&ChannelItem> for Item {
 //     fn from(value: &ChannelItem) -> Self {
 //         let author = value
@@ -289,3 +1061,217 @@ pub struct Category {
     pub name: String,
     pub domain: Option<String>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const URL: &str = "https://example.com/feed.xml";
+
+    fn read(body: &[u8], content_type: Option<&str>) -> Feed {
+        Feed::read_with_content_type(body, content_type, URL.to_owned()).unwrap()
+    }
+
+    fn titles(feed: &Feed) -> Vec<&str> {
+        feed.items().iter().filter_map(Item::title).collect()
+    }
+
+    /// An RSS feed in `encoding`, named in its XML declaration if `declared`, with "Café"
+    /// as the feed title and "Crème brûlée" as the item title, both encoded as Latin-1.
+    fn latin1_rss(encoding: &str, declared: bool) -> Vec<u8> {
+        let mut body = vec![];
+        if declared {
+            body.extend_from_slice(b"<?xml version=\"1.0\" encoding=\"");
+            body.extend_from_slice(encoding.as_bytes());
+            body.extend_from_slice(b"\"?>");
+        }
+        body.extend_from_slice(
+            b"<rss version=\"2.0\"><channel><title>Caf\xe9</title><link>https://example.com/</link>\
+            <description></description><item><title>Cr\xe8me br\xfbl\xe9e</title>\
+            <link>https://example.com/1</link></item></channel></rss>",
+        );
+        body
+    }
+
+    #[test]
+    fn reads_iso_8859_1_named_in_the_xml_declaration() {
+        let feed = read(&latin1_rss("ISO-8859-1", true), Some("application/rss+xml"));
+        assert_eq!(feed.title(), "Café");
+        assert_eq!(titles(&feed), vec!["Crème brûlée"]);
+        assert!(feed.parse_warnings().is_empty());
+    }
+
+    #[test]
+    fn reads_iso_8859_1_named_in_the_content_type() {
+        let feed = read(
+            &latin1_rss("ISO-8859-1", false),
+            Some("text/xml; charset=iso-8859-1"),
+        );
+        assert_eq!(feed.title(), "Café");
+        assert_eq!(titles(&feed), vec!["Crème brûlée"]);
+    }
+
+    #[test]
+    fn prefers_the_content_type_charset_to_the_declaration() {
+        let feed = read(
+            &latin1_rss("UTF-8", true),
+            Some("application/rss+xml; charset=\"ISO-8859-1\""),
+        );
+        assert_eq!(feed.title(), "Café");
+    }
+
+    #[test]
+    fn reads_windows_1252_punctuation() {
+        // curly quotes, a euro sign and an en dash, which ISO-8859-1 has no place for
+        let body = b"<?xml version=\"1.0\" encoding=\"windows-1252\"?>\
+            <rss version=\"2.0\"><channel><title>Prices</title><link>https://example.com/</link>\
+            <description></description><item><title>\x93Cheap\x94 at \x8010 \x96 or less</title>\
+            </item></channel></rss>";
+        let feed = read(body, None);
+        assert_eq!(
+            titles(&feed),
+            vec!["\u{201c}Cheap\u{201d} at €10 – or less"]
+        );
+    }
+
+    #[test]
+    fn reads_windows_1252_named_in_the_content_type() {
+        let body = b"<rss version=\"2.0\"><channel><title>Na\xefve \x85</title>\
+            <link>https://example.com/</link><description></description></channel></rss>";
+        let feed = read(body, Some("application/xml; charset=windows-1252"));
+        assert_eq!(feed.title(), "Naïve …");
+    }
+
+    const RSS: &[u8] = b"<?xml version=\"1.0\"?>\n<rss version=\"2.0\"><channel><title>RSS</title>\
+        <link>https://example.com/</link><description></description>\
+        <item><title>An item</title></item></channel></rss>";
+
+    const ATOM: &[u8] = b"<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<!-- generated -->\n\
+        <feed xmlns=\"http://www.w3.org/2005/Atom\"><title>Atom</title><id>urn:feed</id>\
+        <updated>2024-01-01T00:00:00Z</updated><entry><title>An entry</title><id>urn:entry</id>\
+        <updated>2024-01-01T00:00:00Z</updated></entry></feed>";
+
+    #[test]
+    fn sniffs_rss_served_as_application_xml() {
+        assert_eq!(
+            FeedFormat::detect(Some("application/xml"), RSS),
+            FeedFormat::Rss
+        );
+        let feed = read(RSS, Some("application/xml; charset=utf-8"));
+        assert_eq!(feed.title(), "RSS");
+        assert_eq!(titles(&feed), vec!["An item"]);
+    }
+
+    #[test]
+    fn sniffs_rss_served_as_text_html() {
+        assert_eq!(FeedFormat::detect(Some("text/html"), RSS), FeedFormat::Rss);
+        let feed = read(RSS, Some("text/html; charset=UTF-8"));
+        assert_eq!(feed.format(), FeedFormat::Rss);
+        assert_eq!(titles(&feed), vec!["An item"]);
+    }
+
+    #[test]
+    fn sniffs_atom_from_the_body() {
+        for content_type in [
+            None,
+            Some("application/xml"),
+            Some("text/xml"),
+            Some("text/html"),
+        ] {
+            assert_eq!(FeedFormat::detect(content_type, ATOM), FeedFormat::Atom);
+            let feed = read(ATOM, content_type);
+            assert_eq!(feed.format(), FeedFormat::Atom);
+            assert_eq!(feed.title(), "Atom");
+            assert_eq!(titles(&feed), vec!["An entry"]);
+        }
+    }
+
+    #[test]
+    fn sniffs_a_prefixed_atom_root_after_a_byte_order_mark() {
+        let body = b"\xEF\xBB\xBF<atom:feed xmlns:atom=\"http://www.w3.org/2005/Atom\"/>";
+        assert_eq!(
+            FeedFormat::detect(Some("application/xml"), body),
+            FeedFormat::Atom
+        );
+    }
+
+    #[test]
+    fn trusts_a_feed_content_type_over_the_body() {
+        assert_eq!(
+            FeedFormat::detect(Some("application/atom+xml"), RSS),
+            FeedFormat::Atom
+        );
+        assert_eq!(
+            FeedFormat::detect(Some("Application/Feed+JSON; charset=utf-8"), RSS),
+            FeedFormat::JsonFeed
+        );
+    }
+
+    #[test]
+    fn sniffs_json_feeds_and_rdf() {
+        assert_eq!(
+            FeedFormat::detect(Some("application/json"), b"  {\"version\": 1}"),
+            FeedFormat::JsonFeed
+        );
+        assert_eq!(
+            FeedFormat::detect(Some("text/xml"), b"<rdf:RDF xmlns:rdf=\"r\"></rdf:RDF>"),
+            FeedFormat::Rdf
+        );
+    }
+
+    const RDF: &[u8] = br#"<?xml version="1.0" encoding="utf-8"?>
+<rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#"
+         xmlns:dc="http://purl.org/dc/elements/1.1/"
+         xmlns="http://purl.org/rss/1.0/">
+  <channel rdf:about="https://example.com/">
+    <title>RDF</title>
+    <link>https://example.com/</link>
+    <description>An RSS 1.0 feed</description>
+    <items>
+      <rdf:Seq>
+        <rdf:li rdf:resource="https://example.com/first"/>
+        <rdf:li rdf:resource="https://example.com/second"/>
+      </rdf:Seq>
+    </items>
+  </channel>
+  <item rdf:about="https://example.com/first">
+    <title>First</title>
+    <link>https://example.com/first</link>
+    <dc:date>2024-03-01T12:30:00Z</dc:date>
+  </item>
+  <item rdf:about="https://example.com/second">
+    <title>Second</title>
+    <link>https://example.com/second</link>
+    <dc:date>2024-02-29T08:00:00+02:00</dc:date>
+  </item>
+</rdf:RDF>"#;
+
+    #[test]
+    fn reads_rss_1_0_items_and_dublin_core_dates() {
+        let feed = read(RDF, Some("application/rdf+xml"));
+        assert_eq!(feed.format(), FeedFormat::Rdf);
+        assert_eq!(feed.title(), "RDF");
+        assert_eq!(feed.link(), "https://example.com/");
+        assert_eq!(titles(&feed), vec!["First", "Second"]);
+        let links: Vec<_> = feed.items().iter().filter_map(Item::link).collect();
+        assert_eq!(
+            links,
+            vec!["https://example.com/first", "https://example.com/second"]
+        );
+        let dates: Vec<_> = feed.items().iter().filter_map(Item::pub_date).collect();
+        assert_eq!(
+            dates,
+            vec![
+                "Fri, 1 Mar 2024 12:30:00 +0000",
+                "Thu, 29 Feb 2024 08:00:00 +0200"
+            ]
+        );
+    }
+
+    #[test]
+    fn sniffs_rss_1_0_served_as_text_xml() {
+        let feed = read(RDF, Some("text/xml"));
+        assert_eq!(feed.format(), FeedFormat::Rdf);
+        assert_eq!(titles(&feed), vec!["First", "Second"]);
+    }
+}
@@ -0,0 +1,125 @@
+//! Fixes for the mistakes most often found in feeds that fail to parse, so that feeds other
+//! readers tolerate can be read here too.
+
+/// Repairs `xml`, returning the repaired document and what was changed, which is empty if
+/// nothing needed repairing.
+pub fn repair_xml(xml: &[u8]) -> (Vec<u8>, Vec<String>) {
+    let mut warnings = Vec::new();
+
+    let mut xml = if is_utf8(xml) {
+        let (text, count) = replace_invalid_utf8(xml);
+        if count > 0 {
+            warnings.push(format!("Replaced {} invalid UTF-8 sequences", count));
+        }
+        text
+    } else {
+        xml.to_vec()
+    };
+
+    let before = xml.len();
+    xml.retain(|b| !is_invalid_control(*b));
+    if xml.len() < before {
+        warnings.push(format!(
+            "Removed {} invalid control characters",
+            before - xml.len()
+        ));
+    }
+
+    let (escaped, count) = escape_ampersands(&xml);
+    if count > 0 {
+        warnings.push(format!("Escaped {} unescaped ampersands", count));
+        xml = escaped;
+    }
+
+    (xml, warnings)
+}
+
+/// Whether the document is UTF-8, as it is unless its declaration names another encoding.
+fn is_utf8(xml: &[u8]) -> bool {
+    let xml = xml.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(xml);
+    let Some(declaration) = xml
+        .strip_prefix(b"<?xml")
+        .and_then(|rest| Some(&rest[..rest.windows(2).position(|w| w == b"?>")?]))
+    else {
+        return true;
+    };
+    let declaration = String::from_utf8_lossy(declaration).to_ascii_lowercase();
+    match declaration.find("encoding") {
+        Some(start) => {
+            let value = declaration[start + "encoding".len()..].trim_start_matches(|c: char| {
+                c.is_whitespace() || c == '=' || c == '"' || c == '\''
+            });
+            value.starts_with("utf-8") || value.starts_with("utf8")
+        }
+        None => true,
+    }
+}
+
+/// C0 control characters other than tab, newline and carriage return, which XML forbids.
+fn is_invalid_control(b: u8) -> bool {
+    b < 0x20 && !matches!(b, b'\t' | b'\n' | b'\r')
+}
+
+/// Escapes each `&` that doesn't start an entity or character reference, such as those in
+/// the query strings of URLs, leaving CDATA sections alone.
+fn escape_ampersands(xml: &[u8]) -> (Vec<u8>, usize) {
+    const CDATA_START: &[u8] = b"<![CDATA[";
+    const CDATA_END: &[u8] = b"]]>";
+
+    let mut out = Vec::with_capacity(xml.len());
+    let mut count = 0;
+    let mut i = 0;
+    while i < xml.len() {
+        if xml[i..].starts_with(CDATA_START) {
+            let end = xml[i..]
+                .windows(CDATA_END.len())
+                .position(|w| w == CDATA_END)
+                .map_or(xml.len(), |end| i + end + CDATA_END.len());
+            out.extend_from_slice(&xml[i..end]);
+            i = end;
+            continue;
+        }
+        if xml[i] == b'&' && !starts_reference(&xml[i + 1..]) {
+            out.extend_from_slice(b"&amp;");
+            count += 1;
+        } else {
+            out.push(xml[i]);
+        }
+        i += 1;
+    }
+    (out, count)
+}
+
+/// Whether `rest`, following an `&`, is the remainder of a reference like `amp;`, `#38;` or
+/// `#x26;`.
+fn starts_reference(rest: &[u8]) -> bool {
+    let Some(end) = rest.iter().take(32).position(|b| *b == b';') else {
+        return false;
+    };
+    match &rest[..end] {
+        [b'#', b'x' | b'X', hex @ ..] => !hex.is_empty() && hex.iter().all(u8::is_ascii_hexdigit),
+        [b'#', digits @ ..] => !digits.is_empty() && digits.iter().all(u8::is_ascii_digit),
+        [first, name @ ..] => {
+            (first.is_ascii_alphabetic() || *first == b'_')
+                && name
+                    .iter()
+                    .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'_' | b'-' | b'.'))
+        }
+        [] => false,
+    }
+}
+
+/// Replaces each invalid sequence in `xml`, such as an encoded UTF-16 surrogate, with U+FFFD,
+/// counting them.
+fn replace_invalid_utf8(xml: &[u8]) -> (Vec<u8>, usize) {
+    let mut out = Vec::with_capacity(xml.len());
+    let mut count = 0;
+    for chunk in xml.utf8_chunks() {
+        out.extend_from_slice(chunk.valid().as_bytes());
+        if !chunk.invalid().is_empty() {
+            out.extend_from_slice(char::REPLACEMENT_CHARACTER.to_string().as_bytes());
+            count += 1;
+        }
+    }
+    (out, count)
+}
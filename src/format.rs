@@ -0,0 +1,79 @@
+use chrono::{DateTime, Utc};
+
+/// How long ago `dt` was, in the largest whole unit, e.g. `"5m ago"` or `"3d ago"`.
+pub fn format_relative(dt: DateTime<Utc>) -> String {
+    let elapsed = Utc::now().signed_duration_since(dt);
+
+    if elapsed.num_minutes() < 1 {
+        "just now".to_owned()
+    } else if elapsed.num_hours() < 1 {
+        format!("{}m ago", elapsed.num_minutes())
+    } else if elapsed.num_days() < 1 {
+        format!("{}h ago", elapsed.num_hours())
+    } else if elapsed.num_weeks() < 1 {
+        format!("{}d ago", elapsed.num_days())
+    } else if elapsed.num_days() < 365 {
+        format!("{}w ago", elapsed.num_weeks())
+    } else {
+        format!("{}y ago", elapsed.num_days() / 365)
+    }
+}
+
+/// A size in bytes in the largest unit it fills, e.g. `"45 MB"` or `"1.2 GB"`.
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = UNITS[0];
+    for next in &UNITS[1..] {
+        if size < 1024.0 {
+            break;
+        }
+        size /= 1024.0;
+        unit = next;
+    }
+    if size < 10.0 {
+        format!("{:.1} {}", size, unit)
+    } else {
+        format!("{:.0} {}", size, unit)
+    }
+}
+
+/// An `<itunes:duration>` as `h:mm:ss` or `m:ss`. Durations are given either in seconds or
+/// already split by colons, which are shown as they are.
+pub fn format_duration(duration: &str) -> String {
+    let duration = duration.trim();
+    let Ok(seconds) = duration.parse::<u64>() else {
+        return duration.to_owned();
+    };
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, seconds)
+    } else {
+        format!("{}:{:02}", minutes, seconds)
+    }
+}
+
+/// A publication date as `YYYY-MM-DD`, read as RFC 2822 or RFC 3339. Dates in neither
+/// format are shown as they are.
+pub fn format_date(date: &str) -> String {
+    DateTime::parse_from_rfc2822(date.trim())
+        .or_else(|_| DateTime::parse_from_rfc3339(date.trim()))
+        .map(|date| date.format("%Y-%m-%d").to_string())
+        .unwrap_or_else(|_| date.trim().to_owned())
+}
+
+/// The name in an RSS `<author>`, which gives an email address with the name after it in
+/// parentheses, as in `"jo@example.com (Jo Bloggs)"`. Other authors are shown as they are.
+pub fn format_author(author: &str) -> &str {
+    author
+        .trim()
+        .strip_suffix(')')
+        .and_then(|rest| rest.split_once(" ("))
+        .filter(|(email, _)| email.contains('@'))
+        .map(|(_, name)| name.trim())
+        .unwrap_or(author.trim())
+}
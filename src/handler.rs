@@ -1,7 +1,50 @@
-use crate::app::{App, AppResult};
+use crate::app::{App, AppResult, Direction, Status, Tab, View, WizardStep};
+use crate::config::keymap::Action;
+use crate::config::KeybindingMode;
 use crossterm::event::{
     KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
 };
+use std::time::{Duration, Instant};
+
+/// How long the first key of a sequence such as `gg` waits for the second.
+const KEY_SEQUENCE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// The first key of an unfinished two-key sequence, such as `gg` in vim mode, and when it was
+/// pressed.
+#[derive(Debug, Default)]
+pub struct PendingKey(Option<(KeyEvent, Instant)>);
+
+/// What a key pressed does to the sequence waiting on it.
+#[derive(Debug, PartialEq)]
+enum Sequence {
+    /// The key starts a sequence, and waits for the next.
+    Started,
+    /// The key finishes the sequence the pending key started.
+    Completed,
+    /// The key is no part of a sequence, and whatever was pending is dropped.
+    None,
+}
+
+impl PendingKey {
+    /// Feeds in `key`, pressed at `now`. A `g` starts `gg`, and a second `g` pressed within
+    /// `KEY_SEQUENCE_TIMEOUT` completes it. Any other key, or a `g` too late, drops the first.
+    fn press(&mut self, key: KeyEvent, now: Instant) -> Sequence {
+        let pending = self
+            .0
+            .take()
+            .filter(|(_, pressed)| now.duration_since(*pressed) < KEY_SEQUENCE_TIMEOUT);
+        if key.code != KeyCode::Char('g') || key.modifiers == KeyModifiers::CONTROL {
+            return Sequence::None;
+        }
+        match pending {
+            Some((first, _)) if first.code == key.code => Sequence::Completed,
+            _ => {
+                self.0 = Some((key, now));
+                Sequence::Started
+            }
+        }
+    }
+}
 
 /// Handles the key events and updates the state of [`App`].
 pub fn handle_key_events(key_event: KeyEvent, app: &mut App) -> AppResult<()> {
@@ -12,6 +55,11 @@ pub fn handle_key_events(key_event: KeyEvent, app: &mut App) -> AppResult<()> {
         }
     }
 
+    if app.should_render_wizard() {
+        handle_wizard_keys(key_event, app);
+        return Ok(());
+    }
+
     if app.should_render_console() {
         match key_event.code {
             KeyCode::Char('c') | KeyCode::Char('C')
@@ -40,10 +88,123 @@ pub fn handle_key_events(key_event: KeyEvent, app: &mut App) -> AppResult<()> {
         return Ok(());
     }
 
-    if app.show_keybinds {
+    if app.should_render_item_filter() {
+        match key_event.code {
+            KeyCode::Char('c') | KeyCode::Char('C')
+                if key_event.modifiers == KeyModifiers::CONTROL =>
+            {
+                app.quit();
+            }
+            KeyCode::Char(c) => app.item_filter_push(c),
+            KeyCode::Backspace => app.item_filter_pop(),
+            KeyCode::Enter => app.close_item_filter(),
+            KeyCode::Esc => app.clear_item_filter(),
+            _ => {}
+        }
+        return Ok(());
+    }
+
+    if app.should_render_feed_filter() {
+        match key_event.code {
+            KeyCode::Char('c') | KeyCode::Char('C')
+                if key_event.modifiers == KeyModifiers::CONTROL =>
+            {
+                app.quit();
+            }
+            KeyCode::Char(c) => app.feed_filter_push(c),
+            KeyCode::Backspace => app.feed_filter_pop(),
+            KeyCode::Enter => app.close_feed_filter(),
+            KeyCode::Esc => app.clear_feed_filter(),
+            _ => {}
+        }
+        return Ok(());
+    }
+
+    if app.should_render_list_search() {
+        match key_event.code {
+            KeyCode::Char('c') | KeyCode::Char('C')
+                if key_event.modifiers == KeyModifiers::CONTROL =>
+            {
+                app.quit();
+            }
+            KeyCode::Char(c) => app.list_search_push(c),
+            KeyCode::Backspace => app.list_search_pop(),
+            KeyCode::Enter => app.close_list_search(),
+            KeyCode::Esc => app.clear_last_search(),
+            _ => {}
+        }
+        return Ok(());
+    }
+
+    if app.should_render_search_input() {
         match key_event.code {
-            // Exit application on `q`
-            KeyCode::Char('q') | KeyCode::Char('Q') => {
+            KeyCode::Char('c') | KeyCode::Char('C')
+                if key_event.modifiers == KeyModifiers::CONTROL =>
+            {
+                app.quit();
+            }
+            KeyCode::Char(c) => app.search_push(c),
+            KeyCode::Backspace => app.search_pop(),
+            KeyCode::Enter | KeyCode::Esc | KeyCode::Down => app.close_search_input(),
+            _ => {}
+        }
+        return Ok(());
+    }
+
+    // only y, n and Esc answer, so a stray key can't delete a feed or lose the prompt
+    if app.should_render_delete_prompt() {
+        match key_event.code {
+            KeyCode::Char('c') | KeyCode::Char('C')
+                if key_event.modifiers == KeyModifiers::CONTROL =>
+            {
+                app.quit();
+            }
+            KeyCode::Char('y') | KeyCode::Char('Y') => app.confirm_delete_feed(),
+            KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => app.dismiss_delete_prompt(),
+            _ => {}
+        }
+        return Ok(());
+    }
+
+    let action = app.keymap.action_for(&key_event);
+
+    if app.should_render_error_detail() {
+        match action {
+            Some(Action::Quit) if key_event.modifiers == KeyModifiers::CONTROL => app.quit(),
+            _ => app.dismiss_error_detail(),
+        }
+        return Ok(());
+    }
+
+    if app.should_render_feed_candidates() {
+        match (action, key_event.code) {
+            (Some(Action::Next), _) => app.next_feed_candidate(),
+            (Some(Action::Prev), _) => app.prev_feed_candidate(),
+            (_, KeyCode::Enter) => app.add_feed_candidate(),
+            (Some(Action::Quit), _) | (_, KeyCode::Esc) => app.dismiss_feed_candidates(),
+            _ => {}
+        }
+        return Ok(());
+    }
+
+    if app.should_render_preset_picker() {
+        match (action, key_event.code) {
+            (Some(Action::Next), _) => app.next_preset(),
+            (Some(Action::Prev), _) => app.prev_preset(),
+            (_, KeyCode::Enter) => {
+                if let Err(err) = app.pick_preset() {
+                    app.status = Status::Errored(err.to_string());
+                }
+            }
+            (Some(Action::Quit), _) | (_, KeyCode::Esc) => app.dismiss_preset_picker(),
+            _ => {}
+        }
+        return Ok(());
+    }
+
+    if app.show_keybinds {
+        match action {
+            Some(Action::Quit) => {
                 app.quit();
             }
             _ => {
@@ -53,79 +214,287 @@ pub fn handle_key_events(key_event: KeyEvent, app: &mut App) -> AppResult<()> {
         }
     }
 
-    match key_event.code {
-        // Exit application on `q`
-        KeyCode::Char('q') | KeyCode::Char('Q') => {
-            app.quit();
+    // after the jump key, a letter or digit jumps to a feed whatever it is bound to
+    if app.take_feed_jump() {
+        if let KeyCode::Char(c) = key_event.code {
+            if c.is_alphanumeric() && (key_event.modifiers - KeyModifiers::SHIFT).is_empty() {
+                app.jump_to_feed_by_prefix(c);
+            }
         }
-        // Exit application on `Ctrl-C`
-        KeyCode::Char('c') | KeyCode::Char('C') if key_event.modifiers == KeyModifiers::CONTROL => {
+        return Ok(());
+    }
+
+    // while a search is active, n and N step through its matches before any binding
+    if app.has_last_search() && (key_event.modifiers - KeyModifiers::SHIFT).is_empty() {
+        let direction = match key_event.code {
+            KeyCode::Char('n') => Some(Direction::Forward),
+            KeyCode::Char('N') => Some(Direction::Backward),
+            _ => None,
+        };
+        if let Some(direction) = direction {
+            app.find_next(direction);
+            return Ok(());
+        }
+    }
+
+    if app.config.keybinding_mode() == KeybindingMode::Vim && handle_vim_keys(key_event, app) {
+        return Ok(());
+    }
+
+    // letters and digits bound to nothing jump between feeds, other keys are ignored
+    let Some(action) = action else {
+        if let KeyCode::Char(c) = key_event.code {
+            if c.is_alphanumeric()
+                && (key_event.modifiers - KeyModifiers::SHIFT).is_empty()
+                && app.active_tab == Tab::Browse
+                && app.active_view == View::MainList
+            {
+                app.jump_to_feed_by_prefix(c);
+            }
+        }
+        return Ok(());
+    };
+
+    match action {
+        Action::Quit => {
             app.quit();
         }
         // Navigation handlers
-        KeyCode::Down | KeyCode::Char('j') => {
+        Action::Next => {
             app.next();
         }
-        KeyCode::Up | KeyCode::Char('k') => {
+        Action::Prev => {
             app.prev();
         }
-        KeyCode::Right | KeyCode::Enter | KeyCode::Char('l') => {
+        Action::NextPane | Action::Select if app.active_tab == Tab::Search => {
+            app.open_search_result();
+        }
+        Action::NextPane | Action::Select if app.active_tab == Tab::Queue => {
+            app.open_queued_item();
+        }
+        Action::NextPane | Action::Select => {
             app.next_view(false);
         }
-        KeyCode::Left | KeyCode::Char('h') => {
+        Action::PrevPane => {
             app.prev_view(false);
         }
-        KeyCode::Tab => {
+        Action::NextTab => {
             app.next_tab();
         }
-        KeyCode::BackTab => {
+        Action::PrevTab => {
             app.prev_tab();
         }
-        KeyCode::Char('b') => app.set_tab(0),
-        KeyCode::Char('f') => app.set_tab(1),
-        KeyCode::Char('t') => app.set_tab(2),
-        // Other handlers you could add here.
-        KeyCode::Esc => {
-            app.unselect();
+        Action::Browse => app.set_tab(0),
+        Action::Favorites => app.enter_starred_view(),
+        Action::Tags => app.set_tab(2),
+        Action::Stats => app.set_tab(3),
+        Action::Timeline => app.set_tab(4),
+        Action::Queue => app.set_tab(6),
+        Action::Enqueue => app.enqueue_item(),
+        Action::Dequeue => app.dequeue_item(),
+        Action::Unselect | Action::Search | Action::SearchAll if app.active_tab == Tab::Search => {
+            app.open_search(None);
+        }
+        Action::SearchAll => app.open_search(None),
+        Action::Unselect => {
+            if app.active_view == View::SubList && app.has_item_filter() {
+                app.clear_item_filter();
+            } else if app.has_last_search() {
+                app.clear_last_search();
+            } else if app.active_view == View::MainList && app.has_feed_filter() {
+                app.clear_feed_filter();
+            } else {
+                app.unselect();
+            }
         }
-        KeyCode::Char('a') => {
+        Action::Add => {
             app.toggle_console(Some(":add "));
         }
-        KeyCode::Char('d') => {
-            app.toggle_console(Some(":delete "));
+        Action::Delete if app.active_tab == Tab::Queue => {
+            app.remove_queued_item();
         }
-        KeyCode::Char('/') => {
-            app.toggle_console(Some(":search "));
+        Action::Delete => {
+            if app.active_tab == Tab::Browse
+                && app.active_view == View::MainList
+                && app.current_feed().is_some()
+            {
+                app.prompt_delete_feed();
+            } else {
+                app.toggle_console(Some(":delete "));
+            }
+        }
+        Action::Search => {
+            if app.active_tab == Tab::Browse && app.active_view == View::SubList {
+                app.open_item_filter();
+            } else if app.active_tab == Tab::Browse && app.active_view == View::MainList {
+                app.open_list_search();
+            } else {
+                app.toggle_console(Some(":search "));
+            }
+        }
+        Action::Export => {
+            app.toggle_console(Some(":export "));
         }
-        KeyCode::Char(':') => {
+        Action::Console => {
             app.toggle_console(Some(":"));
         }
-        KeyCode::Char('o') => {
+        Action::Open => {
             app.open();
         }
-        KeyCode::Char('r') => {
+        Action::CopyLink => {
+            if let Err(err) = app.yank_item_url() {
+                app.status = Status::Errored(err.to_string());
+            }
+        }
+        Action::Star => {
+            app.toggle_star();
+        }
+        Action::ToggleRead => {
+            app.toggle_current_item_read();
+        }
+        Action::MarkAllRead => {
+            if let Some(index) = app.feeds.state.selected() {
+                app.bulk_mark_feed_read(index);
+            }
+        }
+        Action::ToggleGroup => {
+            if let Some(group) = app.current_group() {
+                app.toggle_group_collapse(&group);
+            }
+        }
+        Action::ToggleAllGroups => {
+            app.toggle_all_groups();
+        }
+        Action::CycleFeedColor => {
+            if let Err(err) = app.cycle_feed_color() {
+                app.status = Status::Errored(err.to_string());
+            }
+        }
+        Action::ShowError => {
+            if let Err(err) = app.show_error_detail() {
+                app.status = Status::Errored(err.to_string());
+            }
+        }
+        Action::ToggleUnreadFilter => {
+            app.toggle_unread_filter();
+        }
+        // the feeds pane has no content to toggle, so there the key finds a feed
+        Action::ToggleContent
+            if app.active_tab == Tab::Browse && app.active_view == View::MainList =>
+        {
+            app.open_feed_filter();
+        }
+        Action::ToggleContent => {
+            app.toggle_content_mode();
+        }
+        Action::JumpToFeed => {
+            app.start_feed_jump();
+        }
+        Action::ToggleExpand => {
+            if app.active_tab == Tab::Browse && app.active_view == View::SubList {
+                app.toggle_expanded_item();
+            }
+        }
+        Action::CycleItemSort => {
+            if app.active_tab == Tab::Browse && app.active_view == View::SubList {
+                app.cycle_item_sort();
+            }
+        }
+        Action::CycleTimeFilter => {
+            if app.active_tab == Tab::Browse {
+                app.cycle_time_filter();
+            }
+        }
+        Action::Presets => {
+            if app.active_tab == Tab::Browse {
+                app.open_preset_picker();
+            }
+        }
+        Action::OpenEnclosure => {
+            if let Err(err) = app.open_enclosure() {
+                app.status = Status::Errored(err.to_string());
+            }
+        }
+        Action::Refresh => {
             app.refresh_all();
         }
-        KeyCode::Char('?') => {
+        Action::RefreshFeed => {
+            if let Err(err) = app.refresh_current_feed() {
+                app.status = Status::Errored(err.to_string());
+            }
+        }
+        Action::ReloadConfig => {
+            if let Err(err) = app.reload_config() {
+                app.status = Status::Errored(err.to_string());
+            }
+        }
+        Action::Help => {
             app.toggle_keybinds();
         }
-        KeyCode::Char(',') => {
+        Action::EditConfig => {
             app.open_config();
         }
-        _ => {}
     }
     Ok(())
 }
 
+/// Handles keys while the setup wizard is shown: text is typed into the URL and interval
+/// steps, and the theme step is moved through like a list.
+fn handle_wizard_keys(key_event: KeyEvent, app: &mut App) {
+    let on_theme_step = app
+        .wizard
+        .as_ref()
+        .is_some_and(|wizard| wizard.step == WizardStep::Theme);
+
+    match key_event.code {
+        KeyCode::Char('c') | KeyCode::Char('C') if key_event.modifiers == KeyModifiers::CONTROL => {
+            app.quit();
+        }
+        KeyCode::Enter => app.submit_wizard_step(),
+        KeyCode::Esc => app.wizard_back(),
+        _ if on_theme_step => match app.keymap.action_for(&key_event) {
+            Some(Action::Next) => app.next(),
+            Some(Action::Prev) => app.prev(),
+            _ => {}
+        },
+        KeyCode::Char(c) if !key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+            app.wizard_push(c)
+        }
+        KeyCode::Backspace => app.wizard_pop(),
+        _ => {}
+    }
+}
+
+/// Handles the keys vim mode adds to the defaults, returning whether `key_event` was one of
+/// them. A `g` waits in `pending_key` for the next key, and only a second `g` completes `gg`.
+fn handle_vim_keys(key_event: KeyEvent, app: &mut App) -> bool {
+    let ctrl = key_event.modifiers == KeyModifiers::CONTROL;
+
+    match app.pending_key.press(key_event, Instant::now()) {
+        Sequence::Completed => app.first(),
+        Sequence::Started => {}
+        Sequence::None => match key_event.code {
+            KeyCode::Char('G') => app.last(),
+            KeyCode::Char('d') if ctrl => app.move_by(app.half_page()),
+            KeyCode::Char('u') if ctrl => app.move_by(-app.half_page()),
+            _ => return false,
+        },
+    }
+    true
+}
+
 pub fn handle_mouse_events(mouse_event: MouseEvent, app: &mut App) -> AppResult<()> {
     match mouse_event.kind {
         MouseEventKind::ScrollDown => {
-            app.next();
+            app.scroll_at(mouse_event.column, mouse_event.row, true);
         }
         MouseEventKind::ScrollUp => {
-            app.prev();
+            app.scroll_at(mouse_event.column, mouse_event.row, false);
         }
-        MouseEventKind::ScrollRight | MouseEventKind::Down(MouseButton::Left) => {
+        MouseEventKind::Down(MouseButton::Left) => {
+            app.click_at(mouse_event.column, mouse_event.row);
+        }
+        MouseEventKind::ScrollRight => {
             app.next_view(false);
         }
         MouseEventKind::ScrollLeft | MouseEventKind::Down(MouseButton::Right) => {
@@ -140,3 +509,144 @@ pub fn handle_resize_events(dimensions: (u16, u16), app: &mut App) -> AppResult<
     app.set_dimensions(dimensions);
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::Args;
+    use crate::feed::Feed;
+    use tempfile::TempDir;
+
+    fn key(c: char) -> KeyEvent {
+        KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE)
+    }
+
+    /// An app with its config and database in a temporary directory, showing feeds with
+    /// these titles in the feeds pane.
+    fn app_with_feeds(dir: &TempDir, titles: &[&str], vim: bool) -> App {
+        let config = dir.path().join("moccasin.toml");
+        let mode = if vim { "vim" } else { "default" };
+        std::fs::write(
+            &config,
+            format!(
+                "[sources]\nfeeds = []\n\n[preferences]\nkeybinding_mode = \"{}\"\n",
+                mode
+            ),
+        )
+        .unwrap();
+        let args = Args {
+            config: Some(config.display().to_string()),
+            no_restore_session: true,
+            log_level: Some(log::LevelFilter::Off),
+            ..Args::default()
+        };
+        let mut app = App::init(args, (80, 24)).unwrap();
+        let feeds = titles
+            .iter()
+            .map(|title| {
+                let mut feed = Feed::placeholder(&format!("https://example.com/{}", title));
+                feed.title = title.to_string();
+                feed
+            })
+            .collect();
+        app.set_feeds(feeds);
+        app.feeds.state.select(Some(0));
+        app
+    }
+
+    fn selected_title(app: &App) -> Option<&str> {
+        app.current_feed().map(Feed::title)
+    }
+
+    fn press(app: &mut App, keys: &str) {
+        for c in keys.chars() {
+            handle_key_events(key(c), app).unwrap();
+        }
+    }
+
+    #[test]
+    fn jumps_to_feeds_starting_with_a_bound_letter() {
+        let dir = TempDir::new().unwrap();
+        let mut app = app_with_feeds(&dir, &["Alpha", "Science", "Tech", "Sports"], false);
+
+        press(&mut app, "'s");
+        assert_eq!(selected_title(&app), Some("Science"));
+        press(&mut app, "'S");
+        assert_eq!(selected_title(&app), Some("Sports"));
+        press(&mut app, "'t");
+        assert_eq!(selected_title(&app), Some("Tech"));
+        // none of them starred an item or switched tabs, and q with no feed to jump to
+        // doesn't quit either
+        assert_eq!(app.active_tab, Tab::Browse);
+        press(&mut app, "'q");
+        assert!(app.running);
+        assert_eq!(selected_title(&app), Some("Tech"));
+    }
+
+    #[test]
+    fn jumps_to_feeds_starting_with_g_in_vim_mode() {
+        let dir = TempDir::new().unwrap();
+        let mut app = app_with_feeds(&dir, &["Alpha", "Beta", "Gamma"], true);
+
+        press(&mut app, "'g");
+        assert_eq!(selected_title(&app), Some("Gamma"));
+        press(&mut app, "gg");
+        assert_eq!(selected_title(&app), Some("Alpha"));
+    }
+
+    #[test]
+    fn leaves_bindings_alone_without_the_jump_key() {
+        let dir = TempDir::new().unwrap();
+        let mut app = app_with_feeds(&dir, &["Alpha", "Stats", "Tech"], false);
+
+        press(&mut app, "S");
+        assert_eq!(app.active_tab, Tab::Stats);
+    }
+
+    #[test]
+    fn completes_a_sequence_with_its_second_key() {
+        let mut pending = PendingKey::default();
+        let now = Instant::now();
+        assert_eq!(pending.press(key('g'), now), Sequence::Started);
+        assert_eq!(
+            pending.press(key('g'), now + Duration::from_millis(200)),
+            Sequence::Completed
+        );
+        // completing it leaves nothing pending, so a third g starts over
+        assert_eq!(
+            pending.press(key('g'), now + Duration::from_millis(400)),
+            Sequence::Started
+        );
+    }
+
+    #[test]
+    fn drops_the_first_key_before_an_unrelated_one() {
+        let mut pending = PendingKey::default();
+        let now = Instant::now();
+        assert_eq!(pending.press(key('g'), now), Sequence::Started);
+        assert_eq!(pending.press(key('j'), now), Sequence::None);
+        assert_eq!(pending.press(key('g'), now), Sequence::Started);
+    }
+
+    #[test]
+    fn drops_the_first_key_after_the_timeout() {
+        let mut pending = PendingKey::default();
+        let now = Instant::now();
+        assert_eq!(pending.press(key('g'), now), Sequence::Started);
+        assert_eq!(
+            pending.press(key('g'), now + KEY_SEQUENCE_TIMEOUT),
+            Sequence::Started
+        );
+    }
+
+    #[test]
+    fn ignores_ctrl_g() {
+        let mut pending = PendingKey::default();
+        let now = Instant::now();
+        let ctrl_g = KeyEvent::new(KeyCode::Char('g'), KeyModifiers::CONTROL);
+        assert_eq!(pending.press(ctrl_g, now), Sequence::None);
+        assert_eq!(pending.press(key('g'), now), Sequence::Started);
+        assert_eq!(pending.press(ctrl_g, now), Sequence::None);
+        assert_eq!(pending.press(key('g'), now), Sequence::Started);
+    }
+}
@@ -0,0 +1,220 @@
+//! Commands for a running instance, sent over a Unix domain socket as lines of JSON, such as
+//! `{"cmd":"refresh"}`, each answered with `{"ok":true}` or `{"ok":false,"error":"…"}`.
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde::Deserialize;
+use serde_json::json;
+use std::env;
+use std::path::PathBuf;
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+
+const SOCKET_FILE: &str = "moccasin.sock";
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(tag = "cmd", rename_all = "snake_case")]
+pub enum IpcCommand {
+    Refresh,
+    OpenUrl { url: String },
+    AddFeed { url: String },
+    Quit,
+}
+
+/// A command, and where to send whether it succeeded.
+pub type IpcRequest = (IpcCommand, oneshot::Sender<Result<()>>);
+
+/// Listens on the socket for commands, handed to the interface by `try_recv` on each tick.
+#[derive(Debug)]
+pub struct UnixSocketServer {
+    path: PathBuf,
+    rx: UnboundedReceiver<IpcRequest>,
+    listener: JoinHandle<()>,
+}
+
+impl Drop for UnixSocketServer {
+    fn drop(&mut self) {
+        self.listener.abort();
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+impl UnixSocketServer {
+    /// Listens on the socket, replacing one left behind by an instance that has stopped.
+    /// Fails if another instance is listening on it.
+    #[cfg(unix)]
+    pub fn start() -> Result<Self> {
+        use tokio::net::UnixListener;
+
+        let path = socket_path()?;
+        if instance_running() {
+            bail!("another instance is listening on {}", path.display());
+        }
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixListener::bind(&path)
+            .with_context(|| format!("could not listen on {}", path.display()))?;
+        log::info!("Listening for commands on {}", path.display());
+
+        let (tx, rx) = mpsc::unbounded_channel();
+        let listener = tokio::spawn(async move {
+            loop {
+                match listener.accept().await {
+                    Ok((stream, _)) => {
+                        tokio::spawn(serve(stream, tx.clone()));
+                    }
+                    Err(err) => {
+                        log::error!("Command socket stopped: {}", err);
+                        return;
+                    }
+                }
+            }
+        });
+        Ok(Self { path, rx, listener })
+    }
+
+    #[cfg(not(unix))]
+    pub fn start() -> Result<Self> {
+        bail!("commands over a socket are only supported on Unix")
+    }
+
+    /// The next command waiting to be run, if any.
+    pub fn try_recv(&mut self) -> Option<IpcRequest> {
+        self.rx.try_recv().ok()
+    }
+}
+
+/// Answers each line sent on `stream` once the interface has run its command.
+#[cfg(unix)]
+async fn serve(stream: tokio::net::UnixStream, tx: UnboundedSender<IpcRequest>) {
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        let result = match serde_json::from_str::<IpcCommand>(&line) {
+            Ok(command) => {
+                let (reply_tx, reply_rx) = oneshot::channel();
+                if tx.send((command, reply_tx)).is_err() {
+                    return;
+                }
+                reply_rx
+                    .await
+                    .unwrap_or_else(|_| Err(anyhow!("moccasin is quitting")))
+            }
+            Err(err) => Err(anyhow!("invalid command: {}", err)),
+        };
+        let reply = match result {
+            Ok(()) => json!({ "ok": true }),
+            Err(err) => json!({ "ok": false, "error": err.to_string() }),
+        };
+        if writer
+            .write_all(format!("{}\n", reply).as_bytes())
+            .await
+            .is_err()
+        {
+            return;
+        }
+    }
+}
+
+/// Sends `command`, a line of JSON, to the running instance and waits for it to be run.
+#[cfg(unix)]
+pub async fn send(command: &str) -> Result<()> {
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::UnixStream;
+
+    #[derive(Deserialize)]
+    struct Reply {
+        ok: bool,
+        error: Option<String>,
+    }
+
+    let path = socket_path()?;
+    check_owner(&path)?;
+    let stream = UnixStream::connect(&path)
+        .await
+        .with_context(|| format!("moccasin is not running, no socket at {}", path.display()))?;
+    let (reader, mut writer) = stream.into_split();
+    writer
+        .write_all(format!("{}\n", command.trim()).as_bytes())
+        .await?;
+
+    let line = BufReader::new(reader)
+        .lines()
+        .next_line()
+        .await?
+        .context("moccasin closed the connection without replying")?;
+    let reply = serde_json::from_str::<Reply>(&line).context("invalid reply")?;
+    if !reply.ok {
+        bail!(reply.error.unwrap_or_else(|| "command failed".to_owned()));
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub async fn send(_command: &str) -> Result<()> {
+    bail!("commands over a socket are only supported on Unix")
+}
+
+/// Whether an instance is listening for commands, and so has the database open.
+pub fn instance_running() -> bool {
+    #[cfg(unix)]
+    return socket_path().is_ok_and(|path| std::os::unix::net::UnixStream::connect(path).is_ok());
+    #[cfg(not(unix))]
+    return false;
+}
+
+pub fn socket_path() -> Result<PathBuf> {
+    Ok(runtime_dir()?.join(SOCKET_FILE))
+}
+
+/// Where the socket and the daemon's pid file live: `$XDG_RUNTIME_DIR` when it is set, or else
+/// a `moccasin-<uid>` directory in the temporary directory that only the user can enter, so
+/// another user can't stand in for a running instance.
+pub fn runtime_dir() -> Result<PathBuf> {
+    match env::var_os("XDG_RUNTIME_DIR") {
+        Some(dir) if !dir.is_empty() => Ok(PathBuf::from(dir)),
+        _ => private_temp_dir(),
+    }
+}
+
+#[cfg(unix)]
+fn private_temp_dir() -> Result<PathBuf> {
+    use std::os::unix::fs::{DirBuilderExt, MetadataExt};
+    use std::{fs, io};
+
+    let uid = nix::unistd::getuid().as_raw();
+    let dir = env::temp_dir().join(format!("moccasin-{}", uid));
+    match fs::DirBuilder::new().mode(0o700).create(&dir) {
+        Err(err) if err.kind() != io::ErrorKind::AlreadyExists => {
+            return Err(err).with_context(|| format!("could not create {}", dir.display()));
+        }
+        _ => {}
+    }
+    // it may have been made by someone else before we got to it
+    let metadata = fs::symlink_metadata(&dir)?;
+    if !metadata.is_dir() || metadata.uid() != uid || metadata.mode() & 0o077 != 0 {
+        bail!("{} is not a directory only you can access", dir.display());
+    }
+    Ok(dir)
+}
+
+#[cfg(not(unix))]
+fn private_temp_dir() -> Result<PathBuf> {
+    Ok(env::temp_dir())
+}
+
+/// Fails unless the socket at `path` belongs to the user, so commands only go to their own
+/// instance.
+#[cfg(unix)]
+fn check_owner(path: &std::path::Path) -> Result<()> {
+    use std::fs;
+    use std::os::unix::fs::MetadataExt;
+
+    let metadata = fs::symlink_metadata(path)
+        .with_context(|| format!("moccasin is not running, no socket at {}", path.display()))?;
+    if metadata.uid() != nix::unistd::getuid().as_raw() {
+        bail!("{} belongs to another user", path.display());
+    }
+    Ok(())
+}
@@ -1,9 +1,21 @@
+pub mod api;
 pub mod app;
 pub mod config;
+pub mod daemon;
 pub mod repo;
 pub mod event;
 pub mod feed;
+pub mod ipc;
+pub mod logging;
+pub mod format;
 pub mod handler;
+pub mod markdown;
+pub mod migrations;
+pub mod notification;
+pub mod opml;
+pub mod render;
+pub mod sync;
 pub mod tui;
 pub mod ui;
 pub mod util;
+pub mod websubscriber;
@@ -0,0 +1,110 @@
+//! Where log records go. `tracing` events, and `log` records turned into them, are written as
+//! lines of JSON to `--log-file`, or to `moccasin.log` in the config directory for the daemon
+//! and the interface, and as text to stderr for commands that don't draw the interface, which
+//! draws on stderr. Both carry the fields of the spans they happen in, such as a fetch's `url`.
+
+use crate::app::Args;
+use crate::config::{env, Config};
+use anyhow::{Context, Result};
+use log::LevelFilter;
+use std::backtrace::Backtrace;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, IsTerminal, Write};
+use std::panic;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tracing_subscriber::filter::LevelFilter as TracingLevelFilter;
+use tracing_subscriber::prelude::*;
+
+const LOG_FILE: &str = "moccasin.log";
+const CRASH_LOG_FILE: &str = "crash.log";
+
+pub fn init(args: &Args, config_dir: &Path) -> Result<()> {
+    // logging is set up by the first config a process loads
+    if tracing::dispatcher::has_been_set() {
+        return Ok(());
+    }
+    let level = args
+        .log_level
+        .or_else(env::log_level)
+        .unwrap_or(LevelFilter::Warn);
+    let file_path = match &args.log_file {
+        Some(path) => Some(path.clone()),
+        None if args.daemon || args.opens_interface() => Some(config_dir.join(LOG_FILE)),
+        None => None,
+    };
+    let file_layer = match file_path {
+        Some(path) => Some(
+            tracing_subscriber::fmt::layer()
+                .json()
+                .with_writer(Mutex::new(open_log_file(&path)?)),
+        ),
+        None => None,
+    };
+    let stderr_layer = (!args.opens_interface()).then(|| {
+        tracing_subscriber::fmt::layer()
+            .with_ansi(io::stderr().is_terminal())
+            .with_writer(io::stderr)
+    });
+
+    tracing_subscriber::registry()
+        .with(tracing_level(level))
+        .with(file_layer)
+        .with(stderr_layer)
+        .try_init()?;
+    Ok(())
+}
+
+fn tracing_level(level: LevelFilter) -> TracingLevelFilter {
+    match level {
+        LevelFilter::Off => TracingLevelFilter::OFF,
+        LevelFilter::Error => TracingLevelFilter::ERROR,
+        LevelFilter::Warn => TracingLevelFilter::WARN,
+        LevelFilter::Info => TracingLevelFilter::INFO,
+        LevelFilter::Debug => TracingLevelFilter::DEBUG,
+        LevelFilter::Trace => TracingLevelFilter::TRACE,
+    }
+}
+
+/// Where panics are recorded, beside the database in the data directory.
+pub fn crash_log_path() -> PathBuf {
+    Config::default_data_path().with_file_name(CRASH_LOG_FILE)
+}
+
+/// Prints panics to stderr with a backtrace, and appends them with the time to `crash.log`
+/// for bug reports. The interface wraps this hook in one restoring the terminal first.
+pub fn install_panic_hook() {
+    panic::set_hook(Box::new(|info| {
+        let backtrace = Backtrace::force_capture();
+        let path = crash_log_path();
+        let report = format!(
+            "[{}] moccasin {} {}\n{}\n",
+            chrono::Local::now().to_rfc3339(),
+            env!("CARGO_PKG_VERSION"),
+            info,
+            backtrace
+        );
+        let written = path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| open_crash_log(&path))
+            .and_then(|mut file| file.write_all(report.as_bytes()));
+
+        eprintln!("{}\n{}", info, backtrace);
+        if written.is_ok() {
+            eprintln!("This report was saved to {}", path.display());
+        }
+    }));
+}
+
+fn open_crash_log(path: &Path) -> io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+fn open_log_file(path: &Path) -> Result<File> {
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("could not open log file {}", path.display()))
+}
@@ -1,27 +1,253 @@
+use anyhow::anyhow;
+use clap::builder::PossibleValuesParser;
+use clap::{CommandFactory, Parser};
 use crossterm::terminal;
-use moccasin::app::{App, AppResult};
+use moccasin::api;
+use moccasin::app::{App, AppResult, Args, CliCommand};
+use moccasin::config::theme::{Theme, BUILT_IN_THEMES};
+use moccasin::config::{env, Config};
+use moccasin::daemon::{shutdown_signal, DaemonRunner};
 use moccasin::event::{Event, EventHandler};
 use moccasin::handler::{handle_key_events, handle_mouse_events, handle_resize_events};
+use moccasin::ipc;
+use moccasin::logging;
+use moccasin::notification;
+use moccasin::repo::{default_backup_path, Repository};
 use moccasin::tui::Tui;
-use std::io;
+use serde_json::json;
+use std::io::{self, Write};
 use tui::backend::CrosstermBackend;
 use tui::Terminal;
 
 #[tokio::main]
 async fn main() -> AppResult<()> {
+    let args = Args::parse();
+    logging::install_panic_hook();
+
+    if args.help_env {
+        let width = env::VARIABLES.iter().map(|(name, _)| name.len()).max();
+        for (name, description) in env::VARIABLES {
+            println!(
+                "{:width$}  {}",
+                name,
+                description,
+                width = width.unwrap_or(0)
+            );
+        }
+        return Ok(());
+    }
+
+    // Run one-shot commands without starting the interface.
+    if let Some(CliCommand::Completions { shell }) = args.command {
+        // profiles installed now are offered for --profile
+        let profiles = Config::new(args.clone())?.installed_profiles();
+        let mut cmd = Args::command().mut_arg("profile", |arg| {
+            arg.value_parser(PossibleValuesParser::new(profiles))
+        });
+        clap_complete::generate(shell, &mut cmd, env!("CARGO_BIN_NAME"), &mut io::stdout());
+        return Ok(());
+    }
+
+    if let Some(CliCommand::Backup { output }) = &args.command {
+        let app = App::init(args.clone(), (0, 0))?;
+        if !app.config.should_cache() {
+            return Err(anyhow!("feeds are not cached, so there is no database to back up").into());
+        }
+        let dest = output
+            .clone()
+            .unwrap_or_else(|| default_backup_path(&app.config.db_path()));
+        app.repo.lock().backup(&dest)?;
+        println!("Backed up the database to {}", dest.display());
+        return Ok(());
+    }
+
+    if let Some(path) = &args.restore {
+        let config = Config::new(args.clone())?;
+        if ipc::instance_running() {
+            return Err(anyhow!("quit the running moccasin before restoring").into());
+        }
+        print!(
+            "Replace the database at {} with {}? [y/N] ",
+            config.db_path().display(),
+            path.display()
+        );
+        io::stdout().flush()?;
+        let mut answer = String::new();
+        io::stdin().read_line(&mut answer)?;
+        if !answer.trim().eq_ignore_ascii_case("y") {
+            println!("Nothing restored");
+            return Ok(());
+        }
+        <Repository>::restore(&config, path)?;
+        println!("Restored the database from {}", path.display());
+        return Ok(());
+    }
+
+    if args.list_themes {
+        let config = Config::new(args.clone())?;
+        for name in BUILT_IN_THEMES {
+            println!("{}", name);
+        }
+        let themes_dir = config.themes_path();
+        for name in Theme::installed(&themes_dir) {
+            println!("{} ({})", name, themes_dir.display());
+        }
+        return Ok(());
+    }
+
+    if let Some(path) = &args.import_opml {
+        let mut config = Config::new(args.clone())?;
+        let (added, present) = config.import_opml(path)?;
+        println!("Imported {} new feeds ({} already present)", added, present);
+        return Ok(());
+    }
+
+    if let Some([url, name, value]) = args.add_feed_header.as_deref() {
+        let mut config = Config::new(args.clone())?;
+        config.add_feed_header(url, name, value)?;
+        println!("Set header {} for {}", name, url);
+        return Ok(());
+    }
+
+    if let Some(path) = &args.export_opml {
+        let app = App::init(args.clone(), (0, 0))?;
+        app.export_opml(path)?;
+        println!(
+            "Exported {} feeds to {}",
+            app.config.feed_urls().len(),
+            path.display()
+        );
+        return Ok(());
+    }
+
+    if let Some(path) = &args.export_json {
+        let app = App::init(args.clone(), (0, 0))?;
+        app.export_json(path)?;
+        println!(
+            "Exported {} feeds to {}",
+            app.all_feeds().count(),
+            path.display()
+        );
+        return Ok(());
+    }
+
+    if let Some(path) = &args.export {
+        let app = App::init(args.clone(), (0, 0))?;
+        let count = app.export_all_markdown(path)?;
+        println!("Exported {} items to {}", count, path.display());
+        return Ok(());
+    }
+
+    if let Some(query) = &args.search {
+        let app = App::init(args.clone(), (0, 0))?;
+        for result in app.search(query) {
+            println!(
+                "{}: {}",
+                result.feed_title(),
+                result.item_title().unwrap_or("[no title]")
+            );
+            if let Some(link) = result.item_link() {
+                println!("  {}", link);
+            }
+            if !result.snippet().is_empty() {
+                println!("  {}", result.snippet());
+            }
+        }
+        return Ok(());
+    }
+
+    if args.refresh_print {
+        // refresh once, without the timer also starting one
+        let mut args = args.clone();
+        args.interval = Some(0);
+        let mut app = App::init(args.clone(), (0, 0))?;
+
+        let counts = match app.oneshot_refresh().await {
+            Ok(counts) => counts,
+            Err(err) => {
+                tracing::error!(%err, "Refresh failed");
+                std::process::exit(2);
+            }
+        };
+        let mut failed = app.feed_errors.keys().cloned().collect::<Vec<_>>();
+        failed.sort();
+
+        if args.json {
+            let feeds = counts
+                .iter()
+                .map(|(title, new_items)| json!({ "title": title, "new_items": new_items }))
+                .collect::<Vec<_>>();
+            println!("{}", json!({ "feeds": feeds, "failed": failed }));
+        } else {
+            for (title, new_items) in &counts {
+                println!("{}: {} new", title, new_items);
+            }
+            for url in &failed {
+                println!("{}: failed", url);
+            }
+        }
+
+        let total = app.config.feed_urls().len();
+        if total > 0 && failed.len() >= total {
+            std::process::exit(2);
+        } else if !failed.is_empty() {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    if let Some(command) = &args.send {
+        ipc::send(command).await?;
+        return Ok(());
+    }
+
+    if args.daemon {
+        DaemonRunner::init(args)?.run().await?;
+        return Ok(());
+    }
+
+    // with stdout piped, print the unread items as text in place of the interface
+    if args.prints_unread() {
+        let app = App::init(args.clone(), (0, 0))?;
+        match app.write_unread(&mut io::stdout().lock()) {
+            Err(err) if err.kind() != io::ErrorKind::BrokenPipe => return Err(err.into()),
+            _ => return Ok(()),
+        }
+    }
+
     // Create an application.
-    let mut app = App::init(terminal::size().unwrap())?;
+    let api_port = args.api_server;
+    let setup = args.setup;
+    let mut app = App::init(args, terminal::size().unwrap())?;
+    if setup || app.config.is_first_run() {
+        app.start_wizard();
+    }
+    notification::warn_if_unsupported(app.config.notifications());
+    app.listen_for_commands();
+    let api = match api_port {
+        Some(port) => Some(api::start(port, app.repo.clone(), app.shared_config())?),
+        None => None,
+    };
 
     // Initialize the terminal user interface.
     let backend = CrosstermBackend::new(io::stderr());
     let terminal = Terminal::new(backend)?;
-    let events = EventHandler::new(250);
+    let events = EventHandler::new(app.config.tick_rate_ms());
     let mut tui = Tui::new(terminal, events);
     tui.init()?;
 
+    // quit as Ctrl-C or q would, so the terminal is restored and the session saved
+    let signals = tui.events.sender();
+    tokio::spawn(async move {
+        shutdown_signal().await;
+        let _ = signals.send(Event::Signal);
+    });
+
     // Start the main loop.
     while app.running {
         // Render the user interface.
+        app.sync_item_detail();
+        app.update_window_title();
         tui.draw(&mut app)?;
         // Handle events.
         match tui.events.next()? {
@@ -29,10 +255,16 @@ async fn main() -> AppResult<()> {
             Event::Key(key_event) => handle_key_events(key_event, &mut app)?,
             Event::Mouse(mouse_event) => handle_mouse_events(mouse_event, &mut app)?,
             Event::Resize(w, h) => handle_resize_events((w, h), &mut app)?,
+            Event::Signal => app.quit(),
         }
     }
 
     // Exit the user interface.
     tui.exit()?;
+    if let Err(err) = app.shutdown() {
+        log::error!("Could not save the session: {}", err);
+    }
+    api.iter().for_each(|handle| handle.abort());
+    app.push_read_items().await;
     Ok(())
 }
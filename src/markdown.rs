@@ -0,0 +1,50 @@
+use crate::feed::{html, Item};
+
+/// Writes items as a Markdown document, under a top-level heading for each feed.
+///
+/// Each item gets its own section with its date, author and link, followed by its
+/// description as plain text.
+pub fn write_document(feeds: &[(&str, Vec<&Item>)]) -> String {
+    let mut doc = String::new();
+
+    for (title, items) in feeds {
+        doc.push_str(&format!("# {}\n\n", title));
+
+        for item in items {
+            doc.push_str(&format!("## {}\n\n", item.title().unwrap_or("[no title]")));
+
+            let mut meta = vec![];
+            if let Some(date) = item.pub_date() {
+                meta.push(format!("- Published: {}", date));
+            }
+            if let Some(author) = item.author() {
+                meta.push(format!("- Author: {}", author));
+            }
+            if let Some(link) = item.link() {
+                meta.push(format!("- Link: <{}>", link));
+            }
+            if !meta.is_empty() {
+                doc.push_str(&meta.join("\n"));
+                doc.push_str("\n\n");
+            }
+
+            if let Some(text) = plain_description(item) {
+                doc.push_str(text.trim());
+                doc.push_str("\n\n");
+            }
+        }
+    }
+
+    doc
+}
+
+fn plain_description(item: &Item) -> Option<String> {
+    match &item.text_description {
+        Some(text) => Some(text.clone()),
+        None => item
+            .description
+            .as_deref()
+            .map(|desc| html::parse_html(desc).unwrap_or_else(|_| desc.to_owned())),
+    }
+    .filter(|text| !text.trim().is_empty())
+}
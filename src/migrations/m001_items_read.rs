@@ -0,0 +1,18 @@
+use super::{ensure_column, Migration};
+use rusqlite::{Connection, Result};
+
+pub struct ItemsRead;
+
+impl Migration for ItemsRead {
+    fn version(&self) -> u32 {
+        1
+    }
+
+    fn description(&self) -> &'static str {
+        "items are unread unless marked read"
+    }
+
+    fn up(&self, conn: &Connection) -> Result<()> {
+        ensure_column(conn, "items", "read", "INTEGER NOT NULL DEFAULT 0")
+    }
+}
@@ -0,0 +1,18 @@
+use super::{ensure_column, Migration};
+use rusqlite::{Connection, Result};
+
+pub struct ItemsStarred;
+
+impl Migration for ItemsStarred {
+    fn version(&self) -> u32 {
+        2
+    }
+
+    fn description(&self) -> &'static str {
+        "items can be starred"
+    }
+
+    fn up(&self, conn: &Connection) -> Result<()> {
+        ensure_column(conn, "items", "starred", "INTEGER NOT NULL DEFAULT 0")
+    }
+}
@@ -0,0 +1,19 @@
+use super::{ensure_column, Migration};
+use rusqlite::{Connection, Result};
+
+pub struct FeedValidators;
+
+impl Migration for FeedValidators {
+    fn version(&self) -> u32 {
+        3
+    }
+
+    fn description(&self) -> &'static str {
+        "feeds keep the ETag and Last-Modified of their last fetch"
+    }
+
+    fn up(&self, conn: &Connection) -> Result<()> {
+        ensure_column(conn, "feeds", "etag", "TEXT")?;
+        ensure_column(conn, "feeds", "last_modified", "TEXT")
+    }
+}
@@ -0,0 +1,18 @@
+use super::{ensure_column, Migration};
+use rusqlite::{Connection, Result};
+
+pub struct FeedFormat;
+
+impl Migration for FeedFormat {
+    fn version(&self) -> u32 {
+        4
+    }
+
+    fn description(&self) -> &'static str {
+        "feeds keep the format they were read in"
+    }
+
+    fn up(&self, conn: &Connection) -> Result<()> {
+        ensure_column(conn, "feeds", "format", "TEXT NOT NULL DEFAULT 'rss'")
+    }
+}
@@ -0,0 +1,23 @@
+use super::{ensure_column, Migration};
+use rusqlite::{Connection, Result};
+
+pub struct FeedErrors;
+
+impl Migration for FeedErrors {
+    fn version(&self) -> u32 {
+        5
+    }
+
+    fn description(&self) -> &'static str {
+        "feeds count the refreshes that failed in a row"
+    }
+
+    fn up(&self, conn: &Connection) -> Result<()> {
+        ensure_column(
+            conn,
+            "feeds",
+            "consecutive_errors",
+            "INTEGER NOT NULL DEFAULT 0",
+        )
+    }
+}
@@ -0,0 +1,52 @@
+use super::Migration;
+use rusqlite::{Connection, Result};
+
+/// Rebuilds `items` keyed on `(feed_id, id)` if it still has the older key on `id` alone,
+/// under which an item shared by two feeds could only belong to one of them.
+pub struct ItemKey;
+
+impl Migration for ItemKey {
+    fn version(&self) -> u32 {
+        6
+    }
+
+    fn description(&self) -> &'static str {
+        "items are keyed on their feed and id"
+    }
+
+    fn up(&self, conn: &Connection) -> Result<()> {
+        let key_columns: usize = conn.query_row(
+            "SELECT COUNT(*) FROM pragma_table_info('items') WHERE pk > 0",
+            [],
+            |row| row.get(0),
+        )?;
+
+        if key_columns == 1 {
+            conn.execute_batch(
+                "ALTER TABLE items RENAME TO items_old;
+                CREATE TABLE items (
+                    id TEXT NOT NULL,
+                    feed_id TEXT NOT NULL,
+                    title TEXT,
+                    author TEXT,
+                    content TEXT,
+                    description TEXT,
+                    text_description TEXT,
+                    categories TEXT,
+                    link TEXT,
+                    pub_date TEXT,
+                    read INTEGER NOT NULL DEFAULT 0,
+                    starred INTEGER NOT NULL DEFAULT 0,
+                    PRIMARY KEY (feed_id, id),
+                    FOREIGN KEY(feed_id) REFERENCES feeds(id) ON DELETE CASCADE
+                );
+                INSERT INTO items
+                    SELECT id, feed_id, title, author, content, description, text_description,
+                        categories, link, pub_date, read, starred
+                    FROM items_old;
+                DROP TABLE items_old;",
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,47 @@
+use super::{ensure_column, Migration};
+use crate::repo::storage::sqlite::parse_date;
+use rusqlite::{params, Connection, Result};
+
+/// Fills in `published_at` for items stored before it existed, from their `pub_date` text,
+/// and indexes it for the timeline.
+pub struct PublishedAt;
+
+impl Migration for PublishedAt {
+    fn version(&self) -> u32 {
+        7
+    }
+
+    fn description(&self) -> &'static str {
+        "items keep when they were published as a timestamp"
+    }
+
+    fn up(&self, conn: &Connection) -> Result<()> {
+        ensure_column(conn, "items", "published_at", "INTEGER")?;
+
+        let missing = conn
+            .prepare("SELECT feed_id, id, pub_date FROM items WHERE published_at IS NULL")?
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, Option<String>>(2)?,
+                ))
+            })?
+            .filter_map(|r| r.ok())
+            .filter_map(|(feed_id, id, pub_date)| {
+                let published_at = parse_date(pub_date.as_deref()?)?.timestamp();
+                Some((feed_id, id, published_at))
+            })
+            .collect::<Vec<_>>();
+
+        let mut stmt =
+            conn.prepare("UPDATE items SET published_at = ?3 WHERE feed_id = ?1 AND id = ?2")?;
+        for (feed_id, id, published_at) in missing {
+            stmt.execute(params![feed_id, id, published_at])?;
+        }
+
+        conn.execute_batch(
+            "CREATE INDEX IF NOT EXISTS items_published_at ON items(published_at DESC)",
+        )
+    }
+}
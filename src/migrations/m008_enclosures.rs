@@ -0,0 +1,21 @@
+use super::{ensure_column, Migration};
+use rusqlite::{Connection, Result};
+
+pub struct Enclosures;
+
+impl Migration for Enclosures {
+    fn version(&self) -> u32 {
+        8
+    }
+
+    fn description(&self) -> &'static str {
+        "items keep their enclosure and duration"
+    }
+
+    fn up(&self, conn: &Connection) -> Result<()> {
+        ensure_column(conn, "items", "enclosure_url", "TEXT")?;
+        ensure_column(conn, "items", "enclosure_length", "INTEGER")?;
+        ensure_column(conn, "items", "enclosure_type", "TEXT")?;
+        ensure_column(conn, "items", "duration", "TEXT")
+    }
+}
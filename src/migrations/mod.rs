@@ -0,0 +1,244 @@
+//! Numbered changes to the SQLite schema, each run once on databases older than it.
+//!
+//! Databases from before versioning start at version 0 whatever their schema, and new ones
+//! are created with the latest schema before any migration runs, so every migration must
+//! leave a database that already has its change as it is.
+
+mod m001_items_read;
+mod m002_items_starred;
+mod m003_feed_validators;
+mod m004_feed_format;
+mod m005_feed_errors;
+mod m006_item_key;
+mod m007_published_at;
+mod m008_enclosures;
+
+use rusqlite::{Connection, OptionalExtension, Result};
+
+pub trait Migration {
+    /// The version the database is at once this has run, one more than the last.
+    fn version(&self) -> u32;
+    fn description(&self) -> &'static str;
+    fn up(&self, conn: &Connection) -> Result<()>;
+}
+
+/// Every migration, in order.
+fn all() -> Vec<Box<dyn Migration>> {
+    vec![
+        Box::new(m001_items_read::ItemsRead),
+        Box::new(m002_items_starred::ItemsStarred),
+        Box::new(m003_feed_validators::FeedValidators),
+        Box::new(m004_feed_format::FeedFormat),
+        Box::new(m005_feed_errors::FeedErrors),
+        Box::new(m006_item_key::ItemKey),
+        Box::new(m007_published_at::PublishedAt),
+        Box::new(m008_enclosures::Enclosures),
+    ]
+}
+
+/// Brings a database up to the latest version, keeping the version it is at in `_meta`.
+pub struct MigrationRunner<'a> {
+    conn: &'a Connection,
+    migrations: Vec<Box<dyn Migration>>,
+}
+
+impl<'a> MigrationRunner<'a> {
+    pub fn new(conn: &'a Connection) -> Self {
+        Self {
+            conn,
+            migrations: all(),
+        }
+    }
+
+    /// The version the database is at, 0 if it has never been migrated.
+    pub fn current_version(&self) -> Result<u32> {
+        self.conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS _meta (
+                key TEXT NOT NULL PRIMARY KEY,
+                value TEXT NOT NULL
+            )",
+        )?;
+        let version = self
+            .conn
+            .query_row(
+                "SELECT value FROM _meta WHERE key = 'schema_version'",
+                [],
+                |row| row.get::<_, String>(0),
+            )
+            .optional()?;
+        Ok(version.and_then(|v| v.parse().ok()).unwrap_or(0))
+    }
+
+    /// Runs the migrations newer than the database in order, each in a transaction with the
+    /// version it brings the database to, and returns the version it ends at.
+    pub fn run(&self) -> Result<u32> {
+        let mut version = self.current_version()?;
+        for migration in &self.migrations {
+            if migration.version() <= version {
+                continue;
+            }
+            log::info!(
+                "Migrating DB to version {}: {}",
+                migration.version(),
+                migration.description()
+            );
+            let tx = self.conn.unchecked_transaction()?;
+            migration.up(&tx)?;
+            tx.execute(
+                "INSERT INTO _meta(key, value) VALUES('schema_version', ?1)
+                ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+                [migration.version().to_string()],
+            )?;
+            tx.commit()?;
+            version = migration.version();
+        }
+        Ok(version)
+    }
+}
+
+/// Adds `column` to `table` unless it is already there.
+fn ensure_column(conn: &Connection, table: &str, column: &str, definition: &str) -> Result<()> {
+    let exists = conn
+        .prepare(&format!(
+            "SELECT 1 FROM pragma_table_info('{}') WHERE name = ?1",
+            table
+        ))?
+        .exists([column])?;
+
+    if !exists {
+        conn.execute_batch(&format!(
+            "ALTER TABLE {} ADD COLUMN {} {}",
+            table, column, definition
+        ))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The schema of databases from before versioning, at version 0.
+    const BASELINE_SCHEMA: &str = "
+        CREATE TABLE feeds (
+            id TEXT NOT NULL PRIMARY KEY,
+            title TEXT NOT NULL,
+            description TEXT NOT NULL,
+            categories TEXT NOT NULL,
+            url TEXT NOT NULL,
+            link TEXT NOT NULL,
+            ttl TEXT,
+            pub_date TEXT,
+            last_fetched TEXT
+        );
+        CREATE TABLE items (
+            id TEXT NOT NULL PRIMARY KEY,
+            feed_id TEXT NOT NULL,
+            title TEXT,
+            author TEXT,
+            content TEXT,
+            description TEXT,
+            text_description TEXT,
+            categories TEXT,
+            link TEXT,
+            pub_date TEXT,
+            FOREIGN KEY(feed_id) REFERENCES feeds(id) ON DELETE CASCADE
+        );";
+
+    const LATEST_SCHEMA: &str = include_str!("../repo/storage/schema.sql");
+
+    fn database(schema: &str) -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(schema).unwrap();
+        conn
+    }
+
+    fn columns(conn: &Connection, table: &str) -> Vec<String> {
+        let mut columns = conn
+            .prepare(&format!("SELECT name FROM pragma_table_info('{}')", table))
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<Vec<String>>>()
+            .unwrap();
+        columns.sort();
+        columns
+    }
+
+    #[test]
+    fn versions_count_up_from_one() {
+        let versions = all().iter().map(|m| m.version()).collect::<Vec<_>>();
+        assert_eq!(versions, (1..=versions.len() as u32).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn every_migration_can_run_twice() {
+        for schema in [BASELINE_SCHEMA, LATEST_SCHEMA] {
+            let conn = database(schema);
+            for migration in all() {
+                migration.up(&conn).unwrap();
+                migration.up(&conn).unwrap_or_else(|err| {
+                    panic!("migration {} failed again: {}", migration.version(), err)
+                });
+            }
+        }
+    }
+
+    #[test]
+    fn leaves_the_latest_schema_as_it_is() {
+        let conn = database(LATEST_SCHEMA);
+        let before = (columns(&conn, "feeds"), columns(&conn, "items"));
+
+        assert_eq!(
+            MigrationRunner::new(&conn).run().unwrap(),
+            all().len() as u32
+        );
+
+        assert_eq!((columns(&conn, "feeds"), columns(&conn, "items")), before);
+    }
+
+    #[test]
+    fn upgrades_a_baseline_database() {
+        let conn = database(BASELINE_SCHEMA);
+        conn.execute_batch(
+            "INSERT INTO feeds(id, title, description, categories, url, link)
+                VALUES('feed', 'Feed', '', '[]', 'https://example.com/feed.xml', '');
+            INSERT INTO items(id, feed_id, title, pub_date)
+                VALUES('item', 'feed', 'Item', 'Mon, 02 Jan 2023 10:00:00 +0000');",
+        )
+        .unwrap();
+
+        let runner = MigrationRunner::new(&conn);
+        assert_eq!(runner.current_version().unwrap(), 0);
+        assert_eq!(runner.run().unwrap(), all().len() as u32);
+        assert_eq!(runner.current_version().unwrap(), all().len() as u32);
+
+        let latest = database(LATEST_SCHEMA);
+        assert_eq!(columns(&conn, "feeds"), columns(&latest, "feeds"));
+        assert_eq!(columns(&conn, "items"), columns(&latest, "items"));
+
+        let key_columns: usize = conn
+            .query_row(
+                "SELECT COUNT(*) FROM pragma_table_info('items') WHERE pk > 0",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(key_columns, 2);
+
+        let (title, read, starred, published_at): (String, bool, bool, i64) = conn
+            .query_row(
+                "SELECT title, read, starred, published_at FROM items
+                WHERE feed_id = 'feed' AND id = 'item'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+            )
+            .unwrap();
+        assert_eq!(title, "Item");
+        assert!(!read && !starred);
+        assert_eq!(published_at, 1672653600);
+
+        // already at the latest version, so nothing runs again
+        assert_eq!(runner.run().unwrap(), all().len() as u32);
+    }
+}
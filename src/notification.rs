@@ -0,0 +1,56 @@
+//! Desktop notifications, on the platforms `notify-rust` supports. Elsewhere they are
+//! dropped, and `SUPPORTED` is false so a warning can be shown instead.
+
+/// Whether desktop notifications can be sent on this platform.
+pub const SUPPORTED: bool = cfg!(any(
+    target_os = "linux",
+    target_os = "freebsd",
+    target_os = "openbsd",
+    target_os = "netbsd",
+    target_os = "dragonfly",
+    target_os = "macos",
+    windows
+));
+
+/// Shows a notification from a thread of its own, so a slow notification daemon doesn't
+/// hold up the caller. Failures are only logged.
+#[cfg(any(
+    target_os = "linux",
+    target_os = "freebsd",
+    target_os = "openbsd",
+    target_os = "netbsd",
+    target_os = "dragonfly",
+    target_os = "macos",
+    windows
+))]
+pub fn send(summary: &str, body: &str) {
+    let (summary, body) = (summary.to_owned(), body.to_owned());
+    std::thread::spawn(move || {
+        let shown = notify_rust::Notification::new()
+            .appname("moccasin")
+            .summary(&summary)
+            .body(&body)
+            .show();
+        if let Err(err) = shown {
+            log::warn!("Failed to send notification: {}", err);
+        }
+    });
+}
+
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "freebsd",
+    target_os = "openbsd",
+    target_os = "netbsd",
+    target_os = "dragonfly",
+    target_os = "macos",
+    windows
+)))]
+pub fn send(_summary: &str, _body: &str) {}
+
+/// Logs a warning when notifications are turned on but can't be sent here.
+pub fn warn_if_unsupported(enabled: bool) {
+    if enabled && !SUPPORTED {
+        tracing::warn!("Desktop notifications are not supported on this platform");
+    }
+}
@@ -0,0 +1,112 @@
+use anyhow::{anyhow, bail, Result};
+use chrono::Local;
+use quick_xml::encoding::Decoder;
+use quick_xml::events::{BytesDecl, BytesStart, BytesText, Event};
+use quick_xml::{Reader, Writer};
+
+/// Reads every feed subscription out of an OPML document.
+///
+/// Category outlines are flattened, so feeds nested at any depth are returned
+/// in document order. Outlines without an `xmlUrl`, or with a `type` other than
+/// `rss`, are skipped.
+pub fn read_feed_urls(contents: &str) -> Result<Vec<String>> {
+    let mut reader = Reader::from_str(contents);
+    reader.config_mut().trim_text(true);
+
+    let mut urls = vec![];
+    let mut seen_root = false;
+
+    loop {
+        let event = reader.read_event().map_err(|err| {
+            anyhow!(
+                "malformed OPML near byte {}: {}",
+                reader.buffer_position(),
+                err
+            )
+        })?;
+
+        match event {
+            Event::Start(el) | Event::Empty(el) => match el.local_name().as_ref() {
+                b"opml" => seen_root = true,
+                b"outline" if seen_root => {
+                    if let Some(url) = feed_url_of(&el, reader.decoder())? {
+                        urls.push(url);
+                    }
+                }
+                _ if !seen_root => bail!("not an OPML document: missing <opml> root"),
+                _ => {}
+            },
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    if !seen_root {
+        bail!("not an OPML document: missing <opml> root");
+    }
+
+    Ok(urls)
+}
+
+fn feed_url_of(el: &BytesStart, decoder: Decoder) -> Result<Option<String>> {
+    let mut kind = None;
+    let mut url = None;
+
+    for attr in el.attributes() {
+        let attr = attr.map_err(|err| anyhow!("malformed <outline> attribute: {}", err))?;
+        let value = attr
+            .decode_and_unescape_value(decoder)
+            .map_err(|err| anyhow!("malformed <outline> attribute: {}", err))?;
+        match attr.key.local_name().as_ref() {
+            b"type" => kind = Some(value.into_owned()),
+            b"xmlUrl" => url = Some(value.trim().to_owned()),
+            _ => {}
+        }
+    }
+
+    let is_feed = kind.is_none_or(|k| k.eq_ignore_ascii_case("rss"));
+    Ok(url.filter(|u| is_feed && !u.is_empty()))
+}
+
+/// Serializes `(title, xml_url)` pairs into an OPML 2.0 document, in the order given.
+pub fn write_document(outlines: &[(String, String)]) -> Result<String> {
+    let mut writer = Writer::new_with_indent(Vec::new(), b' ', 2);
+    let created = Local::now().to_rfc2822();
+
+    writer.write_event(Event::Decl(BytesDecl::new("1.0", Some("UTF-8"), None)))?;
+    writer
+        .create_element("opml")
+        .with_attribute(("version", "2.0"))
+        .write_inner_content::<_, quick_xml::Error>(|writer| {
+            writer
+                .create_element("head")
+                .write_inner_content::<_, quick_xml::Error>(|writer| {
+                    writer
+                        .create_element("title")
+                        .write_text_content(BytesText::new("moccasin subscriptions"))?;
+                    writer
+                        .create_element("dateCreated")
+                        .write_text_content(BytesText::new(&created))?;
+                    Ok(())
+                })?;
+            writer
+                .create_element("body")
+                .write_inner_content::<_, quick_xml::Error>(|writer| {
+                    for (title, url) in outlines {
+                        writer
+                            .create_element("outline")
+                            .with_attributes([
+                                ("type", "rss"),
+                                ("text", title.as_str()),
+                                ("title", title.as_str()),
+                                ("xmlUrl", url.as_str()),
+                            ])
+                            .write_empty()?;
+                    }
+                    Ok(())
+                })?;
+            Ok(())
+        })?;
+
+    Ok(String::from_utf8(writer.into_inner())?)
+}
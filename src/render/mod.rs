@@ -0,0 +1,5 @@
+//! Turns item content into text for the interface.
+
+pub mod text;
+
+pub use text::{html_to_lines, html_to_text};
@@ -0,0 +1,336 @@
+use html_escape::decode_html_entities as decode;
+use html_parser::{Dom, Element, Node};
+use std::mem;
+use tui::style::{Modifier, Style};
+use tui::text::{Line, Span};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// A run of text in one style.
+type Fragment = (String, Style);
+
+/// The plain text of `html`, with blank lines between paragraphs, lists bulleted and links
+/// followed by where they point.
+pub fn html_to_text(html: &str) -> String {
+    render(html)
+        .iter()
+        .map(|line| {
+            line.iter()
+                .map(|(text, _)| text.as_str())
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// `html` as lines wrapped to `available_width`, with bold and italic text styled rather than
+/// marked up.
+pub fn html_to_lines(html: &str, available_width: u16) -> Vec<Line<'static>> {
+    render(html)
+        .into_iter()
+        .flat_map(|line| wrap(line, available_width as usize))
+        .collect()
+}
+
+fn render(html: &str) -> Vec<Vec<Fragment>> {
+    // descriptions stored before they were kept as HTML are already text
+    if !html.contains('<') {
+        return html
+            .lines()
+            .map(|line| vec![(line.trim_end().to_owned(), Style::default())])
+            .collect();
+    }
+
+    let mut writer = TextWriter::default();
+    match Dom::parse(html) {
+        Ok(dom) => writer.nodes(&dom.children),
+        Err(_) => {
+            for paragraph in strip_tags(html).split("\n\n") {
+                writer.text(paragraph);
+                writer.paragraph_break();
+            }
+        }
+    }
+    writer.finish()
+}
+
+/// Collects the text of a document line by line, following the layout of its elements.
+#[derive(Default)]
+struct TextWriter {
+    lines: Vec<Vec<Fragment>>,
+    current: Vec<Fragment>,
+    style: Style,
+    preformatted: bool,
+}
+
+impl TextWriter {
+    fn nodes(&mut self, nodes: &[Node]) {
+        for node in nodes {
+            match node {
+                Node::Text(text) => self.text(text),
+                Node::Element(el) => self.element(el),
+                Node::Comment(_) => {}
+            }
+        }
+    }
+
+    fn element(&mut self, el: &Element) {
+        match el.name.to_ascii_lowercase().as_str() {
+            "script" | "style" | "head" | "img" => {}
+            "br" => self.line_break(),
+            "hr" => self.paragraph_break(),
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                self.paragraph_break();
+                self.styled(Modifier::BOLD, &el.children);
+                self.paragraph_break();
+            }
+            "p" | "div" | "blockquote" | "section" | "article" | "figure" | "table" => {
+                self.paragraph_break();
+                self.nodes(&el.children);
+                self.paragraph_break();
+            }
+            "tr" => {
+                self.line_break();
+                self.nodes(&el.children);
+                self.line_break();
+            }
+            "td" | "th" => {
+                self.nodes(&el.children);
+                self.push(" ");
+            }
+            "pre" => {
+                self.paragraph_break();
+                self.preformatted = true;
+                self.nodes(&el.children);
+                self.preformatted = false;
+                self.paragraph_break();
+            }
+            "strong" | "b" => self.styled(Modifier::BOLD, &el.children),
+            "em" | "i" => self.styled(Modifier::ITALIC, &el.children),
+            "u" => self.styled(Modifier::UNDERLINED, &el.children),
+            "ul" | "ol" => {
+                self.paragraph_break();
+                let ordered = el.name.eq_ignore_ascii_case("ol");
+                let items = el.children.iter().filter_map(|node| match node {
+                    Node::Element(li) if li.name.eq_ignore_ascii_case("li") => Some(li),
+                    _ => None,
+                });
+                for (i, li) in items.enumerate() {
+                    self.line_break();
+                    if ordered {
+                        self.push(&format!("{}. ", i + 1));
+                    } else {
+                        self.push("- ");
+                    }
+                    self.nodes(&li.children);
+                    self.line_break();
+                }
+                self.paragraph_break();
+            }
+            "a" => {
+                self.nodes(&el.children);
+                let href = el.attributes.get("href").cloned().flatten();
+                if let Some(href) = href.filter(|href| !href.starts_with('#')) {
+                    let style = self.style;
+                    self.style = style.add_modifier(Modifier::DIM);
+                    self.push(&format!(" ({})", href));
+                    self.style = style;
+                }
+            }
+            _ => self.nodes(&el.children),
+        }
+    }
+
+    fn styled(&mut self, modifier: Modifier, nodes: &[Node]) {
+        let style = self.style;
+        self.style = style.add_modifier(modifier);
+        self.nodes(nodes);
+        self.style = style;
+    }
+
+    fn text(&mut self, text: &str) {
+        // the parser leaves tags it couldn't match in the text
+        let text = decode(&strip_tags(text)).into_owned();
+        if self.preformatted {
+            for (i, line) in text.split('\n').enumerate() {
+                if i > 0 {
+                    self.line_break();
+                }
+                self.push(line);
+            }
+            return;
+        }
+
+        // runs of whitespace collapse to a single space, as in a browser
+        let words = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        let mut collapsed = String::new();
+        if text.starts_with(char::is_whitespace) && !self.after_space() {
+            collapsed.push(' ');
+        }
+        collapsed.push_str(&words);
+        if !words.is_empty() && text.ends_with(char::is_whitespace) {
+            collapsed.push(' ');
+        }
+        self.push(&collapsed);
+    }
+
+    fn push(&mut self, text: &str) {
+        if !text.is_empty() {
+            self.current.push((text.to_owned(), self.style));
+        }
+    }
+
+    /// Whether the current line is empty or ends in a space, so a space would be doubled.
+    fn after_space(&self) -> bool {
+        self.current
+            .last()
+            .is_none_or(|(text, _)| text.ends_with(' '))
+    }
+
+    fn line_break(&mut self) {
+        let mut line = mem::take(&mut self.current);
+        if let Some((text, _)) = line.last_mut() {
+            text.truncate(text.trim_end().len());
+        }
+        if !self.preformatted {
+            if let Some((text, _)) = line.first_mut() {
+                *text = text.trim_start().to_owned();
+            }
+        }
+        line.retain(|(text, _)| !text.is_empty());
+        // only breaks inside a block start an empty line
+        if !line.is_empty() || self.preformatted {
+            self.lines.push(line);
+        }
+    }
+
+    /// Ends the current line, and leaves one blank line before whatever comes next.
+    fn paragraph_break(&mut self) {
+        self.line_break();
+        if self.lines.last().is_some_and(|line| !line.is_empty()) {
+            self.lines.push(vec![]);
+        }
+    }
+
+    fn finish(mut self) -> Vec<Vec<Fragment>> {
+        self.line_break();
+        while self.lines.last().is_some_and(Vec::is_empty) {
+            self.lines.pop();
+        }
+        self.lines
+    }
+}
+
+/// What's left of `html` without its tags, for markup too broken to parse. A `<` that doesn't
+/// open a tag, as in `a < b`, is kept.
+fn strip_tags(html: &str) -> String {
+    let mut text = String::new();
+    let mut chars = html.chars().peekable();
+    let mut in_tag = false;
+    while let Some(c) = chars.next() {
+        match c {
+            '<' if !in_tag
+                && chars.peek().is_some_and(|next| {
+                    next.is_ascii_alphabetic() || matches!(next, '/' | '!')
+                }) =>
+            {
+                in_tag = true
+            }
+            '>' if in_tag => in_tag = false,
+            c if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    text
+}
+
+/// The byte index where `word` passes `width` columns, after at least its first character.
+fn split_at_width(word: &str, width: usize) -> usize {
+    let mut used = 0;
+    word.char_indices()
+        .find(|(i, c)| {
+            used += c.width().unwrap_or(0);
+            *i > 0 && used > width
+        })
+        .map_or(word.len(), |(i, _)| i)
+}
+
+/// Breaks a line into lines no wider than `width`, between words where it can.
+fn wrap(line: Vec<Fragment>, width: usize) -> Vec<Line<'static>> {
+    if width == 0 {
+        return vec![Line::from(
+            line.into_iter()
+                .map(|(text, style)| Span::styled(text, style))
+                .collect::<Vec<_>>(),
+        )];
+    }
+
+    let mut lines = vec![];
+    let mut spans = vec![];
+    let mut used = 0;
+    for (text, style) in line {
+        for word in text.split_inclusive(' ') {
+            let mut word = word;
+            if used > 0 && used + word.trim_end().width() > width {
+                lines.push(Line::from(mem::take(&mut spans)));
+                used = 0;
+            }
+            // a continued line doesn't start with the space it broke at
+            if used == 0 && !lines.is_empty() {
+                word = word.trim_start();
+            }
+            // a word wider than the line is split across lines
+            while word.trim_end().width() > width {
+                let split = split_at_width(word, width);
+                spans.push(Span::styled(word[..split].to_owned(), style));
+                lines.push(Line::from(mem::take(&mut spans)));
+                word = &word[split..];
+            }
+            if !word.is_empty() {
+                used += word.width();
+                spans.push(Span::styled(word.to_owned(), style));
+            }
+        }
+    }
+    if !spans.is_empty() || lines.is_empty() {
+        lines.push(Line::from(spans));
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(html: &str, width: u16) -> Vec<String> {
+        html_to_lines(html, width)
+            .iter()
+            .map(|line| line.spans.iter().map(|span| &*span.content).collect())
+            .collect()
+    }
+
+    #[test]
+    fn wraps_double_width_text_at_odd_widths() {
+        assert_eq!(
+            texts("日本語テキスト", 5),
+            vec!["日本", "語テ", "キス", "ト"]
+        );
+        assert_eq!(texts("日本語テキスト", 7), vec!["日本語", "テキス", "ト"]);
+        for width in 2..16 {
+            for line in html_to_lines("<p>日本語のテキストを折り返す</p>", width) {
+                assert!(line.width() <= width as usize, "width {width}: {line:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn keeps_a_double_width_character_wider_than_the_line() {
+        assert_eq!(texts("日本", 1), vec!["日", "本"]);
+    }
+
+    #[test]
+    fn wraps_mixed_width_words_at_odd_widths() {
+        let lines = texts("abc 日本語 def", 5);
+        let lines: Vec<_> = lines.iter().map(|line| line.trim_end()).collect();
+        assert_eq!(lines, vec!["abc", "日本", "語", "def"]);
+    }
+}
@@ -1,16 +1,72 @@
-pub mod storage;
 mod repo;
+pub mod storage;
 
-use crate::feed::Feed;
-pub use repo::Repository;
+use crate::config::ItemSortOrder;
+use crate::feed::{Feed, ItemSummary};
+use crate::sync::greader::StreamItem;
+use crate::sync::RemoteItem;
+use crate::websubscriber::Subscription;
+pub use repo::{default_backup_path, Repository};
+use std::sync::{Arc, Mutex, MutexGuard};
 
 #[derive(Clone, Debug)]
 pub enum RepositoryEvent {
     Refresh,
     RetrievedAll(Vec<Feed>),
-    RetrievedOne(Feed),
+    RetrievedOne(Box<Feed>),
+    Discovered(Vec<String>),
+    /// A URL checked for the setup wizard, and why it could not be reached, if it couldn't.
+    FeedUrlChecked(String, Option<String>),
+    Pruned(usize),
+    /// The feeds that refreshed and those that failed to, in that order.
+    FetchResults(Vec<String>, Vec<String>),
     Requesting(usize),
     Requested((usize, usize)),
+    FetchTimedOut(String),
+    /// A feed that failed to refresh, the number of attempts, and why the last one failed.
+    FetchFailed(String, u8, String),
+    /// Asks for the item summaries of a feed from an offset, with a limit, in an order.
+    RequestItemsPage(String, usize, usize, ItemSortOrder),
+    /// A page of a feed's item summaries, the offset it starts at, and the order it is in.
+    ItemsPage(String, usize, ItemSortOrder, Vec<ItemSummary>),
+    /// The config file, or the file of the current profile, changed on disk.
+    ConfigChanged,
+    /// The links of the items starred on the sync server.
+    StarredSynced(Vec<String>),
+    /// Items changed on a Nextcloud News server, to be stored.
+    RemoteItemsPulled(Vec<RemoteItem>),
+    /// Items new to a GReader server's reading list, to be matched by link and stored.
+    StreamItemsPulled(Vec<StreamItem>),
+    /// An item, by feed id and guid, marked read other than by the interface, as through the
+    /// API.
+    ItemRead(String, String),
+    /// The guids of the items read, and of those starred, on the sync server.
+    SyncedItems(Vec<String>, Vec<String>),
+    /// Why reading state could not be synced.
+    SyncFailed(String),
+    /// A hub confirmed a subscription, to be stored.
+    WebSubVerified(Subscription),
+    /// A feed's update, as a hub pushed it to storage, then as stored to the app.
+    Pushed(Box<Feed>),
     Errored,
     Aborted,
 }
+
+/// A repository shared between the interface and the API server.
+#[derive(Clone, Debug)]
+pub struct SharedRepository(Arc<Mutex<Repository>>);
+
+impl SharedRepository {
+    pub fn new(repo: Repository) -> Self {
+        Self(Arc::new(Mutex::new(repo)))
+    }
+
+    pub fn lock(&self) -> MutexGuard<'_, Repository> {
+        self.0.lock().expect("repository lock poisoned")
+    }
+
+    /// Swaps in `repo`, as when switching profiles, for everything sharing this one.
+    pub fn replace(&self, repo: Repository) {
+        *self.lock() = repo;
+    }
+}
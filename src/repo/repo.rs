@@ -1,105 +1,414 @@
-use super::RepositoryEvent;
 use super::storage::sqlite::SQLiteStorage;
-use crate::config::Config;
-use crate::feed::Feed;
-use crate::repo::storage::{StorageError, StorageEvent};
+use super::RepositoryEvent;
+use crate::config::{Config, ItemSortOrder, SyncBackend};
+use crate::feed::{Feed, Item, ItemSummary, QueuedItem, SearchResult, TimelineItem};
+use crate::repo::storage::{
+    FeedStats, IntegrityReport, Storage, StorageError, StorageEvent, Validators,
+};
 use crate::report;
-use crate::util::sort_feeds;
-use anyhow::Result;
-use std::fmt::Debug;
+use crate::sync::greader::{GReaderClient, StreamItem};
+use crate::sync::miniflux::MinifluxClient;
+use crate::sync::nextcloud::NextcloudNewsClient;
+use crate::sync::{ItemState, RemoteItem};
+use crate::util::{read_body, sort_feeds};
+use crate::websubscriber::{self, WebSubManager};
+use anyhow::{anyhow, bail, Context, Result};
+use futures::future::BoxFuture;
+use futures::StreamExt;
+use rand::Rng;
+use reqwest::header::{
+    HeaderName, CONTENT_TYPE, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED, LINK,
+    USER_AGENT,
+};
+use reqwest::redirect::Policy;
+use reqwest::StatusCode;
+use std::collections::{HashMap, VecDeque};
+use std::error::Error;
+use std::fmt::{self, Debug};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, PoisonError};
 use std::task::Poll;
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tokio::{
     sync::mpsc::{self, UnboundedSender},
     task::JoinHandle,
 };
+use tracing::Instrument;
+
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(500);
+const RETRY_MAX_DELAY: Duration = Duration::from_secs(30);
+/// How often to look for feeds due for a refresh.
+const REFRESH_CHECK_RATE: Duration = Duration::from_secs(1);
 
 #[derive(Debug)]
 enum FetchErr {
-    Request,
+    Request(reqwest::Error),
+    Status(StatusCode),
     Deserialize,
-    Parse,
+    /// The response isn't a feed, with the parser's reason.
+    Parse(String),
+    /// The response is longer than the limit, in bytes.
+    ResponseTooLarge(usize),
 }
 
-pub struct Repository {
-    storage: SQLiteStorage,
+impl fmt::Display for FetchErr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FetchErr::Request(err) => f.write_str(&describe_request_error(err)),
+            FetchErr::Status(status) => write!(f, "HTTP {}", status),
+            FetchErr::Deserialize => f.write_str("Failed to read the response"),
+            FetchErr::Parse(reason) => f.write_str(reason),
+            FetchErr::ResponseTooLarge(limit) => {
+                write!(f, "Response larger than {} bytes", limit)
+            }
+        }
+    }
+}
+
+pub struct Repository<S: Storage = SQLiteStorage> {
+    storage: S,
     app_tx: mpsc::UnboundedSender<RepositoryEvent>,
     storage_tx: mpsc::UnboundedSender<RepositoryEvent>,
     storage_rx: mpsc::UnboundedReceiver<RepositoryEvent>,
     handle_one: Option<JoinHandle<()>>,
     handle_many: Option<JoinHandle<()>>,
+    last_refreshed: HashMap<String, Instant>,
+    /// The ids a Nextcloud News server gives the items it shares with storage, by guid.
+    guid_to_remote_id: HashMap<String, u64>,
+    websub: Option<WebSubManager>,
+    /// Whether starting `websub` was tried, which waits for the first tick so the listener
+    /// of a repository being replaced has let go of the port.
+    websub_started: bool,
+    /// What checking the database at startup found.
+    integrity: IntegrityReport,
 }
 
-impl Debug for Repository {
+impl<S: Storage> Debug for Repository<S> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.write_str("Database {}")
     }
 }
 
-impl Repository {
+impl<S: Storage> Drop for Repository<S> {
+    fn drop(&mut self) {
+        for handle in [&self.handle_one, &self.handle_many].into_iter().flatten() {
+            handle.abort();
+        }
+    }
+}
+
+impl<S: Storage> Repository<S> {
     pub fn init(config: &Config, app_tx: UnboundedSender<RepositoryEvent>) -> Result<Self> {
-        let storage = SQLiteStorage::init(config);
+        if config.should_cache() {
+            if let Some(dir) = config.db_path().parent() {
+                create_private_dir(dir)
+                    .with_context(|| format!("could not create {}", dir.display()))?;
+            }
+        }
+        let storage = S::init(config);
+        let integrity = storage
+            .check_integrity(config.check_db())
+            .map_err(|_| anyhow!("could not check the database"))?;
+        for repair in integrity.repaired() {
+            log::warn!("Repaired database: {}", repair);
+        }
+        for warning in integrity.warnings() {
+            log::warn!("Database check: {}", warning);
+        }
+        if !integrity.ok() {
+            let problems = integrity.warnings();
+            let more = match problems.len() {
+                0 | 1 => String::new(),
+                n => format!(" (and {} more problems)", n - 1),
+            };
+            bail!(
+                "the database at {} is damaged: {}{}. Move it aside to start over with an \
+                empty one, or put back a backup",
+                config.db_path().display(),
+                problems.first().map_or("unknown damage", String::as_str),
+                more
+            );
+        }
+        let guid_to_remote_id = storage.read_remote_ids().unwrap_or_default();
 
         let (storage_tx, storage_rx) = mpsc::unbounded_channel::<RepositoryEvent>();
 
-        if config.refresh_interval() > 0 {
-            let tick_rate = Duration::from_secs(config.refresh_interval());
+        if config.refreshes_automatically() {
             let tx = storage_tx.clone();
-            thread::spawn(move || loop {
-                tx.send(RepositoryEvent::Refresh)
-                    .expect("Failed to send storage message");
-                thread::sleep(tick_rate);
+            // stops once the repository is dropped
+            thread::spawn(move || {
+                while tx.send(RepositoryEvent::Refresh).is_ok() {
+                    thread::sleep(REFRESH_CHECK_RATE);
+                }
             });
         }
 
         Ok(Self {
             storage,
+            integrity,
             app_tx,
             storage_tx,
             storage_rx,
             handle_one: None,
             handle_many: None,
+            last_refreshed: HashMap::new(),
+            guid_to_remote_id,
+            websub: None,
+            websub_started: false,
         })
     }
 
+    /// Writes a consistent copy of the database to `dest`, replacing what is there only once
+    /// the copy is whole.
+    pub fn backup(&self, dest: &Path) -> Result<()> {
+        let partial = partial_path(dest);
+        let _ = fs::remove_file(&partial);
+        self.storage
+            .backup(&partial)
+            .map_err(|_| anyhow!("could not back up the database"))?;
+        fs::rename(&partial, dest).with_context(|| format!("could not write {}", dest.display()))
+    }
+
+    /// Replaces the database of `config` with the backup at `src`. Nothing may have the
+    /// database open meanwhile.
+    pub fn restore(config: &Config, src: &Path) -> Result<()> {
+        if !config.should_cache() {
+            bail!("feeds are not cached, so there is no database to restore");
+        }
+        if !S::is_valid_backup(src) {
+            bail!(
+                "{} is not a moccasin database, or is damaged",
+                src.display()
+            );
+        }
+        let dest = config.db_path();
+        if let Some(dir) = dest.parent() {
+            create_private_dir(dir)
+                .with_context(|| format!("could not create {}", dir.display()))?;
+        }
+        let partial = partial_path(&dest);
+        fs::copy(src, &partial).with_context(|| format!("could not read {}", src.display()))?;
+        fs::rename(&partial, &dest).with_context(|| format!("could not write {}", dest.display()))
+    }
+
+    /// Checks the database as at startup, repairing what can be.
+    pub fn check_integrity(&self, thorough: bool) -> Result<IntegrityReport, StorageError> {
+        self.storage.check_integrity(thorough)
+    }
+
+    /// What checking the database at startup found.
+    pub fn integrity_report(&self) -> &IntegrityReport {
+        &self.integrity
+    }
+
     pub fn tick(&mut self, config: &Config) {
+        if !self.websub_started {
+            self.websub_started = true;
+            self.start_websub(config);
+        }
+
         let waker = futures::task::noop_waker();
         let mut cx = std::task::Context::from_waker(&waker);
 
-        match self.storage_rx.poll_recv(&mut cx) {
-            Poll::Ready(m) => match m {
-                Some(RepositoryEvent::RetrievedAll(feeds)) => {
-                    report!(self.storage.write_feeds(&feeds), "Failed to write feeds");
-                    self.app_tx
-                        .send(RepositoryEvent::RetrievedAll(feeds))
-                        .expect("Failed to send app message");
-                    self.handle_many = None;
+        if let Poll::Ready(Some(event)) = self.storage_rx.poll_recv(&mut cx) {
+            self.store(event, config);
+        }
+    }
+
+    /// Stores everything waiting to be stored, as when quitting, without starting a refresh.
+    pub fn flush(&mut self, config: &Config) {
+        let mut count = 0;
+        while let Ok(event) = self.storage_rx.try_recv() {
+            if !matches!(event, RepositoryEvent::Refresh) {
+                self.store(event, config);
+                count += 1;
+            }
+        }
+        log::debug!("Flushed {} pending writes", count);
+    }
+
+    fn store(&mut self, event: RepositoryEvent, config: &Config) {
+        match event {
+            RepositoryEvent::RetrievedAll(mut feeds) => {
+                self.store_all(&feeds);
+                report!(
+                    self.storage.load_item_state(&mut feeds),
+                    "Failed to read item state"
+                );
+
+                // feeds missing from a refresh (unchanged since the last fetch, or failed)
+                // keep their cached copy
+                for url in config.feed_urls() {
+                    if feeds.iter().any(|feed| feed.url() == url) {
+                        continue;
+                    }
+                    if let Ok(Some(feed)) = self.storage.read_feed_with_url(url) {
+                        feeds.push(feed);
+                    }
+                }
+
+                match self.prune_old_items(config, &feeds) {
+                    Ok(0) => {}
+                    Ok(count) => self
+                        .app_tx
+                        .send(RepositoryEvent::Pruned(count))
+                        .expect("Failed to send app message"),
+                    Err(_) => log::error!("Failed to prune old items"),
                 }
-                Some(RepositoryEvent::RetrievedOne(feed)) => {
-                    report!(self.storage.write_feed(&feed, None), "Failed to write feed");
+
+                // read back without the HTML, which only the detail view needs, and with
+                // items in the order item pages list them
+                let mut feeds = feeds
+                    .into_iter()
+                    .map(
+                        |mut feed| match self.storage.read_feed_with_url(feed.url()) {
+                            Ok(Some(stored)) => stored,
+                            _ => {
+                                feed.drop_item_markup();
+                                feed
+                            }
+                        },
+                    )
+                    .collect::<Vec<_>>();
+                sort_feeds(&mut feeds, config);
+                self.app_tx
+                    .send(RepositoryEvent::RetrievedAll(feeds))
+                    .expect("Failed to send app message");
+                self.handle_many = None;
+            }
+            RepositoryEvent::RetrievedOne(mut feed) => {
+                report!(self.storage.write_feed(&feed), "Failed to write feed");
+                self.subscribe_new(std::slice::from_ref(&*feed));
+                report!(
+                    self.storage
+                        .load_item_state(std::slice::from_mut(&mut *feed)),
+                    "Failed to read item state"
+                );
+                let feed = match self.storage.read_feed_with_url(feed.url()) {
+                    Ok(Some(stored)) => Box::new(stored),
+                    _ => {
+                        feed.drop_item_markup();
+                        feed
+                    }
+                };
+                self.app_tx
+                    .send(RepositoryEvent::RetrievedOne(feed))
+                    .expect("Failed to send app message");
+                self.handle_one = None;
+            }
+            RepositoryEvent::Pushed(mut feed) => {
+                report!(
+                    self.storage.write_feed(&feed),
+                    "Failed to write pushed feed"
+                );
+                report!(
+                    self.storage
+                        .load_item_state(std::slice::from_mut(&mut *feed)),
+                    "Failed to read item state"
+                );
+                self.last_refreshed
+                    .insert(feed.url().to_owned(), Instant::now());
+                // the hub may push only the new items, so the app is sent all stored
+                if let Ok(Some(stored)) = self.storage.read_feed_with_url(feed.url()) {
                     self.app_tx
-                        .send(RepositoryEvent::RetrievedOne(feed))
+                        .send(RepositoryEvent::Pushed(Box::new(stored)))
                         .expect("Failed to send app message");
-                    self.handle_one = None;
                 }
-                Some(RepositoryEvent::Refresh) => {
-                    self.refresh_all(config);
+            }
+            RepositoryEvent::WebSubVerified(subscription) => {
+                report!(
+                    self.storage.write_subscription(&subscription),
+                    "Failed to write WebSub subscription"
+                );
+            }
+            RepositoryEvent::FetchResults(succeeded, failed) => {
+                report!(
+                    self.storage.record_fetch_results(&succeeded, &failed),
+                    "Failed to record feed health"
+                );
+            }
+            RepositoryEvent::RequestItemsPage(feed_id, offset, limit, order) => {
+                match self.get_items_page(&feed_id, offset, limit, order) {
+                    Ok(items) => self
+                        .app_tx
+                        .send(RepositoryEvent::ItemsPage(feed_id, offset, order, items))
+                        .expect("Failed to send app message"),
+                    Err(_) => log::error!("Failed to read items of {}", feed_id),
+                }
+            }
+            RepositoryEvent::RemoteItemsPulled(items) => self.store_remote_items(items),
+            RepositoryEvent::StreamItemsPulled(items) => {
+                let links = items
+                    .iter()
+                    .filter_map(StreamItem::link)
+                    .collect::<Vec<_>>();
+                let Ok(ids) = self.storage.item_ids_with_links(&links) else {
+                    log::error!("Failed to match synced items");
+                    return;
+                };
+                let items = items
+                    .into_iter()
+                    .filter_map(|item| {
+                        let guid = ids.get(item.link()?)?.clone();
+                        Some(item.into_remote(guid))
+                    })
+                    .collect();
+                self.store_remote_items(items);
+            }
+            RepositoryEvent::Refresh => {
+                let due = self.refresh_due(config);
+                if !due.is_empty() {
+                    self.refresh_feeds(config, due);
                 }
-                Some(_) => {}
-                None => {}
-            },
-            Poll::Pending => {}
+            }
+            _ => {}
+        }
+    }
+
+    fn start_websub(&mut self, config: &Config) {
+        let Some(websub) = config.websub() else {
+            return;
+        };
+        let subscriptions = self.storage.read_subscriptions().unwrap_or_default();
+        match WebSubManager::start(websub, subscriptions, self.storage_tx.clone()) {
+            Ok(manager) => self.websub = Some(manager),
+            Err(err) => log::error!("Failed to start WebSub listener: {:#}", err),
+        }
+    }
+
+    /// Writes the feeds of a refresh to storage, and subscribes to the hubs they advertise.
+    #[tracing::instrument(skip_all, fields(feeds = feeds.len()))]
+    fn store_all(&mut self, feeds: &[Feed]) {
+        report!(self.storage.write_feeds(feeds), "Failed to write feeds");
+        self.subscribe_new(feeds);
+    }
+
+    /// Subscribes to the hubs `feeds` advertise, if WebSub is set up.
+    fn subscribe_new(&self, feeds: &[Feed]) {
+        let Some(websub) = &self.websub else {
+            return;
+        };
+        for subscription in websub.subscribe_new(feeds) {
+            report!(
+                self.storage.write_subscription(&subscription),
+                "Failed to write WebSub subscription"
+            );
         }
     }
 
+    #[tracing::instrument(skip_all)]
     pub fn read_all(&mut self, config: &Config) -> Result<Vec<Feed>, StorageError> {
         let res = self.storage.read_all(config);
         report!(res, "Failed to read from DB");
         res
     }
 
-    pub fn add_feed_url(&mut self, url: &str, config: &Config) {
+    /// Fetches a new feed. With `discover` set, a URL that turns out to be a web page is
+    /// searched for the feeds it advertises instead.
+    pub fn add_feed_url(&mut self, url: &str, config: &Config, discover: bool) {
         let app_tx = self.app_tx.clone();
         if let Some(handle) = &self.handle_one {
             handle.abort();
@@ -110,30 +419,82 @@ impl Repository {
         }
 
         let url = url.to_owned();
-        let interval = config.refresh_timeout();
+        let timeout = config.feed_timeout(&url);
+        let max_bytes = config.feed_max_response_bytes(&url);
+        let headers = config
+            .feed_config(&url)
+            .map(|feed_config| feed_config.headers().clone())
+            .unwrap_or_default();
+        let config = config.clone();
         let storage_tx = self.storage_tx.clone();
 
         app_tx
             .send(RepositoryEvent::Requesting(1))
             .expect("Failed to send app event");
 
+        let proxy = config.feed_proxy(&url).map(str::to_owned);
+        let user_agent = config.feed_user_agent(&url).to_owned();
+        let (follow_redirects, max_redirects) = (config.follow_redirects(), config.max_redirects());
+
         self.handle_one = Some(tokio::spawn(async move {
-            let client = reqwest::Client::builder()
-                .connect_timeout(Duration::from_secs(interval))
-                .timeout(Duration::from_secs(interval))
+            let client = match client_builder(proxy.as_deref(), &user_agent)
+                .redirect(redirect_policy(follow_redirects, max_redirects))
+                .connect_timeout(Duration::from_secs(timeout))
+                .timeout(Duration::from_secs(timeout))
                 .build()
-                .expect("failed to build client");
+            {
+                Ok(client) => client,
+                Err(err) => {
+                    log::warn!("Failed to add {}: {}", url, describe_request_error(&err));
+                    app_tx
+                        .send(RepositoryEvent::Errored)
+                        .expect("Failed to send app event");
+                    return;
+                }
+            };
+
+            let mut req = client.get(&url).headers(headers);
+            let credentials = read_credentials(config, vec![url.clone()]).await;
+            if let Some((username, password)) = credentials.get(&url) {
+                req = req.basic_auth(username, password.as_ref());
+            }
+            let mut res = make_feed_request(&url, req.send(), max_bytes).await;
+
+            // not a feed, so look for the feeds the page links to
+            if discover && matches!(res, Err(FetchErr::Parse(_))) {
+                match Feed::discover(&client, &url, max_bytes).await {
+                    Ok(candidates) if candidates.len() == 1 => {
+                        let req = client.get(&candidates[0]).send();
+                        res = make_feed_request(&candidates[0], req, max_bytes).await;
+                    }
+                    Ok(candidates) if !candidates.is_empty() => {
+                        log::info!("Discovered {} feeds at {}", candidates.len(), url);
+                        app_tx
+                            .send(RepositoryEvent::Discovered(candidates))
+                            .expect("Failed to send app event");
+                        return;
+                    }
+                    Ok(_) => log::warn!("No feeds found at {}", url),
+                    Err(err) => log::warn!("Failed to discover feeds at {}: {}", url, err),
+                }
+            }
 
-            match make_feed_request(client.get(url).send()).await {
-                Ok(feed) => {
+            match res {
+                Ok(Some(feed)) => {
                     app_tx
                         .send(RepositoryEvent::Requested((1, 1)))
                         .expect("Failed to send app event");
                     storage_tx
-                        .send(RepositoryEvent::RetrievedOne(feed))
+                        .send(RepositoryEvent::RetrievedOne(Box::new(feed)))
                         .expect("Failed to send app event");
                 }
-                Err(_) => {
+                Ok(None) => {
+                    app_tx
+                        .send(RepositoryEvent::Requested((1, 1)))
+                        .expect("Failed to send app event");
+                }
+                Err(err) => {
+                    log::warn!("Failed to add {}: {}", url, err);
                     app_tx
                         .send(RepositoryEvent::Errored)
                         .expect("Failed to make feed request");
@@ -142,11 +503,380 @@ impl Repository {
         }));
     }
 
+    /// Checks that `url` answers a HEAD request, as the setup wizard does before adding it.
+    /// Servers that don't allow HEAD requests are taken to have answered.
+    pub fn check_feed_url(&self, url: &str, config: &Config) {
+        let app_tx = self.app_tx.clone();
+        let url = url.to_owned();
+        let timeout = config.feed_timeout(&url);
+        let proxy = config.feed_proxy(&url).map(str::to_owned);
+        let user_agent = config.feed_user_agent(&url).to_owned();
+        let (follow_redirects, max_redirects) = (config.follow_redirects(), config.max_redirects());
+
+        tokio::spawn(async move {
+            let client = client_builder(proxy.as_deref(), &user_agent)
+                .redirect(redirect_policy(follow_redirects, max_redirects))
+                .timeout(Duration::from_secs(timeout))
+                .build();
+
+            let reason = match client {
+                Err(err) => Some(err.to_string()),
+                Ok(client) => match client.head(&url).send().await {
+                    Ok(res)
+                        if res.status().is_success()
+                            || res.status() == StatusCode::METHOD_NOT_ALLOWED =>
+                    {
+                        None
+                    }
+                    Ok(res) => Some(res.status().to_string()),
+                    Err(err) => Some(err.to_string()),
+                },
+            };
+            app_tx
+                .send(RepositoryEvent::FeedUrlChecked(url, reason))
+                .expect("Failed to send app event");
+        });
+    }
+
     pub fn remove_feed_url(&mut self, url: &str) -> Result<StorageEvent, StorageError> {
+        if let Some(websub) = &self.websub {
+            websub.unsubscribe(url);
+        }
+        report!(
+            self.storage.delete_subscription(url),
+            "Failed to delete WebSub subscription"
+        );
         self.storage.delete_feed_with_url(url)
     }
 
+    pub fn mark_read(
+        &mut self,
+        feed_id: &str,
+        item_id: &str,
+    ) -> Result<StorageEvent, StorageError> {
+        self.storage.set_item_read(feed_id, item_id, true)
+    }
+
+    /// Marks an item read for someone other than the interface, such as an API client, and
+    /// tells the interface so it shows the item read.
+    pub fn mark_read_elsewhere(
+        &mut self,
+        feed_id: &str,
+        item_id: &str,
+    ) -> Result<StorageEvent, StorageError> {
+        let event = self.mark_read(feed_id, item_id)?;
+        self.app_tx
+            .send(RepositoryEvent::ItemRead(
+                feed_id.to_owned(),
+                item_id.to_owned(),
+            ))
+            .expect("Failed to send app event");
+        Ok(event)
+    }
+
+    pub fn mark_unread(
+        &mut self,
+        feed_id: &str,
+        item_id: &str,
+    ) -> Result<StorageEvent, StorageError> {
+        self.storage.set_item_read(feed_id, item_id, false)
+    }
+
+    /// Reads an item in full, for the detail view. Loaded feeds leave out its HTML.
+    pub fn get_item_detail(&self, feed_id: &str, item_id: &str) -> Result<Item, StorageError> {
+        self.storage
+            .read_item(feed_id, item_id)?
+            .ok_or(StorageError)
+    }
+
+    /// Reads up to `limit` item summaries of a feed in `order`, starting `offset` items in.
+    pub fn get_items_page(
+        &self,
+        feed_id: &str,
+        offset: usize,
+        limit: usize,
+        order: ItemSortOrder,
+    ) -> Result<Vec<ItemSummary>, StorageError> {
+        self.storage.read_items_page(feed_id, offset, limit, order)
+    }
+
+    /// Like `get_items_page`, but reads on the next tick and sends the page to the app as
+    /// `ItemsPage`, so scrolling needn't wait on storage.
+    pub fn request_items_page(
+        &self,
+        feed_id: &str,
+        offset: usize,
+        limit: usize,
+        order: ItemSortOrder,
+    ) {
+        self.storage_tx
+            .send(RepositoryEvent::RequestItemsPage(
+                feed_id.to_owned(),
+                offset,
+                limit,
+                order,
+            ))
+            .expect("Failed to send storage message");
+    }
+
+    /// Reads a page of the timeline: item summaries across all feeds, newest first.
+    pub fn get_all_items_sorted(
+        &self,
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<TimelineItem>, StorageError> {
+        self.storage.read_items_by_date(limit, offset)
+    }
+
+    /// Reads every stored item by `author`, as the feeds give it, newest first.
+    pub fn items_by_author(&self, author: &str) -> Result<Vec<ItemSummary>, StorageError> {
+        self.storage.read_items_by_author(author)
+    }
+
+    /// Finds up to `limit` items across every feed whose title or text contains `query`.
+    pub fn search_items(
+        &self,
+        query: &str,
+        limit: usize,
+    ) -> Result<Vec<SearchResult>, StorageError> {
+        self.storage.search_items(query, limit)
+    }
+
+    /// Stars the stored items linking to any of `links`, returning how many were newly starred.
+    pub fn star_items_with_links(&mut self, links: &[String]) -> Result<usize, StorageError> {
+        self.storage.star_items_with_links(links)
+    }
+
+    /// Stores the server's ids and states of `items`, and passes their states on to the app.
+    fn store_remote_items(&mut self, items: Vec<RemoteItem>) {
+        if self.storage.write_remote_items(&items).is_err() {
+            log::error!("Failed to write synced items");
+            return;
+        }
+        self.guid_to_remote_id
+            .extend(items.iter().map(|item| (item.guid().to_owned(), item.id())));
+        let guids = |keep: fn(&RemoteItem) -> bool| {
+            items
+                .iter()
+                .filter(|item| keep(item))
+                .map(|item| item.guid().to_owned())
+                .collect::<Vec<_>>()
+        };
+        self.app_tx
+            .send(RepositoryEvent::SyncedItems(
+                guids(RemoteItem::is_read),
+                guids(RemoteItem::is_starred),
+            ))
+            .expect("Failed to send app message");
+    }
+
+    /// Fetches the state of items on the sync server, if one is configured. Items starred on
+    /// a Miniflux server arrive as `StarredSynced`. Items changed on a Nextcloud News server,
+    /// or new to a GReader one, since the last pull are stored, and arrive as `SyncedItems`.
+    /// Each sends `SyncFailed` if the server can't be reached.
+    pub fn pull_sync_state(&self, config: &Config) {
+        let Some(sync) = config.sync() else {
+            return;
+        };
+        let app_tx = self.app_tx.clone();
+
+        match sync.backend() {
+            SyncBackend::Miniflux { api_key } => {
+                let client = MinifluxClient::new(sync.base_url(), api_key);
+                tokio::spawn(async move {
+                    let event = match client.pull_starred().await {
+                        Ok(links) => RepositoryEvent::StarredSynced(links),
+                        Err(err) => {
+                            log::warn!("Failed to pull starred items: {}", err);
+                            RepositoryEvent::SyncFailed(err.to_string())
+                        }
+                    };
+                    app_tx.send(event).expect("Failed to send app event");
+                });
+            }
+            SyncBackend::NextcloudNews { username, password } => {
+                let client = NextcloudNewsClient::new(sync.base_url(), username, password);
+                let last_modified = self.storage.last_remote_modified().unwrap_or_default();
+                let storage_tx = self.storage_tx.clone();
+                tokio::spawn(async move {
+                    match client.get_updated_items(last_modified).await {
+                        Ok(items) => storage_tx
+                            .send(RepositoryEvent::RemoteItemsPulled(items))
+                            .expect("Failed to send storage event"),
+                        Err(err) => {
+                            log::warn!("Failed to pull updated items: {}", err);
+                            app_tx
+                                .send(RepositoryEvent::SyncFailed(err.to_string()))
+                                .expect("Failed to send app event");
+                        }
+                    }
+                });
+            }
+            SyncBackend::GReader { username, password } => {
+                let client = GReaderClient::new(sync.base_url(), username, password);
+                let since = self.storage.last_remote_modified().unwrap_or_default();
+                let storage_tx = self.storage_tx.clone();
+                tokio::spawn(async move {
+                    match client.get_updated_items(since).await {
+                        Ok(items) => storage_tx
+                            .send(RepositoryEvent::StreamItemsPulled(items))
+                            .expect("Failed to send storage event"),
+                        Err(err) => {
+                            log::warn!("Failed to pull updated items: {}", err);
+                            app_tx
+                                .send(RepositoryEvent::SyncFailed(err.to_string()))
+                                .expect("Failed to send app event");
+                        }
+                    }
+                });
+            }
+        }
+    }
+
+    /// Marks the items with `guids` read on a Nextcloud News or GReader server, in the
+    /// background. Miniflux servers are sent read items as the app quits.
+    pub fn push_read(&self, config: &Config, guids: &[String]) {
+        self.push_state(config, guids, ItemState::Read);
+    }
+
+    /// Stars the item with `guid` on a Nextcloud News or GReader server, in the background.
+    pub fn push_starred(&self, config: &Config, guid: &str) {
+        self.push_state(config, &[guid.to_owned()], ItemState::Starred);
+    }
+
+    /// Marks the items with `guids` that the server has ids for with `state`, sending
+    /// `SyncFailed` at the first failure.
+    fn push_state(&self, config: &Config, guids: &[String], state: ItemState) {
+        let Some(sync) = config.sync() else {
+            return;
+        };
+        let ids = guids
+            .iter()
+            .filter_map(|guid| self.guid_to_remote_id.get(guid).copied())
+            .collect::<Vec<_>>();
+        if ids.is_empty() {
+            return;
+        }
+
+        let app_tx = self.app_tx.clone();
+        let report = move |result: Result<()>| {
+            if let Err(err) = result {
+                log::warn!("Failed to push item state: {}", err);
+                app_tx
+                    .send(RepositoryEvent::SyncFailed(err.to_string()))
+                    .expect("Failed to send app event");
+            }
+        };
+        match sync.backend() {
+            SyncBackend::Miniflux { .. } => {}
+            SyncBackend::NextcloudNews { username, password } => {
+                let client = NextcloudNewsClient::new(sync.base_url(), username, password);
+                tokio::spawn(async move {
+                    // the API takes one item at a time
+                    for id in ids {
+                        let pushed = match state {
+                            ItemState::Read => client.mark_item_read(id).await,
+                            ItemState::Starred => client.mark_item_starred(id).await,
+                        };
+                        if pushed.is_err() {
+                            return report(pushed);
+                        }
+                    }
+                });
+            }
+            SyncBackend::GReader { username, password } => {
+                let client = GReaderClient::new(sync.base_url(), username, password);
+                tokio::spawn(async move { report(client.mark_items(&ids, state).await) });
+            }
+        }
+    }
+
+    pub fn load_queue(&self) -> Result<VecDeque<QueuedItem>, StorageError> {
+        self.storage.read_queue()
+    }
+
+    pub fn save_queue(&mut self, queue: &VecDeque<QueuedItem>) -> Result<(), StorageError> {
+        self.storage.write_queue(queue)
+    }
+
+    pub fn toggle_star(&mut self, feed_id: &str, item_id: &str) -> Result<bool, StorageError> {
+        self.storage.toggle_item_starred(feed_id, item_id)
+    }
+
+    pub fn feed_stats(&self, url: &str) -> Result<FeedStats, StorageError> {
+        self.storage.feed_stats(url)
+    }
+
+    pub fn mark_feed_read(&mut self, feed_id: &str) -> Result<StorageEvent, StorageError> {
+        self.storage.set_feed_read(feed_id, true)
+    }
+
+    /// Deletes read, unstarred items older than the configured `retention_days`, except those
+    /// still listed in `feeds` or waiting in the reading queue. Does nothing when no retention is configured.
+    pub fn prune_old_items(
+        &mut self,
+        config: &Config,
+        feeds: &[Feed],
+    ) -> Result<usize, StorageError> {
+        let Some(days) = config.retention_days() else {
+            return Ok(0);
+        };
+        let cutoff = chrono::Utc::now() - chrono::Duration::days(days as i64);
+        self.storage.prune_items(cutoff, feeds)
+    }
+
+    #[tracing::instrument(skip_all, fields(feeds = config.feed_urls().len()))]
     pub fn refresh_all(&mut self, config: &Config) {
+        let feeds = config
+            .feed_urls()
+            .iter()
+            .map(|url| (url.clone(), feed_timeout(config, url)))
+            .collect();
+        self.refresh_feeds(config, feeds);
+    }
+
+    /// Fetches only the feed at `url`, as `refresh_all` does every feed, ending with
+    /// `RetrievedAll`.
+    pub fn refresh_one(&mut self, url: &str, config: &Config) {
+        self.refresh_feeds(config, vec![(url.to_owned(), feed_timeout(config, url))]);
+    }
+
+    /// Whether a refresh started by `refresh_feeds` is still running.
+    pub fn is_refreshing(&self) -> bool {
+        self.handle_many
+            .as_ref()
+            .is_some_and(|handle| !handle.is_finished())
+    }
+
+    /// The feeds whose refresh interval has passed since they were last refreshed, each with
+    /// its request timeout, leaving out those a WebSub hub pushes. Nothing is due while a
+    /// refresh is still running.
+    pub fn refresh_due(&self, config: &Config) -> Vec<(String, Duration)> {
+        if self.is_refreshing() {
+            return vec![];
+        }
+
+        let now = Instant::now();
+        config
+            .feed_urls()
+            .iter()
+            .filter(|url| {
+                let interval = config.feed_refresh_interval(url);
+                interval > 0
+                    && !self
+                        .websub
+                        .as_ref()
+                        .is_some_and(|websub| websub.is_active(url))
+                    && self.last_refreshed.get(*url).is_none_or(|last| {
+                        now.duration_since(*last) >= Duration::from_secs(interval)
+                    })
+            })
+            .map(|url| (url.clone(), feed_timeout(config, url)))
+            .collect()
+    }
+
+    /// Fetches the given feeds, each with its own request timeout.
+    pub fn refresh_feeds(&mut self, config: &Config, feeds: Vec<(String, Duration)>) {
         let app_tx = self.app_tx.clone();
         if let Some(handle) = &self.handle_many {
             handle.abort();
@@ -158,47 +888,46 @@ impl Repository {
 
         let storage_tx = self.storage_tx.clone();
         let config: Config = config.clone();
-        let urls = config.feed_urls().clone();
-        let count = urls.len();
+        let now = Instant::now();
+        for (url, _) in &feeds {
+            self.last_refreshed.insert(url.clone(), now);
+        }
+        let count = feeds.len();
+        let max_concurrent = config.max_concurrent_fetches().max(1);
+        let validators = self.storage.read_validators().unwrap_or_default();
 
         app_tx
             .send(RepositoryEvent::Requesting(count))
             .expect("Could not send app message");
 
         self.handle_many = Some(tokio::spawn(async move {
-            let client = reqwest::Client::builder()
-                .connect_timeout(Duration::from_secs(config.refresh_timeout()))
-                .timeout(Duration::from_secs(config.refresh_timeout()))
-                .build()
-                .expect("Failed to build client");
-            let futures: Vec<_> = urls.into_iter().map(|url| client.get(url).send()).collect();
-            let handles: Vec<_> = futures
-                .into_iter()
-                .enumerate()
-                .map(|(n, req)| {
-                    let app_tx = app_tx.clone();
-                    tokio::task::spawn(async move {
-                        let res = make_feed_request(req).await;
-                        app_tx
-                            .send(RepositoryEvent::Requested((n, count)))
-                            .expect("Failed to send app message");
-                        res
-                    })
-                })
-                .collect();
-            let results = futures::future::join_all(handles).await;
-            let mut feeds: Vec<Feed> = results
-                .into_iter()
-                .filter_map(|handle| match handle {
-                    Ok(res) => match res {
-                        Ok(feed) => Some(feed),
-                        _ => None,
-                    },
-                    _ => None,
-                })
-                .collect();
-
-            sort_feeds(&mut feeds, &config);
+            let urls = feeds.iter().map(|(url, _)| url.clone()).collect();
+            let credentials = read_credentials(config.clone(), urls).await;
+            let (urls, timeouts) = feeds.into_iter().unzip::<_, _, Vec<_>, Vec<_>>();
+            let transport = HttpTransport {
+                timeouts: urls.iter().cloned().zip(timeouts).collect(),
+                config,
+                credentials,
+                validators,
+                clients: Mutex::default(),
+            };
+            let results = fetch_feeds(&transport, urls, max_concurrent, &app_tx).await;
+
+            let mut feeds = vec![];
+            let (mut succeeded, mut failed) = (vec![], vec![]);
+            for (url, res) in results {
+                match res {
+                    Ok(feed) => {
+                        succeeded.push(url);
+                        feeds.extend(feed);
+                    }
+                    Err(_) => failed.push(url),
+                }
+            }
+
+            storage_tx
+                .send(RepositoryEvent::FetchResults(succeeded, failed))
+                .expect("Failed to send storage message");
             storage_tx
                 .send(RepositoryEvent::RetrievedAll(feeds))
                 .expect("Failed to send storage message");
@@ -206,20 +935,500 @@ impl Repository {
     }
 }
 
+/// Where to back up the database at `db_path` unless told otherwise, beside it and named for
+/// the time. Backups end in `.sqlite`, as they are SQLite databases that only a version of
+/// moccasin storing feeds in SQLite can restore.
+pub fn default_backup_path(db_path: &Path) -> PathBuf {
+    let mut name = db_path.file_name().unwrap_or_default().to_owned();
+    name.push(format!(
+        ".bak.{}.sqlite",
+        chrono::Local::now().format("%Y%m%d-%H%M%S")
+    ));
+    db_path.with_file_name(name)
+}
+
+/// Where a file is written before being renamed to `path`, so it never holds half a copy.
+fn partial_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_owned();
+    name.push(".partial");
+    path.with_file_name(name)
+}
+
+/// Creates `dir` and any missing parents, readable only by the user on Unix.
+fn create_private_dir(dir: &Path) -> io::Result<()> {
+    let mut builder = fs::DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+    builder.create(dir)
+}
+
+fn feed_timeout(config: &Config, url: &str) -> Duration {
+    Duration::from_secs(config.feed_timeout(url))
+}
+
+/// Exponential backoff before retry number `attempt`, with ±10% jitter.
+fn retry_delay(attempt: u8) -> Duration {
+    let delay = RETRY_BASE_DELAY
+        .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1) as u32))
+        .min(RETRY_MAX_DELAY);
+    delay.mul_f64(rand::thread_rng().gen_range(0.9..=1.1))
+}
+
+/// Resolves to `None` when the server reports the feed unchanged since the cached validators.
+/// Fails without reading further once the body passes `max_bytes`. The feed keeps the `url`
+/// requested, with where any redirects led in its `resolved_url`.
 async fn make_feed_request(
+    url: &str,
     req: impl std::future::Future<Output = Result<reqwest::Response, reqwest::Error>>,
-) -> Result<Feed, FetchErr> {
+    max_bytes: usize,
+) -> Result<Option<Feed>, FetchErr> {
     match req.await {
+        Ok(res) if res.status() == StatusCode::NOT_MODIFIED => Ok(None),
+        Ok(res) if !res.status().is_success() => Err(FetchErr::Status(res.status())),
         Ok(res) => {
-            let url = res.url().to_string();
-            match &res.bytes().await {
-                Ok(bytes) => match Feed::read_from(&bytes[..], url) {
-                    Ok(feed) => Ok(feed),
-                    Err(_) => Err(FetchErr::Parse),
-                },
+            let resolved_url = res.url().to_string();
+            let etag = header_value(&res, ETAG);
+            let last_modified = header_value(&res, LAST_MODIFIED);
+            let content_type = header_value(&res, CONTENT_TYPE);
+            let hub = header_value(&res, LINK)
+                .and_then(|link| websubscriber::hub_from_link_header(&link, &resolved_url));
+            match read_body(res, max_bytes).await {
+                Ok(None) => Err(FetchErr::ResponseTooLarge(max_bytes)),
+                Ok(Some(bytes)) => {
+                    let content_type = content_type.as_deref();
+                    match Feed::read_with_content_type(&bytes[..], content_type, url.to_owned()) {
+                        Ok(mut feed) => {
+                            feed.resolved_url = (resolved_url != url).then_some(resolved_url);
+                            feed.hub = hub.or(feed.hub);
+                            feed.etag = etag;
+                            feed.last_modified = last_modified;
+                            Ok(Some(feed))
+                        }
+                        Err(err) => Err(FetchErr::Parse(err.to_string())),
+                    }
+                }
+                Err(err) if err.is_timeout() => Err(FetchErr::Request(err)),
                 Err(_) => Err(FetchErr::Deserialize),
             }
         }
-        Err(_) => Err(FetchErr::Request),
+        Err(err) => Err(FetchErr::Request(err)),
+    }
+}
+
+/// A short reason a request failed, such as `"Connection refused"`, from the innermost error
+/// reqwest wraps.
+fn describe_request_error(err: &reqwest::Error) -> String {
+    if err.is_timeout() {
+        return "Timed out".to_owned();
+    }
+
+    let mut source = err.source();
+    let mut innermost = None;
+    while let Some(cause) = source {
+        if let Some(err) = cause.downcast_ref::<io::Error>() {
+            if err.kind() == io::ErrorKind::ConnectionRefused {
+                return "Connection refused".to_owned();
+            }
+        }
+        if cause.to_string().starts_with("dns error") {
+            return "DNS resolution failed".to_owned();
+        }
+        innermost = Some(cause);
+        source = cause.source();
+    }
+
+    match innermost {
+        Some(cause) if err.is_connect() => format!("Connection failed: {}", cause),
+        Some(cause) => cause.to_string(),
+        None => err.to_string(),
+    }
+}
+
+/// Follows at most `max` redirects, or none unless `follow`. Each is logged, with a warning
+/// when the first is permanent, as the feed's URL is then out of date.
+fn redirect_policy(follow: bool, max: usize) -> Policy {
+    if !follow {
+        return Policy::none();
+    }
+    Policy::custom(move |attempt| {
+        let previous = attempt.previous();
+        if previous.len() > max {
+            return attempt.error(format!("More than {} redirects", max));
+        }
+        tracing::debug!(
+            from = %previous[previous.len() - 1],
+            to = %attempt.url(),
+            status = %attempt.status(),
+            "Following redirect"
+        );
+        if previous.len() == 1
+            && matches!(
+                attempt.status(),
+                StatusCode::MOVED_PERMANENTLY | StatusCode::PERMANENT_REDIRECT
+            )
+        {
+            tracing::warn!(
+                url = %previous[0],
+                to = %attempt.url(),
+                "Feed has moved permanently"
+            );
+        }
+        attempt.follow()
+    })
+}
+
+/// A client builder sending `user_agent` and routing requests through `proxy`, if given and
+/// valid. Otherwise reqwest falls back to the proxy environment variables.
+fn client_builder(proxy: Option<&str>, user_agent: &str) -> reqwest::ClientBuilder {
+    let builder = reqwest::Client::builder().user_agent(user_agent);
+    match proxy.map(reqwest::Proxy::all) {
+        Some(Ok(proxy)) => builder.proxy(proxy),
+        Some(Err(err)) => {
+            log::warn!("Ignoring invalid proxy URL: {}", err);
+            builder
+        }
+        None => builder,
+    }
+}
+
+/// Fetches feeds for a refresh, standing between `fetch_feeds` and the network so that tests
+/// can fetch from a mock instead.
+trait Transport: Sync {
+    /// Fetches the feed at `url`, `None` if it is unchanged, with how many attempts it took.
+    fn fetch<'a>(&'a self, url: &'a str) -> BoxFuture<'a, (u8, Result<Option<Feed>, FetchErr>)>;
+}
+
+/// Fetches feeds over HTTP, with the validators of the last fetch and the credentials in the
+/// keyring, retrying after network errors.
+struct HttpTransport {
+    config: Config,
+    timeouts: HashMap<String, Duration>,
+    credentials: HashMap<String, (String, Option<String>)>,
+    validators: HashMap<String, Validators>,
+    /// Proxies are set per client, so feeds share one client per proxy, built when first
+    /// needed. A client that fails to build fails only the feeds using it.
+    clients: Mutex<HashMap<Option<String>, reqwest::Client>>,
+}
+
+impl HttpTransport {
+    fn client(&self, url: &str) -> reqwest::Result<reqwest::Client> {
+        let proxy = self.config.feed_proxy(url);
+        let mut clients = self.clients.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(client) = clients.get(&proxy.map(str::to_owned)) {
+            return Ok(client.clone());
+        }
+        let client = client_builder(proxy, self.config.user_agent())
+            .redirect(redirect_policy(
+                self.config.follow_redirects(),
+                self.config.max_redirects(),
+            ))
+            .build()?;
+        clients.insert(proxy.map(str::to_owned), client.clone());
+        Ok(client)
+    }
+
+    /// The request for `url`, with its own timeout, since each feed may override the global
+    /// one.
+    fn request(&self, url: &str) -> reqwest::Result<reqwest::RequestBuilder> {
+        let timeout = self
+            .timeouts
+            .get(url)
+            .copied()
+            .unwrap_or_else(|| feed_timeout(&self.config, url));
+        let mut req = self
+            .client(url)?
+            .get(url)
+            .timeout(timeout)
+            .header(USER_AGENT, self.config.feed_user_agent(url));
+        if let Some(feed_config) = self.config.feed_config(url) {
+            req = req.headers(feed_config.headers().clone());
+        }
+        if let Some((username, password)) = self.credentials.get(url) {
+            req = req.basic_auth(username, password.as_ref());
+        }
+        if let Some((etag, last_modified)) = self.validators.get(url) {
+            if let Some(etag) = etag {
+                req = req.header(IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = last_modified {
+                req = req.header(IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+        Ok(req)
+    }
+}
+
+impl Transport for HttpTransport {
+    fn fetch<'a>(&'a self, url: &'a str) -> BoxFuture<'a, (u8, Result<Option<Feed>, FetchErr>)> {
+        Box::pin(async move {
+            let req = match self.request(url) {
+                Ok(req) => req,
+                Err(err) => return (0, Err(FetchErr::Request(err))),
+            };
+            let max_bytes = self.config.feed_max_response_bytes(url);
+            let max_retries = self.config.max_retries();
+            let mut attempts = 0;
+            let res = loop {
+                attempts += 1;
+                // only a request that failed to build (say, from an unparsable URL) can't be
+                // cloned, and sending it reports why
+                let Some(attempt) = req.try_clone() else {
+                    break make_feed_request(url, req.send(), max_bytes).await;
+                };
+                match make_feed_request(url, attempt.send(), max_bytes).await {
+                    Err(FetchErr::Request(err))
+                        if attempts <= max_retries && !err.is_redirect() =>
+                    {
+                        tokio::time::sleep(retry_delay(attempts)).await;
+                    }
+                    res => break res,
+                }
+            };
+            (attempts, res)
+        })
+    }
+}
+
+/// Fetches `urls` through `transport`, at most `max_concurrent` at once, telling the app as
+/// each finishes and why any failed.
+async fn fetch_feeds(
+    transport: &impl Transport,
+    urls: Vec<String>,
+    max_concurrent: usize,
+    app_tx: &UnboundedSender<RepositoryEvent>,
+) -> Vec<(String, Result<Option<Feed>, FetchErr>)> {
+    let count = urls.len();
+    futures::stream::iter(urls.into_iter().enumerate())
+        .map(|(n, url)| {
+            let span = tracing::info_span!("fetch", %url);
+            async move {
+                let (attempts, res) = transport.fetch(&url).await;
+                match &res {
+                    Ok(Some(_)) => tracing::debug!(%url, attempts, "Fetched"),
+                    Ok(None) => tracing::debug!(%url, "Not modified"),
+                    Err(err) => {
+                        tracing::warn!(%url, attempts, error = %err, "Failed to fetch");
+                        if matches!(err, FetchErr::Request(err) if err.is_timeout()) {
+                            app_tx
+                                .send(RepositoryEvent::FetchTimedOut(url.clone()))
+                                .expect("Failed to send app message");
+                        }
+                        app_tx
+                            .send(RepositoryEvent::FetchFailed(
+                                url.clone(),
+                                attempts,
+                                err.to_string(),
+                            ))
+                            .expect("Failed to send app message");
+                    }
+                }
+                app_tx
+                    .send(RepositoryEvent::Requested((n, count)))
+                    .expect("Failed to send app message");
+                (url, res)
+            }
+            .instrument(span)
+        })
+        .buffer_unordered(max_concurrent)
+        .collect()
+        .await
+}
+
+/// The HTTP Basic credentials for each of `urls` that has them, read off the async workers,
+/// since the keyring may block.
+async fn read_credentials(
+    config: Config,
+    urls: Vec<String>,
+) -> HashMap<String, (String, Option<String>)> {
+    tokio::task::spawn_blocking(move || {
+        urls.into_iter()
+            .filter_map(|url| Some((url.clone(), config.feed_credentials(&url)?)))
+            .collect()
+    })
+    .await
+    .unwrap_or_default()
+}
+
+fn header_value(res: &reqwest::Response, name: HeaderName) -> Option<String> {
+    res.headers()
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_owned)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+    use std::convert::Infallible;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    /// Answers every fetch after a short wait, unchanged or with a 404, counting how many
+    /// fetches are in flight at once.
+    #[derive(Default)]
+    struct MockTransport {
+        missing: HashSet<String>,
+        in_flight: AtomicUsize,
+        most_in_flight: AtomicUsize,
+    }
+
+    impl Transport for MockTransport {
+        fn fetch<'a>(
+            &'a self,
+            url: &'a str,
+        ) -> BoxFuture<'a, (u8, Result<Option<Feed>, FetchErr>)> {
+            Box::pin(async move {
+                let in_flight = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                self.most_in_flight.fetch_max(in_flight, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                self.in_flight.fetch_sub(1, Ordering::SeqCst);
+                if self.missing.contains(url) {
+                    (1, Err(FetchErr::Status(StatusCode::NOT_FOUND)))
+                } else {
+                    (1, Ok(None))
+                }
+            })
+        }
+    }
+
+    fn urls(count: usize) -> Vec<String> {
+        (0..count)
+            .map(|n| format!("https://example.com/{}.xml", n))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn fetches_at_most_max_concurrent_feeds_at_once() {
+        let transport = MockTransport::default();
+        let (tx, _rx) = mpsc::unbounded_channel();
+
+        let results = fetch_feeds(&transport, urls(20), 3, &tx).await;
+
+        assert_eq!(results.len(), 20);
+        assert!(results.iter().all(|(_, res)| matches!(res, Ok(None))));
+        assert_eq!(transport.most_in_flight.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn fetches_one_at_a_time_with_a_limit_of_one() {
+        let transport = MockTransport::default();
+        let (tx, _rx) = mpsc::unbounded_channel();
+
+        fetch_feeds(&transport, urls(5), 1, &tx).await;
+
+        assert_eq!(transport.most_in_flight.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn reports_progress_for_each_feed_and_why_any_failed() {
+        let urls = urls(6);
+        let transport = MockTransport {
+            missing: HashSet::from([urls[2].clone(), urls[4].clone()]),
+            ..Default::default()
+        };
+        let (tx, mut rx) = mpsc::unbounded_channel();
+
+        fetch_feeds(&transport, urls.clone(), 2, &tx).await;
+
+        let (mut progress, mut failed) = (vec![], vec![]);
+        while let Ok(event) = rx.try_recv() {
+            match event {
+                RepositoryEvent::Requested((n, count)) => progress.push((n, count)),
+                RepositoryEvent::FetchFailed(url, attempts, reason) => {
+                    failed.push((url, attempts, reason))
+                }
+                event => panic!("unexpected event {:?}", event),
+            }
+        }
+        progress.sort();
+        assert_eq!(progress, (0..6).map(|n| (n, 6)).collect::<Vec<_>>());
+        failed.sort();
+        assert_eq!(
+            failed,
+            vec![
+                (urls[2].clone(), 1, "HTTP 404 Not Found".to_owned()),
+                (urls[4].clone(), 1, "HTTP 404 Not Found".to_owned()),
+            ]
+        );
+    }
+
+    /// Serves every request with a short feed, recording the URI and user agent each arrived
+    /// with, and returns the address it listens on.
+    fn serve_recording(requests: Arc<Mutex<Vec<(String, String)>>>) -> std::net::SocketAddr {
+        use hyper::service::{make_service_fn, service_fn};
+        use hyper::{Body, Request, Response, Server};
+
+        let make_service = make_service_fn(move |_| {
+            let requests = requests.clone();
+            async move {
+                Ok::<_, Infallible>(service_fn(move |req: Request<Body>| {
+                    let user_agent = req
+                        .headers()
+                        .get(USER_AGENT)
+                        .and_then(|value| value.to_str().ok())
+                        .unwrap_or_default()
+                        .to_owned();
+                    requests
+                        .lock()
+                        .unwrap()
+                        .push((req.uri().to_string(), user_agent));
+                    async { Ok::<_, Infallible>(Response::new(Body::from("<rss/>"))) }
+                }))
+            }
+        });
+        let server = Server::bind(&([127, 0, 0, 1], 0).into()).serve(make_service);
+        let addr = server.local_addr();
+        tokio::spawn(server);
+        addr
+    }
+
+    #[tokio::test]
+    async fn sends_requests_through_the_proxy() {
+        let requests = Arc::new(Mutex::new(vec![]));
+        let proxy = format!("http://{}", serve_recording(requests.clone()));
+        let client = client_builder(Some(&proxy), "moccasin-test")
+            .build()
+            .unwrap();
+
+        // the proxy answers for a host that doesn't resolve, so the request can't go direct
+        let body = client
+            .get("http://feeds.invalid/feed.xml")
+            .send()
+            .await
+            .unwrap()
+            .text()
+            .await
+            .unwrap();
+
+        assert_eq!(body, "<rss/>");
+        assert_eq!(
+            *requests.lock().unwrap(),
+            vec![(
+                "http://feeds.invalid/feed.xml".to_owned(),
+                "moccasin-test".to_owned()
+            )]
+        );
+    }
+
+    #[tokio::test]
+    async fn ignores_an_invalid_proxy() {
+        let requests = Arc::new(Mutex::new(vec![]));
+        let addr = serve_recording(requests.clone());
+        let client = client_builder(Some("not a proxy"), "moccasin-test")
+            .build()
+            .unwrap();
+
+        client
+            .get(format!("http://{}/feed.xml", addr))
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(
+            *requests.lock().unwrap(),
+            vec![("/feed.xml".to_owned(), "moccasin-test".to_owned())]
+        );
     }
 }
@@ -1,3 +1,11 @@
+use crate::config::{Config, ItemSortOrder};
+use crate::feed::{Feed, Item, ItemSummary, QueuedItem, SearchResult, TimelineItem};
+use crate::sync::RemoteItem;
+use crate::websubscriber::Subscription;
+use chrono::{DateTime, Utc};
+use std::collections::{HashMap, VecDeque};
+use std::path::Path;
+
 pub mod sqlite;
 
 pub enum StorageEvent {
@@ -8,3 +16,212 @@ pub enum StorageEvent {
 }
 
 pub struct StorageError;
+
+/// A feed's cached `ETag` and `Last-Modified` values.
+pub type Validators = (Option<String>, Option<String>);
+
+/// Item counts for one feed, or for all of them, as stored.
+#[derive(Debug, Clone, Default)]
+pub struct FeedStats {
+    pub(crate) title: String,
+    pub(crate) total_items: usize,
+    pub(crate) unread_items: usize,
+    pub(crate) starred_items: usize,
+    pub(crate) last_fetched: Option<DateTime<Utc>>,
+    pub(crate) oldest_item: Option<DateTime<Utc>>,
+}
+
+impl FeedStats {
+    /// Sums the counts of `stats`, taking the latest fetch and the oldest item of any feed.
+    pub fn totals<'a>(title: &str, stats: impl IntoIterator<Item = &'a FeedStats>) -> Self {
+        stats.into_iter().fold(
+            FeedStats {
+                title: title.to_owned(),
+                ..Default::default()
+            },
+            |totals, stats| FeedStats {
+                total_items: totals.total_items + stats.total_items,
+                unread_items: totals.unread_items + stats.unread_items,
+                starred_items: totals.starred_items + stats.starred_items,
+                last_fetched: totals.last_fetched.max(stats.last_fetched),
+                oldest_item: match (totals.oldest_item, stats.oldest_item) {
+                    (Some(a), Some(b)) => Some(a.min(b)),
+                    (a, b) => a.or(b),
+                },
+                ..totals
+            },
+        )
+    }
+
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    pub fn total_items(&self) -> usize {
+        self.total_items
+    }
+
+    pub fn unread_items(&self) -> usize {
+        self.unread_items
+    }
+
+    pub fn starred_items(&self) -> usize {
+        self.starred_items
+    }
+
+    pub fn last_fetched(&self) -> Option<DateTime<Utc>> {
+        self.last_fetched
+    }
+
+    /// The average number of items published per day, from the oldest item's date until now.
+    /// Spans shorter than a day count as a whole day.
+    pub fn items_per_day(&self) -> Option<f64> {
+        let oldest = self.oldest_item?;
+        let days = (Utc::now() - oldest).num_seconds() as f64 / 86_400.0;
+        Some(self.total_items as f64 / days.max(1.0))
+    }
+}
+
+/// What checking the database found, and what it repaired.
+#[derive(Debug, Clone, Default)]
+pub struct IntegrityReport {
+    /// Whether the database can be used, which it can't if anything wrong was left unrepaired
+    /// that would lose data in use.
+    pub(crate) ok: bool,
+    pub(crate) warnings: Vec<String>,
+    pub(crate) repaired: Vec<String>,
+}
+
+impl IntegrityReport {
+    pub fn ok(&self) -> bool {
+        self.ok
+    }
+
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+
+    pub fn repaired(&self) -> &[String] {
+        &self.repaired
+    }
+}
+
+/// A persistence backend for feeds and their items.
+pub trait Storage: Sized {
+    fn init(config: &Config) -> Self;
+
+    /// Checks the database, repairing what can be: items of unknown feeds, and read or starred
+    /// flags that aren't 0 or 1. `thorough` also checks every page of the file, items stored
+    /// twice in a feed, and the types of values.
+    fn check_integrity(&self, thorough: bool) -> Result<IntegrityReport, StorageError>;
+
+    /// Writes a consistent copy of the database to `dest`, which must not exist yet, even
+    /// while other connections write to it.
+    fn backup(&self, dest: &Path) -> Result<(), StorageError>;
+
+    /// Whether the file at `path` is an undamaged database of this kind, to restore from.
+    fn is_valid_backup(path: &Path) -> bool;
+
+    /// Reads every feed, leaving out the HTML of their items, which `read_item` reads.
+    fn read_all(&mut self, config: &Config) -> Result<Vec<Feed>, StorageError>;
+
+    /// Reads one feed, leaving out the HTML of its items as `read_all` does.
+    fn read_feed_with_url(&self, url: &str) -> Result<Option<Feed>, StorageError>;
+
+    /// Reads a single item in full, including its HTML.
+    fn read_item(&self, feed_id: &str, item_id: &str) -> Result<Option<Item>, StorageError>;
+
+    /// Reads up to `limit` item summaries of a feed in `order`, starting `offset` items in.
+    fn read_items_page(
+        &self,
+        feed_id: &str,
+        offset: usize,
+        limit: usize,
+        order: ItemSortOrder,
+    ) -> Result<Vec<ItemSummary>, StorageError>;
+
+    /// Reads up to `limit` item summaries across all feeds, newest first, skipping the first
+    /// `offset` of them.
+    fn read_items_by_date(
+        &self,
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<TimelineItem>, StorageError>;
+
+    /// Reads the summaries of every item by `author`, across all feeds, newest first.
+    fn read_items_by_author(&self, author: &str) -> Result<Vec<ItemSummary>, StorageError>;
+
+    /// Finds up to `limit` items, newest first, whose title or text contains `query`,
+    /// ignoring case.
+    fn search_items(&self, query: &str, limit: usize) -> Result<Vec<SearchResult>, StorageError>;
+
+    fn read_validators(&self) -> Result<HashMap<String, Validators>, StorageError>;
+
+    /// Resets the consecutive error count of each feed in `succeeded` and marks it fetched now,
+    /// and increments the count for each feed in `failed`.
+    fn record_fetch_results(
+        &self,
+        succeeded: &[String],
+        failed: &[String],
+    ) -> Result<(), StorageError>;
+
+    /// Counts the stored items of the feed at `url`. A feed that was never stored has none.
+    fn feed_stats(&self, url: &str) -> Result<FeedStats, StorageError>;
+
+    fn write_feed(&self, feed: &Feed) -> Result<StorageEvent, StorageError>;
+
+    fn write_feeds(&mut self, feeds: &[Feed]) -> Result<Vec<StorageEvent>, StorageError>;
+
+    fn set_item_read(
+        &self,
+        feed_id: &str,
+        item_id: &str,
+        read: bool,
+    ) -> Result<StorageEvent, StorageError>;
+
+    fn set_feed_read(&self, feed_id: &str, read: bool) -> Result<StorageEvent, StorageError>;
+
+    /// Flips the starred flag of an item, returning whether it is now starred.
+    fn toggle_item_starred(&self, feed_id: &str, item_id: &str) -> Result<bool, StorageError>;
+
+    /// Stars every item linking to one of `links`, returning how many were not starred before.
+    fn star_items_with_links(&self, links: &[String]) -> Result<usize, StorageError>;
+
+    /// Copies persisted per-item state (read and starred flags) onto freshly fetched feeds.
+    fn load_item_state(&self, feeds: &mut [Feed]) -> Result<(), StorageError>;
+
+    fn delete_feed_with_url(&self, url: &str) -> Result<StorageEvent, StorageError>;
+
+    /// Deletes read, unstarred and unqueued items published before `cutoff`, returning how many
+    /// were deleted.
+    /// Items still listed in `current` are kept, or the next refresh would bring them back unread.
+    fn prune_items(&self, cutoff: DateTime<Utc>, current: &[Feed]) -> Result<usize, StorageError>;
+
+    /// The ids of the stored items linking to any of `links`, by link.
+    fn item_ids_with_links(&self, links: &[&str]) -> Result<HashMap<String, String>, StorageError>;
+
+    /// Reads the ids the sync server gives items, by guid.
+    fn read_remote_ids(&self) -> Result<HashMap<String, u64>, StorageError>;
+
+    /// The latest time any item was changed on the sync server, as of the last pull, or 0
+    /// before the first.
+    fn last_remote_modified(&self) -> Result<i64, StorageError>;
+
+    /// Records the server's ids of `items`, and marks the stored items with their guids read
+    /// or starred where they are on the server.
+    fn write_remote_items(&self, items: &[RemoteItem]) -> Result<(), StorageError>;
+
+    /// Reads every WebSub subscription.
+    fn read_subscriptions(&self) -> Result<Vec<Subscription>, StorageError>;
+
+    /// Stores `subscription`, replacing any other to the same feed.
+    fn write_subscription(&self, subscription: &Subscription) -> Result<(), StorageError>;
+
+    fn delete_subscription(&self, feed_url: &str) -> Result<(), StorageError>;
+
+    /// Reads the reading queue, frontmost item first.
+    fn read_queue(&self) -> Result<VecDeque<QueuedItem>, StorageError>;
+
+    /// Replaces the stored reading queue with `queue`.
+    fn write_queue(&self, queue: &VecDeque<QueuedItem>) -> Result<(), StorageError>;
+}
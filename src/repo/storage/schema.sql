@@ -8,10 +8,14 @@ CREATE TABLE IF NOT EXISTS feeds (
     link TEXT NOT NULL,
     ttl TEXT,
     pub_date TEXT,
-    last_fetched TEXT
+    last_fetched TEXT,
+    etag TEXT,
+    last_modified TEXT,
+    format TEXT NOT NULL DEFAULT 'rss',
+    consecutive_errors INTEGER NOT NULL DEFAULT 0
 );
 CREATE TABLE IF NOT EXISTS items (
-    id TEXT NOT NULL PRIMARY KEY,
+    id TEXT NOT NULL,
     feed_id TEXT NOT NULL,
     title TEXT,
     author TEXT,
@@ -21,6 +25,31 @@ CREATE TABLE IF NOT EXISTS items (
     categories TEXT,
     link TEXT,
     pub_date TEXT,
+    read INTEGER NOT NULL DEFAULT 0,
+    starred INTEGER NOT NULL DEFAULT 0,
+    published_at INTEGER,
+    enclosure_url TEXT,
+    enclosure_length INTEGER,
+    enclosure_type TEXT,
+    duration TEXT,
+    PRIMARY KEY (feed_id, id),
     FOREIGN KEY(feed_id) REFERENCES feeds(id) ON DELETE CASCADE
 );
+CREATE TABLE IF NOT EXISTS queue (
+    position INTEGER NOT NULL PRIMARY KEY,
+    feed_id TEXT NOT NULL,
+    item_id TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS remote_items (
+    guid TEXT NOT NULL PRIMARY KEY,
+    remote_id INTEGER NOT NULL,
+    last_modified INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS websub_subscriptions (
+    feed_url TEXT NOT NULL PRIMARY KEY,
+    hub TEXT NOT NULL,
+    topic TEXT NOT NULL,
+    callback_id TEXT NOT NULL UNIQUE,
+    expires_at INTEGER
+);
 END;
\ No newline at end of file
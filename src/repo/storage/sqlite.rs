@@ -1,8 +1,91 @@
-use super::{StorageError, StorageEvent};
-use crate::config::Config;
-use crate::feed::{Feed, Item};
+use super::{FeedStats, IntegrityReport, Storage, StorageError, StorageEvent, Validators};
+use crate::config::{Config, ItemSortOrder};
+use crate::feed::{Enclosure, Feed, Item, ItemSummary, QueuedItem, SearchResult, TimelineItem};
+use crate::migrations::MigrationRunner;
+use crate::sync::RemoteItem;
 use crate::util;
-use rusqlite::{Connection, Result, Row, Transaction};
+use crate::websubscriber::Subscription;
+use chrono::{DateTime, Local, Utc};
+use rusqlite::{
+    params, Connection, ErrorCode, OpenFlags, OptionalExtension, Result, Row, Transaction,
+};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::Path;
+
+/// How much of an item's text a search result shows around the match.
+const SNIPPET_WIDTH: usize = 80;
+
+/// Inserts a feed, or updates it in place. Replacing the row instead would cascade to its items.
+const FEED_UPSERT: &str = "INSERT INTO feeds(
+        id,
+        title,
+        description,
+        categories,
+        url,
+        link,
+        ttl,
+        pub_date,
+        last_fetched,
+        etag,
+        last_modified,
+        format
+    ) VALUES(?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)
+    ON CONFLICT(id) DO UPDATE SET
+        title = excluded.title,
+        description = excluded.description,
+        categories = excluded.categories,
+        url = excluded.url,
+        link = excluded.link,
+        ttl = excluded.ttl,
+        pub_date = excluded.pub_date,
+        last_fetched = excluded.last_fetched,
+        etag = excluded.etag,
+        last_modified = excluded.last_modified,
+        format = excluded.format";
+
+/// Inserts an item, or updates its content if the feed already has an item with that id,
+/// keeping its read and starred flags. Items were once keyed by `<feed id>:<link>` whenever
+/// their GUID wasn't a permalink, so a new item takes the flags of such a row if one exists.
+const ITEM_UPSERT: &str = "INSERT INTO items(
+        id,
+        feed_id,
+        title,
+        author,
+        content,
+        description,
+        text_description,
+        categories,
+        link,
+        pub_date,
+        published_at,
+        enclosure_url,
+        enclosure_length,
+        enclosure_type,
+        duration,
+        read,
+        starred
+    ) VALUES(
+        ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15,
+        IFNULL((SELECT read FROM items WHERE feed_id = ?2 AND id = ?2 || ':' || ?9), 0),
+        IFNULL((SELECT starred FROM items WHERE feed_id = ?2 AND id = ?2 || ':' || ?9), 0)
+    ) ON CONFLICT(feed_id, id) DO UPDATE SET
+        title = excluded.title,
+        author = excluded.author,
+        content = excluded.content,
+        description = excluded.description,
+        text_description = excluded.text_description,
+        categories = excluded.categories,
+        link = excluded.link,
+        pub_date = excluded.pub_date,
+        published_at = excluded.published_at,
+        enclosure_url = excluded.enclosure_url,
+        enclosure_length = excluded.enclosure_length,
+        enclosure_type = excluded.enclosure_type,
+        duration = excluded.duration";
+
+/// Drops the row an item was stored under by its link, once it is stored under its GUID.
+const LEGACY_ITEM_DELETE: &str =
+    "DELETE FROM items WHERE feed_id = ?1 AND id = ?1 || ':' || ?2 AND id != ?3";
 
 pub struct SQLiteStorage {
     conn: Connection,
@@ -21,27 +104,79 @@ impl<'stmt> FromRow<'stmt> for Feed {
             categories: vec![],
             url: row.get(4).unwrap(),
             link: row.get(5).unwrap(),
-            ttl: row.get(6).ok(),
+            ttl: optional_text(row, 6),
             items: vec![],
-            pub_date: row.get(7).ok(),
-            last_fetched: row.get(8).ok(),
+            pub_date: optional_text(row, 7),
+            last_fetched: optional_text(row, 8),
+            etag: row.get(9).ok().flatten(),
+            last_modified: row.get(10).ok().flatten(),
+            format: row
+                .get::<_, String>(11)
+                .ok()
+                .and_then(|f| f.parse().ok())
+                .unwrap_or_default(),
+            consecutive_errors: row.get(12).unwrap_or_default(),
+            hub: None,
+            parse_warnings: vec![],
+            resolved_url: None,
         }
     }
 }
 
+/// Reads a nullable text column, which may also hold the text `NULL` written for missing values.
+fn optional_text(row: &Row, index: usize) -> Option<String> {
+    row.get::<_, Option<String>>(index)
+        .ok()
+        .flatten()
+        .filter(|text| text != "NULL")
+}
+
+/// Reads an enclosure from its URL, length and type columns, starting at `index`.
+fn enclosure(row: &Row, index: usize) -> Option<Enclosure> {
+    let url = row.get::<_, Option<String>>(index).ok().flatten()?;
+    Some(Enclosure {
+        url,
+        length: row.get(index + 1).ok().flatten().unwrap_or_default(),
+        mime_type: row.get(index + 2).ok().flatten().unwrap_or_default(),
+    })
+}
+
+/// The `ORDER BY` terms for items in `order`, falling back to the order they were stored in.
+/// Undated items go last in either date order.
+fn order_by(order: ItemSortOrder) -> &'static str {
+    match order {
+        ItemSortOrder::FeedDefault => "rowid",
+        ItemSortOrder::NewestFirst => "published_at IS NULL, published_at DESC, rowid",
+        ItemSortOrder::OldestFirst => "published_at IS NULL, published_at, rowid",
+        ItemSortOrder::TitleAz => "title COLLATE NOCASE, rowid",
+        ItemSortOrder::TitleZa => "title COLLATE NOCASE DESC, rowid",
+        ItemSortOrder::UnreadFirst => "read, rowid",
+    }
+}
+
+pub(crate) fn parse_date(date: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc2822(date)
+        .ok()
+        .map(|date| date.with_timezone(&Utc))
+}
+
 impl<'stmt> Item {
     fn from_row(row: &'stmt Row, feed_id: &str) -> Self {
         Item {
             id: row.get(0).unwrap(),
             feed_id: feed_id.into(),
-            title: row.get(2).ok(),
-            author: row.get(3).ok(),
-            content: row.get(4).ok(),
-            description: row.get(5).ok(),
-            text_description: row.get(6).ok(),
+            title: optional_text(row, 2),
+            author: optional_text(row, 3),
+            content: optional_text(row, 4),
+            description: optional_text(row, 5),
+            text_description: optional_text(row, 6),
             categories: vec![], // FIXME
-            link: row.get(8).ok(),
-            pub_date: row.get(9).ok(),
+            link: optional_text(row, 8),
+            pub_date: optional_text(row, 9),
+            enclosure: enclosure(row, 13),
+            duration: row.get(16).ok().flatten(),
+            read: row.get(10).unwrap_or_default(),
+            starred: row.get(11).unwrap_or_default(),
         }
     }
 }
@@ -52,27 +187,14 @@ impl SQLiteStorage {
         feed: &Feed,
         tx: &Transaction,
     ) -> Result<StorageEvent, StorageError> {
-        let stmt = "INSERT OR REPLACE INTO feeds(
-            id,
-            title,
-            description,
-            categories,
-            url,
-            link,
-            ttl,
-            pub_date,
-            last_fetched
-        ) VALUES(
-            IFNULL((SELECT id FROM feeds WHERE id = ?1), ?1),
-            ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9
-        )";
+        let stmt = FEED_UPSERT;
 
         let mut stmt = tx.prepare_cached(stmt).map_err(|err| {
             log::warn!("{:?}", err);
             StorageError
         })?;
 
-        match stmt.execute([
+        match stmt.execute(params![
             feed.id(),
             feed.title(),
             feed.description(),
@@ -82,6 +204,9 @@ impl SQLiteStorage {
             feed.ttl().unwrap_or("NULL"),
             feed.pub_date().unwrap_or("NULL"),
             feed.last_fetched().unwrap_or("NULL"),
+            feed.etag(),
+            feed.last_modified(),
+            feed.format().as_str(),
         ]) {
             Ok(_) => {
                 for item in feed.items() {
@@ -99,44 +224,11 @@ impl SQLiteStorage {
 }
 
 impl SQLiteStorage {
-    pub fn init(config: &Config) -> Self {
-        let conn = if config.should_cache() {
-            Connection::open(config.db_path()).expect("Could not open database")
-        } else {
-            Connection::open_in_memory().expect("Could not open database")
-        };
-
-        conn.execute_batch(include_str!("schema.sql"))
-            .expect("Failed to initialize DB schema");
-
-        Self { conn }
-    }
-
-    pub fn read_all(&mut self, config: &Config) -> Result<Vec<Feed>, StorageError> {
-        let stmt = "SELECT * FROM feeds";
-        let mut stmt = self.conn.prepare_cached(stmt).map_err(|_| StorageError)?;
-
-        let feeds_iter = stmt.query_map([], |row| {
-            let mut feed = Feed::from_row(row);
-            match self.read_items_for_feed_id(feed.id()) {
-                Ok(items) => feed.items = items,
-                Err(_) => {
-                    log::error!("Failed to fetch items for feed {}", feed.id());
-                }
-            }
-            Ok(feed)
-        });
-        let mut feeds = feeds_iter
-            .expect("Could not unwrap feeds")
-            .filter_map(|r| r.ok())
-            .collect::<Vec<_>>();
-
-        util::sort_feeds(&mut feeds, config);
-        Ok(feeds)
-    }
-
-    pub fn read_items_for_feed_id(&self, id: &str) -> Result<Vec<Item>, StorageError> {
-        let stmt = "SELECT * FROM items WHERE feed_id = ?1";
+    /// Reads the items of a feed without their HTML `content` and `description`.
+    fn read_items_for_feed_id(&self, id: &str) -> Result<Vec<Item>, StorageError> {
+        let stmt = "SELECT id, feed_id, title, author, NULL, NULL, text_description, categories,
+            link, pub_date, read, starred, published_at, enclosure_url, enclosure_length,
+            enclosure_type, duration FROM items WHERE feed_id = ?1 ORDER BY rowid";
         let mut stmt = self.conn.prepare_cached(stmt).map_err(|_| StorageError)?;
 
         let items_iter = stmt.query_map([id], |r| Ok(Item::from_row(r, id)));
@@ -148,25 +240,12 @@ impl SQLiteStorage {
         Ok(items)
     }
 
-    pub fn write_feed(
+    fn write_feed_in(
         &self,
         feed: &Feed,
         tx: Option<&Transaction>,
     ) -> Result<StorageEvent, StorageError> {
-        let stmt = "INSERT OR REPLACE INTO feeds(
-            id,
-            title,
-            description,
-            categories,
-            url,
-            link,
-            ttl,
-            pub_date,
-            last_fetched
-        ) VALUES(
-            IFNULL((SELECT id FROM feeds WHERE id = ?1), ?1),
-            ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9
-        )";
+        let stmt = FEED_UPSERT;
 
         let mut stmt = (if let Some(tx) = tx {
             tx.prepare_cached(stmt)
@@ -178,7 +257,7 @@ impl SQLiteStorage {
             StorageError
         })?;
 
-        match stmt.execute([
+        match stmt.execute(params![
             feed.id(),
             feed.title(),
             feed.description(),
@@ -188,6 +267,9 @@ impl SQLiteStorage {
             feed.ttl().unwrap_or("NULL"),
             feed.pub_date().unwrap_or("NULL"),
             feed.last_fetched().unwrap_or("NULL"),
+            feed.etag(),
+            feed.last_modified(),
+            feed.format().as_str(),
         ]) {
             Ok(_) => {
                 for item in feed.items() {
@@ -203,45 +285,290 @@ impl SQLiteStorage {
         }
     }
 
-    pub fn write_feeds(&mut self, feeds: &Vec<Feed>) -> Result<Vec<StorageEvent>, StorageError> {
-        if let Ok(tx) = self.conn.transaction() {
-            let feed_stmt = "INSERT OR REPLACE INTO feeds(
-                    id,
-                    title,
-                    description,
-                    categories,
-                    url,
-                    link,
-                    ttl,
-                    pub_date,
-                    last_fetched
-                ) VALUES(
-                    IFNULL((SELECT id FROM feeds WHERE id = ?1), ?1),
-                    ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9
-                )";
-
-            let item_stmt = "INSERT OR REPLACE INTO items(
-                    id,
-                    feed_id,
-                    title,
-                    author,
-                    content,
-                    description,
-                    text_description,
-                    categories,
-                    link,
-                    pub_date
-                ) VALUES(
-                    IFNULL((SELECT id FROM items WHERE id = ?1), ?1),
-                    ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10
-                )";
+    fn write_item(&self, item: &Item) -> Result<StorageEvent, StorageError> {
+        match upsert_item(&self.conn, item) {
+            Ok(_) => Ok(StorageEvent::Insert),
+            Err(err) => {
+                log::error!("{:?}", err);
+                Err(StorageError)
+            }
+        }
+    }
+}
 
-            let mut feed_stmt = tx.prepare_cached(feed_stmt).map_err(|err| {
-                log::warn!("{:?}", err);
+impl Storage for SQLiteStorage {
+    fn init(config: &Config) -> Self {
+        let conn = if config.should_cache() {
+            Connection::open(config.db_path()).expect("Could not open database")
+        } else {
+            Connection::open_in_memory().expect("Could not open database")
+        };
+
+        conn.execute_batch(include_str!("schema.sql"))
+            .expect("Failed to initialize DB schema");
+        MigrationRunner::new(&conn)
+            .run()
+            .expect("Failed to migrate DB schema");
+
+        Self { conn }
+    }
+
+    fn backup(&self, dest: &Path) -> Result<(), StorageError> {
+        let dest = dest.to_str().ok_or(StorageError)?;
+        // copies from a single read transaction, unlike copying the file
+        self.conn
+            .execute("VACUUM INTO ?1", [dest])
+            .map(|_| ())
+            .map_err(|err| {
+                log::error!("Failed to back up DB: {:?}", err);
                 StorageError
-            })?;
+            })
+    }
+
+    fn is_valid_backup(path: &Path) -> bool {
+        let Ok(conn) = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY) else {
+            return false;
+        };
+        let ok = conn
+            .query_row("PRAGMA quick_check", [], |row| row.get::<_, String>(0))
+            .is_ok_and(|result| result == "ok");
+        let has_feeds = conn
+            .prepare("SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'feeds'")
+            .and_then(|mut stmt| stmt.exists([]))
+            .unwrap_or(false);
+        ok && has_feeds
+    }
+
+    fn check_integrity(&self, thorough: bool) -> Result<IntegrityReport, StorageError> {
+        match check_integrity(&self.conn, thorough) {
+            Ok(report) => Ok(report),
+            Err(rusqlite::Error::SqliteFailure(err, message))
+                if err.code == ErrorCode::DatabaseCorrupt =>
+            {
+                Ok(IntegrityReport {
+                    ok: false,
+                    warnings: vec![message.unwrap_or_else(|| err.to_string())],
+                    ..Default::default()
+                })
+            }
+            Err(err) => {
+                log::error!("Failed to check DB integrity: {:?}", err);
+                Err(StorageError)
+            }
+        }
+    }
+
+    fn read_all(&mut self, config: &Config) -> Result<Vec<Feed>, StorageError> {
+        let stmt = "SELECT * FROM feeds";
+        let mut stmt = self.conn.prepare_cached(stmt).map_err(|_| StorageError)?;
+
+        let feeds_iter = stmt.query_map([], |row| {
+            let mut feed = Feed::from_row(row);
+            match self.read_items_for_feed_id(feed.id()) {
+                Ok(items) => feed.items = items,
+                Err(_) => {
+                    log::error!("Failed to fetch items for feed {}", feed.id());
+                }
+            }
+            Ok(feed)
+        });
+        let mut feeds = feeds_iter
+            .expect("Could not unwrap feeds")
+            .filter_map(|r| r.ok())
+            .collect::<Vec<_>>();
+
+        util::sort_feeds(&mut feeds, config);
+        Ok(feeds)
+    }
+
+    fn read_feed_with_url(&self, url: &str) -> Result<Option<Feed>, StorageError> {
+        let stmt = "SELECT * FROM feeds WHERE url = ?1";
+        let mut stmt = self.conn.prepare_cached(stmt).map_err(|_| StorageError)?;
+
+        let feed = stmt
+            .query_row([url], |row| Ok(Feed::from_row(row)))
+            .optional()
+            .map_err(|_| StorageError)?;
+
+        match feed {
+            Some(mut feed) => {
+                feed.items = self.read_items_for_feed_id(feed.id())?;
+                Ok(Some(feed))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn read_item(&self, feed_id: &str, item_id: &str) -> Result<Option<Item>, StorageError> {
+        let stmt = "SELECT * FROM items WHERE feed_id = ?1 AND id = ?2";
+        let mut stmt = self.conn.prepare_cached(stmt).map_err(|_| StorageError)?;
+
+        stmt.query_row([feed_id, item_id], |row| Ok(Item::from_row(row, feed_id)))
+            .optional()
+            .map_err(|_| StorageError)
+    }
+
+    fn read_items_page(
+        &self,
+        feed_id: &str,
+        offset: usize,
+        limit: usize,
+        order: ItemSortOrder,
+    ) -> Result<Vec<ItemSummary>, StorageError> {
+        let stmt = format!(
+            "SELECT id, title, link, pub_date, read, starred, enclosure_url,
+                enclosure_length, enclosure_type, duration, author
+            FROM items WHERE feed_id = ?1 ORDER BY {} LIMIT ?2 OFFSET ?3",
+            order_by(order)
+        );
+        let mut stmt = self.conn.prepare_cached(&stmt).map_err(|_| StorageError)?;
+
+        let items = stmt
+            .query_map(params![feed_id, limit, offset], |row| {
+                Ok(ItemSummary {
+                    id: row.get(0)?,
+                    feed_id: feed_id.to_owned(),
+                    title: optional_text(row, 1),
+                    link: optional_text(row, 2),
+                    pub_date: optional_text(row, 3),
+                    author: optional_text(row, 10),
+                    enclosure: enclosure(row, 6),
+                    duration: row.get(9)?,
+                    read: row.get(4)?,
+                    starred: row.get(5)?,
+                })
+            })
+            .map_err(|_| StorageError)?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(items)
+    }
+
+    fn read_items_by_date(
+        &self,
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<TimelineItem>, StorageError> {
+        // undated items go last
+        let stmt = "SELECT items.id, items.feed_id, items.title, items.link, items.pub_date,
+                items.read, items.starred, feeds.title, items.enclosure_url,
+                items.enclosure_length, items.enclosure_type, items.duration, items.author
+            FROM items JOIN feeds ON feeds.id = items.feed_id
+            ORDER BY items.published_at IS NULL, items.published_at DESC, items.rowid
+            LIMIT ?1 OFFSET ?2";
+        let mut stmt = self.conn.prepare_cached(stmt).map_err(|_| StorageError)?;
+
+        let items = stmt
+            .query_map(params![limit, offset], |row| {
+                Ok(TimelineItem {
+                    summary: ItemSummary {
+                        id: row.get(0)?,
+                        feed_id: row.get(1)?,
+                        title: optional_text(row, 2),
+                        link: optional_text(row, 3),
+                        pub_date: optional_text(row, 4),
+                        author: optional_text(row, 12),
+                        enclosure: enclosure(row, 8),
+                        duration: row.get(11)?,
+                        read: row.get(5)?,
+                        starred: row.get(6)?,
+                    },
+                    feed_title: row.get(7)?,
+                })
+            })
+            .map_err(|_| StorageError)?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(items)
+    }
+
+    fn read_items_by_author(&self, author: &str) -> Result<Vec<ItemSummary>, StorageError> {
+        let stmt = "SELECT id, feed_id, title, link, pub_date, read, starred, enclosure_url,
+                enclosure_length, enclosure_type, duration, author
+            FROM items WHERE author = ?1
+            ORDER BY published_at IS NULL, published_at DESC, rowid";
+        let mut stmt = self.conn.prepare_cached(stmt).map_err(|_| StorageError)?;
+
+        let items = stmt
+            .query_map([author], |row| {
+                Ok(ItemSummary {
+                    id: row.get(0)?,
+                    feed_id: row.get(1)?,
+                    title: optional_text(row, 2),
+                    link: optional_text(row, 3),
+                    pub_date: optional_text(row, 4),
+                    author: optional_text(row, 11),
+                    enclosure: enclosure(row, 7),
+                    duration: row.get(10)?,
+                    read: row.get(5)?,
+                    starred: row.get(6)?,
+                })
+            })
+            .map_err(|_| StorageError)?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(items)
+    }
 
-            let mut item_stmt = tx.prepare_cached(item_stmt).map_err(|err| {
+    fn search_items(&self, query: &str, limit: usize) -> Result<Vec<SearchResult>, StorageError> {
+        // LIKE ignores case, at least for ASCII, and missing text is stored as "NULL"
+        let stmt = "SELECT items.id, items.feed_id, feeds.title, items.title, items.link,
+                items.text_description
+            FROM items JOIN feeds ON feeds.id = items.feed_id
+            WHERE NULLIF(items.title, 'NULL') LIKE ?1 ESCAPE '\\'
+                OR NULLIF(items.text_description, 'NULL') LIKE ?1 ESCAPE '\\'
+            ORDER BY items.published_at IS NULL, items.published_at DESC, items.rowid
+            LIMIT ?2";
+        let mut stmt = self.conn.prepare_cached(stmt).map_err(|_| StorageError)?;
+
+        let pattern = format!(
+            "%{}%",
+            query
+                .replace('\\', "\\\\")
+                .replace('%', "\\%")
+                .replace('_', "\\_")
+        );
+        let results = stmt
+            .query_map(params![pattern, limit], |row| {
+                let text = optional_text(row, 5).unwrap_or_default();
+                Ok(SearchResult {
+                    item_id: row.get(0)?,
+                    feed_id: row.get(1)?,
+                    feed_title: row.get(2)?,
+                    item_title: optional_text(row, 3),
+                    item_link: optional_text(row, 4),
+                    snippet: util::snippet(&text, query, SNIPPET_WIDTH),
+                })
+            })
+            .map_err(|_| StorageError)?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(results)
+    }
+
+    /// Returns the cached `(ETag, Last-Modified)` validators for every stored feed, keyed by URL.
+    fn read_validators(&self) -> Result<HashMap<String, Validators>, StorageError> {
+        let stmt = "SELECT url, etag, last_modified FROM feeds";
+        let mut stmt = self.conn.prepare_cached(stmt).map_err(|_| StorageError)?;
+
+        let validators = stmt
+            .query_map([], |row| Ok((row.get(0)?, (row.get(1)?, row.get(2)?))))
+            .map_err(|_| StorageError)?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(validators)
+    }
+
+    fn write_feeds(&mut self, feeds: &[Feed]) -> Result<Vec<StorageEvent>, StorageError> {
+        if let Ok(tx) = self.conn.transaction() {
+            let feed_stmt = FEED_UPSERT;
+
+            let mut feed_stmt = tx.prepare_cached(feed_stmt).map_err(|err| {
                 log::warn!("{:?}", err);
                 StorageError
             })?;
@@ -249,7 +576,7 @@ impl SQLiteStorage {
             let mut events = vec![];
 
             for feed in feeds {
-                if let Err(e) = feed_stmt.execute([
+                if let Err(e) = feed_stmt.execute(params![
                     feed.id(),
                     feed.title(),
                     feed.description(),
@@ -259,24 +586,16 @@ impl SQLiteStorage {
                     feed.ttl().unwrap_or("NULL"),
                     feed.pub_date().unwrap_or("NULL"),
                     feed.last_fetched().unwrap_or("NULL"),
+                    feed.etag(),
+                    feed.last_modified(),
+                    feed.format().as_str(),
                 ]) {
                     log::error!("{e:?}");
                     return Err(StorageError);
                 }
 
                 for item in feed.items() {
-                    if let Err(e) = item_stmt.execute([
-                        item.id(),
-                        item.feed_id(),
-                        item.title().unwrap_or("NULL"),
-                        item.author().unwrap_or("NULL"),
-                        item.content().unwrap_or("NULL"),
-                        item.description().unwrap_or("NULL"),
-                        item.description().unwrap_or("NULL"),
-                        "[]",
-                        item.link().unwrap_or("NULL"),
-                        item.pub_date().unwrap_or("NULL"),
-                    ]) {
+                    if let Err(e) = upsert_item(&tx, item) {
                         log::error!("{e:?}");
                         return Err(StorageError);
                     }
@@ -284,6 +603,12 @@ impl SQLiteStorage {
 
                 events.push(StorageEvent::Insert);
             }
+
+            drop(feed_stmt);
+            tx.commit().map_err(|err| {
+                log::error!("{:?}", err);
+                StorageError
+            })?;
             return Ok(events);
         } else {
             log::error!("");
@@ -291,41 +616,106 @@ impl SQLiteStorage {
         }
     }
 
-    pub fn write_item(&self, item: &Item) -> Result<StorageEvent, StorageError> {
-        let stmt = "INSERT OR REPLACE INTO items(
-            id,
-            feed_id,
+    fn record_fetch_results(
+        &self,
+        succeeded: &[String],
+        failed: &[String],
+    ) -> Result<(), StorageError> {
+        let tx = self
+            .conn
+            .unchecked_transaction()
+            .map_err(|_| StorageError)?;
+        // unchanged feeds aren't rewritten, so their fetch time is updated here
+        let now = Local::now().to_rfc2822();
+        for url in succeeded {
+            tx.execute(
+                "UPDATE feeds SET consecutive_errors = 0, last_fetched = ?2 WHERE url = ?1",
+                [url, &now],
+            )
+            .map_err(|_| StorageError)?;
+        }
+        for url in failed {
+            tx.execute(
+                "UPDATE feeds SET consecutive_errors = MIN(consecutive_errors + 1, 255) WHERE url = ?1",
+                [url],
+            )
+            .map_err(|_| StorageError)?;
+        }
+        tx.commit().map_err(|_| StorageError)
+    }
+
+    fn write_feed(&self, feed: &Feed) -> Result<StorageEvent, StorageError> {
+        self.write_feed_in(feed, None)
+    }
+
+    fn feed_stats(&self, url: &str) -> Result<FeedStats, StorageError> {
+        let feed = self
+            .conn
+            .query_row(
+                "SELECT id, title, last_fetched FROM feeds WHERE url = ?1",
+                [url],
+                |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get::<_, String>(1)?,
+                        row.get::<_, Option<String>>(2)?,
+                    ))
+                },
+            )
+            .optional()
+            .map_err(|_| StorageError)?;
+        let Some((id, title, last_fetched)) = feed else {
+            return Ok(FeedStats {
+                title: url.to_owned(),
+                ..Default::default()
+            });
+        };
+
+        let (total_items, unread_items, starred_items) = self
+            .conn
+            .query_row(
+                "SELECT COUNT(*), IFNULL(SUM(read = 0), 0), IFNULL(SUM(starred), 0)
+                FROM items WHERE feed_id = ?1",
+                [&id],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .map_err(|err| {
+                log::error!("{:?}", err);
+                StorageError
+            })?;
+
+        // dates are stored as RFC 2822 text, which SQLite can't compare, so find the oldest here
+        let stmt = "SELECT pub_date FROM items WHERE feed_id = ?1 AND pub_date IS NOT NULL";
+        let mut stmt = self.conn.prepare_cached(stmt).map_err(|_| StorageError)?;
+        let oldest_item = stmt
+            .query_map([&id], |row| row.get::<_, String>(0))
+            .map_err(|_| StorageError)?
+            .filter_map(|r| r.ok())
+            .filter_map(|date| parse_date(&date))
+            .min();
+
+        Ok(FeedStats {
             title,
-            author,
-            content,
-            description,
-            text_description,
-            categories,
-            link,
-            pub_date
-        ) VALUES(
-            IFNULL((SELECT id FROM items WHERE id = ?1), ?1),
-            ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10
-        )";
-
-        let mut stmt = self.conn.prepare_cached(stmt).map_err(|err| {
-            log::warn!("{:?}", err);
-            StorageError
-        })?;
+            total_items,
+            unread_items,
+            starred_items,
+            last_fetched: last_fetched.as_deref().and_then(parse_date),
+            oldest_item,
+        })
+    }
 
-        match stmt.execute([
-            item.id(),
-            item.feed_id(),
-            item.title().unwrap_or("NULL"),
-            item.author().unwrap_or("NULL"),
-            item.content().unwrap_or("NULL"),
-            item.description().unwrap_or("NULL"),
-            item.description().unwrap_or("NULL"),
-            "[]",
-            item.link().unwrap_or("NULL"),
-            item.pub_date().unwrap_or("NULL"),
-        ]) {
-            Ok(_) => Ok(StorageEvent::Insert),
+    fn set_item_read(
+        &self,
+        feed_id: &str,
+        item_id: &str,
+        read: bool,
+    ) -> Result<StorageEvent, StorageError> {
+        let stmt = "UPDATE items SET read = ?3 WHERE feed_id = ?1 AND id = ?2";
+        let mut stmt = self.conn.prepare_cached(stmt).map_err(|_| StorageError)?;
+
+        match stmt.execute((feed_id, item_id, read)) {
+            Ok(count) if count > 0 => Ok(StorageEvent::Update),
+            Ok(_) => Ok(StorageEvent::NoOp),
             Err(err) => {
                 log::error!("{:?}", err);
                 Err(StorageError)
@@ -333,7 +723,55 @@ impl SQLiteStorage {
         }
     }
 
-    pub fn delete_feed_with_url(&self, url: &str) -> Result<StorageEvent, StorageError> {
+    fn set_feed_read(&self, feed_id: &str, read: bool) -> Result<StorageEvent, StorageError> {
+        let stmt = "UPDATE items SET read = ?2 WHERE feed_id = ?1";
+        let mut stmt = self.conn.prepare_cached(stmt).map_err(|_| StorageError)?;
+
+        match stmt.execute((feed_id, read)) {
+            Ok(count) if count > 0 => Ok(StorageEvent::Update),
+            Ok(_) => Ok(StorageEvent::NoOp),
+            Err(err) => {
+                log::error!("{:?}", err);
+                Err(StorageError)
+            }
+        }
+    }
+
+    fn toggle_item_starred(&self, feed_id: &str, item_id: &str) -> Result<bool, StorageError> {
+        let stmt =
+            "UPDATE items SET starred = NOT starred WHERE feed_id = ?1 AND id = ?2 RETURNING starred";
+        let mut stmt = self.conn.prepare_cached(stmt).map_err(|_| StorageError)?;
+
+        stmt.query_row((feed_id, item_id), |row| row.get(0))
+            .map_err(|err| {
+                log::error!("{:?}", err);
+                StorageError
+            })
+    }
+
+    fn load_item_state(&self, feeds: &mut [Feed]) -> Result<(), StorageError> {
+        let stmt =
+            "SELECT id, read, starred FROM items WHERE feed_id = ?1 AND (read = 1 OR starred = 1)";
+        let mut stmt = self.conn.prepare_cached(stmt).map_err(|_| StorageError)?;
+
+        for feed in feeds {
+            let state = stmt
+                .query_map([feed.id()], |row| {
+                    Ok((row.get::<_, String>(0)?, (row.get(1)?, row.get(2)?)))
+                })
+                .map_err(|_| StorageError)?
+                .filter_map(|r| r.ok())
+                .collect::<HashMap<_, (bool, bool)>>();
+
+            for item in feed.items.iter_mut() {
+                (item.read, item.starred) = state.get(item.id()).copied().unwrap_or_default();
+            }
+        }
+
+        Ok(())
+    }
+
+    fn delete_feed_with_url(&self, url: &str) -> Result<StorageEvent, StorageError> {
         let stmt = "DELETE FROM feeds WHERE url = ?1";
         let mut stmt = self.conn.prepare_cached(stmt).map_err(|_| StorageError)?;
 
@@ -346,4 +784,381 @@ impl SQLiteStorage {
             }
         }
     }
+
+    fn prune_items(&self, cutoff: DateTime<Utc>, current: &[Feed]) -> Result<usize, StorageError> {
+        let current = current
+            .iter()
+            .flat_map(|feed| feed.items().iter())
+            .map(|item| (item.feed_id(), item.id()))
+            .collect::<HashSet<_>>();
+
+        // dates are stored as RFC 2822 text, which SQLite can't compare, so filter here
+        let stmt = "SELECT feed_id, id, pub_date FROM items
+            WHERE read = 1 AND starred = 0 AND NOT EXISTS (
+                SELECT 1 FROM queue WHERE queue.feed_id = items.feed_id AND queue.item_id = items.id
+            )";
+        let mut stmt = self.conn.prepare_cached(stmt).map_err(|_| StorageError)?;
+        let expired = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, Option<String>>(2)?,
+                ))
+            })
+            .map_err(|_| StorageError)?
+            .filter_map(|r| r.ok())
+            .filter(|(feed_id, id, _)| !current.contains(&(feed_id.as_str(), id.as_str())))
+            .filter(|(_, _, pub_date)| {
+                pub_date
+                    .as_deref()
+                    .and_then(|date| DateTime::parse_from_rfc2822(date).ok())
+                    .is_some_and(|date| date < cutoff)
+            })
+            .collect::<Vec<_>>();
+
+        let tx = self
+            .conn
+            .unchecked_transaction()
+            .map_err(|_| StorageError)?;
+        let mut count = 0;
+        {
+            let stmt = "DELETE FROM items WHERE feed_id = ?1 AND id = ?2";
+            let mut stmt = tx.prepare_cached(stmt).map_err(|_| StorageError)?;
+            for (feed_id, id, _) in expired {
+                count += stmt.execute([feed_id, id]).map_err(|err| {
+                    log::error!("{:?}", err);
+                    StorageError
+                })?;
+            }
+        }
+        tx.commit().map_err(|_| StorageError)?;
+
+        Ok(count)
+    }
+
+    fn star_items_with_links(&self, links: &[String]) -> Result<usize, StorageError> {
+        let tx = self
+            .conn
+            .unchecked_transaction()
+            .map_err(|_| StorageError)?;
+        let mut count = 0;
+        {
+            let stmt = "UPDATE items SET starred = 1 WHERE link = ?1 AND starred = 0";
+            let mut stmt = tx.prepare_cached(stmt).map_err(|_| StorageError)?;
+            for link in links {
+                count += stmt.execute([link]).map_err(|err| {
+                    log::error!("{:?}", err);
+                    StorageError
+                })?;
+            }
+        }
+        tx.commit().map_err(|_| StorageError)?;
+
+        Ok(count)
+    }
+
+    fn item_ids_with_links(&self, links: &[&str]) -> Result<HashMap<String, String>, StorageError> {
+        let stmt = "SELECT id FROM items WHERE link = ?1";
+        let mut stmt = self.conn.prepare_cached(stmt).map_err(|_| StorageError)?;
+        let mut ids = HashMap::new();
+        for link in links {
+            let id = stmt
+                .query_row([link], |row| row.get::<_, String>(0))
+                .optional()
+                .map_err(|_| StorageError)?;
+            if let Some(id) = id {
+                ids.insert(link.to_string(), id);
+            }
+        }
+        Ok(ids)
+    }
+
+    fn read_remote_ids(&self) -> Result<HashMap<String, u64>, StorageError> {
+        let stmt = "SELECT guid, remote_id FROM remote_items";
+        let mut stmt = self.conn.prepare_cached(stmt).map_err(|_| StorageError)?;
+        let ids = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(|_| StorageError)?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(ids)
+    }
+
+    fn last_remote_modified(&self) -> Result<i64, StorageError> {
+        self.conn
+            .query_row(
+                "SELECT COALESCE(MAX(last_modified), 0) FROM remote_items",
+                [],
+                |row| row.get(0),
+            )
+            .map_err(|_| StorageError)
+    }
+
+    fn write_remote_items(&self, items: &[RemoteItem]) -> Result<(), StorageError> {
+        let tx = self
+            .conn
+            .unchecked_transaction()
+            .map_err(|_| StorageError)?;
+        {
+            let mut upsert = tx
+                .prepare_cached(
+                    "INSERT INTO remote_items (guid, remote_id, last_modified) VALUES (?1, ?2, ?3)
+                    ON CONFLICT(guid) DO UPDATE SET
+                        remote_id = excluded.remote_id,
+                        last_modified = excluded.last_modified",
+                )
+                .map_err(|_| StorageError)?;
+            let mut mark_read = tx
+                .prepare_cached("UPDATE items SET read = 1 WHERE id = ?1")
+                .map_err(|_| StorageError)?;
+            let mut star = tx
+                .prepare_cached("UPDATE items SET starred = 1 WHERE id = ?1")
+                .map_err(|_| StorageError)?;
+            for item in items {
+                let written = upsert
+                    .execute(params![item.guid, item.id, item.last_modified])
+                    .and_then(|_| match item.is_read() {
+                        true => mark_read.execute([&item.guid]),
+                        false => Ok(0),
+                    })
+                    .and_then(|_| match item.is_starred() {
+                        true => star.execute([&item.guid]),
+                        false => Ok(0),
+                    });
+                written.map_err(|err| {
+                    log::error!("{:?}", err);
+                    StorageError
+                })?;
+            }
+        }
+        tx.commit().map_err(|_| StorageError)
+    }
+
+    fn read_subscriptions(&self) -> Result<Vec<Subscription>, StorageError> {
+        let stmt = "SELECT feed_url, hub, topic, callback_id, expires_at FROM websub_subscriptions";
+        let mut stmt = self.conn.prepare_cached(stmt).map_err(|_| StorageError)?;
+        let subscriptions = stmt
+            .query_map([], |row| {
+                Ok(Subscription {
+                    feed_url: row.get(0)?,
+                    hub: row.get(1)?,
+                    topic: row.get(2)?,
+                    callback_id: row.get(3)?,
+                    expires_at: row.get(4)?,
+                })
+            })
+            .map_err(|_| StorageError)?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(subscriptions)
+    }
+
+    fn write_subscription(&self, subscription: &Subscription) -> Result<(), StorageError> {
+        self.conn
+            .execute(
+                "INSERT OR REPLACE INTO websub_subscriptions
+                (feed_url, hub, topic, callback_id, expires_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    subscription.feed_url(),
+                    subscription.hub(),
+                    subscription.topic(),
+                    subscription.callback_id(),
+                    subscription.expires_at(),
+                ],
+            )
+            .map(|_| ())
+            .map_err(|err| {
+                log::error!("{:?}", err);
+                StorageError
+            })
+    }
+
+    fn delete_subscription(&self, feed_url: &str) -> Result<(), StorageError> {
+        self.conn
+            .execute(
+                "DELETE FROM websub_subscriptions WHERE feed_url = ?1",
+                [feed_url],
+            )
+            .map(|_| ())
+            .map_err(|_| StorageError)
+    }
+
+    fn read_queue(&self) -> Result<VecDeque<QueuedItem>, StorageError> {
+        let stmt = "SELECT feed_id, item_id FROM queue ORDER BY position";
+        let mut stmt = self.conn.prepare_cached(stmt).map_err(|_| StorageError)?;
+        let queue = stmt
+            .query_map([], |row| {
+                Ok(QueuedItem {
+                    feed_id: row.get(0)?,
+                    item_id: row.get(1)?,
+                })
+            })
+            .map_err(|_| StorageError)?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(queue)
+    }
+
+    fn write_queue(&self, queue: &VecDeque<QueuedItem>) -> Result<(), StorageError> {
+        let tx = self
+            .conn
+            .unchecked_transaction()
+            .map_err(|_| StorageError)?;
+        tx.execute("DELETE FROM queue", [])
+            .map_err(|_| StorageError)?;
+        {
+            let stmt = "INSERT INTO queue (position, feed_id, item_id) VALUES (?1, ?2, ?3)";
+            let mut stmt = tx.prepare_cached(stmt).map_err(|_| StorageError)?;
+            for (position, item) in queue.iter().enumerate() {
+                stmt.execute(params![position, item.feed_id, item.item_id])
+                    .map_err(|err| {
+                        log::error!("{:?}", err);
+                        StorageError
+                    })?;
+            }
+        }
+        tx.commit().map_err(|_| StorageError)
+    }
+}
+
+fn upsert_item(conn: &Connection, item: &Item) -> Result<()> {
+    conn.prepare_cached(ITEM_UPSERT)?.execute(params![
+        item.id(),
+        item.feed_id(),
+        item.title().unwrap_or("NULL"),
+        item.author().unwrap_or("NULL"),
+        item.content().unwrap_or("NULL"),
+        item.html_description().unwrap_or("NULL"),
+        item.description().unwrap_or("NULL"),
+        "[]",
+        item.link().unwrap_or("NULL"),
+        item.pub_date().unwrap_or("NULL"),
+        item.pub_date()
+            .and_then(parse_date)
+            .map(|date| date.timestamp()),
+        item.enclosure().map(Enclosure::url),
+        item.enclosure().map(Enclosure::length),
+        item.enclosure().map(Enclosure::mime_type),
+        item.duration(),
+    ])?;
+
+    if let Some(link) = item.link() {
+        conn.prepare_cached(LEGACY_ITEM_DELETE)?
+            .execute([item.feed_id(), link, item.id()])?;
+    }
+    Ok(())
+}
+
+fn check_integrity(conn: &Connection, thorough: bool) -> Result<IntegrityReport> {
+    let mut report = IntegrityReport {
+        ok: true,
+        ..Default::default()
+    };
+
+    let check = if thorough {
+        "PRAGMA integrity_check"
+    } else {
+        "PRAGMA quick_check"
+    };
+    let problems = conn
+        .prepare(check)?
+        .query_map([], |row| row.get::<_, String>(0))?
+        .collect::<Result<Vec<_>>>()?;
+    if problems != ["ok"] {
+        // a row may hold several problems, under a heading naming the database
+        let problems = problems
+            .iter()
+            .flat_map(|row| row.lines())
+            .filter(|line| !line.starts_with("*** "))
+            .map(str::to_owned)
+            .collect();
+        report.ok = false;
+        report.warnings = problems;
+        return Ok(report);
+    }
+
+    let tx = conn.unchecked_transaction()?;
+    let orphans = tx.execute(
+        "DELETE FROM items WHERE feed_id NOT IN (SELECT id FROM feeds)",
+        [],
+    )?;
+    if orphans > 0 {
+        report
+            .repaired
+            .push(format!("deleted {} items of unknown feeds", orphans));
+    }
+    // the key on (feed_id, id) rules these out unless the file itself is damaged
+    let duplicates = if thorough {
+        tx.execute(
+            "DELETE FROM items
+            WHERE rowid NOT IN (SELECT MIN(rowid) FROM items GROUP BY feed_id, id)",
+            [],
+        )?
+    } else {
+        0
+    };
+    if duplicates > 0 {
+        report.repaired.push(format!(
+            "deleted {} items stored twice in a feed",
+            duplicates
+        ));
+    }
+    for flag in ["read", "starred"] {
+        let fixed = tx.execute(
+            &format!(
+                "UPDATE items SET {0} = CASE WHEN {0} IN (1, '1', 'true') THEN 1 ELSE 0 END
+                WHERE typeof({0}) != 'integer' OR {0} NOT IN (0, 1)",
+                flag
+            ),
+            [],
+        )?;
+        if fixed > 0 {
+            report
+                .repaired
+                .push(format!("reset the {} flag of {} items", flag, fixed));
+        }
+    }
+    tx.commit()?;
+
+    let unlinked = conn
+        .prepare("SELECT url FROM feeds WHERE link IS NULL OR link = ''")?
+        .query_map([], |row| row.get::<_, String>(0))?
+        .collect::<Result<Vec<_>>>()?;
+    report.warnings.extend(
+        unlinked
+            .into_iter()
+            .map(|url| format!("feed {} has no link", url)),
+    );
+
+    if thorough {
+        let checks = [
+            ("feeds", "id", "text"),
+            ("feeds", "title", "text"),
+            ("feeds", "url", "text"),
+            ("feeds", "link", "text"),
+            ("items", "id", "text"),
+            ("items", "feed_id", "text"),
+            ("items", "published_at", "integer"),
+            ("items", "enclosure_length", "integer"),
+        ];
+        for (table, column, kind) in checks {
+            let wrong: usize = conn.query_row(
+                &format!(
+                    "SELECT COUNT(*) FROM {0} WHERE {1} IS NOT NULL AND typeof({1}) != '{2}'",
+                    table, column, kind
+                ),
+                [],
+                |row| row.get(0),
+            )?;
+            if wrong > 0 {
+                report.warnings.push(format!(
+                    "{} {} have a {} that isn't of type {}",
+                    wrong, table, column, kind
+                ));
+            }
+        }
+    }
+
+    Ok(report)
 }
@@ -0,0 +1,286 @@
+//! A client for the [Google Reader API](https://github.com/FreshRSS/FreshRSS/blob/edge/docs/en/developers/06_GoogleReader_API.md)
+//! as served by FreshRSS, Miniflux and others, used to sync read and starred items. Like
+//! Miniflux's own API it doesn't share the guids of items, so they are matched by link.
+//!
+//! The auth token the server hands out at login is kept in the system keyring, and replaced
+//! by logging in again whenever the server stops accepting it.
+
+use super::{ItemState, RemoteItem};
+use anyhow::{Context, Result};
+use reqwest::header::AUTHORIZATION;
+use reqwest::{RequestBuilder, Response, StatusCode};
+use serde::Deserialize;
+use std::sync::Mutex;
+use std::time::Duration;
+
+const READING_LIST: &str = "user/-/state/com.google/reading-list";
+const READ_TAG: &str = "user/-/state/com.google/read";
+const STARRED_TAG: &str = "user/-/state/com.google/starred";
+const PAGE_SIZE: usize = 1000;
+const TIMEOUT: Duration = Duration::from_secs(15);
+
+#[derive(Deserialize)]
+struct StreamPage {
+    items: Vec<StreamEntry>,
+    continuation: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StreamEntry {
+    id: String,
+    #[serde(default)]
+    categories: Vec<String>,
+    #[serde(default)]
+    canonical: Vec<Link>,
+    #[serde(default)]
+    alternate: Vec<Link>,
+    /// When the server fetched the item, in milliseconds, as a string.
+    crawl_time_msec: Option<String>,
+    #[serde(default)]
+    published: i64,
+}
+
+#[derive(Deserialize)]
+struct Link {
+    href: String,
+}
+
+/// An item in the reading list, with the link it's matched to a stored item by.
+#[derive(Clone, Debug)]
+pub struct StreamItem {
+    id: u64,
+    link: Option<String>,
+    read: bool,
+    starred: bool,
+    crawled: i64,
+}
+
+impl StreamItem {
+    pub fn link(&self) -> Option<&str> {
+        self.link.as_deref()
+    }
+
+    /// The item as a `RemoteItem`, matched to the stored item with `guid`.
+    pub fn into_remote(self, guid: String) -> RemoteItem {
+        RemoteItem {
+            id: self.id,
+            guid,
+            unread: !self.read,
+            starred: self.starred,
+            last_modified: self.crawled,
+        }
+    }
+}
+
+impl TryFrom<StreamEntry> for StreamItem {
+    type Error = anyhow::Error;
+
+    fn try_from(entry: StreamEntry) -> Result<Self> {
+        let crawled = match entry.crawl_time_msec {
+            Some(msec) => msec.parse::<i64>().context("invalid crawl time")? / 1000,
+            None => entry.published,
+        };
+        Ok(Self {
+            id: parse_item_id(&entry.id)?,
+            link: entry
+                .canonical
+                .into_iter()
+                .chain(entry.alternate)
+                .map(|link| link.href)
+                .next(),
+            read: entry.categories.iter().any(|c| c == READ_TAG),
+            starred: entry.categories.iter().any(|c| c == STARRED_TAG),
+            crawled,
+        })
+    }
+}
+
+/// Reads an item id in either the long form, `tag:google.com,2005:reader/item/<hex>`, or
+/// the short decimal form.
+fn parse_item_id(id: &str) -> Result<u64> {
+    match id.rsplit_once("/item/") {
+        Some((_, hex)) => u64::from_str_radix(hex, 16),
+        None => id.parse(),
+    }
+    .with_context(|| format!("invalid item id '{}'", id))
+}
+
+pub struct GReaderClient {
+    client: reqwest::Client,
+    base_url: String,
+    username: String,
+    password: String,
+    token: Mutex<Option<String>>,
+}
+
+impl GReaderClient {
+    pub fn new(base_url: &str, username: &str, password: &str) -> Self {
+        Self {
+            client: reqwest::Client::builder()
+                .timeout(TIMEOUT)
+                .build()
+                .expect("failed to build client"),
+            base_url: base_url.to_owned(),
+            username: username.to_owned(),
+            password: password.to_owned(),
+            token: Mutex::new(None),
+        }
+    }
+
+    /// Every item in the reading list fetched by the server since `since`, a Unix timestamp,
+    /// or every item at all from 0.
+    pub async fn get_updated_items(&self, since: i64) -> Result<Vec<StreamItem>> {
+        let mut items = vec![];
+        let mut continuation = None::<String>;
+        loop {
+            let url = format!(
+                "{}/reader/api/0/stream/contents/{}",
+                self.base_url, READING_LIST
+            );
+            let mut query = vec![
+                ("output", "json".to_owned()),
+                ("n", PAGE_SIZE.to_string()),
+                ("ot", since.to_string()),
+            ];
+            if let Some(continuation) = continuation.take() {
+                query.push(("c", continuation));
+            }
+            let text = self
+                .send(|| self.client.get(&url).query(&query))
+                .await?
+                .text()
+                .await?;
+            let page = serde_json::from_str::<StreamPage>(&text)?;
+
+            for entry in page.items {
+                items.push(StreamItem::try_from(entry)?);
+            }
+            match page.continuation {
+                Some(next) if !next.is_empty() => continuation = Some(next),
+                _ => return Ok(items),
+            }
+        }
+    }
+
+    /// Marks the items with `ids` read or starred on the server.
+    pub async fn mark_items(&self, ids: &[u64], state: ItemState) -> Result<()> {
+        let tag = match state {
+            ItemState::Read => READ_TAG,
+            ItemState::Starred => STARRED_TAG,
+        };
+        // edits take a short-lived token of their own, besides the auth token
+        let edit_token = self
+            .send(|| {
+                self.client
+                    .get(format!("{}/reader/api/0/token", self.base_url))
+            })
+            .await?
+            .text()
+            .await?;
+
+        let mut form = ids
+            .iter()
+            .map(|id| ("i", id.to_string()))
+            .collect::<Vec<_>>();
+        form.push(("a", tag.to_owned()));
+        form.push(("T", edit_token.trim().to_owned()));
+        self.send(|| {
+            self.client
+                .post(format!("{}/reader/api/0/edit-tag", self.base_url))
+                .form(&form)
+        })
+        .await?;
+        Ok(())
+    }
+
+    /// Sends the request `build` makes with the auth token, logging in again and retrying once
+    /// if the server no longer accepts it.
+    async fn send(&self, build: impl Fn() -> RequestBuilder) -> Result<Response> {
+        let token = self.auth_token(false).await?;
+        let response = authorize(build(), &token).send().await?;
+        if response.status() != StatusCode::UNAUTHORIZED {
+            return Ok(response.error_for_status()?);
+        }
+
+        log::info!("GReader token expired, logging in again");
+        let token = self.auth_token(true).await?;
+        Ok(authorize(build(), &token)
+            .send()
+            .await?
+            .error_for_status()?)
+    }
+
+    /// The auth token, from memory, then the keyring, then by logging in. With `renew` it is
+    /// always got by logging in.
+    async fn auth_token(&self, renew: bool) -> Result<String> {
+        if !renew {
+            if let Some(token) = self.token.lock().expect("token lock poisoned").clone() {
+                return Ok(token);
+            }
+            if let Some(token) = self.keyring_token().await {
+                *self.token.lock().expect("token lock poisoned") = Some(token.clone());
+                return Ok(token);
+            }
+        }
+
+        let token = self.login().await?;
+        self.store_keyring_token(&token).await;
+        *self.token.lock().expect("token lock poisoned") = Some(token.clone());
+        Ok(token)
+    }
+
+    async fn login(&self) -> Result<String> {
+        let text = self
+            .client
+            .post(format!("{}/accounts/ClientLogin", self.base_url))
+            .form(&[("Email", &self.username), ("Passwd", &self.password)])
+            .send()
+            .await?
+            .error_for_status()?
+            .text()
+            .await?;
+        text.lines()
+            .find_map(|line| line.strip_prefix("Auth="))
+            .map(str::to_owned)
+            .context("no auth token in login response")
+    }
+
+    /// The token stored in the keyring by an earlier login, if the keyring can be reached.
+    async fn keyring_token(&self) -> Option<String> {
+        let (service, username) = self.keyring_key();
+        // the keyring may block
+        tokio::task::spawn_blocking(move || {
+            keyring::Entry::new(&service, &username)
+                .and_then(|entry| entry.get_password())
+                .ok()
+        })
+        .await
+        .ok()
+        .flatten()
+    }
+
+    async fn store_keyring_token(&self, token: &str) {
+        let (service, username) = self.keyring_key();
+        let token = token.to_owned();
+        let stored = tokio::task::spawn_blocking(move || {
+            keyring::Entry::new(&service, &username).and_then(|entry| entry.set_password(&token))
+        })
+        .await;
+        if !matches!(stored, Ok(Ok(()))) {
+            log::warn!("Could not store GReader token in keyring");
+        }
+    }
+
+    /// The keyring service and username the token is stored under, `moccasin-sync:<base_url>`.
+    fn keyring_key(&self) -> (String, String) {
+        (
+            format!("moccasin-sync:{}", self.base_url),
+            self.username.clone(),
+        )
+    }
+}
+
+fn authorize(request: RequestBuilder, token: &str) -> RequestBuilder {
+    request.header(AUTHORIZATION, format!("GoogleLogin auth={}", token))
+}
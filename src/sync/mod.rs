@@ -0,0 +1,48 @@
+//! Keeping reading state in step with a service configured in the `[sync]` table.
+
+use serde::Deserialize;
+
+pub mod greader;
+pub mod miniflux;
+pub mod nextcloud;
+
+/// A state an item can be marked with on a sync server.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ItemState {
+    Read,
+    Starred,
+}
+
+/// An item as a sync server has it, matched to a stored item by guid.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteItem {
+    pub(crate) id: u64,
+    pub(crate) guid: String,
+    pub(crate) unread: bool,
+    pub(crate) starred: bool,
+    /// When the item last changed on the server, as a Unix timestamp.
+    pub(crate) last_modified: i64,
+}
+
+impl RemoteItem {
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn guid(&self) -> &str {
+        &self.guid
+    }
+
+    pub fn is_read(&self) -> bool {
+        !self.unread
+    }
+
+    pub fn is_starred(&self) -> bool {
+        self.starred
+    }
+
+    pub fn last_modified(&self) -> i64 {
+        self.last_modified
+    }
+}
@@ -1,33 +1,57 @@
-use crate::app::{App, View};
+use super::{pane_block, row_style};
+use crate::app::{App, ContentMode, GroupedFeed, View, SUMMARY_INDENT};
+use crate::config::keymap::Action;
+use crate::config::{
+    self, Column, ItemColumn, ItemDisplayMode, ItemSortOrder, PaneLayout, ScrollbarStyle,
+};
+use crate::feed::{Enclosure, Feed, HealthStatus, Item, ItemSummary};
+use crate::{format, render, util};
+use std::collections::HashMap;
+use std::mem;
 use tui::{
     backend::Backend,
     layout::Alignment,
     prelude::*,
     style::{Color, Modifier, Style},
     widgets::{
-        scrollbar, Block, BorderType, Borders, List, ListItem, Padding, Paragraph, Scrollbar, Wrap,
+        block::{Position, Title},
+        Block, Borders, List, ListItem, ListState, Padding, Paragraph, Scrollbar, Wrap,
     },
     Frame,
 };
+use unicode_width::UnicodeWidthStr;
+
+/// Room for the longest relative time, `"just now"`.
+const LAST_FETCHED_WIDTH: usize = 8;
 
 pub fn render_browse_area<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, area: Rect) {
-    let chunks = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints(
-            [
-                Constraint::Max(40),
-                Constraint::Min(60),
-                Constraint::Min(60),
-            ]
-            .as_ref(),
-        )
-        .split(area);
+    let chunks = match app.effective_layout((frame.size().width, frame.size().height)) {
+        PaneLayout::Horizontal => Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(
+                [
+                    Constraint::Max(40),
+                    Constraint::Min(60),
+                    Constraint::Min(60),
+                ]
+                .as_ref(),
+            )
+            .split(area),
+        PaneLayout::Vertical => Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Ratio(1, 3); 3].as_ref())
+            .split(area),
+    };
 
-    let left = Block::default()
-        .title("Feeds")
-        .title_alignment(Alignment::Left)
-        .title_style(Style::default().bg(Color::White).fg(Color::Red))
-        .padding(if app.should_render_feeds_scroll() {
+    let mut left = pane_block(
+        app,
+        if app.is_unread_filter_active() {
+            "Feeds (unread)"
+        } else {
+            "Feeds"
+        },
+        app.active_view == View::MainList,
+        if app.should_render_feeds_scroll() {
             Padding {
                 top: 1,
                 bottom: 1,
@@ -36,20 +60,146 @@ pub fn render_browse_area<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, a
             }
         } else {
             Padding::uniform(1)
+        },
+    )
+    .title_style(Style::default().bg(Color::White).fg(Color::Red));
+    if app.is_awaiting_feed_jump() {
+        left = left.title(
+            Title::from(Span::styled(
+                format!("{} jump to…", app.keymap.label(Action::JumpToFeed)),
+                app.config.theme().status(),
+            ))
+            .position(Position::Bottom)
+            .alignment(Alignment::Left),
+        );
+    } else if app.should_render_list_search()
+        || (app.has_last_search() && app.active_view == View::MainList)
+    {
+        left = left.title(
+            Title::from(Span::styled(
+                format!("/{}", app.list_search.input),
+                app.config.theme().status(),
+            ))
+            .position(Position::Bottom)
+            .alignment(Alignment::Left),
+        );
+    } else if app.should_render_feed_filter() || app.has_feed_filter() {
+        left = left.title(
+            Title::from(Span::styled(
+                format!("/{}", app.feed_filter.input),
+                app.config.theme().status(),
+            ))
+            .position(Position::Bottom)
+            .alignment(Alignment::Left),
+        );
+    }
+
+    let feeds_area = if app.current_feed().is_some() {
+        chunks[0]
+    } else {
+        area
+    };
+    if app.should_render_list_search() {
+        frame.set_cursor(
+            feeds_area.x + 2 + app.list_search.input.width() as u16,
+            feeds_area.bottom().saturating_sub(1),
+        );
+    } else if app.should_render_feed_filter() {
+        frame.set_cursor(
+            feeds_area.x + 2 + app.feed_filter.input.width() as u16,
+            feeds_area.bottom().saturating_sub(1),
+        );
+    }
+    app.pane_areas.feeds = left.inner(feeds_area);
+    let row_width = app.pane_areas.feeds.width as usize;
+    let badge_width = app
+        .all_feeds()
+        .map(|feed| {
+            unread_badge(app, app.feed_unread_count(feed))
+                .chars()
+                .count()
         })
-        .borders(Borders::ALL)
-        .border_style(if app.active_view == View::MainList {
-            app.config.theme().active_border()
-        } else {
-            app.config.theme().border()
-        })
-        .border_type(BorderType::Plain);
+        .max()
+        .unwrap_or_default();
+    let fetched_width = if app.should_render_last_fetched((frame.size().width, frame.size().height))
+    {
+        LAST_FETCHED_WIDTH + 1
+    } else {
+        0
+    };
+    let icon_width = if app.feed_errors.is_empty() && app.filter_unhealthy().next().is_none() {
+        0
+    } else {
+        2
+    };
 
     let feeds_list = List::new(
         app.feeds
             .items()
             .iter()
-            .map(|feed| ListItem::new(format!("{} ({})", feed.title(), feed.items().len())))
+            .enumerate()
+            .map(|(i, row)| {
+                let feed = match row {
+                    GroupedFeed::Header(name) => return group_header(app, name),
+                    GroupedFeed::Feed(feed) => feed,
+                };
+                let unread = app.feed_unread_count(feed);
+                let title_width =
+                    row_width.saturating_sub(badge_width + icon_width + fetched_width + 1);
+                let badge_style = if unread > 0 {
+                    app.config.theme().badge()
+                } else {
+                    Style::default().add_modifier(Modifier::DIM)
+                };
+                let health = app.feed_health(feed);
+                let title_style = match health {
+                    HealthStatus::Healthy => app
+                        .config
+                        .feed_color(feed.url())
+                        .map_or(Style::default(), |color| {
+                            Style::default().fg(tui_color(color))
+                        }),
+                    HealthStatus::Degraded => Style::default().fg(Color::Yellow),
+                    HealthStatus::Dead => Style::default().fg(Color::Red),
+                };
+                let icon = match (app.feed_error(feed), health) {
+                    (Some(err), _) => err.icon(&app.symbols),
+                    (None, HealthStatus::Healthy) => "",
+                    (None, _) => app.symbols.warning,
+                };
+                let mut spans = highlight_chars(
+                    format!("{} ", util::fit(feed.title(), title_width)),
+                    &app.feed_filter_matches(feed.title()),
+                    title_style,
+                    app.config.theme().highlight(),
+                );
+                spans.extend([
+                    Span::styled(
+                        format!("{:<width$}", icon, width = icon_width),
+                        Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+                    ),
+                    last_fetched_span(feed, fetched_width),
+                    Span::styled(
+                        format!("{:>width$}", unread_badge(app, unread), width = badge_width),
+                        badge_style,
+                    ),
+                ]);
+                let mut lines = vec![Line::from(spans)];
+                // the selected feed says why its refresh failed, see `App::feed_index_at`
+                if let Some(err) = app
+                    .feed_error(feed)
+                    .filter(|_| app.feeds.state.selected() == Some(i))
+                {
+                    lines.push(Line::styled(
+                        format!(
+                            "  {}",
+                            util::truncate(err.reason(), row_width.saturating_sub(2))
+                        ),
+                        Style::default().add_modifier(Modifier::DIM),
+                    ));
+                }
+                ListItem::new(lines).style(row_style(app, i, app.feeds.state.selected()))
+            })
             .collect::<Vec<_>>(),
     )
     .block(left)
@@ -64,13 +214,27 @@ pub fn render_browse_area<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, a
         .feeds
         .state
         .selected()
-        .and_then(|i| app.feeds.items().get(i));
+        .and_then(|i| app.feeds.items().get(i))
+        .and_then(GroupedFeed::feed);
 
     if let Some(feed) = current_feed {
-        let block = Block::default()
-            .title(feed.title())
-            .title_alignment(Alignment::Left)
-            .padding(if app.should_render_items_scroll() {
+        let items_area = if app.current_item().is_some() {
+            chunks[1]
+        } else {
+            chunks[1].union(chunks[2])
+        };
+        let order = app.config.item_sort_order();
+        // the name gives way to the format and sort order on the right of the border
+        let mut title_width =
+            (items_area.width as usize).saturating_sub(feed.format().as_str().width() + 3);
+        if order != ItemSortOrder::FeedDefault {
+            title_width = title_width.saturating_sub(order.label().width() + 1);
+        }
+        let mut block = pane_block(
+            app,
+            items_title(app, feed, title_width),
+            app.active_view == View::SubList,
+            if app.should_render_items_scroll() {
                 Padding {
                     top: 1,
                     bottom: 1,
@@ -79,21 +243,99 @@ pub fn render_browse_area<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, a
                 }
             } else {
                 Padding::uniform(1)
-            })
-            .borders(Borders::ALL)
-            .border_style(if app.active_view == View::SubList {
-                app.config.theme().active_border()
-            } else {
-                app.config.theme().border()
-            })
-            .border_type(BorderType::Plain);
+            },
+        )
+        .title(
+            Title::from(Span::styled(
+                feed.format().as_str(),
+                Style::default().add_modifier(Modifier::DIM),
+            ))
+            .alignment(Alignment::Right),
+        );
+        if order != ItemSortOrder::FeedDefault {
+            block = block.title(
+                Title::from(Span::styled(
+                    order.label(),
+                    Style::default().add_modifier(Modifier::DIM),
+                ))
+                .alignment(Alignment::Right),
+            );
+        }
+        // the filter is typed into the bottom border
+        if app.should_render_item_filter() || app.has_item_filter() {
+            block = block.title(
+                Title::from(Span::styled(
+                    format!("/{}", app.item_filter.input),
+                    app.config.theme().status(),
+                ))
+                .position(Position::Bottom)
+                .alignment(Alignment::Left),
+            );
+        }
 
+        app.pane_areas.items = block.inner(items_area);
+        if app.should_render_item_filter() {
+            frame.set_cursor(
+                items_area.x + 2 + app.item_filter.input.width() as u16,
+                items_area.bottom().saturating_sub(1),
+            );
+        }
+
+        // the star column only takes room in feeds with starred items
+        let any_starred = feed.items().iter().any(Item::is_starred);
+        let double = app.config.item_display_mode() == ItemDisplayMode::Double;
+        // in two rows, the title's row leaves the date and author to the row below
+        let columns = app
+            .config
+            .item_columns()
+            .iter()
+            .filter(|column| any_starred || column.kind() != ItemColumn::Star)
+            .filter(|column| {
+                !double || matches!(column.kind(), ItemColumn::Title | ItemColumn::ReadIndicator)
+            })
+            .copied()
+            .collect::<Vec<_>>();
+        let row_width = app.pane_areas.items.width;
+        // highlights and summaries come from descriptions, which only the feed's own items carry
+        let full_items: HashMap<&str, &Item> = if app.config.highlights().is_empty() && !double {
+            HashMap::new()
+        } else {
+            feed.items().iter().map(|item| (item.id(), item)).collect()
+        };
+        let (first_loaded, loaded) = app.items.loaded();
         let items_list = List::new(
-            feed.items()
+            loaded
                 .iter()
-                .map(|item| {
-                    let title = item.title().clone().unwrap_or("default".into());
-                    ListItem::new(title)
+                .enumerate()
+                .map(|(i, item)| {
+                    // striped by place in the whole list, so stripes hold still while paging
+                    let mut style = row_style(app, first_loaded + i, app.items.state.selected());
+                    if full_items
+                        .get(item.id())
+                        .is_some_and(|item| app.config.highlights().matches(item))
+                    {
+                        style = style.patch(app.config.theme().highlight());
+                    }
+                    if item.is_read() {
+                        style = style.add_modifier(Modifier::DIM);
+                    }
+                    let mut lines =
+                        vec![Line::from(render_item_row(app, item, &columns, row_width))];
+                    if double {
+                        let summary = full_items
+                            .get(item.id())
+                            .and_then(|item| item.description());
+                        lines.push(render_item_details(app, item, summary, row_width));
+                    }
+                    let indent = Span::raw(" ".repeat(SUMMARY_INDENT as usize));
+                    lines.extend(app.expanded_summary(item).into_iter().map(|line| {
+                        let mut spans = vec![indent.clone()];
+                        spans.extend(line.spans);
+                        let mut line = Line::from(spans);
+                        line.patch_style(Style::default().add_modifier(Modifier::DIM));
+                        line
+                    }));
+                    ListItem::new(lines).style(style)
                 })
                 .collect::<Vec<_>>(),
         )
@@ -105,16 +347,22 @@ pub fn render_browse_area<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, a
             app.config.theme().selection()
         });
 
+        // only the loaded rows are drawn, so the state counts from the first of them
+        let loaded_len = loaded.len();
+        let mut items_state = ListState::default()
+            .with_selected(
+                app.items
+                    .state
+                    .selected()
+                    .and_then(|i| i.checked_sub(first_loaded))
+                    .filter(|i| *i < loaded_len),
+            )
+            .with_offset(app.items.state.offset().saturating_sub(first_loaded));
         if app.current_item().is_some() {
-            frame.render_stateful_widget(items_list, chunks[1], &mut app.items.state);
+            frame.render_stateful_widget(items_list, chunks[1], &mut items_state);
             if app.should_render_items_scroll() {
                 frame.render_stateful_widget(
-                    Scrollbar::default()
-                        .begin_symbol(None)
-                        .end_symbol(None)
-                        .track_symbol(scrollbar::VERTICAL.thumb)
-                        .track_style(app.config.theme().scrollbar_track())
-                        .thumb_style(app.config.theme().scrollbar_thumb()),
+                    scrollbar(app),
                     chunks[1].inner(&Margin {
                         vertical: 1,
                         horizontal: 1,
@@ -123,19 +371,10 @@ pub fn render_browse_area<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, a
                 );
             }
         } else {
-            frame.render_stateful_widget(
-                items_list,
-                chunks[1].union(chunks[2]),
-                &mut app.items.state,
-            );
+            frame.render_stateful_widget(items_list, chunks[1].union(chunks[2]), &mut items_state);
             if app.should_render_items_scroll() {
                 frame.render_stateful_widget(
-                    Scrollbar::default()
-                        .begin_symbol(None)
-                        .end_symbol(None)
-                        .track_symbol(scrollbar::VERTICAL.thumb)
-                        .track_style(app.config.theme().scrollbar_track())
-                        .thumb_style(app.config.theme().scrollbar_thumb()),
+                    scrollbar(app),
                     chunks[1].union(chunks[2]).inner(&Margin {
                         vertical: 1,
                         horizontal: 1,
@@ -144,95 +383,16 @@ pub fn render_browse_area<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, a
                 );
             }
         }
+        *app.items.state.offset_mut() = first_loaded + items_state.offset();
 
-        if let Some(detail) = &app.current_item() {
-            let block = Block::default()
-                .title("Detail")
-                .title_alignment(Alignment::Left)
-                .padding(Padding::uniform(1))
-                .style(app.config.theme().base())
-                .borders(Borders::ALL)
-                .border_style(if app.active_view == View::Detail {
-                    app.config.theme().active_border()
-                } else {
-                    app.config.theme().border()
-                });
-
-            frame.render_widget(block, chunks[2]);
-
-            let content_chunks = Layout::default()
-                .direction(Direction::Vertical)
-                .constraints([
-                    Constraint::Min(1),
-                    Constraint::Min(1),
-                    Constraint::Min(1),
-                    Constraint::Length(1),
-                    Constraint::Min(1),
-                ])
-                .margin(2)
-                .split(chunks[2]);
-
-            let title = Paragraph::new(detail.title().unwrap_or("[no title]"))
-                .style(Style::default().add_modifier(Modifier::ITALIC))
-                .wrap(Wrap { trim: true })
-                .alignment(Alignment::Center);
-
-            let author = Paragraph::new(detail.author().unwrap_or("[anonymous]"))
-                .alignment(Alignment::Center);
-
-            let date = Paragraph::new(detail.pub_date().unwrap_or("[no date]"))
-                .alignment(Alignment::Center);
-
-            let body = Paragraph::new(detail.description().unwrap_or("[no content]"))
-                .wrap(Wrap { trim: true })
-                .block(Block::default().padding(Padding {
-                    top: 0,
-                    bottom: 0,
-                    left: 1,
-                    right: if app.should_render_detail_scroll() {
-                        2
-                    } else {
-                        1
-                    },
-                }))
-                .scroll((app.detail_scroll_index, 0));
-
-            frame.render_widget(title, content_chunks[0]);
-            frame.render_widget(author, content_chunks[1]);
-            frame.render_widget(date, content_chunks[2]);
-            frame.render_widget(
-                Block::default()
-                    .borders(Borders::TOP)
-                    .border_style(app.config.theme().border())
-                    .padding(Padding::vertical(1)),
-                content_chunks[3],
-            );
-            frame.render_widget(body, content_chunks[4]);
-
-            app.detail_scroll = app.detail_scroll.content_length(48);
-            if app.should_render_detail_scroll() {
-                frame.render_stateful_widget(
-                    Scrollbar::default()
-                        .begin_symbol(None)
-                        .end_symbol(None)
-                        .track_symbol(scrollbar::VERTICAL.thumb)
-                        .track_style(app.config.theme().scrollbar_track())
-                        .thumb_style(app.config.theme().scrollbar_thumb()),
-                    content_chunks[4],
-                    &mut app.detail_scroll,
-                );
-            }
+        if let Some(detail) = app.current_item_detail.clone() {
+            render_item_detail(app, frame, chunks[2], &detail);
         }
 
         frame.render_stateful_widget(feeds_list, chunks[0], &mut app.feeds.state);
         if app.should_render_feeds_scroll() {
             frame.render_stateful_widget(
-                Scrollbar::default()
-                    .begin_symbol(None)
-                    .end_symbol(None)
-                    .track_symbol(scrollbar::VERTICAL.thumb)
-                    .track_style(app.config.theme().scrollbar_track())
-                    .thumb_style(app.config.theme().scrollbar_thumb()),
+                scrollbar(app),
                 chunks[0].inner(&Margin {
                     vertical: 1,
                     horizontal: 1,
@@ -244,12 +404,7 @@ pub fn render_browse_area<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, a
         frame.render_stateful_widget(feeds_list, area, &mut app.feeds.state);
         if app.should_render_feeds_scroll() {
             frame.render_stateful_widget(
-                Scrollbar::default()
-                    .begin_symbol(None)
-                    .end_symbol(None)
-                    .track_symbol(scrollbar::VERTICAL.thumb)
-                    .track_style(app.config.theme().scrollbar_track())
-                    .thumb_style(app.config.theme().scrollbar_thumb()),
+                scrollbar(app),
                 area.inner(&Margin {
                     vertical: 1,
                     horizontal: 1,
@@ -259,3 +414,386 @@ pub fn render_browse_area<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, a
         }
     }
 }
+
+pub fn render_item_detail<B: Backend>(
+    app: &mut App,
+    frame: &mut Frame<'_, B>,
+    area: Rect,
+    detail: &Item,
+) {
+    // which body is shown only matters when the item has both
+    let title = if detail.has_content_and_summary() {
+        format!(
+            "Detail {} {}",
+            app.symbols.separator,
+            app.content_mode().label()
+        )
+    } else {
+        "Detail".to_owned()
+    };
+    let block = pane_block(
+        app,
+        title,
+        app.active_view == View::Detail,
+        Padding::uniform(1),
+    )
+    .style(app.config.theme().base());
+
+    frame.render_widget(block, area);
+    app.pane_areas.detail = area;
+
+    let content_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Min(1),
+            Constraint::Min(1),
+            Constraint::Min(1),
+            Constraint::Length(1),
+            Constraint::Min(1),
+        ])
+        .margin(2)
+        .split(area);
+
+    let title = Paragraph::new(detail.title().unwrap_or("[no title]"))
+        .style(Style::default().add_modifier(Modifier::ITALIC))
+        .wrap(Wrap { trim: true })
+        .alignment(Alignment::Center);
+
+    let author =
+        Paragraph::new(detail.author().unwrap_or("[anonymous]")).alignment(Alignment::Center);
+
+    let date =
+        Paragraph::new(detail.pub_date().unwrap_or("[no date]")).alignment(Alignment::Center);
+
+    let body = match app.content_mode() {
+        ContentMode::Full => detail.content().or(detail.html_description()),
+        ContentMode::Summary => detail.html_description().or(detail.content()),
+    };
+    let more = format!(
+        "… (press {} to open full content in browser)",
+        app.keymap.label(Action::Open)
+    );
+    let body_lines = |width: u16| {
+        let mut lines = match body {
+            Some(html) => render::html_to_lines(html, width),
+            None => vec![Line::from("[no content]")],
+        };
+        if let Some(max) = app.config.max_detail_lines() {
+            if lines.len() > max {
+                lines.truncate(max);
+                lines.push(Line::styled(
+                    more.clone(),
+                    Style::default().add_modifier(Modifier::DIM),
+                ));
+            }
+        }
+        lines
+    };
+
+    // the scrollbar takes a column from the text once the text is too long to fit
+    app.pane_areas.detail_body = content_chunks[4];
+    let mut lines = body_lines(content_chunks[4].width.saturating_sub(2));
+    app.detail_line_count = lines.len();
+    if app.should_render_detail_scroll() {
+        lines = body_lines(content_chunks[4].width.saturating_sub(3));
+        app.detail_line_count = lines.len();
+    }
+    let body_padding = Padding {
+        top: 0,
+        bottom: 0,
+        left: 1,
+        right: if app.should_render_detail_scroll() {
+            2
+        } else {
+            1
+        },
+    };
+    let body_length = lines.len() as u16;
+    let body = Paragraph::new(lines)
+        .block(Block::default().padding(body_padding))
+        .scroll((app.detail_scroll_index, 0));
+
+    frame.render_widget(title, content_chunks[0]);
+    frame.render_widget(author, content_chunks[1]);
+    frame.render_widget(date, content_chunks[2]);
+    frame.render_widget(
+        Block::default()
+            .borders(Borders::TOP)
+            .border_style(app.config.theme().border())
+            .padding(Padding::vertical(1)),
+        content_chunks[3],
+    );
+    frame.render_widget(body, content_chunks[4]);
+
+    app.detail_scroll = app.detail_scroll.content_length(body_length);
+    if app.should_render_detail_scroll() {
+        frame.render_stateful_widget(scrollbar(app), content_chunks[4], &mut app.detail_scroll);
+    }
+}
+
+/// A vertical scrollbar drawn in the `scrollbar_style` preference and the theme's colors.
+fn scrollbar(app: &App) -> Scrollbar<'static> {
+    let theme = app.config.theme();
+    let style = app.config.scrollbar_style();
+    let bar = Scrollbar::default()
+        .symbols(app.symbols.scrollbar(style))
+        .track_style(theme.scrollbar_track())
+        .thumb_style(theme.scrollbar_thumb());
+    if style == ScrollbarStyle::Arrows {
+        bar.begin_style(theme.scrollbar_thumb())
+            .end_style(theme.scrollbar_thumb())
+    } else {
+        bar.begin_symbol(None).end_symbol(None)
+    }
+}
+
+/// The narrowest the title gets before columns to its right are hidden.
+const MIN_TITLE_WIDTH: u16 = 10;
+
+/// One row of the items pane, laid out in `columns`. The title takes the width the other
+/// columns leave, and the rightmost of those are hidden when they would leave it too little.
+pub fn render_item_row(
+    app: &App,
+    item: &ItemSummary,
+    columns: &[Column],
+    available_width: u16,
+) -> Vec<Span<'static>> {
+    let mut columns = columns.to_vec();
+    let fixed_width = |columns: &[Column]| -> u16 {
+        columns
+            .iter()
+            .filter(|column| column.kind() != ItemColumn::Title)
+            .map(|column| column.width() + 1)
+            .sum()
+    };
+    while fixed_width(&columns) + MIN_TITLE_WIDTH > available_width {
+        match columns
+            .iter()
+            .rposition(|column| column.kind() != ItemColumn::Title)
+        {
+            Some(i) => columns.remove(i),
+            None => break,
+        };
+    }
+    let title_width = available_width.saturating_sub(fixed_width(&columns)) as usize;
+
+    let dim = Style::default().add_modifier(Modifier::DIM);
+    let mut spans = vec![];
+    for (i, column) in columns.iter().enumerate() {
+        if i > 0 {
+            spans.push(Span::raw(" "));
+        }
+        let width = column.width() as usize;
+        match column.kind() {
+            ItemColumn::Title => {
+                let media = item
+                    .enclosure()
+                    .map(|enclosure| media_label(app, enclosure, item.duration()))
+                    .unwrap_or_default();
+                let title = item.title().unwrap_or("default");
+                let title_room = title_width.saturating_sub(media.width());
+                let title = util::truncate(title, title_room);
+                // later columns line up only if the title fills its width
+                let padding = if i + 1 < columns.len() {
+                    title_width.saturating_sub(title.width() + media.width())
+                } else {
+                    0
+                };
+                spans.push(Span::raw(title));
+                if !media.is_empty() {
+                    spans.push(Span::styled(util::truncate(&media, title_width), dim));
+                }
+                spans.push(Span::raw(" ".repeat(padding)));
+            }
+            ItemColumn::Date => {
+                let date = item.pub_date().map(format::format_date).unwrap_or_default();
+                spans.push(Span::styled(util::fit(&date, width), dim));
+            }
+            ItemColumn::Author => {
+                let author = item.author().map(format::format_author).unwrap_or_default();
+                spans.push(Span::styled(util::fit(author, width), dim));
+            }
+            ItemColumn::ReadIndicator => {
+                let indicator = if item.is_read() {
+                    ""
+                } else {
+                    app.symbols.unread
+                };
+                spans.push(Span::styled(
+                    util::fit(indicator, width),
+                    app.config.theme().badge(),
+                ));
+            }
+            ItemColumn::Star => {
+                let star = if item.is_starred() {
+                    app.symbols.star
+                } else {
+                    ""
+                };
+                spans.push(Span::raw(util::fit(star, width)));
+            }
+        }
+    }
+    spans
+}
+
+/// Room for the start of an item's summary, below its title in the double display mode.
+const SUMMARY_WIDTH: usize = 80;
+
+/// The second row of an item in the double display mode: its date, author and the start of
+/// its summary, indented under its title.
+fn render_item_details(
+    app: &App,
+    item: &ItemSummary,
+    summary: Option<&str>,
+    available_width: u16,
+) -> Line<'static> {
+    let details = [
+        item.pub_date().map(format::format_date),
+        item.author()
+            .map(|author| format::format_author(author).to_owned()),
+        summary.map(|summary| util::snippet(summary, "", SUMMARY_WIDTH)),
+    ]
+    .into_iter()
+    .flatten()
+    .filter(|detail| !detail.is_empty())
+    .collect::<Vec<_>>()
+    .join(&format!(" {} ", app.symbols.separator));
+    Line::styled(
+        format!(
+            "  {}",
+            util::truncate(&details, available_width.saturating_sub(2) as usize)
+        ),
+        Style::default().add_modifier(Modifier::DIM),
+    )
+}
+
+/// `▶` with the running time and size of an item's media, where the feed gives them.
+fn media_label(app: &App, enclosure: &Enclosure, duration: Option<&str>) -> String {
+    let mut label = format!(" {}", app.symbols.media);
+    if let Some(duration) = duration {
+        label.push(' ');
+        label.push_str(&format::format_duration(duration));
+    }
+    if enclosure.length() > 0 {
+        label.push_str(&format!(" {} ", app.symbols.separator));
+        label.push_str(&format::format_size(enclosure.length()));
+    }
+    label
+}
+
+/// `text` in `style`, with the characters at `positions`, such as those matching a filter, in
+/// `highlight` as well.
+fn highlight_chars(
+    text: String,
+    positions: &[usize],
+    style: Style,
+    highlight: Style,
+) -> Vec<Span<'static>> {
+    if positions.is_empty() {
+        return vec![Span::styled(text, style)];
+    }
+    let mut spans = vec![];
+    let mut run = String::new();
+    let mut run_highlighted = false;
+    for (i, c) in text.chars().enumerate() {
+        let highlighted = positions.contains(&i);
+        if highlighted != run_highlighted && !run.is_empty() {
+            let run_style = if run_highlighted {
+                style.patch(highlight)
+            } else {
+                style
+            };
+            spans.push(Span::styled(mem::take(&mut run), run_style));
+        }
+        run_highlighted = highlighted;
+        run.push(c);
+    }
+    let run_style = if run_highlighted {
+        style.patch(highlight)
+    } else {
+        style
+    };
+    spans.push(Span::styled(run, run_style));
+    spans
+}
+
+fn group_header<'a>(app: &App, name: &str) -> ListItem<'a> {
+    let label = if app.is_group_collapsed(name) {
+        format!(
+            "{} {} ({})",
+            app.symbols.collapsed,
+            name,
+            app.hidden_feed_count(name)
+        )
+    } else {
+        format!("{} {}", app.symbols.expanded, name)
+    };
+    ListItem::new(label).style(Style::default().add_modifier(Modifier::BOLD | Modifier::DIM))
+}
+
+fn tui_color(color: config::Color) -> Color {
+    match color {
+        config::Color::Black => Color::Black,
+        config::Color::Red => Color::Red,
+        config::Color::Green => Color::Green,
+        config::Color::Yellow => Color::Yellow,
+        config::Color::Blue => Color::Blue,
+        config::Color::Magenta => Color::Magenta,
+        config::Color::Cyan => Color::Cyan,
+        config::Color::Gray => Color::Gray,
+        config::Color::DarkGray => Color::DarkGray,
+        config::Color::LightRed => Color::LightRed,
+        config::Color::LightGreen => Color::LightGreen,
+        config::Color::LightYellow => Color::LightYellow,
+        config::Color::LightBlue => Color::LightBlue,
+        config::Color::LightMagenta => Color::LightMagenta,
+        config::Color::LightCyan => Color::LightCyan,
+        config::Color::White => Color::White,
+        config::Color::Hex(r, g, b) => Color::Rgb(r, g, b),
+    }
+}
+
+/// The feed's name with how many of its items are listed and unread, the name shortened so
+/// all of it fits in `width` columns.
+fn items_title(app: &App, feed: &Feed, width: usize) -> String {
+    let counts = if app.is_unread_filter_active() {
+        format!("{} unread shown", app.items.len())
+    } else {
+        let listed = app.items.len();
+        format!(
+            "{} item{}, {} unread",
+            listed,
+            if listed == 1 { "" } else { "s" },
+            feed.unread_count()
+        )
+    };
+    let name = util::truncate(feed.title(), width.saturating_sub(counts.width() + 3));
+    format!("{} ({})", name, counts)
+}
+
+fn unread_badge(app: &App, unread: usize) -> String {
+    if unread > 0 {
+        format!("[{}]", unread)
+    } else {
+        app.symbols.separator.into()
+    }
+}
+
+/// How long ago `feed` was last fetched, right-aligned in `width` columns.
+fn last_fetched_span(feed: &Feed, width: usize) -> Span<'static> {
+    if width == 0 {
+        return Span::raw("");
+    }
+    match feed.last_fetched_at() {
+        Some(date) => Span::raw(format!(
+            "{:>width$} ",
+            format::format_relative(date),
+            width = width - 1
+        )),
+        None => Span::styled(
+            format!("{:>width$} ", "never", width = width - 1),
+            Style::default().add_modifier(Modifier::DIM),
+        ),
+    }
+}
@@ -0,0 +1,80 @@
+use super::{pane_block, row_style};
+use crate::app::App;
+use crate::config::PaneLayout;
+use crate::ui::browse;
+use crate::util;
+use tui::{
+    backend::Backend,
+    layout::Alignment,
+    prelude::*,
+    style::{Modifier, Style},
+    widgets::{List, ListItem, Padding, Paragraph},
+    Frame,
+};
+
+const FEED_COLUMN_WIDTH: usize = 20;
+
+pub fn render_favorites_area<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, area: Rect) {
+    let direction = match app.effective_layout((frame.size().width, frame.size().height)) {
+        PaneLayout::Horizontal => Direction::Horizontal,
+        PaneLayout::Vertical => Direction::Vertical,
+    };
+    let chunks = Layout::default()
+        .direction(direction)
+        .constraints([Constraint::Ratio(1, 2); 2].as_ref())
+        .split(area);
+
+    let block = pane_block(app, "Starred", true, Padding::uniform(1));
+
+    if app.starred.items().is_empty() {
+        let empty = Paragraph::new("No starred items. Press s on an item to star it.")
+            .block(block)
+            .style(app.config.theme().base())
+            .alignment(Alignment::Center);
+        frame.render_widget(empty, area);
+        return;
+    }
+
+    app.pane_areas.starred = block.inner(match app.current_starred() {
+        Some(_) => chunks[0],
+        None => area,
+    });
+
+    let rows = app
+        .starred
+        .items()
+        .iter()
+        .filter_map(|item_ref| app.resolve_item(item_ref))
+        .enumerate()
+        .map(|(i, (feed, item))| {
+            ListItem::new(Line::from(vec![
+                Span::styled(
+                    format!("{} ", util::fit(feed.title(), FEED_COLUMN_WIDTH)),
+                    Style::default().add_modifier(Modifier::DIM),
+                ),
+                Span::styled(
+                    item.title().unwrap_or("[no title]").to_owned(),
+                    if app.config.highlights().matches(item) {
+                        app.config.theme().highlight()
+                    } else {
+                        Style::default()
+                    },
+                ),
+            ]))
+            .style(row_style(app, i, app.starred.state.selected()))
+        })
+        .collect::<Vec<_>>();
+
+    let list = List::new(rows)
+        .block(block)
+        .style(app.config.theme().base())
+        .highlight_style(app.config.theme().active_selection());
+
+    match app.current_starred().map(|(_, item)| item.clone()) {
+        Some(detail) => {
+            frame.render_stateful_widget(list, chunks[0], &mut app.starred.state);
+            browse::render_item_detail(app, frame, chunks[1], &detail);
+        }
+        None => frame.render_stateful_widget(list, area, &mut app.starred.state),
+    }
+}
@@ -1,33 +1,87 @@
-use crate::app::{App, Status, Tab};
+use crate::app::{App, FeedError, PaneAreas, Status, Tab, TimeWindow};
+use crate::config::keymap::Action;
+use crate::config::{BorderStyle, KeybindingMode};
+use crate::util;
+use symbols::AsciiBorders;
 use tui::{
     backend::Backend,
     layout::Alignment,
     prelude::*,
-    widgets::{Block, BorderType, Borders, Clear, Gauge, Padding, Paragraph, Tabs},
+    widgets::{
+        block::Title, Block, BorderType, Borders, Clear, Gauge, List, ListItem, Padding, Paragraph,
+        Tabs, Wrap,
+    },
     Frame,
 };
+use unicode_width::UnicodeWidthStr;
 
 pub mod browse;
 pub mod detail;
+pub mod favorites;
+pub mod queue;
+pub mod search;
+pub mod stats;
+pub mod symbols;
 pub mod themed;
+pub mod timeline;
+pub mod wizard;
+
+/// Room in the status bar for the load state, such as `"Loading 12/34"`.
+const LOAD_STATE_WIDTH: u16 = 16;
+/// Rows of the tabs bar, with the line under it, above the panes.
+pub const TABS_BAR_HEIGHT: u16 = 2;
+/// Rows of the status bar below the panes.
+pub const STATUS_BAR_HEIGHT: u16 = 1;
+/// Rows a pane's borders and padding take around its list, or its title and padding once
+/// borders are off.
+pub const PANE_FRAME_HEIGHT: u16 = 4;
 
 /// Renders the user interface widgets.
 pub fn render<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>) {
+    if app.should_render_wizard() {
+        wizard::render_wizard(app, frame, frame.size());
+    } else {
+        render_interface(app, frame);
+    }
+    if app.symbols.is_ascii() {
+        frame.render_widget(AsciiBorders, frame.size());
+    }
+}
+
+fn render_interface<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>) {
     let wrapper = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(2),
+            Constraint::Length(TABS_BAR_HEIGHT),
             Constraint::Min(10),
-            Constraint::Length(2),
+            Constraint::Length(STATUS_BAR_HEIGHT),
         ])
         .split(frame.size());
 
     render_tabs_bar(app, frame, wrapper[0]);
 
+    // filled in by whichever panes are drawn below
+    app.pane_areas = PaneAreas::default();
+
     match app.active_tab {
         Tab::Browse => {
             browse::render_browse_area(app, frame, wrapper[1]);
         }
+        Tab::Favorites => {
+            favorites::render_favorites_area(app, frame, wrapper[1]);
+        }
+        Tab::Stats => {
+            stats::render_stats_area(app, frame, wrapper[1]);
+        }
+        Tab::Timeline => {
+            timeline::render_timeline_area(app, frame, wrapper[1]);
+        }
+        Tab::Search => {
+            search::render_search_area(app, frame, wrapper[1]);
+        }
+        Tab::Queue => {
+            queue::render_queue_area(app, frame, wrapper[1]);
+        }
         _ => {}
     }
 
@@ -36,6 +90,22 @@ pub fn render<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>) {
     if app.show_keybinds {
         render_keybinds_overlay(app, frame, frame.size());
     }
+
+    if app.should_render_feed_candidates() {
+        render_feed_candidates_overlay(app, frame, frame.size());
+    }
+
+    if app.should_render_preset_picker() {
+        render_preset_picker_overlay(app, frame, frame.size());
+    }
+
+    if app.should_render_delete_prompt() {
+        render_delete_prompt_overlay(app, frame, frame.size());
+    }
+
+    if app.should_render_error_detail() {
+        render_error_detail_overlay(app, frame, frame.size());
+    }
 }
 
 fn render_tabs_bar<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, area: Rect) {
@@ -54,15 +124,30 @@ fn render_tabs_bar<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, area: Re
     let t = t.underlined().to_owned();
     let tags = Line::from(vec![t, ags.into()]);
 
-    let tabs = Tabs::new(vec![browse, favorites, tags])
-        .block(
-            Block::default()
-                .style(app.config.theme().status())
-                .borders(Borders::BOTTOM)
-                .border_style(app.config.theme().active_border()),
-        )
-        .select(app.active_tab.index_of())
-        .highlight_style(app.config.theme().selection());
+    let stats = Tab::Stats.to_string().clone();
+    let (s, tats) = stats.split_at(1);
+    let s = s.underlined().to_owned();
+    let stats = Line::from(vec![s, tats.into()]);
+
+    let timeline = Tab::Timeline.to_string().clone();
+    let (t, imeline) = timeline.split_at(1);
+    let t = t.underlined().to_owned();
+    let timeline = Line::from(vec![t, imeline.into()]);
+
+    let search = Line::from(Tab::Search.to_string());
+    let queue = Line::from(Tab::Queue.to_string());
+
+    let tabs = Tabs::new(vec![
+        browse, favorites, tags, stats, timeline, search, queue,
+    ])
+    .block(
+        Block::default()
+            .style(app.config.theme().status())
+            .borders(Borders::BOTTOM)
+            .border_style(app.config.theme().active_border()),
+    )
+    .select(app.active_tab.index_of())
+    .highlight_style(app.config.theme().selection());
     frame.render_widget(tabs, area);
 }
 
@@ -86,26 +171,97 @@ fn render_keybinds_overlay<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>,
         .constraints([Constraint::Ratio(1, 2), Constraint::Ratio(1, 2)])
         .split(area);
 
-    let basic = vec![
-        Line::from("j/k    scroll down/up"),
-        Line::from("h/l    focus previous/next panel"),
-        Line::from("Ent    select current"),
-        Line::from("Esc    deselect current"),
-        Line::from("Tab    cycle tabs"),
-        Line::from("b/f/t  go to Browse/Favorites/Tags tab"),
-        Line::from(":      console mode"),
-        Line::from("r      refresh all feeds"),
-        Line::from("q      quit"),
-        Line::from("o      open feed/item in browser"),
-        Line::from(",      open config file"),
-        Line::from("?      toggle this help dialog"),
-    ];
+    let keys = |actions: &[Action]| {
+        actions
+            .iter()
+            .map(|action| app.keymap.label(*action))
+            .collect::<Vec<_>>()
+            .join("/")
+    };
+    let mut basic = [
+        (keys(&[Action::Next, Action::Prev]), "scroll down/up"),
+        (
+            keys(&[Action::PrevPane, Action::NextPane]),
+            "focus previous/next panel",
+        ),
+        (keys(&[Action::Select]), "select current"),
+        (keys(&[Action::Unselect]), "deselect current"),
+        (keys(&[Action::NextTab]), "cycle tabs"),
+        (
+            keys(&[
+                Action::Browse,
+                Action::Favorites,
+                Action::Tags,
+                Action::Stats,
+                Action::Timeline,
+            ]),
+            "go to Browse/Favorites/Tags/Stats/Timeline tab",
+        ),
+        (keys(&[Action::Console]), "console mode"),
+        (keys(&[Action::SearchAll]), "search all items"),
+        (keys(&[Action::Refresh]), "refresh all feeds"),
+        (keys(&[Action::RefreshFeed]), "refresh current feed"),
+        (keys(&[Action::ReloadConfig]), "reload config"),
+        (keys(&[Action::Quit]), "quit"),
+        (keys(&[Action::Open]), "open feed/item in browser"),
+        (keys(&[Action::OpenEnclosure]), "play item media"),
+        (keys(&[Action::CopyLink]), "copy item link"),
+        (keys(&[Action::ToggleRead]), "toggle item read/unread"),
+        (keys(&[Action::Star]), "star/unstar item"),
+        (keys(&[Action::MarkAllRead]), "mark all items in feed read"),
+        (
+            keys(&[Action::ToggleGroup]),
+            "collapse/expand current group",
+        ),
+        (
+            keys(&[Action::ToggleAllGroups]),
+            "collapse/expand all groups",
+        ),
+        (keys(&[Action::CycleFeedColor]), "cycle feed color"),
+        (keys(&[Action::ShowError]), "show why a feed failed"),
+        (
+            keys(&[Action::ToggleUnreadFilter]),
+            "show only unread items",
+        ),
+        (keys(&[Action::CycleItemSort]), "change order of items"),
+        (keys(&[Action::Enqueue]), "add item to reading queue"),
+        (keys(&[Action::Dequeue]), "read next queued item"),
+        (keys(&[Action::CycleTimeFilter]), "show only recent items"),
+        (keys(&[Action::Presets]), "apply a filter preset"),
+        (
+            keys(&[Action::ToggleContent]),
+            "find a feed, or show full post/summary",
+        ),
+        (
+            keys(&[Action::ToggleExpand]),
+            "show/hide item summary in list",
+        ),
+        (keys(&[Action::Export]), "export items to Markdown"),
+        (keys(&[Action::EditConfig]), "open config file"),
+        (
+            keys(&[Action::JumpToFeed]),
+            "jump to a feed by its first letter",
+        ),
+        (keys(&[Action::Help]), "toggle this help dialog"),
+    ]
+    .into_iter()
+    .map(|(keys, description)| Line::from(format!("{:<6} {}", keys, description)))
+    .collect::<Vec<_>>();
+    if app.config.keybinding_mode() == KeybindingMode::Vim {
+        basic.push(Line::from("gg/G   go to first/last row"));
+        basic.push(Line::from("^d/^u  scroll half a page down/up"));
+    }
     let basic_keybinds = Paragraph::new(basic).block(block.clone().title("Keybinds"));
 
     let console = vec![
         Line::from(":add <URL>      scroll down/up"),
         Line::from(":delete <URL>   focus previous/next panel"),
-        Line::from(":search <TERM>  filter feeds"),
+        Line::from(":search <TERM>  search all items"),
+        Line::from(":auth <U> <P>   set feed login"),
+        Line::from(":export <PATH>  save items as Markdown"),
+        Line::from(":profile <NAME> switch profile"),
+        Line::from(":within <SPAN>  show only items from last 3d"),
+        Line::from(":savepreset <N> save filters as a preset"),
         Line::from("Esc             exit console mode"),
     ];
     let console_keybinds = Paragraph::new(console).block(block.title("Console"));
@@ -115,72 +271,307 @@ fn render_keybinds_overlay<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>,
     frame.render_widget(console_keybinds, layout[1]);
 }
 
-fn render_console_area<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, area: Rect) {
+fn render_feed_candidates_overlay<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, area: Rect) {
+    let area = centered_rect_ratio((3, 5), (1, 3), area);
+
+    let block = Block::default()
+        .title("Feeds found")
+        .title(
+            Title::from(Span::styled(
+                format!("Ent add {} Esc cancel", app.symbols.separator),
+                Style::default().add_modifier(Modifier::DIM),
+            ))
+            .alignment(Alignment::Right),
+        )
+        .borders(Borders::ALL)
+        .border_style(app.config.theme().overlay())
+        .border_type(BorderType::Plain)
+        .style(app.config.theme().overlay())
+        .padding(Padding::uniform(1));
+
+    let list = List::new(
+        app.feed_candidates
+            .items()
+            .iter()
+            .map(|url| ListItem::new(url.clone()))
+            .collect::<Vec<_>>(),
+    )
+    .block(block)
+    .highlight_style(app.config.theme().active_selection());
+
+    frame.render_widget(Clear, area);
+    frame.render_stateful_widget(list, area, &mut app.feed_candidates.state);
+}
+
+fn render_preset_picker_overlay<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, area: Rect) {
+    let height = app.preset_picker.items().len() as u16 + 4;
+    let area = centered_rect_sized(40.min(area.width), height.min(area.height), area);
+
+    let block = Block::default()
+        .title("Filter presets")
+        .title(
+            Title::from(Span::styled(
+                format!("Ent apply {} Esc cancel", app.symbols.separator),
+                Style::default().add_modifier(Modifier::DIM),
+            ))
+            .alignment(Alignment::Right),
+        )
+        .borders(Borders::ALL)
+        .border_style(app.config.theme().overlay())
+        .border_type(BorderType::Plain)
+        .style(app.config.theme().overlay())
+        .padding(Padding::uniform(1));
+
+    let list = List::new(
+        app.preset_picker
+            .items()
+            .iter()
+            .map(|name| ListItem::new(name.clone()))
+            .collect::<Vec<_>>(),
+    )
+    .block(block)
+    .highlight_style(app.config.theme().active_selection());
+
+    frame.render_widget(Clear, area);
+    frame.render_stateful_widget(list, area, &mut app.preset_picker.state);
+}
+
+fn render_delete_prompt_overlay<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, area: Rect) {
+    let Some(feed) = app.pending_delete_feed() else {
+        return;
+    };
+    let text = format!("Delete '{}'? (y/n)", feed.title());
+    let width = (text.width() as u16 + 6).min(area.width);
+    let area = centered_rect_sized(width, 5, area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(app.config.theme().overlay())
+        .border_type(BorderType::Plain)
+        .style(app.config.theme().overlay())
+        .padding(Padding::uniform(1));
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(
+        Paragraph::new(text)
+            .alignment(Alignment::Center)
+            .block(block),
+        area,
+    );
+}
+
+fn render_error_detail_overlay<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, area: Rect) {
+    let Some((feed, err)) = app.error_detail() else {
+        return;
+    };
+    let mut text = vec![Line::styled(
+        feed.title().to_owned(),
+        Style::default().add_modifier(Modifier::BOLD),
+    )];
+    // feeds that were never fetched are titled with their URL
+    if feed.title() != feed.url() {
+        text.push(Line::styled(
+            feed.url().to_owned(),
+            Style::default().add_modifier(Modifier::DIM),
+        ));
+    }
+    text.push(Line::default());
+    text.push(Line::from(err.reason().to_owned()));
+    if let FeedError::Failed(attempts, _) = err {
+        text.push(Line::styled(
+            match attempts {
+                1 => "after 1 attempt".to_owned(),
+                attempts => format!("after {} attempts", attempts),
+            },
+            Style::default().add_modifier(Modifier::DIM),
+        ));
+    }
+
+    // tall enough for the lines once wrapped, inside the borders and padding
+    let width = (area.width * 3 / 5).max(20).min(area.width);
+    let text_width = width.saturating_sub(4).max(1) as usize;
+    let height = text
+        .iter()
+        .map(|line| line.width().max(1).div_ceil(text_width) as u16)
+        .sum::<u16>()
+        + 4;
+    let area = centered_rect_sized(width, height.min(area.height), area);
+
     let block = Block::default()
-        .style(app.config.theme().status())
-        .borders(Borders::TOP)
-        .border_style(app.config.theme().active_border());
+        .title("Refresh failed")
+        .title_alignment(Alignment::Left)
+        .borders(Borders::ALL)
+        .border_style(app.config.theme().overlay())
+        .border_type(BorderType::Plain)
+        .style(app.config.theme().overlay())
+        .padding(Padding::uniform(1));
 
-    let input_field = Paragraph::new(app.command_state.input.as_str()).block(block);
+    frame.render_widget(Clear, area);
+    frame.render_widget(
+        Paragraph::new(text).wrap(Wrap { trim: false }).block(block),
+        area,
+    );
+}
+
+fn render_console_area<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, area: Rect) {
+    let input_field =
+        Paragraph::new(mask_password(&app.command_state.input)).style(app.config.theme().status());
 
     frame.render_widget(input_field, area);
     frame.set_cursor(
         // Draw the cursor at the current position in the input field.
         // This position is can be controlled via the left and right arrow key
         area.x + app.command_state.cursor_position as u16,
-        // Move one line down, from the border to the input line
-        area.y + 1,
+        area.y,
     )
 }
 
-fn render_status_bar<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, area: Rect) {
-    let block = Block::default()
-        .style(app.config.theme().status())
-        .borders(Borders::TOP)
-        .border_style(app.config.theme().active_border());
+/// Hides the password typed into an `:auth <USERNAME> <PASSWORD>` command, however it is
+/// spaced, as the console reads it.
+fn mask_password(input: &str) -> String {
+    if input.split_whitespace().next() != Some(":auth") {
+        return input.to_owned();
+    }
 
-    if app.should_render_console() {
-        render_console_area(app, frame, area)
-    } else {
-        match &app.status {
-            Status::Loading(n, count) => {
-                if *count > 0 {
-                    frame.render_widget(
-                        Gauge::default()
-                            .block(block)
-                            .ratio(*n as f64 / *count as f64)
-                            .label(format!("Loading {}/{}", n, count))
-                            .use_unicode(true)
-                            .gauge_style(app.config.theme().status()),
-                        area,
-                    );
-                }
+    let (mut words, mut in_word) = (0, false);
+    input
+        .chars()
+        .map(|c| {
+            if c.is_whitespace() {
+                in_word = false;
+                return c;
             }
-            Status::Done => {
-                let text = match app.current_feed().cloned() {
-                    Some(feed) => {
-                        let mut message = String::from("Last fetched: ");
-                        let date = feed.last_fetched().unwrap_or("never").into();
-                        message.push_str(date);
-                        message
-                    }
-                    _ => "[no selection]".to_string(),
-                };
-                frame.render_widget(
-                    Paragraph::new(text)
-                        .alignment(Alignment::Center)
-                        .block(block),
-                    area,
-                );
+            if !in_word {
+                words += 1;
+                in_word = true;
             }
-            Status::Errored(s) => {
-                frame.render_widget(
-                    Paragraph::new(format!("ERROR: {}", s))
-                        .alignment(Alignment::Center)
-                        .block(block),
-                    area,
-                );
+            if words > 2 {
+                '*'
+            } else {
+                c
             }
+        })
+        .collect()
+}
+
+/// A single row: the focused view and feed counts on the left, the load state on the right,
+/// and a message, error or the last fetch time between them.
+fn render_status_bar<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, area: Rect) {
+    if app.should_render_console() {
+        return render_console_area(app, frame, area);
+    }
+
+    let feed_count = app.all_feeds().count();
+    let feeds = match app.filtered_feed_count() {
+        Some(shown) => format!("Showing {} of {}", shown, feed_count),
+        None => feed_count.to_string(),
+    };
+    let mut summary = format!(
+        " {} │ {} {} │ {} unread ",
+        app.active_view_name(),
+        feeds,
+        if feed_count == 1 { "feed" } else { "feeds" },
+        app.unread_count()
+    );
+    if app.time_filter() != TimeWindow::All {
+        summary.push_str(&format!("│ {} ", app.time_filter().label()));
+    }
+    if !app.reading_queue().is_empty() {
+        summary.push_str(&format!("│ {} queued ", app.reading_queue().len()));
+    }
+    match (app.active_preset(), app.group_filter()) {
+        (Some(preset), _) => summary.push_str(&format!("│ {} preset ", preset)),
+        (None, Some(group)) => summary.push_str(&format!("│ {} only ", group)),
+        _ => {}
+    }
+    match app.search_position() {
+        Some((Some(position), count)) => {
+            summary.push_str(&format!("│ {} of {} ", position, count));
+        }
+        Some((None, count)) => {
+            summary.push_str(&format!(
+                "│ {} {} ",
+                count,
+                if count == 1 { "match" } else { "matches" }
+            ));
+        }
+        None => {}
+    }
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Length(summary.width() as u16),
+            Constraint::Min(0),
+            Constraint::Length(LOAD_STATE_WIDTH),
+        ])
+        .split(area);
+    let style = app.config.theme().status();
+
+    frame.render_widget(Paragraph::new(summary).style(style), chunks[0]);
+
+    let message = match (&app.status_message, &app.status, app.current_feed()) {
+        (Some((message, _)), _, _) => message.clone(),
+        (None, Status::Errored(err), _) => format!("ERROR: {}", err),
+        (None, _, Some(feed)) => {
+            format!("Last fetched: {}", feed.last_fetched().unwrap_or("never"))
+        }
+        _ => "[no selection]".to_string(),
+    };
+    let mut line = vec![Span::raw(message)];
+    if app.has_highlighted_items() {
+        line.insert(
+            0,
+            Span::styled(
+                format!("{} ", app.symbols.flag),
+                app.config.theme().highlight().bold(),
+            ),
+        );
+    }
+    frame.render_widget(
+        Paragraph::new(Line::from(line))
+            .alignment(Alignment::Center)
+            .style(style),
+        chunks[1],
+    );
+
+    match &app.status {
+        // a feed refreshed on its own is named in place of the gauge
+        Status::Loading(..) if app.refreshing_feed_title().is_some() => {
+            let title = app.refreshing_feed_title().unwrap_or_default();
+            let label =
+                util::truncate(&format!("Loading {}", title), LOAD_STATE_WIDTH as usize - 1);
+            frame.render_widget(
+                Paragraph::new(format!("{} ", label))
+                    .alignment(Alignment::Right)
+                    .style(style),
+                chunks[2],
+            );
+        }
+        Status::Loading(n, count) if *count > 0 => {
+            frame.render_widget(
+                Gauge::default()
+                    .ratio(*n as f64 / *count as f64)
+                    .label(format!("Loading {}/{}", n, count))
+                    .use_unicode(true)
+                    .gauge_style(style),
+                chunks[2],
+            );
+        }
+        Status::Loading(..) => {
+            frame.render_widget(
+                Paragraph::new("Loading ")
+                    .alignment(Alignment::Right)
+                    .style(style),
+                chunks[2],
+            );
+        }
+        Status::Errored(_) | Status::Done => {
+            frame.render_widget(
+                Paragraph::new("Ready ")
+                    .alignment(Alignment::Right)
+                    .style(style),
+                chunks[2],
+            );
         }
     }
 }
@@ -242,3 +633,64 @@ fn centered_rect_sized(width: u16, height: u16, r: Rect) -> Rect {
         )
         .split(popup_layout[1])[1]
 }
+
+/// The style of the list row at `index`: the theme's stripe on every other row while rows are
+/// striped, except on the selected row, which only the selection style marks.
+fn row_style(app: &App, index: usize, selected: Option<usize>) -> Style {
+    match app.config.theme().stripe() {
+        Some(stripe) if index % 2 == 1 && selected != Some(index) => stripe,
+        _ => Style::default(),
+    }
+}
+
+/// The block around a pane, bordered in the `border_style` preference and the theme's active or
+/// inactive border color, with the active pane's title in bold. Without borders, the pane keeps
+/// their room as padding, below the title row the block keeps anyway, and its title shows
+/// whether it is active.
+fn pane_block<'a>(
+    app: &App,
+    title: impl Into<Title<'a>>,
+    active: bool,
+    padding: Padding,
+) -> Block<'a> {
+    let style = if active {
+        app.config.theme().active_border()
+    } else {
+        app.config.theme().border()
+    };
+    let block = Block::default().title_alignment(Alignment::Left);
+    let border_type = match app.config.border_style() {
+        BorderStyle::Plain => BorderType::Plain,
+        BorderStyle::Rounded => BorderType::Rounded,
+        BorderStyle::Double => BorderType::Double,
+        BorderStyle::Thick => BorderType::Thick,
+        BorderStyle::None => {
+            let mut title = title.into();
+            title.content.spans.insert(0, Span::raw(" "));
+            title.content.spans.push(Span::raw(" "));
+            title.content.patch_style(if active {
+                style.add_modifier(Modifier::REVERSED | Modifier::BOLD)
+            } else {
+                style
+            });
+            return block.title(title).padding(Padding {
+                top: padding.top,
+                bottom: padding.bottom + 1,
+                left: padding.left + 1,
+                right: padding.right + 1,
+            });
+        }
+    };
+    let mut title = title.into();
+    if active {
+        title
+            .content
+            .patch_style(Style::default().add_modifier(Modifier::BOLD));
+    }
+    block
+        .title(title)
+        .padding(padding)
+        .borders(Borders::ALL)
+        .border_style(style)
+        .border_type(border_type)
+}
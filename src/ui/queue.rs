@@ -0,0 +1,56 @@
+use super::pane_block;
+use crate::app::App;
+use crate::util;
+use tui::{
+    backend::Backend,
+    layout::Alignment,
+    prelude::*,
+    style::{Modifier, Style},
+    widgets::{List, ListItem, Padding, Paragraph},
+    Frame,
+};
+
+const FEED_COLUMN_WIDTH: usize = 20;
+
+pub fn render_queue_area<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, area: Rect) {
+    let block = pane_block(
+        app,
+        format!("Queue ({})", app.reading_queue().len()),
+        true,
+        Padding::uniform(1),
+    );
+
+    if app.reading_queue().is_empty() {
+        let empty = Paragraph::new("Nothing queued. Press L on an item to read it later.")
+            .block(block)
+            .style(app.config.theme().base())
+            .alignment(Alignment::Center);
+        frame.render_widget(empty, area);
+        return;
+    }
+
+    let dim = Style::default().add_modifier(Modifier::DIM);
+    let rows = app
+        .reading_queue()
+        .iter()
+        .map(|queued| {
+            let (feed_title, title) = match app.queued_item(queued) {
+                Some((feed, item)) => (feed.title(), item.title().unwrap_or("[no title]")),
+                None => ("", "[no longer cached]"),
+            };
+            ListItem::new(Line::from(vec![
+                Span::styled(
+                    format!("{} ", util::fit(feed_title, FEED_COLUMN_WIDTH)),
+                    dim,
+                ),
+                Span::raw(title.to_owned()),
+            ]))
+        })
+        .collect::<Vec<_>>();
+
+    let list = List::new(rows)
+        .block(block)
+        .style(app.config.theme().base())
+        .highlight_style(app.config.theme().active_selection());
+    frame.render_stateful_widget(list, area, &mut app.queue_state);
+}
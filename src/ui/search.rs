@@ -0,0 +1,93 @@
+use super::pane_block;
+use crate::app::App;
+use crate::util;
+use tui::{
+    backend::Backend,
+    layout::Alignment,
+    prelude::*,
+    style::{Modifier, Style},
+    widgets::{List, ListItem, Padding, Paragraph},
+    Frame,
+};
+
+const FEED_COLUMN_WIDTH: usize = 20;
+
+pub fn render_search_area<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, area: Rect) {
+    let block = pane_block(app, "Search", true, Padding::uniform(1));
+    let inner = block.inner(area);
+    frame.render_widget(block.style(app.config.theme().base()), area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(1),
+            Constraint::Length(1),
+            Constraint::Min(1),
+        ])
+        .split(inner);
+
+    let query = Paragraph::new(format!("/{}", app.search.input)).style(
+        if app.should_render_search_input() {
+            app.config.theme().status()
+        } else {
+            Style::default().add_modifier(Modifier::DIM)
+        },
+    );
+    frame.render_widget(query, chunks[0]);
+    if app.should_render_search_input() {
+        frame.set_cursor(
+            chunks[0].x + 1 + app.search.cursor_position as u16,
+            chunks[0].y,
+        );
+    }
+
+    if app.search_results.items().is_empty() {
+        let message = if app.search.input.trim().is_empty() {
+            "Type to search the titles and text of every item."
+        } else {
+            "No items found."
+        };
+        let empty = Paragraph::new(message)
+            .style(app.config.theme().base())
+            .alignment(Alignment::Center);
+        frame.render_widget(empty, chunks[2]);
+        return;
+    }
+
+    // each result takes two rows, the title and the text around the match below it
+    let dim = Style::default().add_modifier(Modifier::DIM);
+    let rows = app
+        .search_results
+        .items()
+        .iter()
+        .map(|result| {
+            ListItem::new(vec![
+                Line::from(vec![
+                    Span::styled(
+                        format!("{} ", util::fit(result.feed_title(), FEED_COLUMN_WIDTH)),
+                        dim,
+                    ),
+                    Span::raw(result.item_title().unwrap_or("[no title]").to_owned()),
+                ]),
+                Line::from(Span::styled(
+                    format!(
+                        "{:width$} {}",
+                        "",
+                        result.snippet(),
+                        width = FEED_COLUMN_WIDTH
+                    ),
+                    dim,
+                )),
+            ])
+        })
+        .collect::<Vec<_>>();
+
+    let list = List::new(rows)
+        .style(app.config.theme().base())
+        .highlight_style(if app.should_render_search_input() {
+            app.config.theme().selection()
+        } else {
+            app.config.theme().active_selection()
+        });
+    frame.render_stateful_widget(list, chunks[2], &mut app.search_results.state);
+}
@@ -0,0 +1,76 @@
+use super::pane_block;
+use crate::app::App;
+use crate::format::format_relative;
+use tui::{
+    backend::Backend,
+    prelude::*,
+    widgets::{Cell, Padding, Row, Table, TableState},
+    Frame,
+};
+
+const COLUMN_WIDTHS: [Constraint; 6] = [
+    Constraint::Min(20),
+    Constraint::Length(7),
+    Constraint::Length(7),
+    Constraint::Length(8),
+    Constraint::Length(13),
+    Constraint::Length(10),
+];
+
+pub fn render_stats_area<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, area: Rect) {
+    let block = pane_block(app, "Stats", true, Padding::uniform(1));
+
+    let header = Row::new(
+        [
+            "Feed",
+            "Items",
+            "Unread",
+            "Starred",
+            "Last fetched",
+            "Items/day",
+        ]
+        .map(|title| Cell::from(title).style(Style::default().add_modifier(Modifier::DIM))),
+    )
+    .bottom_margin(1);
+
+    let rows = app.stats.items().iter().enumerate().map(|(i, stats)| {
+        let row = Row::new(vec![
+            Cell::from(stats.title().to_owned()),
+            Cell::from(stats.total_items().to_string()),
+            Cell::from(stats.unread_items().to_string()),
+            Cell::from(stats.starred_items().to_string()),
+            Cell::from(
+                stats
+                    .last_fetched()
+                    .map(format_relative)
+                    .unwrap_or_else(|| "never".to_owned()),
+            ),
+            Cell::from(
+                stats
+                    .items_per_day()
+                    .map(|rate| format!("{:.1}", rate))
+                    .unwrap_or_else(|| "-".to_owned()),
+            ),
+        ]);
+        // the first row holds the totals
+        if i == 0 {
+            row.style(Style::default().add_modifier(Modifier::BOLD))
+        } else {
+            row
+        }
+    });
+
+    let table = Table::new(rows)
+        .header(header)
+        .block(block)
+        .widths(&COLUMN_WIDTHS)
+        .column_spacing(2)
+        .style(app.config.theme().base())
+        .highlight_style(app.config.theme().active_selection());
+
+    let mut state = TableState::default()
+        .with_selected(app.stats.state.selected())
+        .with_offset(app.stats.state.offset());
+    frame.render_stateful_widget(table, area, &mut state);
+    *app.stats.state.offset_mut() = state.offset();
+}
@@ -0,0 +1,120 @@
+//! The symbols the interface draws with, in Unicode or, for terminals that can't show it, in
+//! ASCII.
+
+use crate::config::ScrollbarStyle;
+use tui::{buffer::Buffer, layout::Rect, symbols, widgets::scrollbar, widgets::Widget};
+
+#[derive(Debug, Clone)]
+pub struct SymbolSet {
+    ascii: bool,
+    /// A feed that is failing to refresh.
+    pub warning: &'static str,
+    pub star: &'static str,
+    /// An unread item, in the read indicator column.
+    pub unread: &'static str,
+    /// An item with media attached.
+    pub media: &'static str,
+    /// The status bar's mark for highlighted items.
+    pub flag: &'static str,
+    pub collapsed: &'static str,
+    pub expanded: &'static str,
+    /// Between the parts of a label, and in place of an empty one.
+    pub separator: &'static str,
+}
+
+impl SymbolSet {
+    pub fn unicode() -> Self {
+        Self {
+            ascii: false,
+            warning: "⚠",
+            star: "★",
+            unread: "●",
+            media: "▶",
+            flag: "⚑",
+            collapsed: "▸",
+            expanded: "▾",
+            separator: "·",
+        }
+    }
+
+    pub fn ascii() -> Self {
+        Self {
+            ascii: true,
+            warning: "!",
+            star: "*",
+            unread: "*",
+            media: ">",
+            flag: "!",
+            collapsed: "+",
+            expanded: "-",
+            separator: "-",
+        }
+    }
+
+    pub fn is_ascii(&self) -> bool {
+        self.ascii
+    }
+
+    /// The track, thumb and arrows of scrollbars drawn in `style`. In ASCII every style is
+    /// drawn alike, with `#` on `|`.
+    pub fn scrollbar(&self, style: ScrollbarStyle) -> scrollbar::Set {
+        if self.ascii {
+            return scrollbar::Set {
+                track: "|",
+                thumb: "#",
+                begin: "^",
+                end: "v",
+            };
+        }
+        match style {
+            ScrollbarStyle::Block | ScrollbarStyle::None => scrollbar::Set {
+                track: scrollbar::VERTICAL.thumb,
+                ..scrollbar::VERTICAL
+            },
+            ScrollbarStyle::Thin => scrollbar::Set {
+                track: symbols::line::VERTICAL,
+                thumb: symbols::line::THICK_VERTICAL,
+                ..scrollbar::VERTICAL
+            },
+            ScrollbarStyle::Arrows => scrollbar::VERTICAL,
+        }
+    }
+}
+
+impl Default for SymbolSet {
+    fn default() -> Self {
+        Self::unicode()
+    }
+}
+
+/// Redraws the borders and dividers the widgets drew in box-drawing characters with `+`, `-`
+/// and `|`, as ratatui can't be given ASCII borders, along with block elements as `#` and the
+/// ellipses of shortened text as `.`.
+pub struct AsciiBorders;
+
+impl Widget for AsciiBorders {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        for y in area.top()..area.bottom() {
+            for x in area.left()..area.right() {
+                let cell = buf.get_mut(x, y);
+                let mut chars = cell.symbol.chars();
+                if let (Some(c), None) = (chars.next(), chars.next()) {
+                    if let Some(ascii) = ascii_border(c) {
+                        cell.set_char(ascii);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn ascii_border(c: char) -> Option<char> {
+    match c {
+        '─' | '━' | '═' | '┄' | '┅' | '┈' | '┉' | '╌' | '╍' => Some('-'),
+        '│' | '┃' | '║' | '┆' | '┇' | '┊' | '┋' | '╎' | '╏' => Some('|'),
+        '\u{2500}'..='\u{257F}' => Some('+'),
+        '\u{2580}'..='\u{259F}' => Some('#'),
+        '…' => Some('.'),
+        _ => None,
+    }
+}
@@ -0,0 +1,78 @@
+use super::{pane_block, row_style};
+use crate::app::App;
+use crate::config::PaneLayout;
+use crate::ui::browse;
+use crate::util;
+use tui::{
+    backend::Backend,
+    layout::Alignment,
+    prelude::*,
+    style::{Modifier, Style},
+    widgets::{List, ListItem, Padding, Paragraph},
+    Frame,
+};
+
+const FEED_COLUMN_WIDTH: usize = 20;
+
+pub fn render_timeline_area<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, area: Rect) {
+    let direction = match app.effective_layout((frame.size().width, frame.size().height)) {
+        PaneLayout::Horizontal => Direction::Horizontal,
+        PaneLayout::Vertical => Direction::Vertical,
+    };
+    let chunks = Layout::default()
+        .direction(direction)
+        .constraints([Constraint::Ratio(1, 2); 2].as_ref())
+        .split(area);
+
+    let block = pane_block(app, "Timeline", true, Padding::uniform(1));
+
+    if app.timeline.items().is_empty() {
+        let empty = Paragraph::new("No items yet. Press r to refresh feeds.")
+            .block(block)
+            .style(app.config.theme().base())
+            .alignment(Alignment::Center);
+        frame.render_widget(empty, area);
+        return;
+    }
+
+    let detail = app.current_item_detail.clone();
+    app.pane_areas.timeline = block.inner(match detail {
+        Some(_) => chunks[0],
+        None => area,
+    });
+
+    let rows = app
+        .timeline
+        .items()
+        .iter()
+        .enumerate()
+        .map(|(i, row)| {
+            let item = row.summary();
+            let mut style = Style::default();
+            if item.is_read() {
+                style = style.add_modifier(Modifier::DIM);
+            }
+            ListItem::new(Line::from(vec![
+                Span::styled(
+                    format!("{} ", util::fit(row.feed_title(), FEED_COLUMN_WIDTH)),
+                    Style::default().add_modifier(Modifier::DIM),
+                ),
+                Span::styled(item.title().unwrap_or("[no title]").to_owned(), style),
+            ]))
+            .style(row_style(app, i, app.timeline.state.selected()))
+        })
+        .collect::<Vec<_>>();
+
+    let list = List::new(rows)
+        .block(block)
+        .style(app.config.theme().base())
+        .highlight_style(app.config.theme().active_selection());
+
+    match detail {
+        Some(detail) => {
+            frame.render_stateful_widget(list, chunks[0], &mut app.timeline.state);
+            browse::render_item_detail(app, frame, chunks[1], &detail);
+        }
+        None => frame.render_stateful_widget(list, area, &mut app.timeline.state),
+    }
+}
@@ -0,0 +1,102 @@
+use super::centered_rect_sized;
+use crate::app::{App, WizardStep};
+use crate::config::theme::{Theme, BUILT_IN_THEMES};
+use tui::{
+    backend::Backend,
+    layout::Alignment,
+    prelude::*,
+    widgets::{Block, BorderType, Borders, Clear, List, ListItem, Padding, Paragraph, Wrap},
+    Frame,
+};
+
+const WIZARD_WIDTH: u16 = 64;
+const WIZARD_HEIGHT: u16 = 19;
+
+/// Draws the current step of the setup wizard over the whole screen. The theme step shows
+/// itself in the selected theme.
+pub fn render_wizard<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, area: Rect) {
+    let Some(wizard) = &mut app.wizard else {
+        return;
+    };
+    let theme = match wizard.step {
+        WizardStep::Theme => Theme::built_in(wizard.theme_name()).unwrap_or_default(),
+        _ => app.config.theme().clone(),
+    };
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(Block::default().style(theme.base()), area);
+
+    let area = centered_rect_sized(
+        WIZARD_WIDTH.min(area.width),
+        WIZARD_HEIGHT.min(area.height),
+        area,
+    );
+    let block = Block::default()
+        .title(format!("Set up moccasin ({}/3)", wizard.step.number()))
+        .title_alignment(Alignment::Left)
+        .borders(Borders::ALL)
+        .border_style(theme.active_border())
+        .border_type(BorderType::Plain)
+        .style(theme.base())
+        .padding(Padding::uniform(1));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Min(1),
+            Constraint::Length(3),
+            Constraint::Length(1),
+        ])
+        .split(inner);
+
+    let (prompt, keys) = match wizard.step {
+        WizardStep::FeedUrl => (
+            "Enter the URL of a feed to start with. More can be added later with :add.",
+            ["Enter check and continue", "Esc skip setup"],
+        ),
+        WizardStep::Theme => (
+            "Choose a color scheme. More can be installed in the themes directory.",
+            ["Enter continue", "Esc back"],
+        ),
+        WizardStep::RefreshInterval => (
+            "How often to refresh feeds: in seconds, as a span like 30m or 2h, or 0 for never.",
+            ["Enter save and finish", "Esc back"],
+        ),
+    };
+    let help = keys.join(&format!(" {} ", app.symbols.separator));
+    frame.render_widget(Paragraph::new(prompt).wrap(Wrap { trim: true }), chunks[0]);
+
+    let dim = Style::default().add_modifier(Modifier::DIM);
+    match wizard.step {
+        WizardStep::Theme => {
+            let list = List::new(
+                BUILT_IN_THEMES
+                    .iter()
+                    .map(|name| ListItem::new(*name))
+                    .collect::<Vec<_>>(),
+            )
+            .highlight_style(theme.active_selection());
+            frame.render_stateful_widget(list, chunks[1], &mut wizard.theme);
+        }
+        _ => {
+            let input = wizard.input_mut().expect("text step has an input");
+            let line = Line::from(vec![
+                Span::styled("> ", dim),
+                Span::raw(input.input.clone()),
+            ]);
+            frame.render_widget(Paragraph::new(line), chunks[1]);
+            frame.set_cursor(chunks[1].x + 2 + input.cursor_position as u16, chunks[1].y);
+        }
+    }
+
+    let message = match (&wizard.checking, &wizard.error) {
+        (Some(url), _) => Line::styled(format!("Checking {}…", url), dim),
+        (None, Some(error)) => Line::styled(error.clone(), theme.highlight()),
+        (None, None) => Line::default(),
+    };
+    frame.render_widget(Paragraph::new(message).wrap(Wrap { trim: true }), chunks[2]);
+    frame.render_widget(Paragraph::new(Line::styled(help, dim)), chunks[3]);
+}
@@ -1,5 +1,16 @@
-use crate::config::{Config, SortOrder};
-use crate::feed::Feed;
+use crate::app::GroupedFeed;
+use crate::config::{Config, ItemSortOrder, SortOrder};
+use crate::feed::{Feed, ItemSummary};
+use chrono::{DateTime, FixedOffset};
+use fuzzy_matcher::skim::SkimMatcherV2;
+use fuzzy_matcher::FuzzyMatcher;
+use regex::RegexBuilder;
+use std::cmp::Reverse;
+use std::time::Duration;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// Header for feeds that are not listed in any group.
+pub const UNGROUPED: &str = "Other";
 
 pub fn sort_feeds(feeds: &mut Vec<Feed>, config: &Config) {
     match config.sort_order() {
@@ -17,12 +28,189 @@ pub fn sort_feeds(feeds: &mut Vec<Feed>, config: &Config) {
                 a_index.cmp(&b_index)
             })
         }
+        // most unread first, alphabetically among feeds with as many
         SortOrder::Unread => {
-            unimplemented!()
+            feeds.sort_by(|a, b| a.title().partial_cmp(b.title()).unwrap());
+            feeds.sort_by_key(|feed| Reverse(feed.unread_count()));
         }
         SortOrder::Newest => feeds.sort_by(|a, b| a.last_fetched().cmp(&b.last_fetched())),
         SortOrder::Oldest => feeds.sort_by(|a, b| b.last_fetched().cmp(&a.last_fetched())),
     }
+
+    // stable, so the order above is kept within each group
+    if !config.groups().is_empty() {
+        feeds.sort_by_key(|feed| config.group_index(feed.url()).unwrap_or(usize::MAX));
+    }
+}
+
+/// Sorts items held in full in the same order `read_items_page` reads them from storage.
+pub fn sort_items(items: &mut [ItemSummary], order: ItemSortOrder) {
+    let published = |item: &ItemSummary| item.pub_date().and_then(parse_pub_date);
+    let title = |item: &ItemSummary| item.title().unwrap_or_default().to_lowercase();
+
+    // stable, so ties keep the feed's order
+    match order {
+        ItemSortOrder::FeedDefault => {}
+        ItemSortOrder::NewestFirst => items.sort_by(|a, b| match (published(a), published(b)) {
+            (Some(a), Some(b)) => b.cmp(&a),
+            (a, b) => b.is_some().cmp(&a.is_some()),
+        }),
+        ItemSortOrder::OldestFirst => items.sort_by(|a, b| match (published(a), published(b)) {
+            (Some(a), Some(b)) => a.cmp(&b),
+            (a, b) => b.is_some().cmp(&a.is_some()),
+        }),
+        ItemSortOrder::TitleAz => items.sort_by_key(title),
+        ItemSortOrder::TitleZa => items.sort_by_key(|item| Reverse(title(item))),
+        ItemSortOrder::UnreadFirst => items.sort_by_key(ItemSummary::is_read),
+    }
+}
+
+pub fn parse_pub_date(date: &str) -> Option<DateTime<FixedOffset>> {
+    DateTime::parse_from_rfc2822(date)
+        .or_else(|_| DateTime::parse_from_rfc3339(date))
+        .ok()
+}
+
+/// Splits feeds, already sorted by `sort_feeds`, into rows under a header for each group.
+pub fn group_feeds(feeds: Vec<Feed>, config: &Config) -> Vec<GroupedFeed> {
+    if config.groups().is_empty() {
+        return feeds
+            .into_iter()
+            .map(|feed| GroupedFeed::Feed(Box::new(feed)))
+            .collect();
+    }
+
+    let mut rows = vec![];
+    let mut current = None;
+    for feed in feeds {
+        let group = config.group_index(feed.url());
+        if rows.is_empty() || group != current {
+            let name = match group {
+                Some(i) => config.groups()[i].name(),
+                None => UNGROUPED,
+            };
+            rows.push(GroupedFeed::Header(name.to_owned()));
+            current = group;
+        }
+        rows.push(GroupedFeed::Feed(Box::new(feed)));
+    }
+    rows
+}
+
+/// Shortens `s` to at most `width` columns, marking the cut with an ellipsis. Wide characters,
+/// as in Chinese, Japanese and Korean, take two columns.
+pub fn truncate(s: &str, width: usize) -> String {
+    if s.width() <= width {
+        s.to_owned()
+    } else if width == 0 {
+        String::new()
+    } else {
+        let mut used = 0;
+        let mut truncated = s
+            .chars()
+            .take_while(|c| {
+                used += c.width().unwrap_or(0);
+                used < width
+            })
+            .collect::<String>();
+        truncated.push('…');
+        truncated
+    }
+}
+
+/// `s` truncated or padded to exactly `width` columns.
+pub fn fit(s: &str, width: usize) -> String {
+    let mut fitted = truncate(s, width);
+    let padding = width.saturating_sub(fitted.width());
+    fitted.push_str(&" ".repeat(padding));
+    fitted
+}
+
+/// About `width` characters of `text` around the first match of `query`, ignoring case, with
+/// whitespace collapsed and ellipses where it was cut. Without a match, the start of `text`.
+pub fn snippet(text: &str, query: &str, width: usize) -> String {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    let start = RegexBuilder::new(&regex::escape(query))
+        .case_insensitive(true)
+        .build()
+        .ok()
+        .and_then(|regex| regex.find(&text))
+        .map(|found| text[..found.start()].chars().count())
+        .unwrap_or(0);
+
+    // a third of the width before the match, for context
+    let len = text.chars().count();
+    let start = start
+        .saturating_sub(width / 3)
+        .min(len.saturating_sub(width));
+    let mut snippet = text.chars().skip(start).take(width).collect::<String>();
+    if start > 0 {
+        snippet.insert(0, '…');
+    }
+    if start + width < len {
+        snippet.push('…');
+    }
+    snippet
+}
+
+/// Scores how well `text` matches `query` when its characters appear in order, ignoring case,
+/// with the positions of the matched characters in `text`, as the skim fuzzy finder does.
+/// `None` if `text` doesn't match.
+pub fn fuzzy_match(query: &str, text: &str) -> Option<(i64, Vec<usize>)> {
+    if query.is_empty() {
+        return None;
+    }
+    SkimMatcherV2::default()
+        .ignore_case()
+        .fuzzy_indices(text, query)
+}
+
+/// Units of the spans of time read by `parse_duration`, largest first, in seconds.
+const DURATION_UNITS: [(char, u64); 4] = [
+    ('w', 7 * 24 * 60 * 60),
+    ('d', 24 * 60 * 60),
+    ('h', 60 * 60),
+    ('m', 60),
+];
+
+/// Reads a span of time such as `90m`, `36h`, `3d` or `2w`.
+pub fn parse_duration(s: &str) -> Option<Duration> {
+    let unit = s.chars().last()?;
+    let count = s[..s.len() - unit.len_utf8()].parse::<u64>().ok()?;
+    let (_, secs) = DURATION_UNITS.iter().find(|(name, _)| *name == unit)?;
+    Some(Duration::from_secs(count * secs)).filter(|duration| !duration.is_zero())
+}
+
+/// Writes a span of time in the largest of the units `parse_duration` reads that fits it.
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    let (name, unit) = DURATION_UNITS
+        .iter()
+        .find(|(_, unit)| secs.is_multiple_of(*unit))
+        .unwrap_or(&DURATION_UNITS[3]);
+    format!("{}{}", secs / unit, name)
+}
+
+/// Reads the body of `res` as it arrives, giving up with `None` as soon as it is known to be
+/// longer than `limit` bytes.
+pub async fn read_body(
+    mut res: reqwest::Response,
+    limit: usize,
+) -> reqwest::Result<Option<Vec<u8>>> {
+    if res
+        .content_length()
+        .is_some_and(|length| length > limit as u64)
+    {
+        return Ok(None);
+    }
+    let mut body = Vec::new();
+    while let Some(chunk) = res.chunk().await? {
+        if body.len() + chunk.len() > limit {
+            return Ok(None);
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok(Some(body))
 }
 
 #[macro_export]
@@ -37,3 +225,41 @@ macro_rules! report {
         }
     };
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn truncates_double_width_text_at_odd_widths() {
+        // each character is two columns wide, so an odd width can't be filled exactly
+        assert_eq!(truncate("日本語テキスト", 5), "日本…");
+        assert_eq!(truncate("日本語テキスト", 4), "日…");
+        assert_eq!(truncate("日本語テキスト", 3), "日…");
+        assert_eq!(truncate("日本語テキスト", 1), "…");
+        assert_eq!(truncate("日本語", 6), "日本語");
+        for width in 1..14 {
+            assert!(
+                truncate("日本語テキスト", width).width() <= width,
+                "width {width}"
+            );
+        }
+    }
+
+    #[test]
+    fn truncates_mixed_width_text() {
+        assert_eq!(truncate("ab日本語", 5), "ab日…");
+        assert_eq!(truncate("ab日本語", 4), "ab…");
+        assert_eq!(truncate("한국어 text", 7), "한국어…");
+    }
+
+    #[test]
+    fn pads_double_width_text_to_odd_widths() {
+        for width in 0..16 {
+            let fitted = fit("日本語テキスト", width);
+            assert_eq!(fitted.width(), width, "{fitted:?}");
+        }
+        assert_eq!(fit("日本語テキスト", 4), "日… ");
+        assert_eq!(fit("日本", 7), "日本   ");
+    }
+}
@@ -0,0 +1,421 @@
+//! Receiving feed updates from [WebSub](https://www.w3.org/TR/websub/) hubs as they are
+//! published, in place of polling the feeds that advertise one.
+//!
+//! Each subscription gets a random callback path, so only the hub it was sent to can push
+//! updates for its feed.
+
+use crate::config::WebSubConfig;
+use crate::feed::Feed;
+use crate::repo::RepositoryEvent;
+use anyhow::{Context, Result};
+use chrono::Utc;
+use hyper::header::CONTENT_TYPE;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+use rand::Rng;
+use std::collections::{HashMap, HashSet};
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::mpsc::UnboundedSender;
+use tokio::task::JoinHandle;
+
+const TIMEOUT: Duration = Duration::from_secs(15);
+const RENEW_CHECK_RATE: Duration = Duration::from_secs(60);
+/// How long before a lease runs out to renew it.
+const RENEW_MARGIN: i64 = 60 * 60;
+/// The lease assumed when a hub verifies a subscription without giving one.
+const DEFAULT_LEASE: i64 = 24 * 60 * 60;
+const MAX_PUSH_SIZE: u64 = 16 * 1024 * 1024;
+
+/// A subscription to the updates a hub publishes for a feed.
+#[derive(Clone, Debug)]
+pub struct Subscription {
+    pub(crate) feed_url: String,
+    pub(crate) hub: String,
+    pub(crate) topic: String,
+    /// The last segment of the callback URL, which the hub posts updates to.
+    pub(crate) callback_id: String,
+    /// When the lease runs out, as a Unix timestamp, or `None` until the hub verifies it.
+    pub(crate) expires_at: Option<i64>,
+}
+
+impl Subscription {
+    pub fn feed_url(&self) -> &str {
+        &self.feed_url
+    }
+
+    pub fn hub(&self) -> &str {
+        &self.hub
+    }
+
+    pub fn topic(&self) -> &str {
+        &self.topic
+    }
+
+    pub fn callback_id(&self) -> &str {
+        &self.callback_id
+    }
+
+    pub fn expires_at(&self) -> Option<i64> {
+        self.expires_at
+    }
+
+    fn is_active(&self) -> bool {
+        self.expires_at
+            .is_some_and(|expires| expires > Utc::now().timestamp())
+    }
+
+    fn is_due(&self) -> bool {
+        self.expires_at
+            .is_none_or(|expires| expires - RENEW_MARGIN <= Utc::now().timestamp())
+    }
+}
+
+#[derive(Default)]
+struct State {
+    /// Every subscription, by callback id.
+    subscriptions: HashMap<String, Subscription>,
+    /// The callback ids requested from hubs this session and not verified yet, which aren't
+    /// requested again until the next.
+    requested: HashSet<String>,
+}
+
+type SharedState = Arc<Mutex<State>>;
+
+pub struct WebSubManager {
+    client: reqwest::Client,
+    callback_url: String,
+    state: SharedState,
+    tasks: Vec<JoinHandle<()>>,
+}
+
+impl Drop for WebSubManager {
+    fn drop(&mut self) {
+        self.tasks.iter().for_each(JoinHandle::abort);
+    }
+}
+
+impl WebSubManager {
+    /// Listens on the configured port for hubs verifying `subscriptions` and pushing updates,
+    /// sent on to `storage_tx` as `WebSubVerified` and `Pushed`, and renews subscriptions
+    /// before their leases run out.
+    pub fn start(
+        config: &WebSubConfig,
+        subscriptions: Vec<Subscription>,
+        storage_tx: UnboundedSender<RepositoryEvent>,
+    ) -> Result<Self> {
+        let state = Arc::new(Mutex::new(State {
+            subscriptions: subscriptions
+                .into_iter()
+                .map(|sub| (sub.callback_id.clone(), sub))
+                .collect(),
+            requested: HashSet::new(),
+        }));
+        let client = reqwest::Client::builder()
+            .timeout(TIMEOUT)
+            .build()
+            .expect("failed to build client");
+        let callback_url = config.callback_url().to_owned();
+
+        let addr = SocketAddr::from(([127, 0, 0, 1], config.port()));
+        let listener_state = state.clone();
+        let server = Server::try_bind(&addr)
+            .with_context(|| format!("could not listen on port {}", config.port()))?
+            .serve(make_service_fn(move |_| {
+                let state = listener_state.clone();
+                let storage_tx = storage_tx.clone();
+                async move {
+                    Ok::<_, Infallible>(service_fn(move |req| {
+                        handle(req, state.clone(), storage_tx.clone())
+                    }))
+                }
+            }));
+        log::info!("Listening for WebSub pushes on {}", addr);
+        let listener = tokio::spawn(async move {
+            if let Err(err) = server.await {
+                log::error!("WebSub listener stopped: {}", err);
+            }
+        });
+
+        let renewer = tokio::spawn({
+            let (client, callback_url, state) =
+                (client.clone(), callback_url.clone(), state.clone());
+            async move {
+                let mut interval = tokio::time::interval(RENEW_CHECK_RATE);
+                loop {
+                    interval.tick().await;
+                    let due = {
+                        let mut state = state.lock().expect("WebSub state poisoned");
+                        let due = state
+                            .subscriptions
+                            .values()
+                            .filter(|sub| {
+                                sub.is_due() && !state.requested.contains(&sub.callback_id)
+                            })
+                            .cloned()
+                            .collect::<Vec<_>>();
+                        state
+                            .requested
+                            .extend(due.iter().map(|sub| sub.callback_id.clone()));
+                        due
+                    };
+                    for sub in due {
+                        request(&client, &callback_url, &sub, "subscribe").await;
+                    }
+                }
+            }
+        });
+
+        Ok(Self {
+            client,
+            callback_url,
+            state,
+            tasks: vec![listener, renewer],
+        })
+    }
+
+    /// Whether updates to the feed at `url` are being pushed, so it needn't be polled.
+    pub fn is_active(&self, url: &str) -> bool {
+        let state = self.state.lock().expect("WebSub state poisoned");
+        state
+            .subscriptions
+            .values()
+            .any(|sub| sub.feed_url == url && sub.is_active())
+    }
+
+    /// Subscribes to the hubs of the `feeds` that advertise one and aren't subscribed to,
+    /// returning the new subscriptions to store.
+    pub fn subscribe_new(&self, feeds: &[Feed]) -> Vec<Subscription> {
+        let mut state = self.state.lock().expect("WebSub state poisoned");
+        let new = feeds
+            .iter()
+            .filter(|feed| {
+                !state
+                    .subscriptions
+                    .values()
+                    .any(|sub| sub.feed_url == feed.url())
+            })
+            .filter_map(|feed| {
+                let (hub, topic) = feed.hub()?;
+                Some(Subscription {
+                    feed_url: feed.url().to_owned(),
+                    hub: hub.to_owned(),
+                    topic: topic.to_owned(),
+                    callback_id: format!("{:032x}", rand::thread_rng().gen::<u128>()),
+                    expires_at: None,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        for sub in &new {
+            log::info!("Subscribing to {} at {}", sub.topic, sub.hub);
+            state
+                .subscriptions
+                .insert(sub.callback_id.clone(), sub.clone());
+            state.requested.insert(sub.callback_id.clone());
+            let (client, callback_url, sub) =
+                (self.client.clone(), self.callback_url.clone(), sub.clone());
+            tokio::spawn(async move { request(&client, &callback_url, &sub, "subscribe").await });
+        }
+        new
+    }
+
+    /// Unsubscribes from the hub of the feed at `url`, if subscribed to.
+    pub fn unsubscribe(&self, url: &str) {
+        let mut state = self.state.lock().expect("WebSub state poisoned");
+        let mut removed = vec![];
+        state.subscriptions.retain(|_, sub| {
+            if sub.feed_url != url {
+                return true;
+            }
+            removed.push(sub.clone());
+            false
+        });
+        for sub in removed {
+            state.requested.remove(&sub.callback_id);
+            let (client, callback_url) = (self.client.clone(), self.callback_url.clone());
+            tokio::spawn(async move { request(&client, &callback_url, &sub, "unsubscribe").await });
+        }
+    }
+}
+
+/// Asks the hub of `sub` to `mode`, `"subscribe"` or `"unsubscribe"`. The hub then verifies
+/// the request with the listener, so failures are only logged.
+async fn request(client: &reqwest::Client, callback_url: &str, sub: &Subscription, mode: &str) {
+    let callback = format!("{}/{}", callback_url, sub.callback_id);
+    let sent = client
+        .post(&sub.hub)
+        .form(&[
+            ("hub.mode", mode),
+            ("hub.topic", &sub.topic),
+            ("hub.callback", &callback),
+        ])
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status);
+    if let Err(err) = sent {
+        log::warn!(
+            "Failed to {} to {} at {}: {}",
+            mode,
+            sub.topic,
+            sub.hub,
+            err
+        );
+    }
+}
+
+async fn handle(
+    req: Request<Body>,
+    state: SharedState,
+    storage_tx: UnboundedSender<RepositoryEvent>,
+) -> Result<Response<Body>, Infallible> {
+    let callback_id = req
+        .uri()
+        .path()
+        .trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .unwrap_or_default()
+        .to_owned();
+    let response = match *req.method() {
+        Method::GET => verify(&req, &callback_id, &state, &storage_tx),
+        Method::POST => receive(req, &callback_id, &state, &storage_tx).await,
+        _ => reply(StatusCode::METHOD_NOT_ALLOWED, ""),
+    };
+    Ok(response)
+}
+
+/// Answers a hub checking that a subscription, or unsubscription, was asked for.
+fn verify(
+    req: &Request<Body>,
+    callback_id: &str,
+    state: &SharedState,
+    storage_tx: &UnboundedSender<RepositoryEvent>,
+) -> Response<Body> {
+    let params = reqwest::Url::parse(&format!("http://localhost{}", req.uri()))
+        .map(|uri| uri.query_pairs().into_owned().collect::<HashMap<_, _>>())
+        .unwrap_or_default();
+    let param = |name: &str| params.get(name).map(String::as_str);
+    let challenge = param("hub.challenge").unwrap_or_default();
+
+    let mut state = state.lock().expect("WebSub state poisoned");
+    match param("hub.mode") {
+        Some("subscribe") => {
+            let Some(sub) = state.subscriptions.get_mut(callback_id) else {
+                return reply(StatusCode::NOT_FOUND, "");
+            };
+            if param("hub.topic") != Some(sub.topic.as_str()) {
+                return reply(StatusCode::NOT_FOUND, "");
+            }
+            let lease = param("hub.lease_seconds")
+                .and_then(|lease| lease.parse().ok())
+                .unwrap_or(DEFAULT_LEASE);
+            sub.expires_at = Some(Utc::now().timestamp() + lease);
+            log::info!("Subscribed to {} for {}s", sub.topic, lease);
+            storage_tx
+                .send(RepositoryEvent::WebSubVerified(sub.clone()))
+                .expect("Failed to send storage message");
+            state.requested.remove(callback_id);
+            reply(StatusCode::OK, challenge)
+        }
+        // only subscriptions since removed are unsubscribed from
+        Some("unsubscribe") if !state.subscriptions.contains_key(callback_id) => {
+            reply(StatusCode::OK, challenge)
+        }
+        Some("denied") => {
+            log::warn!(
+                "Hub denied subscription to {}: {}",
+                param("hub.topic").unwrap_or_default(),
+                param("hub.reason").unwrap_or("no reason given")
+            );
+            reply(StatusCode::OK, "")
+        }
+        _ => reply(StatusCode::NOT_FOUND, ""),
+    }
+}
+
+/// Reads the feed a hub pushed, sending it on to storage.
+async fn receive(
+    req: Request<Body>,
+    callback_id: &str,
+    state: &SharedState,
+    storage_tx: &UnboundedSender<RepositoryEvent>,
+) -> Response<Body> {
+    let Some(url) = state
+        .lock()
+        .expect("WebSub state poisoned")
+        .subscriptions
+        .get(callback_id)
+        .map(|sub| sub.feed_url.clone())
+    else {
+        return reply(StatusCode::NOT_FOUND, "");
+    };
+    let length = req
+        .headers()
+        .get(hyper::header::CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok()?.parse::<u64>().ok());
+    if length.is_some_and(|length| length > MAX_PUSH_SIZE) {
+        return reply(StatusCode::PAYLOAD_TOO_LARGE, "");
+    }
+
+    let content_type = req
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_owned);
+    let body = match hyper::body::to_bytes(req.into_body()).await {
+        Ok(body) => body,
+        Err(err) => {
+            log::warn!("Failed to read push for {}: {}", url, err);
+            return reply(StatusCode::BAD_REQUEST, "");
+        }
+    };
+    // hubs only need to hear the push arrived, not whether it could be read
+    match Feed::read_with_content_type(&body[..], content_type.as_deref(), url.clone()) {
+        Ok(feed) => {
+            log::info!("Received {} pushed items for {}", feed.items().len(), url);
+            storage_tx
+                .send(RepositoryEvent::Pushed(Box::new(feed)))
+                .expect("Failed to send storage message");
+        }
+        Err(err) => log::warn!("Failed to read push for {}: {}", url, err),
+    }
+    reply(StatusCode::ACCEPTED, "")
+}
+
+fn reply(status: StatusCode, body: &str) -> Response<Body> {
+    let mut response = Response::new(Body::from(body.to_owned()));
+    *response.status_mut() = status;
+    response
+}
+
+/// The hub and topic in an HTTP `Link` header, as in
+/// `<https://hub.example.com/>; rel="hub", <https://example.com/feed>; rel="self"`. The topic
+/// falls back to `url` without a `rel="self"` link.
+pub fn hub_from_link_header(header: &str, url: &str) -> Option<(String, String)> {
+    let links = header
+        .split(',')
+        .filter_map(|link| {
+            let (target, params) = link.trim().split_once(';')?;
+            let target = target.trim().strip_prefix('<')?.strip_suffix('>')?;
+            let rels = params.split(';').find_map(|param| {
+                let (name, value) = param.trim().split_once('=')?;
+                name.trim()
+                    .eq_ignore_ascii_case("rel")
+                    .then(|| value.trim().trim_matches('"').to_owned())
+            })?;
+            Some((target.to_owned(), rels))
+        })
+        .collect::<Vec<_>>();
+    let with_rel = |rel: &str| {
+        links
+            .iter()
+            .find(|(_, rels)| rels.split_whitespace().any(|r| r.eq_ignore_ascii_case(rel)))
+            .map(|(target, _)| target.clone())
+    };
+
+    let hub = with_rel("hub")?;
+    Some((hub, with_rel("self").unwrap_or(url.to_owned())))
+}